@@ -0,0 +1,158 @@
+use async_graphql::ErrorExtensions;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// サーバー内部のエラーでクライアントに返すメッセージ
+pub const INTERNAL_ERROR_MESSAGE: &str = "internal server error";
+
+/// 失敗時のAPIレスポンスのボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WriteApiResponseFailureBody {
+    /// エラーコード. [ApiError::code]を参照
+    pub code: String,
+    pub message: String,
+}
+
+/// APIのエラー
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// リクエストの値が不正
+    #[error("{0}")]
+    Validation(String),
+    /// 認証されていない
+    #[error("{0}")]
+    Unauthorized(String),
+    /// 権限がない
+    #[error("{0}")]
+    Forbidden(String),
+    /// 対象が存在しない
+    #[error("{0}")]
+    NotFound(String),
+    /// 対象が削除済み
+    #[error("{0}")]
+    Deleted(String),
+    /// 現在の状態では実行できない
+    #[error("{0}")]
+    Conflict(String),
+    /// プランの利用上限に達した
+    #[error("{0}")]
+    QuotaExceeded(String),
+    /// 支払いに失敗した
+    #[error("{0}")]
+    PaymentRequired(String),
+    /// サーバー内部のエラー
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    /// クライアントが判別に使用するエラーコード
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation(_) => "VALIDATION_ERROR",
+            ApiError::Unauthorized(_) => "UNAUTHENTICATED",
+            ApiError::Forbidden(_) => "FORBIDDEN",
+            ApiError::NotFound(_) => "NOT_FOUND",
+            ApiError::Deleted(_) => "DELETED",
+            ApiError::Conflict(_) => "CONFLICT",
+            ApiError::QuotaExceeded(_) => "QUOTA_EXCEEDED",
+            ApiError::PaymentRequired(_) => "PAYMENT_REQUIRED",
+            ApiError::Internal(_) => "INTERNAL_SERVER_ERROR",
+        }
+    }
+
+    /// クライアントに返すメッセージ
+    ///
+    /// サーバー内部のエラーはSQLやテーブル名を含むため, 内容を返さない. 原因は呼び出し元でログに出力する
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::Internal(_) => INTERNAL_ERROR_MESSAGE.to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Deleted(_) => StatusCode::GONE,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::PaymentRequired(_) => StatusCode::PAYMENT_REQUIRED,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.status(),
+            Json(WriteApiResponseFailureBody {
+                code: self.code().to_string(),
+                message: self.message(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+impl ErrorExtensions for ApiError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.message()).extend_with(|_, e| e.set("code", self.code()))
+    }
+}
+
+#[cfg(test)]
+mod test_api_error {
+    use super::*;
+
+    #[test]
+    fn status_and_code() {
+        let error = ApiError::Validation("invalid vid".to_string());
+        assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.code(), "VALIDATION_ERROR");
+        assert_eq!(error.to_string(), "invalid vid");
+
+        let error = ApiError::NotFound("volunteer not found".to_string());
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error.code(), "NOT_FOUND");
+
+        let error = ApiError::QuotaExceeded("scout quota exceeded".to_string());
+        assert_eq!(error.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(error.code(), "QUOTA_EXCEEDED");
+
+        let error = ApiError::PaymentRequired("payment failed: card_declined".to_string());
+        assert_eq!(error.status(), StatusCode::PAYMENT_REQUIRED);
+        assert_eq!(error.code(), "PAYMENT_REQUIRED");
+    }
+
+    #[test]
+    fn hide_internal_message() {
+        let error = ApiError::Internal("Duplicate entry 'x' for key 'apply.PRIMARY'".to_string());
+        assert_eq!(error.message(), INTERNAL_ERROR_MESSAGE);
+        assert_eq!(error.extend().message, INTERNAL_ERROR_MESSAGE);
+        assert_eq!(
+            ApiError::Conflict("already applied".to_string()).message(),
+            "already applied"
+        );
+    }
+
+    #[test]
+    fn graphql_extension() {
+        let error = ApiError::Conflict("already applied".to_string()).extend();
+        assert_eq!(error.message, "already applied");
+        assert_eq!(
+            error.extensions.unwrap().get("code"),
+            Some(&async_graphql::Value::from("CONFLICT"))
+        );
+    }
+}
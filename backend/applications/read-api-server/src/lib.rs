@@ -1,5 +1,6 @@
 use anyhow::Result;
 use config::{Config, File};
+use firebase_auth::verifier::AuthSettings;
 use serde::Deserialize;
 
 /// APIの設定を表す構造体
@@ -17,6 +18,7 @@ pub struct ApiSettings {
 #[derive(Deserialize, Debug)]
 pub struct AppSettings {
     pub api: ApiSettings, // アプリケーション内のAPI設定
+    pub auth: AuthSettings, // Firebase ID トークンの検証設定
 }
 
 /// アプリケーション設定を読み込む関数
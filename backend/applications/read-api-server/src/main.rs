@@ -1,6 +1,7 @@
 use std::env;
 use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::Result;
 use axum::{headers::HeaderValue, Router};
@@ -8,7 +9,10 @@ use dotenv::dotenv;
 use sqlx::{MySql, MySqlPool, Pool};
 use tower_http::cors::{Any, CorsLayer};
 
-use query_infrastructure::controllers::create_router;
+use firebase_auth::verifier::TokenVerifier;
+use query_infrastructure::{
+    certificate::CertificateSigner, controllers::create_router, event::create_event_subscriber,
+};
 use read_api_server::{load_app_config, AppSettings};
 
 #[tokio::main]
@@ -25,8 +29,18 @@ async fn main() -> Result<()> {
     // データベースプールを作成し, MySqlに接続
     let pool: Pool<MySql> = MySqlPool::connect(&database_url).await?;
 
+    // Firebase ID トークンの検証に使用する公開鍵を取得
+    let verifier: Arc<TokenVerifier> = Arc::new(TokenVerifier::new(&app_settings.auth).await?);
+
+    // 応募・スカウトの出来事の購読先を作成. REDIS_URLが設定されていない場合はデータベースから購読する
+    let subscriber = create_event_subscriber(pool.clone())?;
+
+    // 活動時間の証明書の署名に使用する秘密鍵. 設定されていない場合は起動しない
+    let signer: Arc<CertificateSigner> = Arc::new(CertificateSigner::from_env()?);
+
     // アプリケーションのルーターを作成
-    let router: Router = create_router(pool).layer(create_cors_layer(&app_settings));
+    let router: Router =
+        create_router(pool, verifier, subscriber, signer).layer(create_cors_layer(&app_settings));
 
     // サーバーのアドレスを指定
     let socket_addr: SocketAddr = SocketAddr::new(
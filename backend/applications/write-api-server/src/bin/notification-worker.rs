@@ -0,0 +1,50 @@
+use std::{env, time::Duration};
+
+use dotenv::dotenv;
+use sqlx::{MySql, MySqlPool, Pool};
+
+use command_infrastructure::{
+    mailer::create_mailer,
+    outbox::{deliver_due, OutboxImpl},
+};
+use command_repository::mailer::Mailer;
+use domain::model::notification::RetryPolicy;
+
+/// 送信待ちのメールを確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 1回の確認で送信するメールの上限
+const BATCH_SIZE: u32 = 50;
+
+/// 応募・スカウトのメールを送信する通知ワーカー
+///
+/// 送信待ちのメールを一定間隔で確認し, 送信に失敗したメールは間隔を空けて再送する
+#[tokio::main]
+async fn main() {
+    // .envファイルから環境変数をロード
+    dotenv().ok();
+
+    // ログの出力先を初期化. 出力するレベルは環境変数RUST_LOGで設定する
+    env_logger::init();
+
+    // データベース接続URLを環境変数から取得
+    let database_url: String = env::var("DATABASE_URL").unwrap();
+
+    // データベースプールを作成し, MySqlに接続
+    let pool: Pool<MySql> = MySqlPool::connect(&database_url).await.unwrap();
+
+    let repository: OutboxImpl = OutboxImpl::new(pool);
+    // SMTPサーバーの設定がない場合は起動しない. MAILER=fileの場合はファイルに書き出す
+    let mailer: Box<dyn Mailer> = create_mailer().unwrap();
+    let policy: RetryPolicy = RetryPolicy::default();
+
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        match deliver_due(&repository, mailer.as_ref(), &policy, BATCH_SIZE).await {
+            Ok(0) => {}
+            Ok(sent) => log::info!("sent {} emails", sent),
+            Err(error) => log::error!("failed to deliver emails: {}", error),
+        }
+    }
+}
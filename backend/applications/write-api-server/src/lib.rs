@@ -1,13 +1,22 @@
-use utoipa::OpenApi;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
 
-use command_infrastructure::controllers::{
-    self,
-    participant as controllers_participant,
-    group as controllers_group,
-    apply as controllers_apply,
-    scout as controllers_scout,
-    volunteer as controllers_volunteer,
-    review as controllers_review
+use command_infrastructure::{
+    authz::WriteApiResponseForbiddenBody,
+    controllers::{
+        self,
+        participant as controllers_participant,
+        group as controllers_group,
+        apply as controllers_apply,
+        attendance as controllers_attendance,
+        scout as controllers_scout,
+        volunteer as controllers_volunteer,
+        review as controllers_review,
+        message as controllers_message,
+        payment as controllers_payment
+    }
 };
 
 #[derive(OpenApi)]
@@ -16,9 +25,12 @@ use command_infrastructure::controllers::{
         controllers_group::create_group_account,
         controllers_group::update_group_account,
         controllers_group::delete_group_account,
+        controllers_group::change_group_account_plan,
         controllers_participant::create_participant_account,
         controllers_participant::update_participant_account,
         controllers_participant::delete_participant_account,
+        controllers_participant::register_participant_availability,
+        controllers_participant::delete_participant_availability,
         controllers_volunteer::create_volunteer,
         controllers_volunteer::update_volunteer,
         controllers_volunteer::delete_volunteer,
@@ -26,40 +38,79 @@ use command_infrastructure::controllers::{
         controllers_volunteer::unregister_favorite,
         controllers_apply::create_apply,
         controllers_apply::update_apply_allowed_status,
-        controllers_apply::update_apply_is_sent,
+        controllers_apply::cancel_apply,
+        controllers_attendance::issue_check_in_code,
+        controllers_attendance::check_in,
+        controllers_attendance::record_attendance,
         controllers_scout::create_scout,
-        controllers_scout::update_scout_is_sent,
         controllers_scout::update_scout_is_read,
         controllers_scout::update_scout_denied,
+        controllers_scout::accept_scout,
+        controllers_scout::withdraw_scout,
         controllers_review::review_to_volunteer,
-        controllers_review::review_to_participant
+        controllers_review::review_to_participant,
+        controllers_message::send_message,
+        controllers_message::read_message_thread,
+        controllers_message::delete_message,
+        controllers_payment::payment_webhook
     ),
     components(schemas(
         controllers::WriteApiResponseSuccessBody,
         controllers::WriteApiResponseFailureBody,
+        WriteApiResponseForbiddenBody,
         controllers_group::CreateGroupAccountRequestBody,
         controllers_group::UpdateGroupAccountRequestBody,
-        controllers_group::DeleteGroupAccountRequestBody,
+        controllers_group::ChangeGroupAccountPlanRequestBody,
         controllers_participant::CreateParticipantAccountRequestBody,
         controllers_participant::UpdateParticipantAccountRequestBody,
-        controllers_participant::DeleteParticipantAccountRequestBody,
+        controllers_participant::RegisterParticipantAvailabilityRequestBody,
+        controllers_participant::DeleteParticipantAvailabilityRequestBody,
         controllers_volunteer::CreateVolunteerRequestBody,
         controllers_volunteer::UpdateVolunteerRequestBody,
+        controllers_volunteer::VolunteerSessionRequestBody,
         controllers_volunteer::DeleteVolunteerRequestBody,
         controllers_volunteer::RegisterVolunteerFavoriteRequestBody,
         controllers_volunteer::UnregisterVolunteerFavoriteRequestBody,
         controllers_apply::CreateApplyRequestBody,
         controllers_apply::UpdateApplyAllowedStatusRequestBody,
-        controllers_apply::UpdateApplyIsSentRequestBody,
+        controllers_apply::CancelApplyRequestBody,
+        controllers_attendance::IssueCheckInCodeRequestBody,
+        controllers_attendance::CheckInRequestBody,
+        controllers_attendance::MemberAttendanceBody,
+        controllers_attendance::RecordAttendanceRequestBody,
         controllers_scout::CreateScoutRequestBody,
-        controllers_scout::UpdateScoutIsSentRequestBody,
         controllers_scout::UpdateScoutIsReadRequestBody,
         controllers_scout::UpdateScoutDeniedRequestBody,
+        controllers_scout::AcceptScoutRequestBody,
+        controllers_scout::WithdrawScoutRequestBody,
         controllers_review::ReviewToVolunteerRequestBody,
-        controllers_review::ReviewToParticipantRequestBody
+        controllers_review::ReviewToParticipantRequestBody,
+        controllers_message::SendMessageRequestBody,
+        controllers_message::ReadMessageThreadRequestBody,
+        controllers_message::DeleteMessageRequestBody
     )),
+    modifiers(&SecurityAddon),
     tags(
         (name = "write-api-server", description = "Write API Server")
     )
 )]
 pub struct ApiDoc;
+
+/// Firebase ID トークンによるBearer認証をOpenAPIに追加する
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            )
+        }
+    }
+}
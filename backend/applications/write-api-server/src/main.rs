@@ -2,6 +2,8 @@ use std::{
     env,
     net::{IpAddr, SocketAddr},
     str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Result;
@@ -15,13 +17,30 @@ use tower_http::cors::{Any, CorsLayer};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use command_infrastructure::controllers::create_router;
+use command_infrastructure::{
+    activities::scout::ScoutImpl,
+    controllers::create_router,
+    event::create_event_publisher,
+    payment::create_payment_provider,
+    user_account::billing::{settle_ended_trials, BillingImpl},
+};
+use command_repository::{
+    activities::scout::ScoutRepository, event::EventPublisher, payment::PaymentProvider,
+};
+use firebase_auth::verifier::{AuthSettings, TokenVerifier};
 
 use write_api_server::ApiDoc;
 
+/// 募集締切日を過ぎたスカウトを期限切れにする間隔
+const SCOUT_EXPIRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 無料体験が終了した団体に請求する間隔
+const TRIAL_SETTLEMENT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Deserialize, Debug)]
 struct AppSettings {
     api: ApiSettings,
+    auth: AuthSettings,
 }
 
 #[derive(Deserialize, Debug)]
@@ -36,6 +55,9 @@ async fn main() -> Result<(), Error> {
     // .envファイルから環境変数をロード
     dotenv().ok();
 
+    // ログの出力先を初期化. 出力するレベルは環境変数RUST_LOGで設定する
+    env_logger::init();
+
     // アプリケーション設定を取得
     let app_settings: AppSettings = load_app_config().unwrap();
 
@@ -45,8 +67,17 @@ async fn main() -> Result<(), Error> {
     // データベースプールを作成し, MySqlに接続
     let pool: Pool<MySql> = MySqlPool::connect(&database_url).await.unwrap();
 
+    // Firebase ID トークンの検証に使用する公開鍵を取得
+    let verifier: Arc<TokenVerifier> = Arc::new(TokenVerifier::new(&app_settings.auth).await.unwrap());
+
+    // 応募・スカウトの出来事の配信先. REDIS_URLが設定されていない場合はデータベースに配信する
+    let publisher: Arc<dyn EventPublisher> = create_event_publisher(pool.clone()).unwrap();
+
+    // 決済サービス. Webhookの秘密鍵が設定されていない場合は起動しない
+    let payment_provider: Arc<dyn PaymentProvider> = create_payment_provider().unwrap();
+
     // --- AWS Lambda上で動かす場合 ---
-    // let app: Router<(), Body> = create_router(pool)
+    // let app: Router<(), Body> = create_router(pool, verifier, publisher, payment_provider)
     //     .layer(create_cors_layer(&app_settings))
     //     .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
 
@@ -54,7 +85,12 @@ async fn main() -> Result<(), Error> {
     // ---------------------------
 
     // --- ローカル or EC2上で動かす場合 ---
-    let app: Router = create_router(pool)
+    // 募集締切日を過ぎたスカウトを定期的に期限切れにする
+    tokio::spawn(expire_scouts(pool.clone()));
+    // 無料体験が終了した団体に定期的に請求する
+    tokio::spawn(settle_trials(pool.clone(), payment_provider.clone()));
+
+    let app: Router = create_router(pool, verifier, publisher, payment_provider)
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .layer(create_cors_layer(&app_settings));
 
@@ -70,6 +106,34 @@ async fn main() -> Result<(), Error> {
     // ---------------------------
 }
 
+/// 募集締切日を過ぎた返答待ちのスカウトを一定間隔で期限切れにする関数
+///
+/// AWS Lambda上で動かす場合は, 定期実行のイベントから同様に呼び出す
+async fn expire_scouts(pool: MySqlPool) {
+    let repository: ScoutImpl = ScoutImpl::new(pool);
+    let mut interval = tokio::time::interval(SCOUT_EXPIRY_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(error) = repository.expire_overdue().await {
+            log::error!("failed to expire scouts: {}", error);
+        }
+    }
+}
+
+/// 無料体験が終了した団体に一定間隔で請求する関数
+///
+/// AWS Lambda上で動かす場合は, 定期実行のイベントから同様に呼び出す
+async fn settle_trials(pool: MySqlPool, provider: Arc<dyn PaymentProvider>) {
+    let repository: BillingImpl = BillingImpl::new(pool);
+    let mut interval = tokio::time::interval(TRIAL_SETTLEMENT_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(error) = settle_ended_trials(&repository, provider.as_ref()).await {
+            log::error!("failed to settle ended trials: {}", error);
+        }
+    }
+}
+
 /// アプリケーション設定を読み込む関数
 fn load_app_config() -> Result<AppSettings> {
     // Configオブジェクトを構築し, 設定ファイルを読み込む
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use jsonwebtoken::jwk::JwkSet;
+
+/// Firebase ID トークンの署名検証に使用する公開鍵の取得先
+#[derive(Debug, Clone)]
+pub enum JwksSource {
+    /// URLから取得する
+    Url(String),
+    /// ローカルのファイルから読み込む. オフラインでのテストに使用する
+    File(String),
+}
+
+impl JwksSource {
+    /// 公開鍵の一覧を取得する
+    pub async fn load(&self) -> Result<JwkSet> {
+        match self {
+            JwksSource::Url(url) => {
+                let jwks: JwkSet = reqwest::get(url)
+                    .await?
+                    .error_for_status()?
+                    .json::<JwkSet>()
+                    .await?;
+                Ok(jwks)
+            }
+            JwksSource::File(path) => {
+                let contents: String = tokio::fs::read_to_string(path).await?;
+                let jwks: JwkSet = serde_json::from_str(&contents)?;
+                Ok(jwks)
+            }
+        }
+    }
+
+    /// 取得し直すことで公開鍵が更新される可能性があるか
+    pub fn is_refreshable(&self) -> bool {
+        matches!(self, JwksSource::Url(_))
+    }
+}
@@ -0,0 +1,324 @@
+use std::{
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+
+use domain::model::user_account::user_id::UserId;
+
+use crate::jwks::JwksSource;
+
+/// Firebase ID トークンの署名に使われる公開鍵のURL
+pub const FIREBASE_JWKS_URL: &str =
+    "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
+
+/// Firebase ID トークンの発行者
+const FIREBASE_ISSUER: &str = "https://securetoken.google.com/";
+
+/// テスト用の鍵を置くディレクトリ. 秘密鍵もリポジトリに含まれるため, テスト以外では使用しない
+const TEST_FIXTURES_DIR: &str = "fixtures";
+
+/// 未知の`kid`のトークンで公開鍵を取得し直す最小の間隔
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 認証の設定
+///
+/// `jwks_file`が指定されている場合は`jwks_url`より優先する
+#[derive(Deserialize, Debug, Clone)]
+pub struct AuthSettings {
+    pub project_id: String,
+    pub jwks_url: Option<String>,
+    pub jwks_file: Option<String>,
+}
+
+impl AuthSettings {
+    pub fn jwks_source(&self) -> JwksSource {
+        match (&self.jwks_file, &self.jwks_url) {
+            (Some(path), _) => JwksSource::File(path.clone()),
+            (None, Some(url)) => JwksSource::Url(url.clone()),
+            (None, None) => JwksSource::Url(FIREBASE_JWKS_URL.to_string()),
+        }
+    }
+
+    /// `jwks_file`がテスト用の鍵を指しているか
+    pub fn uses_test_fixture(&self) -> bool {
+        self.jwks_file.as_deref().is_some_and(|path| {
+            Path::new(path)
+                .components()
+                .any(|component| component.as_os_str() == TEST_FIXTURES_DIR)
+        })
+    }
+}
+
+/// Firebase ID トークンのクレーム
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirebaseClaims {
+    pub sub: String,
+    pub aud: String,
+    pub iss: String,
+    pub iat: u64,
+    pub exp: u64,
+    /// ユーザーのメールアドレス. メールアドレスを登録していない場合は含まれない
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// トークンの検証に成功したユーザー
+#[derive(Debug, Clone)]
+pub struct VerifiedUser {
+    pub uid: UserId,
+    pub email: Option<String>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("authorization header is missing")]
+    MissingToken,
+    #[error("kid is missing in token header")]
+    MissingKid,
+    #[error("signing key not found (kid: {0})")]
+    UnknownKid(String),
+    #[error("invalid token: {0}")]
+    InvalidToken(String),
+    #[error("invalid subject")]
+    InvalidSubject,
+}
+
+/// Firebase ID トークンを検証する構造体
+pub struct TokenVerifier {
+    project_id: String,
+    source: JwksSource,
+    jwks: RwLock<JwkSet>,
+    /// 最後に公開鍵を取得した時刻
+    last_refresh: Mutex<Instant>,
+}
+
+impl TokenVerifier {
+    /// 設定に従って公開鍵を取得し, 検証器を作成する
+    pub async fn new(settings: &AuthSettings) -> Result<TokenVerifier> {
+        if settings.uses_test_fixture() {
+            log::warn!(
+                "jwks_file points to a test fixture whose private key is public. do not use it outside tests"
+            );
+        }
+        let source: JwksSource = settings.jwks_source();
+        let jwks: JwkSet = source.load().await?;
+        Ok(TokenVerifier {
+            project_id: settings.project_id.clone(),
+            source,
+            jwks: RwLock::new(jwks),
+            last_refresh: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// トークンを検証し, 認証されたユーザーのIDを返す
+    pub async fn verify(&self, token: &str) -> Result<UserId> {
+        Ok(self.verify_user(token).await?.uid)
+    }
+
+    /// トークンを検証し, 認証されたユーザーのIDとメールアドレスを返す
+    ///
+    /// 未知の`kid`の場合は公開鍵を1度だけ取得し直す. ただし前回の取得から[MIN_REFRESH_INTERVAL]が経つまでは取得しない
+    pub async fn verify_user(&self, token: &str) -> Result<VerifiedUser> {
+        let header = decode_header(token).map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+        let kid: String = header.kid.ok_or(AuthError::MissingKid)?;
+
+        let key: DecodingKey = match self.find_key(&kid).await? {
+            Some(key) => key,
+            None if self.source.is_refreshable() => {
+                self.refresh().await?;
+                self.find_key(&kid)
+                    .await?
+                    .ok_or(AuthError::UnknownKid(kid))?
+            }
+            None => return Err(AuthError::UnknownKid(kid).into()),
+        };
+
+        let mut validation: Validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&[&self.project_id]);
+        validation.set_issuer(&[format!("{}{}", FIREBASE_ISSUER, self.project_id)]);
+        validation.set_required_spec_claims(&["exp", "aud", "iss", "sub"]);
+
+        let claims: FirebaseClaims = decode::<FirebaseClaims>(token, &key, &validation)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))?
+            .claims;
+
+        let uid: UserId = UserId::from_str(&claims.sub).map_err(|_| AuthError::InvalidSubject)?;
+        Ok(VerifiedUser {
+            uid,
+            email: claims.email,
+        })
+    }
+
+    /// 公開鍵を取得し直す. 前回の取得から[MIN_REFRESH_INTERVAL]が経っていない場合は取得しない
+    ///
+    /// 未知の`kid`のトークンを繰り返し送られても, 公開鍵の取得先にリクエストが集中しないようにする
+    async fn refresh(&self) -> Result<()> {
+        let mut last_refresh = self.last_refresh.lock().await;
+        if last_refresh.elapsed() < MIN_REFRESH_INTERVAL {
+            return Ok(());
+        }
+        let jwks: JwkSet = self.source.load().await?;
+        *self.jwks.write().await = jwks;
+        *last_refresh = Instant::now();
+        Ok(())
+    }
+
+    async fn find_key(&self, kid: &str) -> Result<Option<DecodingKey>> {
+        let jwks = self.jwks.read().await;
+        match jwks.find(kid) {
+            Some(jwk) => Ok(Some(DecodingKey::from_jwk(jwk)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_verifier {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    use super::*;
+
+    const PROJECT_ID: &str = "volunscout-test";
+    const UID: &str = "abcdefghijklmnopqrstuvwxyz01";
+
+    async fn verifier() -> TokenVerifier {
+        let settings = AuthSettings {
+            project_id: PROJECT_ID.to_string(),
+            jwks_url: None,
+            jwks_file: Some(
+                concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/test-jwks.json").to_string(),
+            ),
+        };
+        TokenVerifier::new(&settings).await.unwrap()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    fn claims() -> FirebaseClaims {
+        FirebaseClaims {
+            sub: UID.to_string(),
+            aud: PROJECT_ID.to_string(),
+            iss: format!("{}{}", FIREBASE_ISSUER, PROJECT_ID),
+            iat: now(),
+            exp: now() + 3600,
+            email: Some("participant@example.com".to_string()),
+        }
+    }
+
+    fn sign(claims: &FirebaseClaims, kid: &str) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(kid.to_string());
+        let key =
+            EncodingKey::from_rsa_pem(include_bytes!("../fixtures/test-private-key.pem")).unwrap();
+        encode(&header, claims, &key).unwrap()
+    }
+
+    fn auth_error(error: anyhow::Error) -> AuthError {
+        error.downcast::<AuthError>().unwrap()
+    }
+
+    #[tokio::test]
+    async fn valid_token() {
+        let token = sign(&claims(), "test-key");
+        let uid = verifier().await.verify(&token).await.unwrap();
+        assert_eq!(uid.to_string(), UID);
+
+        let user = verifier().await.verify_user(&token).await.unwrap();
+        assert_eq!(user.email.as_deref(), Some("participant@example.com"));
+    }
+
+    #[tokio::test]
+    async fn other_project() {
+        let mut claims = claims();
+        claims.aud = "other-project".to_string();
+        let token = sign(&claims, "test-key");
+        let error = verifier().await.verify(&token).await.unwrap_err();
+        assert!(matches!(auth_error(error), AuthError::InvalidToken(_)));
+    }
+
+    #[tokio::test]
+    async fn other_issuer() {
+        let mut claims = claims();
+        claims.iss = "https://example.com/".to_string();
+        let token = sign(&claims, "test-key");
+        assert!(verifier().await.verify(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn expired() {
+        let mut claims = claims();
+        claims.iat = now() - 7200;
+        claims.exp = now() - 3600;
+        let token = sign(&claims, "test-key");
+        assert!(verifier().await.verify(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn unknown_kid() {
+        let token = sign(&claims(), "unknown-key");
+        let error = verifier().await.verify(&token).await.unwrap_err();
+        assert_eq!(
+            auth_error(error),
+            AuthError::UnknownKid("unknown-key".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_interval() {
+        // 取得できないURLを指定し, 取得し直したかをエラーで確認する
+        let verifier = TokenVerifier {
+            source: JwksSource::Url("http://127.0.0.1:1/jwks.json".to_string()),
+            ..verifier().await
+        };
+        let token = sign(&claims(), "unknown-key");
+
+        // 前回の取得から間隔が経っていない場合は取得し直さない
+        let error = verifier.verify(&token).await.unwrap_err();
+        assert_eq!(
+            auth_error(error),
+            AuthError::UnknownKid("unknown-key".to_string())
+        );
+
+        *verifier.last_refresh.lock().await = Instant::now() - MIN_REFRESH_INTERVAL;
+        let error = verifier.verify(&token).await.unwrap_err();
+        assert!(error.downcast_ref::<AuthError>().is_none());
+    }
+
+    #[tokio::test]
+    async fn invalid_subject() {
+        let mut claims = claims();
+        claims.sub = "short".to_string();
+        let token = sign(&claims, "test-key");
+        let error = verifier().await.verify(&token).await.unwrap_err();
+        assert_eq!(auth_error(error), AuthError::InvalidSubject);
+    }
+
+    #[tokio::test]
+    async fn malformed() {
+        let error = verifier().await.verify("not-a-token").await.unwrap_err();
+        assert!(matches!(auth_error(error), AuthError::InvalidToken(_)));
+    }
+
+    #[test]
+    fn uses_test_fixture() {
+        let settings = |jwks_file: Option<&str>| AuthSettings {
+            project_id: PROJECT_ID.to_string(),
+            jwks_url: None,
+            jwks_file: jwks_file.map(str::to_string),
+        };
+        assert!(settings(Some("auth/fixtures/test-jwks.json")).uses_test_fixture());
+        assert!(!settings(Some("/etc/volunscout/jwks.json")).uses_test_fixture());
+        assert!(!settings(None).uses_test_fixture());
+    }
+}
@@ -2,3 +2,5 @@ pub mod volunteer;
 pub mod apply;
 pub mod scout;
 pub mod review;
+pub mod message;
+pub mod attendance;
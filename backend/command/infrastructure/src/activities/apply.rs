@@ -1,31 +1,126 @@
+use std::str::FromStr;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Utc;
-use futures::future;
-use sqlx::MySqlPool;
+use sqlx::{MySqlConnection, MySqlPool};
 
 use command_repository::activities::apply::ApplyRepository;
 use domain::model::{
-    apply::ApplyId,
+    apply::{
+        ensure_cancellable, ensure_capacity, select_promotions, ApplyId, ApplyStatus, CancelReason,
+        WaitlistEntry,
+    },
+    notification::NotificationKind,
+    schedule::{ScheduleError, SessionId},
     user_account::user_id::UserId, volunteer::VolunteerId, group_participants::GroupParticipants, gender::gender_to_i8
 };
 
+use crate::{outbox::enqueue_apply_notification, transaction::Database};
+
 pub struct ApplyImpl {
-    pool: MySqlPool,
+    db: Database,
 }
 
 impl ApplyImpl {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self { db: Database::Pool(pool) }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
     }
 }
 
+/// 応募する回を決め, 回IDを返す
+///
+/// 回を指定しない場合は, 回が1つだけのボランティアのみその回とする
+async fn resolve_session(
+    conn: &mut MySqlConnection,
+    vid: &str,
+    vsid: Option<SessionId>,
+) -> Result<String> {
+    let sessions = sqlx::query!(
+        "SELECT vsid, start_at FROM volunteer_session WHERE vid = ? AND is_deleted = false",
+        vid
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let session = match vsid {
+        Some(vsid) => sessions
+            .into_iter()
+            .find(|session| session.vsid == vsid.to_string())
+            .ok_or(ScheduleError::SessionNotFound)?,
+        None if sessions.len() > 1 => return Err(ScheduleError::SessionRequired.into()),
+        None => sessions
+            .into_iter()
+            .next()
+            .ok_or(ScheduleError::SessionNotFound)?,
+    };
+
+    if session.start_at.and_utc() <= Utc::now() {
+        return Err(ScheduleError::SessionStarted.into());
+    }
+
+    Ok(session.vsid)
+}
+
+/// 回の承認済の人数を取得する. 集団応募はメンバー数, 個人応募は1人として数える
+async fn approved_headcount(conn: &mut MySqlConnection, vsid: &str) -> Result<u32> {
+    let approved = sqlx::query!(
+        r#"
+        SELECT CAST(COALESCE(SUM(
+            CASE WHEN apply.as_group THEN GREATEST((SELECT COUNT(*) FROM group_participants WHERE gpid = apply.aid), 1) ELSE 1 END
+        ), 0) AS UNSIGNED) as "approved_num!: u64"
+        FROM apply
+        WHERE vsid = ? AND allowed_status = ?
+        "#,
+        vsid,
+        ApplyStatus::Approved.to_u8()
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(approved.approved_num as u32)
+}
+
+/// 回の未承認の応募を応募の古い順に取得する
+async fn find_waitlist(conn: &mut MySqlConnection, vsid: &str) -> Result<Vec<WaitlistEntry>> {
+    let pending = sqlx::query!(
+        r#"
+        SELECT aid, CAST(
+            CASE WHEN as_group THEN GREATEST((SELECT COUNT(*) FROM group_participants WHERE gpid = apply.aid), 1) ELSE 1 END
+        AS UNSIGNED) as "headcount!: u64"
+        FROM apply
+        WHERE vsid = ? AND allowed_status = ?
+        ORDER BY applied_at, aid
+        FOR UPDATE
+        "#,
+        vsid,
+        ApplyStatus::Pending.to_u8()
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    pending
+        .into_iter()
+        .map(|apply| {
+            Ok(WaitlistEntry {
+                aid: ApplyId::from_str(&apply.aid)?,
+                headcount: apply.headcount as u32,
+            })
+        })
+        .collect()
+}
+
 #[async_trait]
 impl ApplyRepository for ApplyImpl {
     async fn create(
         &self,
         aid: ApplyId,
         vid: VolunteerId,
+        vsid: Option<SessionId>,
         user_id: UserId,
         as_group: bool,
         members: Option<Vec<GroupParticipants>>
@@ -34,70 +129,182 @@ impl ApplyRepository for ApplyImpl {
         let vid: String = vid.to_string();
         let uid: String = user_id.to_string();
 
+        let mut tx = self.db.begin().await?;
+
+        let vsid: String = resolve_session(&mut tx, &vid, vsid).await?;
+
         sqlx::query!(
-            "INSERT INTO apply (aid, vid, uid, applied_at, as_group) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO apply (aid, vid, vsid, uid, applied_at, as_group) VALUES (?, ?, ?, ?, ?, ?)",
             aid,
             vid,
+            vsid,
             uid,
             Utc::now(),
             as_group
-        ).execute(&self.pool).await?;
-
-        match members {
-            Some(mems) => {
-                let insert_members_query: Vec<_> = mems
-                    .iter()
-                    .map(|gp: &GroupParticipants| {
-                        sqlx::query!(
-                            "INSERT INTO group_participants (gpid, serial, name, furigana, gender, age) VALUES (?, ?, ?, ?, ?, ?)",
-                            aid,
-                            gp.serial as i16,
-                            gp.name.to_string(),
-                            gp.furigana.to_string(),
-                            gender_to_i8(&gp.gender).unwrap(),
-                            gp.age as u8
-                        ).execute(&self.pool)
-                    })
-                    .collect::<Vec<_>>();
-
-                future::try_join_all(
-                    insert_members_query
-                        .into_iter()
-                )
-                .await?;
-            }
-            None => {}
+        ).execute(&mut *tx).await?;
+
+        for gp in members.unwrap_or_default() {
+            sqlx::query!(
+                "INSERT INTO group_participants (gpid, serial, name, furigana, gender, age) VALUES (?, ?, ?, ?, ?, ?)",
+                aid,
+                gp.serial as i16,
+                gp.name.to_string(),
+                gp.furigana.to_string(),
+                gender_to_i8(&gp.gender)?,
+                gp.age as u8
+            ).execute(&mut *tx).await?;
         }
 
+        enqueue_apply_notification(&mut tx, NotificationKind::ApplyReceived, &aid).await?;
+
+        tx.commit().await?;
+
         Ok(())
     }
 
     async fn update_allowed_status(
         &self,
         aid: ApplyId,
-        allowed_status: u8
+        allowed_status: ApplyStatus
     ) -> Result<()> {
         let aid: String = aid.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        let vsid = sqlx::query!(
+            "SELECT vsid FROM apply WHERE aid = ?",
+            aid
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .vsid;
+
+        // 同時に承認されて募集人数を超えないよう, 回の行をロックする
+        let session = sqlx::query!(
+            "SELECT recruited_num FROM volunteer_session WHERE vsid = ? FOR UPDATE",
+            vsid
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let apply = sqlx::query!(
+            r#"
+            SELECT allowed_status as "allowed_status: u8", as_group as "as_group: bool" FROM apply WHERE aid = ? FOR UPDATE
+            "#,
+            aid
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let current: ApplyStatus = ApplyStatus::from_u8(apply.allowed_status)?;
+        let next: ApplyStatus = current.transition_to(allowed_status)?;
+
+        if next == ApplyStatus::Approved {
+            let approved_num: u32 = approved_headcount(&mut tx, &vsid).await?;
+
+            let requested_num: u32 = if apply.as_group {
+                let members = sqlx::query!(
+                    "SELECT COUNT(*) as count FROM group_participants WHERE gpid = ?",
+                    aid
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                std::cmp::max(members.count as u32, 1)
+            } else {
+                1
+            };
+
+            ensure_capacity(session.recruited_num, approved_num, requested_num)?;
+        }
+
         sqlx::query!(
             "UPDATE apply SET allowed_status = ?, decided_at = ? WHERE aid = ?",
-            allowed_status,
+            next.to_u8(),
             Utc::now(),
             aid
         )
-        .execute(&self.pool).await?;
+        .execute(&mut *tx).await?;
+
+        match next {
+            ApplyStatus::Approved => {
+                enqueue_apply_notification(&mut tx, NotificationKind::ApplyApproved, &aid).await?
+            }
+            ApplyStatus::Rejected => {
+                enqueue_apply_notification(&mut tx, NotificationKind::ApplyRejected, &aid).await?
+            }
+            ApplyStatus::Pending | ApplyStatus::Cancelled => {}
+        }
+
+        tx.commit().await?;
 
         Ok(())
     }
 
-    async fn update_is_sent(&self, aid: ApplyId) -> Result<()> {
-
+    async fn cancel(&self, aid: ApplyId, reason: CancelReason) -> Result<Vec<ApplyId>> {
         let aid: String = aid.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        let vsid = sqlx::query!(
+            "SELECT vsid FROM apply WHERE aid = ?",
+            aid
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .vsid;
+
+        // 繰り上げと団体による承認が同時に行われて募集人数を超えないよう, 回の行をロックする
+        let session = sqlx::query!(
+            "SELECT recruited_num, start_at FROM volunteer_session WHERE vsid = ? FOR UPDATE",
+            vsid
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let apply = sqlx::query!(
+            r#"SELECT allowed_status as "allowed_status: u8" FROM apply WHERE aid = ? FOR UPDATE"#,
+            aid
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let current: ApplyStatus = ApplyStatus::from_u8(apply.allowed_status)?;
+        let next: ApplyStatus = ensure_cancellable(current, session.start_at.and_utc(), Utc::now())?;
+
         sqlx::query!(
-            "UPDATE apply SET is_sent = ? WHERE aid = ?",
-            true,
+            "UPDATE apply SET allowed_status = ?, cancel_reason = ?, cancelled_at = ? WHERE aid = ?",
+            next.to_u8(),
+            reason.to_string(),
+            Utc::now(),
             aid
         )
-        .execute(&self.pool).await?;
-        Ok(())
+        .execute(&mut *tx).await?;
+
+        enqueue_apply_notification(&mut tx, NotificationKind::ApplyCancelled, &aid).await?;
+
+        // 未承認の応募の取消では枠が空かないため, 繰り上げない
+        let promoted: Vec<ApplyId> = if current == ApplyStatus::Approved {
+            let approved_num: u32 = approved_headcount(&mut tx, &vsid).await?;
+            let waitlist: Vec<WaitlistEntry> = find_waitlist(&mut tx, &vsid).await?;
+            select_promotions(session.recruited_num, approved_num, &waitlist)
+        } else {
+            vec![]
+        };
+
+        for promoted_aid in &promoted {
+            let promoted_aid: String = promoted_aid.to_string();
+            sqlx::query!(
+                "UPDATE apply SET allowed_status = ?, decided_at = ? WHERE aid = ?",
+                ApplyStatus::Approved.to_u8(),
+                Utc::now(),
+                promoted_aid
+            )
+            .execute(&mut *tx).await?;
+            enqueue_apply_notification(&mut tx, NotificationKind::ApplyApproved, &promoted_aid).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(promoted)
     }
 }
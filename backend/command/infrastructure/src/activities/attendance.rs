@@ -0,0 +1,235 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{MySqlConnection, MySqlPool};
+
+use command_repository::activities::attendance::AttendanceRepository;
+use domain::model::{
+    apply::{ApplyId, ApplyStatus},
+    attendance::{
+        ensure_check_in_open, ensure_recordable, summarize, AttendanceError, AttendanceRecord,
+        AttendanceStatus, CheckInCode,
+    },
+    volunteer::VolunteerId,
+};
+
+use crate::transaction::Database;
+
+pub struct AttendanceImpl {
+    db: Database,
+}
+
+impl AttendanceImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            db: Database::Pool(pool),
+        }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+/// 出欠を記録する承認済の応募
+struct ApprovedApply {
+    vid: String,
+    as_group: bool,
+    start_at: DateTime<Utc>,
+    finish_at: DateTime<Utc>,
+}
+
+/// 応募と応募した回の日時を取得し, 応募の行をロックする. 承認済の応募でない場合はエラーを返す
+async fn lock_approved_apply(conn: &mut MySqlConnection, aid: &str) -> Result<ApprovedApply> {
+    let apply = sqlx::query!(
+        r#"
+        SELECT
+            apply.vid, apply.as_group as "as_group: bool", apply.allowed_status as "allowed_status: u8",
+            volunteer_session.start_at, volunteer_session.finish_at
+        FROM apply
+        INNER JOIN volunteer_session ON apply.vsid = volunteer_session.vsid
+        WHERE apply.aid = ?
+        FOR UPDATE
+        "#,
+        aid
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if ApplyStatus::from_u8(apply.allowed_status)? != ApplyStatus::Approved {
+        return Err(AttendanceError::NotApproved.into());
+    }
+
+    Ok(ApprovedApply {
+        vid: apply.vid,
+        as_group: apply.as_group,
+        start_at: apply.start_at.and_utc(),
+        finish_at: apply.finish_at.and_utc(),
+    })
+}
+
+/// 応募の出欠を更新する
+async fn update_apply_status(
+    conn: &mut MySqlConnection,
+    aid: &str,
+    status: AttendanceStatus,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query!(
+        "UPDATE apply SET attendance_status = ?, attendance_recorded_at = ? WHERE aid = ?",
+        status.to_u8(),
+        now,
+        aid
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl AttendanceRepository for AttendanceImpl {
+    async fn issue_code(&self, vid: VolunteerId, code: CheckInCode) -> Result<()> {
+        let vid: String = vid.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        // 終了していない回のうち, 最も早い回のチェックインを受け付ける
+        let session = sqlx::query!(
+            r#"
+            SELECT start_at, finish_at FROM volunteer_session
+            WHERE vid = ? AND is_deleted = false AND finish_at > ?
+            ORDER BY start_at
+            LIMIT 1
+            "#,
+            vid,
+            code.issued_at
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AttendanceError::CheckInClosed)?;
+        ensure_check_in_open(
+            session.start_at.and_utc(),
+            session.finish_at.and_utc(),
+            code.issued_at,
+        )?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO check_in_code (vid, code, issued_at, expires_at)
+            VALUES (?, ?, ?, ?)
+            ON DUPLICATE KEY UPDATE
+                code = VALUES(code),
+                issued_at = VALUES(issued_at),
+                expires_at = VALUES(expires_at)
+            "#,
+            vid,
+            code.code,
+            code.issued_at,
+            code.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn check_in(&self, aid: ApplyId, code: String) -> Result<()> {
+        let aid: String = aid.to_string();
+        let now: DateTime<Utc> = Utc::now();
+
+        let mut tx = self.db.begin().await?;
+
+        let apply: ApprovedApply = lock_approved_apply(&mut tx, &aid).await?;
+        ensure_check_in_open(apply.start_at, apply.finish_at, now)?;
+
+        let issued = sqlx::query!(
+            "SELECT code, issued_at, expires_at FROM check_in_code WHERE vid = ?",
+            apply.vid
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AttendanceError::InvalidCode)?;
+        CheckInCode {
+            code: issued.code,
+            issued_at: issued.issued_at.and_utc(),
+            expires_at: issued.expires_at.and_utc(),
+        }
+        .verify(&code, now)?;
+
+        // 団体が欠席と記録した後でも, コードでチェックインした場合は出席とする
+        update_apply_status(&mut tx, &aid, AttendanceStatus::Attended, now).await?;
+        sqlx::query!(
+            "UPDATE apply SET checked_in_at = COALESCE(checked_in_at, ?) WHERE aid = ?",
+            now,
+            aid
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn record(&self, aid: ApplyId, record: AttendanceRecord) -> Result<()> {
+        let aid: String = aid.to_string();
+        let now: DateTime<Utc> = Utc::now();
+
+        let mut tx = self.db.begin().await?;
+
+        let apply: ApprovedApply = lock_approved_apply(&mut tx, &aid).await?;
+        ensure_recordable(&record, apply.start_at, now)?;
+
+        let status: AttendanceStatus = match record {
+            AttendanceRecord::Apply(status) => status,
+            AttendanceRecord::Members(members) => {
+                if !apply.as_group {
+                    return Err(AttendanceError::NotGroupApply.into());
+                }
+                for member in &members {
+                    let updated = sqlx::query!(
+                        "UPDATE group_participants SET attendance_status = ? WHERE gpid = ? AND serial = ?",
+                        member.status.to_u8(),
+                        aid,
+                        member.serial
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                    if updated.rows_affected() == 0 {
+                        // 記録済みで変更がない場合も0件になるため, メンバーが存在するか確認する
+                        let exists = sqlx::query!(
+                            r#"SELECT EXISTS(SELECT 1 FROM group_participants WHERE gpid = ? AND serial = ?) as "exists: bool""#,
+                            aid,
+                            member.serial
+                        )
+                        .fetch_one(&mut *tx)
+                        .await?;
+                        if !exists.exists {
+                            return Err(AttendanceError::MemberNotFound {
+                                serial: member.serial,
+                            }
+                            .into());
+                        }
+                    }
+                }
+
+                let statuses = sqlx::query!(
+                    r#"SELECT attendance_status as "attendance_status: u8" FROM group_participants WHERE gpid = ?"#,
+                    aid
+                )
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .map(|member| AttendanceStatus::from_u8(member.attendance_status))
+                .collect::<Result<Vec<AttendanceStatus>>>()?;
+                summarize(&statuses)
+            }
+        };
+
+        update_apply_status(&mut tx, &aid, status, now).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
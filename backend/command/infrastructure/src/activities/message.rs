@@ -0,0 +1,204 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{MySqlConnection, MySqlPool};
+
+use command_repository::activities::message::MessageRepository;
+use domain::model::{
+    message::{Message, MessageError, MessageId, MessageThread, ThreadId, ThreadSubject},
+    user_account::user_id::UserId,
+};
+
+use crate::transaction::Database;
+
+pub struct MessageImpl {
+    db: Database,
+}
+
+impl MessageImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            db: Database::Pool(pool),
+        }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+/// 応募・スカウトのスレッドを取得する. スレッドがない場合は応募・スカウトの団体と参加者のスレッドを作成する
+///
+/// 同時に最初のメッセージが送信された場合も, スレッドは1つだけ作成する
+async fn find_or_create_thread(
+    conn: &mut MySqlConnection,
+    subject: &ThreadSubject,
+) -> Result<String> {
+    let thread: MessageThread = MessageThread::new(subject.clone());
+    let now: DateTime<Utc> = Utc::now();
+
+    let tid = match subject {
+        ThreadSubject::Apply(aid) => {
+            sqlx::query!(
+                r#"
+                INSERT INTO message_thread (tid, aid, vid, gid, uid, created_at)
+                SELECT ?, apply.aid, apply.vid, volunteer.gid, apply.uid, ?
+                FROM apply
+                INNER JOIN volunteer ON apply.vid = volunteer.vid
+                WHERE apply.aid = ?
+                ON DUPLICATE KEY UPDATE tid = tid
+                "#,
+                thread.tid.to_string(),
+                now,
+                aid.to_string()
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            sqlx::query!(
+                "SELECT tid FROM message_thread WHERE aid = ?",
+                aid.to_string()
+            )
+            .fetch_one(&mut *conn)
+            .await?
+            .tid
+        }
+        ThreadSubject::Scout(sid) => {
+            sqlx::query!(
+                r#"
+                INSERT INTO message_thread (tid, sid, vid, gid, uid, created_at)
+                SELECT ?, scout.sid, scout.vid, volunteer.gid, scout.uid, ?
+                FROM scout
+                INNER JOIN volunteer ON scout.vid = volunteer.vid
+                WHERE scout.sid = ?
+                ON DUPLICATE KEY UPDATE tid = tid
+                "#,
+                thread.tid.to_string(),
+                now,
+                sid.to_string()
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            sqlx::query!(
+                "SELECT tid FROM message_thread WHERE sid = ?",
+                sid.to_string()
+            )
+            .fetch_one(&mut *conn)
+            .await?
+            .tid
+        }
+    };
+
+    Ok(tid)
+}
+
+/// `uid`のユーザーが`mid`までのメッセージを読んだことを記録する
+async fn upsert_read(conn: &mut MySqlConnection, tid: &str, uid: &str, mid: &str) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO message_read (tid, uid, last_read_mid, read_at)
+        VALUES (?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            last_read_mid = GREATEST(last_read_mid, VALUES(last_read_mid)),
+            read_at = VALUES(read_at)
+        "#,
+        tid,
+        uid,
+        mid,
+        Utc::now()
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl MessageRepository for MessageImpl {
+    async fn send(&self, subject: ThreadSubject, message: Message) -> Result<ThreadId> {
+        let mid: String = message.mid.to_string();
+        let sender: String = message.sender.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        let tid: String = find_or_create_thread(&mut tx, &subject).await?;
+
+        sqlx::query!(
+            "INSERT INTO message (mid, tid, sender, body, sent_at) VALUES (?, ?, ?, ?, ?)",
+            mid,
+            tid,
+            sender,
+            message.body,
+            message.sent_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE message_thread SET last_message_at = ? WHERE tid = ?",
+            message.sent_at,
+            tid
+        )
+        .execute(&mut *tx)
+        .await?;
+        upsert_read(&mut tx, &tid, &sender, &mid).await?;
+
+        tx.commit().await?;
+
+        ThreadId::from_str(&tid)
+    }
+
+    async fn mark_read(&self, tid: ThreadId, uid: UserId) -> Result<()> {
+        let tid: String = tid.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        let latest = sqlx::query!(
+            r#"SELECT MAX(mid) as "mid: String" FROM message WHERE tid = ?"#,
+            tid
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // メッセージがないスレッドは既読にするものがない
+        if let Some(mid) = latest.mid {
+            upsert_read(&mut tx, &tid, &uid.to_string(), &mid).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, mid: MessageId) -> Result<()> {
+        let mid: String = mid.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        let message = sqlx::query!(
+            r#"SELECT is_deleted as "is_deleted: bool" FROM message WHERE mid = ? FOR UPDATE"#,
+            mid
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match message {
+            None => return Err(MessageError::NotFound.into()),
+            Some(message) if message.is_deleted => return Err(MessageError::AlreadyDeleted.into()),
+            Some(_) => {}
+        }
+
+        sqlx::query!(
+            "UPDATE message SET is_deleted = ?, deleted_at = ? WHERE mid = ?",
+            true,
+            Utc::now(),
+            mid
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -7,13 +7,19 @@ use domain::model::{
     user_account::user_id::UserId, volunteer::VolunteerId
 };
 
+use crate::transaction::Database;
+
 pub struct ReviewImpl {
-    pool: MySqlPool,
+    db: Database,
 }
 
 impl ReviewImpl {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self { db: Database::Pool(pool) }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
     }
 }
 
@@ -29,13 +35,16 @@ impl ReviewRepository for ReviewImpl {
         let uid: String = uid.to_string();
         let vid: String = vid.to_string();
 
+        let mut tx = self.db.begin().await?;
+
         sqlx::query!(
             "INSERT INTO volunteer_review (uid, vid, point, comment) VALUES (?, ?, ?, ?)",
             uid,
             vid,
             point,
             comment
-        ).execute(&self.pool).await?;
+        ).execute(&mut *tx).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -49,13 +58,16 @@ impl ReviewRepository for ReviewImpl {
         let uid: String = uid.to_string();
         let vid: String = vid.to_string();
 
+        let mut tx = self.db.begin().await?;
+
         sqlx::query!(
             "INSERT INTO participant_review (uid, vid, point, comment) VALUES (?, ?, ?, ?)",
             uid,
             vid,
             point,
             comment
-        ).execute(&self.pool).await?;
+        ).execute(&mut *tx).await?;
+        tx.commit().await?;
         Ok(())
     }
 }
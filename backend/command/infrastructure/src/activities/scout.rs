@@ -1,21 +1,30 @@
+use std::str::FromStr;
+
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::Utc;
-use sqlx::MySqlPool;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{MySqlConnection, MySqlPool};
 
 use command_repository::activities::scout::ScoutRepository;
 use domain::model::{
-    scout::ScoutId,
+    apply::ApplyId,
+    scout::{Scout, ScoutError, ScoutId, ScoutStatus},
     user_account::user_id::UserId, volunteer::VolunteerId
 };
 
+use crate::{outbox::enqueue_scout_notification, plan::ensure_scout_quota, transaction::Database};
+
 pub struct ScoutImpl {
-    pool: MySqlPool,
+    db: Database,
 }
 
 impl ScoutImpl {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self { db: Database::Pool(pool) }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
     }
 }
 
@@ -32,6 +41,10 @@ impl ScoutRepository for ScoutImpl {
         let vid: String = vid.to_string();
         let uid: String = user_id.to_string();
 
+        let mut tx = self.db.begin().await?;
+
+        ensure_scout_quota(&mut tx, &vid).await?;
+
         sqlx::query!(
             "INSERT INTO scout (sid, vid, uid, message, scouted_at) VALUES (?, ?, ?, ?, ?)",
             sid,
@@ -39,20 +52,9 @@ impl ScoutRepository for ScoutImpl {
             uid,
             message,
             Utc::now()
-        ).execute(&self.pool).await?;
-        Ok(())
-    }
-
-    async fn update_is_sent(&self, sid: ScoutId) -> Result<()> {
-
-        let sid: String = sid.to_string();
-        sqlx::query!(
-            "UPDATE scout SET is_sent = ?, sent_at = ? WHERE sid = ?",
-            true,
-            Utc::now(),
-            sid
-        )
-        .execute(&self.pool).await?;
+        ).execute(&mut *tx).await?;
+        enqueue_scout_notification(&mut tx, &sid).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -61,12 +63,17 @@ impl ScoutRepository for ScoutImpl {
         sid: ScoutId
     ) -> Result<()> {
         let sid: String = sid.to_string();
+        let mut tx = self.db.begin().await?;
+        // 返答前に初めて既読にした場合のみ, 既読の状態に遷移する
         sqlx::query!(
-            "UPDATE scout SET is_read = ? WHERE sid = ?",
+            "UPDATE scout SET is_read = ?, status = IF(status = ?, ?, status) WHERE sid = ?",
             true,
+            ScoutStatus::Sent.to_u8(),
+            ScoutStatus::Read.to_u8(),
             sid
         )
-        .execute(&self.pool).await?;
+        .execute(&mut *tx).await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -75,15 +82,126 @@ impl ScoutRepository for ScoutImpl {
         &self,
         sid: ScoutId
     ) -> Result<()> {
-        let sid: String = sid.to_string();
+        let mut tx = self.db.begin().await?;
+        transition(&mut tx, &sid, ScoutStatus::Declined).await?;
         sqlx::query!(
             "UPDATE scout SET is_denied = ?, denied_at = ? WHERE sid = ?",
             true,
             Utc::now(),
-            sid
+            sid.to_string()
+        )
+        .execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn accept(&self, sid: ScoutId) -> Result<Scout> {
+        let mut tx = self.db.begin().await?;
+        let scout: Scout = transition(&mut tx, &sid, ScoutStatus::Accepted).await?;
+
+        let applied = sqlx::query!(
+            "SELECT EXISTS(SELECT aid FROM apply WHERE vid = ? AND uid = ?) AS exist",
+            scout.sender.to_string(),
+            scout.destination.to_string()
         )
-        .execute(&self.pool).await?;
+        .fetch_one(&mut *tx)
+        .await?;
+        if applied.exist == 1 {
+            return Err(ScoutError::AlreadyApplied.into());
+        }
+
+        tx.commit().await?;
+
+        Ok(scout)
+    }
+
+    async fn link_apply(&self, sid: ScoutId, aid: ApplyId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "INSERT INTO scout_apply (sid, aid) VALUES (?, ?)",
+            sid.to_string(),
+            aid.to_string()
+        )
+        .execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn withdraw(&self, sid: ScoutId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        transition(&mut tx, &sid, ScoutStatus::Withdrawn).await?;
+        tx.commit().await?;
 
         Ok(())
     }
+
+    async fn expire_overdue(&self) -> Result<u64> {
+        let mut tx = self.db.begin().await?;
+        let result = sqlx::query!(
+            r#"
+            UPDATE scout INNER JOIN volunteer ON scout.vid = volunteer.vid
+            SET scout.status = ?, scout.decided_at = ?
+            WHERE scout.status IN (?, ?) AND volunteer.deadline_on < CURDATE()
+            "#,
+            ScoutStatus::Expired.to_u8(),
+            Utc::now(),
+            ScoutStatus::Sent.to_u8(),
+            ScoutStatus::Read.to_u8()
+        )
+        .execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// スカウトの状態を遷移させ, 遷移後のスカウトを返す
+///
+/// 募集締切日を過ぎた返答待ちのスカウトは期限切れとして遷移を判定する
+async fn transition(
+    conn: &mut MySqlConnection,
+    sid: &ScoutId,
+    next: ScoutStatus,
+) -> Result<Scout> {
+    let scout = sqlx::query!(
+        r#"
+        SELECT
+            scout.vid, scout.uid, scout.message, scout.scouted_at as "scouted_at: DateTime<Utc>",
+            scout.is_sent as "is_sent: bool", scout.is_read as "is_read: bool", scout.status as "status: u8",
+            volunteer.deadline_on, CURDATE() as "today!: NaiveDate"
+        FROM scout
+        INNER JOIN volunteer ON scout.vid = volunteer.vid
+        WHERE scout.sid = ?
+        FOR UPDATE
+        "#,
+        sid.to_string()
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let current: ScoutStatus =
+        ScoutStatus::from_u8(scout.status)?.effective(scout.deadline_on, scout.today);
+    let next: ScoutStatus = current.transition_to(next)?;
+
+    sqlx::query!(
+        "UPDATE scout SET status = ?, decided_at = ? WHERE sid = ?",
+        next.to_u8(),
+        Utc::now(),
+        sid.to_string()
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Scout {
+        id: sid.clone(),
+        destination: UserId::from_str(&scout.uid)?,
+        sender: VolunteerId::from_str(&scout.vid)?,
+        message: scout.message,
+        send_at: scout.scouted_at,
+        is_sent: scout.is_sent,
+        is_read: scout.is_read,
+        status: next,
+    })
 }
@@ -1,22 +1,196 @@
+use std::str::FromStr;
+
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::{DateTime, NaiveDate, Utc};
-use futures::future;
-use sqlx::MySqlPool;
+use chrono::{NaiveDate, Utc};
+use sqlx::{MySqlConnection, MySqlPool};
 
 use command_repository::activities::volunteer::VolunteerRepository;
 use domain::model::{
-    condition::Condition, region::Region, target_status::TargetStatus, terms::Terms, theme::Theme, user_account::user_id::UserId, volunteer::VolunteerId
+    apply::ApplyStatus,
+    location::GeoPoint,
+    schedule::{Schedule, ScheduleError, SessionChanges, SessionId, VolunteerSession},
+    terms::Terms, user_account::user_id::UserId, volunteer::VolunteerId
+};
+
+use crate::{
+    plan::{ensure_photo_quota, ensure_volunteer_quota},
+    transaction::Database,
 };
 
 pub struct VolunteerImpl {
-    pool: MySqlPool,
+    db: Database,
 }
 
 impl VolunteerImpl {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self { db: Database::Pool(pool) }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+/// ボランティアの地域・要素・写真を登録する
+async fn insert_terms(
+    conn: &mut MySqlConnection,
+    vid: &str,
+    terms: &Terms,
+    s3_keys: &[String],
+) -> Result<()> {
+    for r in terms.regions.iter() {
+        sqlx::query!(
+            "INSERT INTO volunteer_region (vid, rid) VALUES (?, ?)",
+            vid,
+            r.to_uint()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let elements = terms
+        .target_status
+        .iter()
+        .map(|t| (t.to_id(), false))
+        .chain(terms.themes.iter().map(|t| (t.to_id(), false)))
+        .chain(terms.required_themes.iter().map(|t| (t.to_id(), true)))
+        .chain(terms.conditions.iter().map(|c| (c.to_id(), false)))
+        .chain(terms.required_conditions.iter().map(|c| (c.to_id(), true)));
+
+    for (eid, is_need) in elements {
+        sqlx::query!(
+            "INSERT INTO volunteer_element (vid, eid, is_need) VALUES (?, ?, ?)",
+            vid,
+            eid,
+            is_need
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    for p in s3_keys.iter() {
+        sqlx::query!(
+            "INSERT INTO volunteer_photo VALUES (?, ?)",
+            p,
+            vid
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// ボランティアの開催場所の緯度経度を登録する
+async fn insert_location(
+    conn: &mut MySqlConnection,
+    vid: &str,
+    location: &Option<GeoPoint>,
+) -> Result<()> {
+    if let Some(location) = location {
+        sqlx::query!(
+            "INSERT INTO volunteer_location (vid, location) VALUES (?, ST_PointFromText(?, 4326, 'axis-order=long-lat'))",
+            vid,
+            location.to_wkt()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// ボランティアの回を登録する
+async fn insert_sessions(
+    conn: &mut MySqlConnection,
+    vid: &str,
+    sessions: &[VolunteerSession],
+) -> Result<()> {
+    for session in sessions {
+        sqlx::query!(
+            "INSERT INTO volunteer_session (vsid, vid, start_at, finish_at, recruited_num) VALUES (?, ?, ?, ?, ?)",
+            session.vsid.to_string(),
+            vid,
+            session.start_at,
+            session.finish_at,
+            session.recruited_num
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// 登録済みの回を日程に合わせて更新する. 日程から除いた回は, 応募の参照を残すため論理削除する
+async fn sync_sessions(conn: &mut MySqlConnection, vid: &str, schedule: &Schedule) -> Result<()> {
+    let registered = sqlx::query!(
+        r#"
+        SELECT vsid, start_at, finish_at, recruited_num
+        FROM volunteer_session
+        WHERE vid = ? AND is_deleted = false
+        FOR UPDATE
+        "#,
+        vid
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let registered: Vec<VolunteerSession> = registered
+        .into_iter()
+        .map(|session| {
+            Ok(VolunteerSession {
+                vsid: SessionId::from_str(&session.vsid)?,
+                start_at: session.start_at.and_utc(),
+                finish_at: session.finish_at.and_utc(),
+                recruited_num: session.recruited_num,
+            })
+        })
+        .collect::<Result<Vec<VolunteerSession>>>()?;
+
+    let changes: SessionChanges = schedule.changes_from(&registered);
+
+    for session in &changes.removed {
+        let applied = sqlx::query!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM apply WHERE vsid = ? AND allowed_status IN (?, ?)
+            ) as "exists: bool"
+            "#,
+            session.vsid.to_string(),
+            ApplyStatus::Pending.to_u8(),
+            ApplyStatus::Approved.to_u8()
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        if applied.exists {
+            return Err(ScheduleError::SessionHasApplies {
+                start_at: session.start_at,
+            }
+            .into());
+        }
+
+        sqlx::query!(
+            "UPDATE volunteer_session SET is_deleted = true, deleted_at = ? WHERE vsid = ?",
+            Utc::now(),
+            session.vsid.to_string()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    for session in &changes.updated {
+        sqlx::query!(
+            "UPDATE volunteer_session SET finish_at = ?, recruited_num = ? WHERE vsid = ?",
+            session.finish_at,
+            session.recruited_num,
+            session.vsid.to_string()
+        )
+        .execute(&mut *conn)
+        .await?;
     }
+
+    insert_sessions(conn, vid, &changes.inserted).await
 }
 
 #[async_trait]
@@ -30,8 +204,8 @@ impl VolunteerRepository for VolunteerImpl {
         overview: String,
         recruited_num: u32,
         place: String,
-        start_at: DateTime<Utc>,
-        finish_at: DateTime<Utc>,
+        location: Option<GeoPoint>,
+        schedule: Schedule,
         deadline_on: NaiveDate,
         as_group: bool,
         reward: Option<String>,
@@ -40,8 +214,12 @@ impl VolunteerRepository for VolunteerImpl {
     ) -> Result<()> {
         let id: String = vid.to_string();
 
+        let mut tx = self.db.begin().await?;
+
+        ensure_volunteer_quota(&mut tx, &gid.to_string(), s3_keys.len()).await?;
+
         sqlx::query!(
-            "INSERT INTO volunteer (vid, gid, title, message, overview, recruited_num, place, start_at, finish_at, deadline_on, as_group, reward, registered_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO volunteer (vid, gid, title, message, overview, recruited_num, place, start_at, finish_at, recurrence, deadline_on, as_group, reward, registered_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             id,
             gid.to_string(),
             title,
@@ -49,118 +227,23 @@ impl VolunteerRepository for VolunteerImpl {
             overview,
             recruited_num,
             place,
-            start_at,
-            finish_at,
+            schedule.start_at(),
+            schedule.finish_at(),
+            schedule.recurrence.as_ref().map(|rule| rule.to_string()),
             deadline_on,
             as_group,
             reward,
             Utc::now(),
             Utc::now()
-        ).execute(&self.pool).await?;
-
-        let insert_target_status_query: Vec<_> = terms
-            .target_status
-            .iter()
-            .map(|t: &TargetStatus| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid) VALUES (?, ?)",
-                    id,
-                    t.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_region_query: Vec<_> = terms
-            .regions
-            .iter()
-            .map(|r: &Region| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_region (vid, rid) VALUES (?, ?)",
-                    id,
-                    r.to_uint()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_query: Vec<_> = terms
-            .themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid) VALUES (?, ?)",
-                    id,
-                    t.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_required_query: Vec<_> = terms
-            .required_themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    t.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_query: Vec<_> = terms
-            .conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid) VALUES (?, ?)",
-                    id,
-                    c.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_required_query: Vec<_> = terms
-            .required_conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    c.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_photo_query: Vec<_> = s3_keys
-            .iter()
-            .map(|p: &String| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_photo VALUES (?, ?)",
-                    p,
-                    id
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        future::try_join_all(
-            insert_target_status_query
-                .into_iter()
-                .chain(insert_region_query)
-                .chain(insert_theme_query)
-                .chain(insert_theme_required_query)
-                .chain(insert_condition_query)
-                .chain(insert_condition_required_query)
-                .chain(insert_photo_query),
-        )
-        .await?;
+        ).execute(&mut *tx).await?;
+
+        insert_sessions(&mut tx, &id, &schedule.sessions).await?;
+
+        insert_terms(&mut tx, &id, &terms, &s3_keys).await?;
+
+        insert_location(&mut tx, &id, &location).await?;
+
+        tx.commit().await?;
 
         Ok(())
     }
@@ -173,8 +256,8 @@ impl VolunteerRepository for VolunteerImpl {
         overview: String,
         recruited_num: u32,
         place: String,
-        start_at: DateTime<Utc>,
-        finish_at: DateTime<Utc>,
+        location: Option<GeoPoint>,
+        schedule: Schedule,
         deadline_on: NaiveDate,
         as_group: bool,
         reward: Option<String>,
@@ -183,137 +266,53 @@ impl VolunteerRepository for VolunteerImpl {
     ) -> Result<()> {
         let id: String = vid.to_string();
 
-        let update_query = sqlx::query!(
-            "UPDATE volunteer SET title = ?, message = ?, overview = ?, recruited_num = ?, place = ?, start_at = ?, finish_at = ?, deadline_on = ?, as_group = ?, reward = ?, updated_at = ? WHERE vid = ?",
+        let mut tx = self.db.begin().await?;
+
+        ensure_photo_quota(&mut tx, &id, s3_keys.len()).await?;
+
+        sqlx::query!(
+            "UPDATE volunteer SET title = ?, message = ?, overview = ?, recruited_num = ?, place = ?, start_at = ?, finish_at = ?, recurrence = ?, deadline_on = ?, as_group = ?, reward = ?, updated_at = ? WHERE vid = ?",
             title,
             message,
             overview,
             recruited_num,
             place,
-            start_at,
-            finish_at,
+            schedule.start_at(),
+            schedule.finish_at(),
+            schedule.recurrence.as_ref().map(|rule| rule.to_string()),
             deadline_on,
             as_group,
             reward,
             Utc::now(),
             id
         )
-        .execute(&self.pool);
+        .execute(&mut *tx)
+        .await?;
 
-        let delete_region_query =
-            sqlx::query!("DELETE FROM volunteer_region WHERE vid = ?", id).execute(&self.pool);
+        sync_sessions(&mut tx, &id, &schedule).await?;
 
-        let delete_element_query =
-            sqlx::query!("DELETE FROM volunteer_element WHERE vid = ?", id).execute(&self.pool);
+        sqlx::query!("DELETE FROM volunteer_region WHERE vid = ?", id)
+            .execute(&mut *tx)
+            .await?;
 
-        let delete_photo_query =
-            sqlx::query!("DELETE FROM volunteer_photo WHERE vid = ?", id).execute(&self.pool);
+        sqlx::query!("DELETE FROM volunteer_element WHERE vid = ?", id)
+            .execute(&mut *tx)
+            .await?;
 
-        future::try_join4(update_query, delete_region_query, delete_element_query, delete_photo_query).await?;
+        sqlx::query!("DELETE FROM volunteer_photo WHERE vid = ?", id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM volunteer_location WHERE vid = ?", id)
+            .execute(&mut *tx)
+            .await?;
+
+        insert_terms(&mut tx, &id, &terms, &s3_keys).await?;
+
+        insert_location(&mut tx, &id, &location).await?;
+
+        tx.commit().await?;
 
-        let insert_target_status_query: Vec<_> = terms
-            .target_status
-            .iter()
-            .map(|t: &TargetStatus| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid) VALUES (?, ?)",
-                    id,
-                    t.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_region_query: Vec<_> = terms
-            .regions
-            .iter()
-            .map(|r: &Region| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_region (vid, rid) VALUES (?, ?)",
-                    id,
-                    r.to_uint()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_query: Vec<_> = terms
-            .themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid) VALUES (?, ?)",
-                    id,
-                    t.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_required_query: Vec<_> = terms
-            .required_themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    t.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_query: Vec<_> = terms
-            .conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid) VALUES (?, ?)",
-                    id,
-                    c.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_required_query: Vec<_> = terms
-            .required_conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_element (vid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    c.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_photo_query: Vec<_> = s3_keys
-            .iter()
-            .map(|p: &String| {
-                sqlx::query!(
-                    "INSERT INTO volunteer_photo VALUES (?, ?)",
-                    p,
-                    id
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        future::try_join_all(
-            insert_target_status_query
-                .into_iter()
-                .chain(insert_region_query)
-                .chain(insert_theme_query)
-                .chain(insert_theme_required_query)
-                .chain(insert_condition_query)
-                .chain(insert_condition_required_query)
-                .chain(insert_photo_query),
-        )
-        .await?;
         Ok(())
     }
 
@@ -323,14 +322,16 @@ impl VolunteerRepository for VolunteerImpl {
             is_deleted: bool
         }
 
+        let mut tx = self.db.begin().await?;
+
         let is_deleted = sqlx::query_as!(
             IsExists,
             r#"
-            SELECT is_deleted as "is_deleted: bool" FROM volunteer WHERE vid = ?
+            SELECT is_deleted as "is_deleted: bool" FROM volunteer WHERE vid = ? FOR UPDATE
             "#,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
         if is_deleted.is_deleted {
@@ -341,32 +342,37 @@ impl VolunteerRepository for VolunteerImpl {
                 Utc::now(),
                 vid.to_string()
             )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+            tx.commit().await?;
             Ok(())
         }
     }
 
     async fn register_favorite(&self, uid: UserId, vid: VolunteerId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
         sqlx::query!(
             "INSERT INTO favorite VALUES(?, ?, ?)",
             uid.to_string(),
             vid.to_string(),
             Utc::now()
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
         Ok(())
     }
 
     async fn unregister_favorite(&self, uid: UserId, vid: VolunteerId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
         sqlx::query!(
             "DELETE FROM favorite where uid = ? AND vid = ?",
             uid.to_string(),
             vid.to_string()
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
         Ok(())
     }
 }
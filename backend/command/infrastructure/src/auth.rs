@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, State},
+    http::{header::AUTHORIZATION, request::Parts, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use api_error::ApiError;
+use domain::model::user_account::user_id::UserId;
+use firebase_auth::verifier::{AuthError, TokenVerifier, VerifiedUser};
+
+/// Firebase ID トークンで認証されたユーザー
+///
+/// ハンドラの引数に指定することで, 認証されたユーザーのIDを取得できる
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub uid: UserId,
+    /// トークンに含まれるメールアドレス. 応募・スカウトのメールの宛先として保存する
+    pub email: Option<String>,
+}
+
+/// `Authorization: Bearer <token>`ヘッダーのトークンを検証するミドルウェア
+///
+/// 検証に成功した場合は[AuthUser]をリクエストに追加し, 失敗した場合は401を返す
+pub async fn authenticate<B>(
+    State(verifier): State<Arc<TokenVerifier>>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let token: Option<String> = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+
+    let token: String = match token {
+        Some(token) => token,
+        None => return unauthorized(AuthError::MissingToken.into()),
+    };
+
+    match verifier.verify_user(&token).await {
+        Ok(VerifiedUser { uid, email }) => {
+            request.extensions_mut().insert(AuthUser { uid, email });
+            next.run(request).await
+        }
+        Err(error) => unauthorized(error),
+    }
+}
+
+fn unauthorized(error: anyhow::Error) -> Response {
+    log::warn!("error = {}", error);
+    ApiError::Unauthorized(error.to_string()).into_response()
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| unauthorized(AuthError::MissingToken.into()))
+    }
+}
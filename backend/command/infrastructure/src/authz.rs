@@ -0,0 +1,519 @@
+use anyhow::Result;
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use domain::model::{
+    apply::ApplyId,
+    message::{MessageId, ThreadId},
+    scout::ScoutId,
+    user_account::user_id::UserId,
+    volunteer::VolunteerId,
+};
+
+use api_error::ApiError;
+
+use crate::controllers::Endpoints;
+
+/// エンドポイントごとに必要な権限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// 認証しない. 決済サービスからのリクエストなど, 署名で検証するエンドポイント
+    Public,
+    /// 認証されていれば良い. 操作対象は認証されたユーザー自身
+    Authenticated,
+    /// 対象のボランティアを所有するグループであること
+    VolunteerOwner,
+    /// 対象の応募をした参加者自身であること
+    Applicant,
+    /// 対象のスカウトを受けた参加者であること
+    ScoutedParticipant,
+    /// 対象のボランティアに参加した参加者自身であること
+    AttendedParticipant,
+    /// 対象のボランティアを所有するグループであり, 参加者がボランティアに参加していること
+    OwnerOfAttendedParticipant,
+    /// 対象の応募・スカウト・スレッドの団体または参加者であること
+    ThreadMember,
+    /// 対象のメッセージを送信したユーザー自身であること
+    MessageSender,
+}
+
+impl Rule {
+    /// エンドポイントに対応する権限を返す
+    pub fn of(endpoint: &Endpoints) -> Rule {
+        match *endpoint {
+            Endpoints::CreateGroupAccount
+            | Endpoints::UpdateGroupAccount
+            | Endpoints::DeleteGroupAccount
+            | Endpoints::ChangeGroupAccountPlan
+            | Endpoints::CreateParticipantAccount
+            | Endpoints::UpdateParticipantAccount
+            | Endpoints::DeleteParticipantAccount
+            | Endpoints::RegisterParticipantAvailability
+            | Endpoints::DeleteParticipantAvailability
+            | Endpoints::CreateVolunteer
+            | Endpoints::RegisterVolunteerFavorite
+            | Endpoints::UnregisterVolunteerFavorite
+            | Endpoints::CreateApply => Rule::Authenticated,
+            Endpoints::UpdateVolunteer
+            | Endpoints::DeleteVolunteer
+            | Endpoints::UpdateApplyAllowedStatus
+            | Endpoints::IssueCheckInCode
+            | Endpoints::RecordAttendance
+            | Endpoints::CreateScout
+            | Endpoints::WithdrawScout => Rule::VolunteerOwner,
+            Endpoints::CancelApply | Endpoints::CheckIn => Rule::Applicant,
+            Endpoints::UpdateScoutIsRead
+            | Endpoints::UpdateScoutDenied
+            | Endpoints::AcceptScout => Rule::ScoutedParticipant,
+            Endpoints::ReviewToVolunteer => Rule::AttendedParticipant,
+            Endpoints::ReviewToParticipant => Rule::OwnerOfAttendedParticipant,
+            Endpoints::SendMessage | Endpoints::ReadMessageThread => Rule::ThreadMember,
+            Endpoints::DeleteMessage => Rule::MessageSender,
+            Endpoints::PaymentWebhook => Rule::Public,
+        }
+    }
+}
+
+/// 権限の判定に使用する操作対象
+#[derive(Debug, Clone)]
+pub enum Target {
+    None,
+    Volunteer(VolunteerId),
+    Apply(ApplyId),
+    Scout(ScoutId),
+    /// メッセージのスレッド
+    Thread(ThreadId),
+    Message(MessageId),
+    /// ボランティアへの参加者
+    Participation { uid: UserId, vid: VolunteerId },
+}
+
+/// 操作対象から取得した権限の判定材料
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Facts {
+    /// 対象のボランティアを所有するグループのID
+    pub owner_gid: Option<String>,
+    /// 対象の応募・スカウト・参加に関わる参加者のID
+    pub participant_uid: Option<String>,
+    /// 参加者が出席し, ボランティアが終了しているか
+    pub attended: bool,
+    /// 対象のメッセージを送信したユーザーのID
+    pub sender_uid: Option<String>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AuthzError {
+    #[error("only the group that owns the volunteer can do this")]
+    NotVolunteerOwner,
+    #[error("only the participant who applied can do this")]
+    NotApplicant,
+    #[error("only the scouted participant can do this")]
+    NotScoutedParticipant,
+    #[error("only the participant who joined the volunteer can do this")]
+    NotParticipant,
+    #[error("the participant has not attended the volunteer")]
+    NotAttended,
+    #[error("only the group and the participant of the thread can do this")]
+    NotThreadMember,
+    #[error("only the sender of the message can do this")]
+    NotMessageSender,
+}
+
+impl AuthzError {
+    /// 権限がない理由
+    pub fn reason(&self) -> &'static str {
+        match self {
+            AuthzError::NotVolunteerOwner => "NOT_VOLUNTEER_OWNER",
+            AuthzError::NotApplicant => "NOT_APPLICANT",
+            AuthzError::NotScoutedParticipant => "NOT_SCOUTED_PARTICIPANT",
+            AuthzError::NotParticipant => "NOT_PARTICIPANT",
+            AuthzError::NotAttended => "NOT_ATTENDED",
+            AuthzError::NotThreadMember => "NOT_THREAD_MEMBER",
+            AuthzError::NotMessageSender => "NOT_MESSAGE_SENDER",
+        }
+    }
+}
+
+/// 権限がない場合のAPIレスポンスのボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WriteApiResponseForbiddenBody {
+    /// 常に`FORBIDDEN`
+    pub code: String,
+    /// 権限がない理由. [AuthzError::reason]を参照
+    pub reason: String,
+    pub endpoint: String,
+    pub message: String,
+}
+
+/// 権限を判定する
+pub fn authorize(rule: Rule, caller: &UserId, facts: &Facts) -> Result<(), AuthzError> {
+    let caller: String = caller.to_string();
+    let is_owner: bool = facts.owner_gid.as_deref() == Some(caller.as_str());
+    let is_participant: bool = facts.participant_uid.as_deref() == Some(caller.as_str());
+    let is_sender: bool = facts.sender_uid.as_deref() == Some(caller.as_str());
+
+    match rule {
+        Rule::Public | Rule::Authenticated => Ok(()),
+        Rule::VolunteerOwner if is_owner => Ok(()),
+        Rule::VolunteerOwner => Err(AuthzError::NotVolunteerOwner),
+        Rule::Applicant if is_participant => Ok(()),
+        Rule::Applicant => Err(AuthzError::NotApplicant),
+        Rule::ScoutedParticipant if is_participant => Ok(()),
+        Rule::ScoutedParticipant => Err(AuthzError::NotScoutedParticipant),
+        Rule::ThreadMember if is_owner || is_participant => Ok(()),
+        Rule::ThreadMember => Err(AuthzError::NotThreadMember),
+        Rule::MessageSender if is_sender => Ok(()),
+        Rule::MessageSender => Err(AuthzError::NotMessageSender),
+        Rule::AttendedParticipant if !is_participant => Err(AuthzError::NotParticipant),
+        Rule::OwnerOfAttendedParticipant if !is_owner => Err(AuthzError::NotVolunteerOwner),
+        Rule::AttendedParticipant | Rule::OwnerOfAttendedParticipant => {
+            if facts.attended {
+                Ok(())
+            } else {
+                Err(AuthzError::NotAttended)
+            }
+        }
+    }
+}
+
+/// MySQLから判定材料を取得し, 権限を判定する構造体
+pub struct Authorizer {
+    pool: MySqlPool,
+}
+
+impl Authorizer {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// 権限がない場合は403, 判定材料の取得に失敗した場合は500のレスポンスを返す
+    pub async fn enforce(
+        &self,
+        endpoint: Endpoints,
+        caller: &UserId,
+        target: Target,
+    ) -> Result<(), Response> {
+        let rule: Rule = Rule::of(&endpoint);
+        if rule == Rule::Public || rule == Rule::Authenticated {
+            return Ok(());
+        }
+
+        let facts: Facts = match self.facts(target).await {
+            Ok(facts) => facts,
+            Err(error) => {
+                log::error!("error = {}", error);
+                return Err(ApiError::Internal(error.to_string()).into_response());
+            }
+        };
+
+        authorize(rule, caller, &facts).map_err(|error| {
+            log::warn!("error = {}", error);
+            let forbidden: ApiError = ApiError::Forbidden(error.to_string());
+            (
+                forbidden.status(),
+                Json(WriteApiResponseForbiddenBody {
+                    code: forbidden.code().to_string(),
+                    reason: error.reason().to_string(),
+                    endpoint: endpoint.as_str().to_string(),
+                    message: error.to_string(),
+                }),
+            )
+                .into_response()
+        })
+    }
+
+    /// 操作対象から判定材料を取得する. 操作対象が存在しない場合は空の判定材料を返す
+    async fn facts(&self, target: Target) -> Result<Facts> {
+        match target {
+            Target::None => Ok(Facts::default()),
+            Target::Volunteer(vid) => {
+                let owner = sqlx::query!("SELECT gid FROM volunteer WHERE vid = ?", vid.to_string())
+                    .fetch_optional(&self.pool)
+                    .await?;
+                Ok(Facts {
+                    owner_gid: owner.map(|v| v.gid),
+                    ..Default::default()
+                })
+            }
+            Target::Apply(aid) => {
+                let apply = sqlx::query!(
+                    r#"
+                    SELECT volunteer.gid, apply.uid
+                    FROM apply
+                    INNER JOIN volunteer ON apply.vid = volunteer.vid
+                    WHERE apply.aid = ?
+                    "#,
+                    aid.to_string()
+                )
+                .fetch_optional(&self.pool)
+                .await?;
+                Ok(match apply {
+                    Some(apply) => Facts {
+                        owner_gid: Some(apply.gid),
+                        participant_uid: Some(apply.uid),
+                        ..Default::default()
+                    },
+                    None => Facts::default(),
+                })
+            }
+            Target::Scout(sid) => {
+                let scout = sqlx::query!(
+                    r#"
+                    SELECT volunteer.gid, scout.uid
+                    FROM scout
+                    INNER JOIN volunteer ON scout.vid = volunteer.vid
+                    WHERE scout.sid = ?
+                    "#,
+                    sid.to_string()
+                )
+                .fetch_optional(&self.pool)
+                .await?;
+                Ok(match scout {
+                    Some(scout) => Facts {
+                        owner_gid: Some(scout.gid),
+                        participant_uid: Some(scout.uid),
+                        ..Default::default()
+                    },
+                    None => Facts::default(),
+                })
+            }
+            Target::Thread(tid) => {
+                let thread = sqlx::query!(
+                    "SELECT gid, uid FROM message_thread WHERE tid = ?",
+                    tid.to_string()
+                )
+                .fetch_optional(&self.pool)
+                .await?;
+                Ok(match thread {
+                    Some(thread) => Facts {
+                        owner_gid: Some(thread.gid),
+                        participant_uid: Some(thread.uid),
+                        ..Default::default()
+                    },
+                    None => Facts::default(),
+                })
+            }
+            Target::Message(mid) => {
+                let message = sqlx::query!("SELECT sender FROM message WHERE mid = ?", mid.to_string())
+                    .fetch_optional(&self.pool)
+                    .await?;
+                Ok(Facts {
+                    sender_uid: message.map(|m| m.sender),
+                    ..Default::default()
+                })
+            }
+            Target::Participation { uid, vid } => {
+                let owner = sqlx::query!("SELECT gid FROM volunteer WHERE vid = ?", vid.to_string())
+                    .fetch_optional(&self.pool)
+                    .await?;
+                let attended = sqlx::query!(
+                    r#"
+                    SELECT EXISTS(
+                        SELECT 1
+                        FROM apply
+                        INNER JOIN volunteer_session ON apply.vsid = volunteer_session.vsid
+                        WHERE apply.vid = ? AND apply.uid = ? AND apply.attendance_status = 1 AND volunteer_session.finish_at < NOW()
+                    ) AS "attended: bool"
+                    "#,
+                    vid.to_string(),
+                    uid.to_string()
+                )
+                .fetch_one(&self.pool)
+                .await?;
+                Ok(Facts {
+                    owner_gid: owner.map(|v| v.gid),
+                    participant_uid: Some(uid.to_string()),
+                    attended: attended.attended,
+                    ..Default::default()
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_authz {
+    use std::str::FromStr;
+
+    use super::*;
+
+    const GROUP: &str = "group00000000000000000000001";
+    const OTHER_GROUP: &str = "group00000000000000000000002";
+    const PARTICIPANT: &str = "participant00000000000000001";
+    const OTHER_PARTICIPANT: &str = "participant00000000000000002";
+
+    fn uid(value: &str) -> UserId {
+        UserId::from_str(value).unwrap()
+    }
+
+    fn facts(attended: bool) -> Facts {
+        Facts {
+            owner_gid: Some(GROUP.to_string()),
+            participant_uid: Some(PARTICIPANT.to_string()),
+            attended,
+            sender_uid: None,
+        }
+    }
+
+    fn group() -> UserId {
+        uid(GROUP)
+    }
+
+    fn other_group() -> UserId {
+        uid(OTHER_GROUP)
+    }
+
+    #[test]
+    fn endpoint_rules() {
+        assert_eq!(Rule::of(&Endpoints::UpdateGroupAccount), Rule::Authenticated);
+        assert_eq!(Rule::of(&Endpoints::ChangeGroupAccountPlan), Rule::Authenticated);
+        assert_eq!(Rule::of(&Endpoints::CreateVolunteer), Rule::Authenticated);
+        assert_eq!(Rule::of(&Endpoints::CreateApply), Rule::Authenticated);
+        assert_eq!(Rule::of(&Endpoints::UpdateVolunteer), Rule::VolunteerOwner);
+        assert_eq!(Rule::of(&Endpoints::DeleteVolunteer), Rule::VolunteerOwner);
+        assert_eq!(Rule::of(&Endpoints::UpdateApplyAllowedStatus), Rule::VolunteerOwner);
+        assert_eq!(Rule::of(&Endpoints::CancelApply), Rule::Applicant);
+        assert_eq!(Rule::of(&Endpoints::IssueCheckInCode), Rule::VolunteerOwner);
+        assert_eq!(Rule::of(&Endpoints::CheckIn), Rule::Applicant);
+        assert_eq!(Rule::of(&Endpoints::RecordAttendance), Rule::VolunteerOwner);
+        assert_eq!(Rule::of(&Endpoints::CreateScout), Rule::VolunteerOwner);
+        assert_eq!(Rule::of(&Endpoints::UpdateScoutIsRead), Rule::ScoutedParticipant);
+        assert_eq!(Rule::of(&Endpoints::UpdateScoutDenied), Rule::ScoutedParticipant);
+        assert_eq!(Rule::of(&Endpoints::AcceptScout), Rule::ScoutedParticipant);
+        assert_eq!(Rule::of(&Endpoints::WithdrawScout), Rule::VolunteerOwner);
+        assert_eq!(Rule::of(&Endpoints::ReviewToVolunteer), Rule::AttendedParticipant);
+        assert_eq!(
+            Rule::of(&Endpoints::ReviewToParticipant),
+            Rule::OwnerOfAttendedParticipant
+        );
+        assert_eq!(Rule::of(&Endpoints::SendMessage), Rule::ThreadMember);
+        assert_eq!(Rule::of(&Endpoints::ReadMessageThread), Rule::ThreadMember);
+        assert_eq!(Rule::of(&Endpoints::DeleteMessage), Rule::MessageSender);
+        assert_eq!(Rule::of(&Endpoints::PaymentWebhook), Rule::Public);
+    }
+
+    #[test]
+    fn authenticated() {
+        assert_eq!(authorize(Rule::Authenticated, &other_group(), &Facts::default()), Ok(()));
+    }
+
+    #[test]
+    fn volunteer_owner() {
+        assert_eq!(authorize(Rule::VolunteerOwner, &group(), &facts(false)), Ok(()));
+        assert_eq!(
+            authorize(Rule::VolunteerOwner, &other_group(), &facts(false)),
+            Err(AuthzError::NotVolunteerOwner)
+        );
+    }
+
+    #[test]
+    fn volunteer_not_found() {
+        assert_eq!(
+            authorize(Rule::VolunteerOwner, &group(), &Facts::default()),
+            Err(AuthzError::NotVolunteerOwner)
+        );
+    }
+
+    #[test]
+    fn applicant() {
+        assert_eq!(authorize(Rule::Applicant, &uid(PARTICIPANT), &facts(false)), Ok(()));
+        // 団体は参加者の代わりに応募を取り消せない
+        assert_eq!(
+            authorize(Rule::Applicant, &group(), &facts(false)),
+            Err(AuthzError::NotApplicant)
+        );
+        assert_eq!(
+            authorize(Rule::Applicant, &uid(OTHER_PARTICIPANT), &facts(false)),
+            Err(AuthzError::NotApplicant)
+        );
+    }
+
+    #[test]
+    fn scouted_participant() {
+        assert_eq!(
+            authorize(Rule::ScoutedParticipant, &uid(PARTICIPANT), &facts(false)),
+            Ok(())
+        );
+        assert_eq!(
+            authorize(Rule::ScoutedParticipant, &uid(OTHER_PARTICIPANT), &facts(false)),
+            Err(AuthzError::NotScoutedParticipant)
+        );
+        assert_eq!(
+            authorize(Rule::ScoutedParticipant, &group(), &facts(false)),
+            Err(AuthzError::NotScoutedParticipant)
+        );
+    }
+
+    #[test]
+    fn attended_participant() {
+        assert_eq!(
+            authorize(Rule::AttendedParticipant, &uid(PARTICIPANT), &facts(true)),
+            Ok(())
+        );
+        assert_eq!(
+            authorize(Rule::AttendedParticipant, &uid(PARTICIPANT), &facts(false)),
+            Err(AuthzError::NotAttended)
+        );
+        assert_eq!(
+            authorize(Rule::AttendedParticipant, &uid(OTHER_PARTICIPANT), &facts(true)),
+            Err(AuthzError::NotParticipant)
+        );
+    }
+
+    #[test]
+    fn owner_of_attended_participant() {
+        assert_eq!(
+            authorize(Rule::OwnerOfAttendedParticipant, &group(), &facts(true)),
+            Ok(())
+        );
+        assert_eq!(
+            authorize(Rule::OwnerOfAttendedParticipant, &group(), &facts(false)),
+            Err(AuthzError::NotAttended)
+        );
+        assert_eq!(
+            authorize(Rule::OwnerOfAttendedParticipant, &other_group(), &facts(true)),
+            Err(AuthzError::NotVolunteerOwner)
+        );
+    }
+
+    #[test]
+    fn thread_member() {
+        assert_eq!(authorize(Rule::ThreadMember, &group(), &facts(false)), Ok(()));
+        assert_eq!(
+            authorize(Rule::ThreadMember, &uid(PARTICIPANT), &facts(false)),
+            Ok(())
+        );
+        assert_eq!(
+            authorize(Rule::ThreadMember, &other_group(), &facts(false)),
+            Err(AuthzError::NotThreadMember)
+        );
+        assert_eq!(
+            authorize(Rule::ThreadMember, &uid(OTHER_PARTICIPANT), &facts(false)),
+            Err(AuthzError::NotThreadMember)
+        );
+    }
+
+    #[test]
+    fn message_sender() {
+        let facts = Facts {
+            sender_uid: Some(PARTICIPANT.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(authorize(Rule::MessageSender, &uid(PARTICIPANT), &facts), Ok(()));
+        // スレッドの相手もメッセージを削除できない
+        assert_eq!(
+            authorize(Rule::MessageSender, &group(), &facts),
+            Err(AuthzError::NotMessageSender)
+        );
+        assert_eq!(
+            authorize(Rule::MessageSender, &group(), &Facts::default()),
+            Err(AuthzError::NotMessageSender)
+        );
+    }
+}
@@ -2,10 +2,18 @@ pub mod group;
 pub mod participant;
 pub mod volunteer;
 pub mod apply;
+pub mod attendance;
 pub mod scout;
 pub mod review;
+pub mod message;
+pub mod payment;
 
-use axum::{routing::post, Router};
+use axum::{
+    middleware,
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
 use lambda_http::Body;
 use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
@@ -13,17 +21,23 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use utoipa::ToSchema;
 
+use command_repository::{event::EventPublisher, geocoder::Geocoder, payment::PaymentProvider};
+use firebase_auth::verifier::TokenVerifier;
+
+pub use api_error::WriteApiResponseFailureBody;
+use api_error::ApiError;
+use domain::model::{plan::PlanError, schedule::ScheduleError};
+
 use crate::{
-    activities::{volunteer::VolunteerImpl, apply::ApplyImpl, scout::ScoutImpl, review::ReviewImpl},
-    user_account::{group::GroupAccountImpl, participant::ParticipantAccountImpl}
+    auth::authenticate,
+    authz::Authorizer,
+    event::EventNotifier,
+    activities::{volunteer::VolunteerImpl, apply::ApplyImpl, attendance::AttendanceImpl, scout::ScoutImpl, review::ReviewImpl, message::MessageImpl},
+    geocoder::GazetteerGeocoder,
+    transaction::MySqlUnitOfWorkFactory,
+    user_account::{billing::BillingImpl, group::GroupAccountImpl, participant::ParticipantAccountImpl}
 };
 
-/// 失敗時のAPIレスポンスのボディを表す構造体
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct WriteApiResponseFailureBody {
-    pub message: String,
-}
-
 /// 成功時のAPIレスポンスのボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct WriteApiResponseSuccessBody {
@@ -36,19 +50,43 @@ pub struct AppState {
     participant_account_repository: ParticipantAccountImpl,
     volunteer_repository: VolunteerImpl,
     apply_repository: ApplyImpl,
+    attendance_repository: AttendanceImpl,
     scout_repository: ScoutImpl,
-    review_repository: ReviewImpl
+    review_repository: ReviewImpl,
+    message_repository: MessageImpl,
+    billing_repository: BillingImpl,
+    /// 複数のリポジトリにまたがる書き込みを1つのトランザクションで行う場合に使用する
+    unit_of_work_factory: MySqlUnitOfWorkFactory,
+    authorizer: Authorizer,
+    /// ボランティアの開催場所を緯度経度に変換する. 外部APIを使う実装に差し替えられる
+    geocoder: Arc<dyn Geocoder>,
+    /// プランの料金を請求する. 外部の決済サービスを使う実装に差し替えられる
+    payment_provider: Arc<dyn PaymentProvider>,
+    /// 応募・スカウトの出来事をコミット後にRead API Serverの購読者へ配信する
+    event_notifier: EventNotifier
 }
 
 impl AppState {
-    pub fn new(pool: MySqlPool) -> Self {
+    pub fn new(
+        pool: MySqlPool,
+        publisher: Arc<dyn EventPublisher>,
+        payment_provider: Arc<dyn PaymentProvider>,
+    ) -> Self {
         Self {
             group_account_repository: GroupAccountImpl::new(pool.clone()),
             participant_account_repository: ParticipantAccountImpl::new(pool.clone()),
             volunteer_repository: VolunteerImpl::new(pool.clone()),
             apply_repository: ApplyImpl::new(pool.clone()),
+            attendance_repository: AttendanceImpl::new(pool.clone()),
             scout_repository: ScoutImpl::new(pool.clone()),
             review_repository: ReviewImpl::new(pool.clone()),
+            message_repository: MessageImpl::new(pool.clone()),
+            billing_repository: BillingImpl::new(pool.clone()),
+            unit_of_work_factory: MySqlUnitOfWorkFactory::new(pool.clone()),
+            authorizer: Authorizer::new(pool.clone()),
+            geocoder: Arc::new(GazetteerGeocoder::new()),
+            payment_provider,
+            event_notifier: EventNotifier::new(pool.clone(), publisher),
         }
     }
 }
@@ -56,15 +94,49 @@ impl AppState {
 /// アプリケーションの状態を保持するための型エイリアス
 pub type AppData = Arc<RwLock<AppState>>;
 
+/// プランの利用上限に達したエラーは429, それ以外は500のレスポンスに変換する
+pub(crate) fn quota_error_response(error: anyhow::Error) -> Response {
+    match error.downcast_ref::<PlanError>() {
+        Some(_) => {
+            log::warn!("error = {}", error);
+            ApiError::QuotaExceeded(error.to_string()).into_response()
+        }
+        None => {
+            log::error!("error = {}", error);
+            ApiError::Internal(error.to_string()).into_response()
+        }
+    }
+}
+
+/// 応募する回のエラーのうち, 開始済みは409, それ以外は400のレスポンスに変換する. 回以外のエラーは500とする
+pub(crate) fn session_error_response(error: anyhow::Error) -> Response {
+    match error.downcast_ref::<ScheduleError>() {
+        Some(ScheduleError::SessionStarted) => {
+            log::warn!("error = {}", error);
+            ApiError::Conflict(error.to_string()).into_response()
+        }
+        Some(_) => {
+            log::warn!("error = {}", error);
+            ApiError::Validation(error.to_string()).into_response()
+        }
+        None => {
+            log::error!("error = {}", error);
+            ApiError::Internal(error.to_string()).into_response()
+        }
+    }
+}
+
 /// APIエンドポイントを表す列挙型
 pub enum Endpoints {
     CreateGroupAccount,
     UpdateGroupAccount,
     DeleteGroupAccount,
-    SwitchGroupAccountPlan,
+    ChangeGroupAccountPlan,
     CreateParticipantAccount,
     UpdateParticipantAccount,
     DeleteParticipantAccount,
+    RegisterParticipantAvailability,
+    DeleteParticipantAvailability,
     CreateVolunteer,
     UpdateVolunteer,
     DeleteVolunteer,
@@ -72,13 +144,21 @@ pub enum Endpoints {
     UnregisterVolunteerFavorite,
     CreateApply,
     UpdateApplyAllowedStatus,
-    UpdateApplyIsSent,
+    CancelApply,
+    IssueCheckInCode,
+    CheckIn,
+    RecordAttendance,
     CreateScout,
-    UpdateScoutIsSent,
     UpdateScoutIsRead,
     UpdateScoutDenied,
+    AcceptScout,
+    WithdrawScout,
     ReviewToVolunteer,
-    ReviewToParticipant
+    ReviewToParticipant,
+    SendMessage,
+    ReadMessageThread,
+    DeleteMessage,
+    PaymentWebhook
 }
 
 impl Endpoints {
@@ -87,10 +167,12 @@ impl Endpoints {
             Endpoints::CreateGroupAccount => "/group-account/create",
             Endpoints::UpdateGroupAccount => "/group-account/update",
             Endpoints::DeleteGroupAccount => "/group-account/delete",
-            Endpoints::SwitchGroupAccountPlan => "/group-account/switch-plan",
+            Endpoints::ChangeGroupAccountPlan => "/group-account/change-plan",
             Endpoints::CreateParticipantAccount => "/participant-account/create",
             Endpoints::UpdateParticipantAccount => "/participant-account/update",
             Endpoints::DeleteParticipantAccount => "/participant-account/delete",
+            Endpoints::RegisterParticipantAvailability => "/participant-account/availability/register",
+            Endpoints::DeleteParticipantAvailability => "/participant-account/availability/delete",
             Endpoints::CreateVolunteer => "/volunteer/create",
             Endpoints::UpdateVolunteer => "/volunteer/update",
             Endpoints::DeleteVolunteer => "/volunteer/delete",
@@ -98,21 +180,35 @@ impl Endpoints {
             Endpoints::UnregisterVolunteerFavorite => "/volunteer/favorite/unregister",
             Endpoints::CreateApply => "/apply/create",
             Endpoints::UpdateApplyAllowedStatus => "/apply/update/allowed-status",
-            Endpoints::UpdateApplyIsSent => "/apply/update/is-sent",
+            Endpoints::CancelApply => "/apply/cancel",
+            Endpoints::IssueCheckInCode => "/attendance/code/issue",
+            Endpoints::CheckIn => "/attendance/check-in",
+            Endpoints::RecordAttendance => "/attendance/record",
             Endpoints::CreateScout => "/scout/create",
-            Endpoints::UpdateScoutIsSent => "/scout/update/is-sent",
             Endpoints::UpdateScoutIsRead => "/scout/update/is-read",
             Endpoints::UpdateScoutDenied => "/scout/update/denied",
+            Endpoints::AcceptScout => "/scout/accept",
+            Endpoints::WithdrawScout => "/scout/withdraw",
             Endpoints::ReviewToVolunteer => "/review/to-volunteer",
-            Endpoints::ReviewToParticipant => "/review/to-participant"
+            Endpoints::ReviewToParticipant => "/review/to-participant",
+            Endpoints::SendMessage => "/message/send",
+            Endpoints::ReadMessageThread => "/message/read",
+            Endpoints::DeleteMessage => "/message/delete",
+            Endpoints::PaymentWebhook => "/payment/webhook"
         }
     }
 }
 
-pub fn create_router(pool: MySqlPool) -> Router {
+/// ルーターを作成する. 決済サービスのWebhook以外の全てのエンドポイントでFirebase ID トークンによる認証を必須とする
+pub fn create_router(
+    pool: MySqlPool,
+    verifier: Arc<TokenVerifier>,
+    publisher: Arc<dyn EventPublisher>,
+    payment_provider: Arc<dyn PaymentProvider>,
+) -> Router {
     // Lambdaで動かす場合
-    // pub fn create_router(pool: MySqlPool) -> Router<(), Body> {
-    let state: Arc<RwLock<AppState>> = Arc::new(RwLock::new(AppState::new(pool)));
+    // pub fn create_router(pool: MySqlPool, verifier: Arc<TokenVerifier>, publisher: Arc<dyn EventPublisher>, payment_provider: Arc<dyn PaymentProvider>) -> Router<(), Body> {
+    let state: Arc<RwLock<AppState>> = Arc::new(RwLock::new(AppState::new(pool, publisher, payment_provider)));
 
     let router = Router::new()
         .route(
@@ -128,8 +224,8 @@ pub fn create_router(pool: MySqlPool) -> Router {
             post(group::delete_group_account),
         )
         .route(
-            Endpoints::SwitchGroupAccountPlan.as_str(),
-            post(group::switch_group_account_plan),
+            Endpoints::ChangeGroupAccountPlan.as_str(),
+            post(group::change_group_account_plan),
         )
         .route(
             Endpoints::CreateParticipantAccount.as_str(),
@@ -143,6 +239,14 @@ pub fn create_router(pool: MySqlPool) -> Router {
             Endpoints::DeleteParticipantAccount.as_str(),
             post(participant::delete_participant_account),
         )
+        .route(
+            Endpoints::RegisterParticipantAvailability.as_str(),
+            post(participant::register_participant_availability),
+        )
+        .route(
+            Endpoints::DeleteParticipantAvailability.as_str(),
+            post(participant::delete_participant_availability),
+        )
         .route(
             Endpoints::CreateVolunteer.as_str(),
             post(volunteer::create_volunteer),
@@ -172,16 +276,24 @@ pub fn create_router(pool: MySqlPool) -> Router {
             post(apply::update_apply_allowed_status),
         )
         .route(
-            Endpoints::UpdateApplyIsSent.as_str(),
-            post(apply::update_apply_is_sent),
+            Endpoints::CancelApply.as_str(),
+            post(apply::cancel_apply),
         )
         .route(
-            Endpoints::CreateScout.as_str(),
-            post(scout::create_scout),
+            Endpoints::IssueCheckInCode.as_str(),
+            post(attendance::issue_check_in_code),
         )
         .route(
-            Endpoints::UpdateScoutIsSent.as_str(),
-            post(scout::update_scout_is_sent),
+            Endpoints::CheckIn.as_str(),
+            post(attendance::check_in),
+        )
+        .route(
+            Endpoints::RecordAttendance.as_str(),
+            post(attendance::record_attendance),
+        )
+        .route(
+            Endpoints::CreateScout.as_str(),
+            post(scout::create_scout),
         )
         .route(
             Endpoints::UpdateScoutIsRead.as_str(),
@@ -191,6 +303,14 @@ pub fn create_router(pool: MySqlPool) -> Router {
             Endpoints::UpdateScoutDenied.as_str(),
             post(scout::update_scout_denied),
         )
+        .route(
+            Endpoints::AcceptScout.as_str(),
+            post(scout::accept_scout),
+        )
+        .route(
+            Endpoints::WithdrawScout.as_str(),
+            post(scout::withdraw_scout),
+        )
         .route(
             Endpoints::ReviewToVolunteer.as_str(),
             post(review::review_to_volunteer),
@@ -199,6 +319,24 @@ pub fn create_router(pool: MySqlPool) -> Router {
             Endpoints::ReviewToParticipant.as_str(),
             post(review::review_to_participant),
         )
+        .route(
+            Endpoints::SendMessage.as_str(),
+            post(message::send_message),
+        )
+        .route(
+            Endpoints::ReadMessageThread.as_str(),
+            post(message::read_message_thread),
+        )
+        .route(
+            Endpoints::DeleteMessage.as_str(),
+            post(message::delete_message),
+        )
+        .route_layer(middleware::from_fn_with_state(verifier, authenticate))
+        // 決済サービスからのリクエストはWebhookの署名で検証するため, 認証の後に追加する
+        .route(
+            Endpoints::PaymentWebhook.as_str(),
+            post(payment::payment_webhook),
+        )
         .with_state(state);
 
     router
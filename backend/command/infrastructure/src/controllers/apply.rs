@@ -5,24 +5,31 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use utoipa::ToSchema;
 
+use api_error::ApiError;
 use command_repository::activities::apply::ApplyRepository;
 use domain::model::{
-    apply::ApplyId,
-    gender::{gender_from_i8, Gender},
+    apply::{ApplyError, ApplyId, ApplyStatus, CancelReason},
+    gender::gender_from_i8,
     group_participants::GroupParticipants,
+    schedule::SessionId,
     user_account::{user_id::UserId, user_name::UserName, user_name_furigana::UserNameFurigana},
     volunteer::VolunteerId,
 };
 
-use super::{AppData, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
+use crate::{
+    auth::AuthUser,
+    authz::{Target, WriteApiResponseForbiddenBody},
+};
+
+use super::{session_error_response, AppData, Endpoints, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
 
 /// ボランティア応募時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateApplyRequestBody {
     #[schema(required = true)]
     pub vid: String,
-    #[schema(required = true)]
-    pub uid: String,
+    /// 応募する回のID. 回が1つだけのボランティアでは省略できる
+    pub vsid: Option<String>,
     pub members: Option<Vec<HashMap<String, Value>>>,
 }
 
@@ -31,157 +38,108 @@ pub struct CreateApplyRequestBody {
 pub struct UpdateApplyAllowedStatusRequestBody {
     #[schema(required = true)]
     pub aid: String,
+    /// 1:承認 2:棄却
     #[schema(required = true)]
     pub allowed_status: u8,
 }
 
-/// 応募メール送信更新時のリクエストボディを表す構造体
+/// 応募取消時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct UpdateApplyIsSentRequestBody {
+pub struct CancelApplyRequestBody {
     #[schema(required = true)]
     pub aid: String,
+    /// 取消理由
+    #[schema(required = true)]
+    pub reason: String,
+}
+
+/// 団体応募のメンバーをリクエストボディから読み取る. 不正な場合はエラーメッセージを返す
+fn parse_members(members: &[HashMap<String, Value>]) -> Result<Vec<GroupParticipants>, String> {
+    members
+        .iter()
+        .enumerate()
+        .map(|(index, member)| {
+            let index: u16 = u16::try_from(index).map_err(|_| "too many members".to_string())?;
+            let Some(name) = member.get("name").and_then(Value::as_str) else {
+                return Err("name is not string".to_string());
+            };
+            let Some(furigana) = member.get("furigana").and_then(Value::as_str) else {
+                return Err("furigana is not string".to_string());
+            };
+            let Some(gender) = member
+                .get("gender")
+                .and_then(Value::as_i64)
+                .and_then(|gender| i8::try_from(gender).ok())
+            else {
+                return Err("gender is not integer".to_string());
+            };
+            let Some(age) = member
+                .get("age")
+                .and_then(Value::as_u64)
+                .and_then(|age| u8::try_from(age).ok())
+            else {
+                return Err("age is not integer between 0 and 255".to_string());
+            };
+
+            Ok(GroupParticipants::new(
+                index,
+                UserName::from_str(name).map_err(|error| error.to_string())?,
+                UserNameFurigana::from_str(furigana).map_err(|error| error.to_string())?,
+                gender_from_i8(&gender).map_err(|error| error.to_string())?,
+                age,
+            ))
+        })
+        .collect()
 }
 
 #[utoipa::path(
     post,
     path="/apply/create",
+    security(("bearer_auth" = [])),
     request_body=CreateApplyRequestBody,
     responses(
         (status=200, description="Create apply successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request or session.", body=WriteApiResponseFailureBody),
+        (status=409, description="Session already started.", body=WriteApiResponseFailureBody),
         (status=500, description="Create apply failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn create_apply(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<CreateApplyRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.apply_repository;
 
     let aid: ApplyId = ApplyId::new();
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
-
-    let uid: UserId = match UserId::from_str(&body.uid) {
-        Ok(uid) => uid,
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
-    let members: Option<Vec<GroupParticipants>> = if body.members != None {
-        let mut return_member: Vec<GroupParticipants> = vec![];
-        let m = body.members.unwrap();
-        for (ind, mp) in m.iter().enumerate() {
-            let body_name = mp.get("name");
-            let body_furigana = mp.get("furigana");
-            let body_gender = mp.get("gender");
-            let body_age = mp.get("age");
-            let name: UserName = if body_name == None {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(WriteApiResponseFailureBody {
-                        message: "name is none".to_string(),
-                    }),
-                )
-                    .into_response();
-            } else {
-                let user_name = UserName::from_str(&body_name.unwrap().to_string());
-                if let Err(error) = user_name {
-                    log::warn!("error = {}", error);
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(WriteApiResponseFailureBody {
-                            message: error.to_string(),
-                        }),
-                    )
-                        .into_response();
-                } else {
-                    user_name.unwrap()
-                }
-            };
-            let furigana: UserNameFurigana = if body_furigana == None {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(WriteApiResponseFailureBody {
-                        message: "furigana is none".to_string(),
-                    }),
-                )
-                    .into_response();
-            } else {
-                let user_name_furigana =
-                    UserNameFurigana::from_str(&body_furigana.unwrap().to_string());
-                if let Err(error) = user_name_furigana {
-                    log::warn!("error = {}", error);
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(WriteApiResponseFailureBody {
-                            message: error.to_string(),
-                        }),
-                    )
-                        .into_response();
-                } else {
-                    user_name_furigana.unwrap()
-                }
-            };
-            let gender: Gender = if body_gender == None {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(WriteApiResponseFailureBody {
-                        message: "gender is none".to_string(),
-                    }),
-                )
-                    .into_response();
-            } else {
-                let user_gender = gender_from_i8(&(body_gender.unwrap().as_i64().unwrap() as i8));
-                if let Err(error) = user_gender {
-                    log::warn!("error = {}", error);
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(WriteApiResponseFailureBody {
-                            message: error.to_string(),
-                        }),
-                    )
-                        .into_response();
-                } else {
-                    user_gender.unwrap()
-                }
-            };
-            let age: u8 = if body_age == None {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(WriteApiResponseFailureBody {
-                        message: "age is none".to_string(),
-                    }),
-                )
-                    .into_response();
-            } else {
-                let user_age = body_age.unwrap().as_u64();
-                if user_age == None {
-                    log::warn!("error = {}", "age is not integer".to_string());
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(WriteApiResponseFailureBody {
-                            message: "age is not integer".to_string(),
-                        }),
-                    )
-                        .into_response();
-                } else {
-                    user_age.unwrap() as u8
-                }
-            };
-            let gp = GroupParticipants::new(ind as u16, name, furigana, gender, age);
-            return_member.push(gp);
+    let vsid: Option<SessionId> = match body.vsid.as_deref().map(SessionId::from_str) {
+        Some(Ok(vsid)) => Some(vsid),
+        Some(Err(error)) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+        None => None,
+    };
+
+    let uid: UserId = auth_user.uid;
+
+    let members: Option<Vec<GroupParticipants>> = match body.members.as_deref().map(parse_members) {
+        Some(Ok(members)) => Some(members),
+        Some(Err(error)) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error).into_response();
         }
-        Some(return_member)
-    } else {
-        None
+        None => None,
     };
 
     let as_group: bool = match members {
@@ -189,103 +147,206 @@ pub async fn create_apply(
         Some(_) => true,
     };
 
-    match repository.create(aid, vid, uid, as_group, members).await {
-        Ok(_) => (
-            StatusCode::OK,
-            Json(WriteApiResponseSuccessBody {
-                message: "Create apply successfully.".to_string(),
-            }),
-        )
-            .into_response(),
-        Err(error) => {
-            log::error!("error = {}", error);
+    match repository.create(aid.clone(), vid, vsid, uid, as_group, members).await {
+        Ok(_) => {
+            lock.event_notifier.apply_changed(&aid).await;
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
+                StatusCode::OK,
+                Json(WriteApiResponseSuccessBody {
+                    message: "Create apply successfully.".to_string(),
                 }),
             )
                 .into_response()
         }
+        Err(error) => session_error_response(error),
     }
 }
 
 #[utoipa::path(
     post,
     path="/apply/update/allowed-status",
+    security(("bearer_auth" = [])),
     request_body=UpdateApplyAllowedStatusRequestBody,
     responses(
         (status=200, description="Update apply's allowed_status successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=400, description="Invalid allowed_status.", body=WriteApiResponseFailureBody),
+        (status=409, description="Invalid status transition or recruited_num exceeded.", body=WriteApiResponseFailureBody),
         (status=500, description="Update apply's allowed_status failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn update_apply_allowed_status(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<UpdateApplyAllowedStatusRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.apply_repository;
 
-    let aid: ApplyId = ApplyId::from_str(&body.aid);
+    let aid: ApplyId = match ApplyId::from_str(&body.aid) {
+        Ok(aid) => aid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    let allowed_status: u8 = body.allowed_status;
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::UpdateApplyAllowedStatus, &auth_user.uid, Target::Apply(aid.clone()))
+        .await
+    {
+        return response;
+    }
 
-    match repository.update_allowed_status(aid, allowed_status).await {
-        Ok(_) => (
-            StatusCode::OK,
-            Json(WriteApiResponseSuccessBody {
-                message: "Update apply's allowed_status successfully.".to_string(),
-            }),
-        )
-            .into_response(),
+    let repository = &mut lock.apply_repository;
+
+    let allowed_status: ApplyStatus = match ApplyStatus::from_u8(body.allowed_status) {
+        Ok(ApplyStatus::Approved) => ApplyStatus::Approved,
+        Ok(ApplyStatus::Rejected) => ApplyStatus::Rejected,
+        Ok(status) => {
+            log::warn!("error = {} cannot be set by group", status);
+            return ApiError::Validation(format!("{} cannot be set by group", status)).into_response();
+        }
         Err(error) => {
-            log::error!("error = {}", error);
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    match repository.update_allowed_status(aid.clone(), allowed_status).await {
+        Ok(_) => {
+            lock.event_notifier.apply_changed(&aid).await;
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
+                StatusCode::OK,
+                Json(WriteApiResponseSuccessBody {
+                    message: "Update apply's allowed_status successfully.".to_string(),
                 }),
             )
                 .into_response()
         }
+        Err(error) => match error.downcast_ref::<ApplyError>() {
+            Some(ApplyError::InvalidTransition { .. }) | Some(ApplyError::CapacityExceeded { .. }) => {
+                log::warn!("error = {}", error);
+                ApiError::Conflict(error.to_string()).into_response()
+            }
+            _ => {
+                log::error!("error = {}", error);
+                ApiError::Internal(error.to_string()).into_response()
+            }
+        },
     }
 }
 
+/// 未承認の応募はボランティアの開始日時まで, 承認済の応募は開始日時の24時間前まで取り消せる
+///
+/// 承認済の応募を取り消した場合は, 空いた枠に未承認の応募を応募順に繰り上げて承認する
 #[utoipa::path(
     post,
-    path="/apply/update/is-sent",
-    request_body=UpdateApplyIsSentRequestBody,
+    path="/apply/cancel",
+    security(("bearer_auth" = [])),
+    request_body=CancelApplyRequestBody,
     responses(
-        (status=200, description="Update apply's is-sent successfully.", body=WriteApiResponseSuccessBody),
-        (status=500, description="Update apply's is-sent failed.", body=WriteApiResponseFailureBody)
+        (status=200, description="Cancel apply successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Invalid status transition or cancellation is closed.", body=WriteApiResponseFailureBody),
+        (status=500, description="Cancel apply failed.", body=WriteApiResponseFailureBody)
     )
 )]
-pub async fn update_apply_is_sent(
+pub async fn cancel_apply(
     State(state): State<AppData>,
-    Json(body): Json<UpdateApplyIsSentRequestBody>,
+    auth_user: AuthUser,
+    Json(body): Json<CancelApplyRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.apply_repository;
 
-    let aid: ApplyId = ApplyId::from_str(&body.aid);
+    let aid: ApplyId = match ApplyId::from_str(&body.aid) {
+        Ok(aid) => aid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    match repository.update_is_sent(aid).await {
-        Ok(_) => (
-            StatusCode::OK,
-            Json(WriteApiResponseSuccessBody {
-                message: "Update apply's is_sent successfully.".to_string(),
-            }),
-        )
-            .into_response(),
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::CancelApply, &auth_user.uid, Target::Apply(aid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let reason: CancelReason = match CancelReason::new(&body.reason) {
+        Ok(reason) => reason,
         Err(error) => {
-            log::error!("error = {}", error);
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    let repository = &mut lock.apply_repository;
+
+    match repository.cancel(aid.clone(), reason).await {
+        Ok(promoted) => {
+            lock.event_notifier.apply_changed(&aid).await;
+            for promoted_aid in &promoted {
+                lock.event_notifier.apply_changed(promoted_aid).await;
+            }
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
+                StatusCode::OK,
+                Json(WriteApiResponseSuccessBody {
+                    message: "Cancel apply successfully.".to_string(),
                 }),
             )
                 .into_response()
         }
+        Err(error) => match error.downcast_ref::<ApplyError>() {
+            Some(ApplyError::InvalidTransition { .. }) | Some(ApplyError::CancellationClosed { .. }) => {
+                log::warn!("error = {}", error);
+                ApiError::Conflict(error.to_string()).into_response()
+            }
+            _ => {
+                log::error!("error = {}", error);
+                ApiError::Internal(error.to_string()).into_response()
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod test_parse_members {
+    use super::*;
+
+    use serde_json::json;
+
+    fn member(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn ok() {
+        let members = parse_members(&[member(
+            json!({"name": "石田健太郎", "furigana": "いしだけんたろう", "gender": 0, "age": 20}),
+        )])
+        .unwrap();
+        assert_eq!(members[0].serial, 0);
+        assert_eq!(members[0].name.to_string(), "石田健太郎");
+        assert_eq!(members[0].furigana.to_string(), "いしだけんたろう");
+        assert_eq!(members[0].age, 20);
+    }
+
+    #[test]
+    fn invalid() {
+        for value in [
+            json!({"furigana": "いしだけんたろう", "gender": 0, "age": 20}),
+            json!({"name": 1, "furigana": "いしだけんたろう", "gender": 0, "age": 20}),
+            json!({"name": "石田健太郎", "furigana": "いしだけんたろう", "gender": 258, "age": 20}),
+            json!({"name": "石田健太郎", "furigana": "いしだけんたろう", "gender": 0, "age": 276}),
+            json!({"name": "石田健太郎", "furigana": "いしだけんたろう", "gender": 0, "age": -1}),
+        ] {
+            assert!(parse_members(&[member(value)]).is_err());
+        }
     }
 }
@@ -0,0 +1,302 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use api_error::ApiError;
+use command_repository::activities::attendance::AttendanceRepository;
+use domain::model::{
+    apply::ApplyId,
+    attendance::{
+        AttendanceError, AttendanceRecord, AttendanceStatus, CheckInCode, MemberAttendance,
+    },
+    volunteer::VolunteerId,
+};
+
+use crate::{
+    auth::AuthUser,
+    authz::{Target, WriteApiResponseForbiddenBody},
+};
+
+use super::{AppData, Endpoints, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
+
+/// チェックインコード発行時のリクエストボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IssueCheckInCodeRequestBody {
+    #[schema(required = true)]
+    pub vid: String,
+}
+
+/// チェックイン時のリクエストボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CheckInRequestBody {
+    #[schema(required = true)]
+    pub aid: String,
+    /// 団体が提示したチェックインコード
+    #[schema(required = true)]
+    pub code: String,
+}
+
+/// 集団応募のメンバーの出欠を表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberAttendanceBody {
+    #[schema(required = true)]
+    pub serial: u16,
+    /// 1:出席 2:欠席
+    #[schema(required = true)]
+    pub status: u8,
+}
+
+/// 出欠記録時のリクエストボディを表す構造体
+///
+/// `status`と`members`のどちらか一方を指定する
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecordAttendanceRequestBody {
+    #[schema(required = true)]
+    pub aid: String,
+    /// 応募の出欠 1:出席 2:欠席
+    pub status: Option<u8>,
+    /// 集団応募のメンバーごとの出欠
+    pub members: Option<Vec<MemberAttendanceBody>>,
+}
+
+/// 団体が記録できる出欠に変換する. 未記録には戻せない
+fn recordable_status(value: u8) -> Result<AttendanceStatus, ApiError> {
+    match AttendanceStatus::from_u8(value) {
+        Ok(AttendanceStatus::Unrecorded) => Err(ApiError::Validation(format!(
+            "{} cannot be set by group",
+            AttendanceStatus::Unrecorded
+        ))),
+        Ok(status) => Ok(status),
+        Err(error) => Err(ApiError::Validation(error.to_string())),
+    }
+}
+
+/// 出欠の記録・チェックインのエラーをレスポンスに変換する
+fn attendance_error_response(error: anyhow::Error) -> Response {
+    match error.downcast_ref::<AttendanceError>() {
+        Some(AttendanceError::NotGroupApply) | Some(AttendanceError::MemberNotFound { .. }) => {
+            log::warn!("error = {}", error);
+            ApiError::Validation(error.to_string()).into_response()
+        }
+        Some(_) => {
+            log::warn!("error = {}", error);
+            ApiError::Conflict(error.to_string()).into_response()
+        }
+        None => {
+            log::error!("error = {}", error);
+            ApiError::Internal(error.to_string()).into_response()
+        }
+    }
+}
+
+/// コードはボランティアの開始日時の1時間前から終了日時まで発行でき, 発行から30分間有効
+///
+/// 発行したコードはRead APIの`getCheckInCode`で取得し, 会場でコードまたはQRコードとして提示する
+#[utoipa::path(
+    post,
+    path="/attendance/code/issue",
+    security(("bearer_auth" = [])),
+    request_body=IssueCheckInCodeRequestBody,
+    responses(
+        (status=200, description="Issue check-in code successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Check-in is not open.", body=WriteApiResponseFailureBody),
+        (status=500, description="Issue check-in code failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn issue_check_in_code(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<IssueCheckInCodeRequestBody>,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(
+            Endpoints::IssueCheckInCode,
+            &auth_user.uid,
+            Target::Volunteer(vid.clone()),
+        )
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.attendance_repository;
+
+    match repository
+        .issue_code(vid, CheckInCode::new(Utc::now()))
+        .await
+    {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Issue check-in code successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => attendance_error_response(error),
+    }
+}
+
+/// 参加者が会場で提示されたコードを入力し, 応募を出席にする
+#[utoipa::path(
+    post,
+    path="/attendance/check-in",
+    security(("bearer_auth" = [])),
+    request_body=CheckInRequestBody,
+    responses(
+        (status=200, description="Check in successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Apply is not approved, check-in is not open, or the code is invalid.", body=WriteApiResponseFailureBody),
+        (status=500, description="Check in failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn check_in(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<CheckInRequestBody>,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+
+    let aid: ApplyId = match ApplyId::from_str(&body.aid) {
+        Ok(aid) => aid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(
+            Endpoints::CheckIn,
+            &auth_user.uid,
+            Target::Apply(aid.clone()),
+        )
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.attendance_repository;
+
+    match repository.check_in(aid, body.code).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Check in successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => attendance_error_response(error),
+    }
+}
+
+/// 欠席はボランティアの開始後のみ記録できる. 集団応募はメンバーごとに記録でき, 応募の出欠はメンバーの出欠から決める
+#[utoipa::path(
+    post,
+    path="/attendance/record",
+    security(("bearer_auth" = [])),
+    request_body=RecordAttendanceRequestBody,
+    responses(
+        (status=200, description="Record attendance successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Apply is not approved or the volunteer has not started.", body=WriteApiResponseFailureBody),
+        (status=500, description="Record attendance failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn record_attendance(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<RecordAttendanceRequestBody>,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+
+    let aid: ApplyId = match ApplyId::from_str(&body.aid) {
+        Ok(aid) => aid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(
+            Endpoints::RecordAttendance,
+            &auth_user.uid,
+            Target::Apply(aid.clone()),
+        )
+        .await
+    {
+        return response;
+    }
+
+    let record: AttendanceRecord = match (body.status, body.members) {
+        (Some(status), None) => match recordable_status(status) {
+            Ok(status) => AttendanceRecord::Apply(status),
+            Err(error) => {
+                log::warn!("error = {}", error);
+                return error.into_response();
+            }
+        },
+        (None, Some(members)) if !members.is_empty() => {
+            let mut records: Vec<MemberAttendance> = vec![];
+            for member in members {
+                match recordable_status(member.status) {
+                    Ok(status) => records.push(MemberAttendance {
+                        serial: member.serial,
+                        status,
+                    }),
+                    Err(error) => {
+                        log::warn!("error = {}", error);
+                        return error.into_response();
+                    }
+                }
+            }
+            AttendanceRecord::Members(records)
+        }
+        _ => {
+            log::warn!("error = either status or members is required");
+            return ApiError::Validation("either status or members is required".to_string())
+                .into_response();
+        }
+    };
+
+    let repository = &mut lock.attendance_repository;
+
+    match repository.record(aid, record).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Record attendance successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => attendance_error_response(error),
+    }
+}
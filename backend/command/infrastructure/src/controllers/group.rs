@@ -4,19 +4,25 @@ use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use api_error::ApiError;
 use command_repository::user_account::group::GroupUserRepository;
-use domain::model::user_account::{
-    user_id::UserId, user_name::UserName, user_name_furigana::UserNameFurigana,
-    user_phone::UserPhone,
+use domain::model::{
+    billing::PlanStatus,
+    payment::PaymentError,
+    plan::{Plan, PlanError},
+    user_account::{
+        user_id::UserId, user_name::UserName, user_name_furigana::UserNameFurigana,
+        user_phone::UserPhone,
+    },
 };
 
+use crate::{auth::AuthUser, user_account::billing};
+
 use super::{AppData, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
 
 /// グループアカウントの作成時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateGroupAccountRequestBody {
-    #[schema(required = true)]
-    pub gid: String,
     #[schema(required = true)]
     pub name: String,
     #[schema(required = true)]
@@ -37,8 +43,6 @@ pub struct CreateGroupAccountRequestBody {
 /// グループアカウントの更新時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateGroupAccountRequestBody {
-    #[schema(required = true)]
-    pub gid: String,
     #[schema(required = true)]
     pub name: String,
     #[schema(required = true)]
@@ -58,61 +62,41 @@ pub struct UpdateGroupAccountRequestBody {
 
 /// グループアカウントのプラン変更時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct SwitchGroupAccountPlanRequestBody {
+pub struct ChangeGroupAccountPlanRequestBody {
+    /// Free, Standard, Premium
     #[schema(required = true)]
-    pub gid: String,
-    #[schema(required = true)]
-    pub is_paid: bool
-}
-
-/// グループアカウントの削除時のリクエストボディを表す構造体
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct DeleteGroupAccountRequestBody {
-    #[schema(required = true)]
-    pub gid: String,
+    pub plan: String,
+    /// 決済サービスで登録した支払い方法. 有料プランへの変更時は必須
+    pub payment_method: Option<String>
 }
 
 #[utoipa::path(
     post,
     path="/group-account/create",
+    security(("bearer_auth" = [])),
     request_body=CreateGroupAccountRequestBody,
     responses(
         (status=200, description="Create group account successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
         (status=500, description="Create group account failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn create_group_account(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<CreateGroupAccountRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.group_account_repository;
 
-    let gid: UserId = match UserId::from_str(&body.gid) {
-        Ok(gid) => gid,
-        Err(error) => {
-            log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let gid: UserId = auth_user.uid;
 
     let name: UserName = match UserName::from_str(&body.name) {
         Ok(name) => name,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -120,13 +104,7 @@ pub async fn create_group_account(
         Ok(furigana) => furigana,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -134,13 +112,7 @@ pub async fn create_group_account(
         Ok(representative_name) => representative_name,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -148,13 +120,7 @@ pub async fn create_group_account(
         Ok(representative_furigana) => representative_furigana,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -162,13 +128,7 @@ pub async fn create_group_account(
         Ok(phone) => phone,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -182,7 +142,7 @@ pub async fn create_group_account(
     };
 
     match repository
-        .create(gid, name, furigana, representative_name, representative_furigana, phone, address, contents, s3_keys)
+        .create(gid, name, furigana, representative_name, representative_furigana, phone, auth_user.email, address, contents, s3_keys)
         .await
     {
         Ok(_) => (
@@ -194,13 +154,7 @@ pub async fn create_group_account(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
@@ -208,44 +162,30 @@ pub async fn create_group_account(
 #[utoipa::path(
     post,
     path="/group-account/update",
+    security(("bearer_auth" = [])),
     request_body=UpdateGroupAccountRequestBody,
     responses(
         (status=200, description="Update group account successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
         (status=500, description="Update group account failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn update_group_account(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<UpdateGroupAccountRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.group_account_repository;
 
-    let gid: UserId = match UserId::from_str(&body.gid) {
-        Ok(gid) => gid,
-        Err(error) => {
-            log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let gid: UserId = auth_user.uid;
 
     let name: UserName = match UserName::from_str(&body.name) {
         Ok(name) => name,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -253,13 +193,7 @@ pub async fn update_group_account(
         Ok(furigana) => furigana,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -267,13 +201,7 @@ pub async fn update_group_account(
         Ok(representative_name) => representative_name,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -281,13 +209,7 @@ pub async fn update_group_account(
         Ok(representative_furigana) => representative_furigana,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -295,13 +217,7 @@ pub async fn update_group_account(
         Ok(phone) => phone,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -315,7 +231,7 @@ pub async fn update_group_account(
     };
 
     match repository
-        .update(gid, name, furigana, representative_name, representative_furigana, phone, address, contents, s3_keys)
+        .update(gid, name, furigana, representative_name, representative_furigana, phone, auth_user.email, address, contents, s3_keys)
         .await
     {
         Ok(_) => (
@@ -327,101 +243,106 @@ pub async fn update_group_account(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
 
 #[utoipa::path(
     post,
-    path="/group-account/switch-plan",
-    request_body=DeleteGroupAccountRequestBody,
+    path="/group-account/change-plan",
+    security(("bearer_auth" = [])),
+    request_body=ChangeGroupAccountPlanRequestBody,
     responses(
-        (status=200, description="Update group account's is_paid successfully.", body=WriteApiResponseSuccessBody),
-        (status=500, description="Update group account's is_paid failed.", body=WriteApiResponseFailureBody)
+        (status=200, description="Change group account's plan successfully.", body=WriteApiResponseSuccessBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=402, description="Payment failed.", body=WriteApiResponseFailureBody),
+        (status=409, description="Already on the plan.", body=WriteApiResponseFailureBody),
+        (status=500, description="Change group account's plan failed.", body=WriteApiResponseFailureBody)
     )
 )]
-pub async fn switch_group_account_plan(
+pub async fn change_group_account_plan(
     State(state): State<AppData>,
-    Json(body): Json<SwitchGroupAccountPlanRequestBody>,
+    auth_user: AuthUser,
+    Json(body): Json<ChangeGroupAccountPlanRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.group_account_repository;
+    let provider = lock.payment_provider.clone();
+    let repository = &mut lock.billing_repository;
 
-    let gid: UserId = match UserId::from_str(&body.gid) {
-        Ok(gid) => gid,
+    let gid: UserId = auth_user.uid;
+
+    let plan: Plan = match Plan::from_str(&body.plan) {
+        Ok(plan) => plan,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
-    let is_paid: bool = body.is_paid;
-
-    match repository.switch_plan(gid, is_paid).await {
+    match billing::change_plan(
+        repository,
+        provider.as_ref(),
+        &gid,
+        plan,
+        body.payment_method,
+    )
+    .await
+    {
+        Ok(PlanStatus::Trialing) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Start free trial successfully.".to_string(),
+            }),
+        )
+            .into_response(),
         Ok(_) => (
             StatusCode::OK,
             Json(WriteApiResponseSuccessBody {
-                message: "Update group account's is_paid successfully.".to_string(),
+                message: "Change group account's plan successfully.".to_string(),
             }),
         )
             .into_response(),
         Err(error) => {
-            log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            log::warn!("error = {}", error);
+            if let Some(PlanError::SamePlan(_)) = error.downcast_ref::<PlanError>() {
+                return ApiError::Conflict(error.to_string()).into_response();
+            }
+            match error.downcast_ref::<PaymentError>() {
+                Some(PaymentError::PaymentMethodRequired) => {
+                    ApiError::Validation(error.to_string()).into_response()
+                }
+                Some(PaymentError::ChargeFailed { .. }) => {
+                    ApiError::PaymentRequired(error.to_string()).into_response()
+                }
+                _ => {
+                    log::error!("error = {}", error);
+                    ApiError::Internal(error.to_string()).into_response()
+                }
+            }
         }
     }
 }
 
-
-
 #[utoipa::path(
     post,
     path="/group-account/delete",
-    request_body=DeleteGroupAccountRequestBody,
+    security(("bearer_auth" = [])),
     responses(
         (status=200, description="Delete group account successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
         (status=500, description="Delete group account failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn delete_group_account(
     State(state): State<AppData>,
-    Json(body): Json<DeleteGroupAccountRequestBody>,
+    auth_user: AuthUser,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.group_account_repository;
 
-    let gid: UserId = match UserId::from_str(&body.gid) {
-        Ok(gid) => gid,
-        Err(error) => {
-            log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let gid: UserId = auth_user.uid;
 
     match repository.delete(gid).await {
         Ok(_) => (
@@ -433,13 +354,7 @@ pub async fn delete_group_account(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
@@ -0,0 +1,243 @@
+use std::str::FromStr;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use api_error::ApiError;
+use command_repository::activities::message::MessageRepository;
+use domain::model::{
+    apply::ApplyId,
+    message::{Message, MessageError, MessageId, ThreadId, ThreadSubject},
+    scout::ScoutId,
+};
+
+use crate::{
+    auth::AuthUser,
+    authz::{Target, WriteApiResponseForbiddenBody},
+};
+
+use super::{AppData, Endpoints, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
+
+/// メッセージ送信時のリクエストボディを表す構造体
+///
+/// `aid`と`sid`のどちらか一方を指定する
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SendMessageRequestBody {
+    pub aid: Option<String>,
+    pub sid: Option<String>,
+    #[schema(required = true)]
+    pub body: String,
+}
+
+/// スレッド既読時のリクエストボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadMessageThreadRequestBody {
+    #[schema(required = true)]
+    pub tid: String,
+}
+
+/// メッセージ削除時のリクエストボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeleteMessageRequestBody {
+    #[schema(required = true)]
+    pub mid: String,
+}
+
+/// 応募・スカウトのスレッドがない場合は, 最初のメッセージの送信時に作成する
+#[utoipa::path(
+    post,
+    path="/message/send",
+    security(("bearer_auth" = [])),
+    request_body=SendMessageRequestBody,
+    responses(
+        (status=200, description="Send message successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=500, description="Send message failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn send_message(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<SendMessageRequestBody>,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+
+    let (subject, target): (ThreadSubject, Target) = match (&body.aid, &body.sid) {
+        (Some(aid), None) => match ApplyId::from_str(aid) {
+            Ok(aid) => (ThreadSubject::Apply(aid.clone()), Target::Apply(aid)),
+            Err(error) => {
+                log::warn!("error = {}", error);
+                return ApiError::Validation(error.to_string()).into_response();
+            }
+        },
+        (None, Some(sid)) => match ScoutId::from_str(sid) {
+            Ok(sid) => (ThreadSubject::Scout(sid.clone()), Target::Scout(sid)),
+            Err(error) => {
+                log::warn!("error = {}", error);
+                return ApiError::Validation(error.to_string()).into_response();
+            }
+        },
+        _ => {
+            log::warn!("error = either aid or sid is required");
+            return ApiError::Validation("either aid or sid is required".to_string())
+                .into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::SendMessage, &auth_user.uid, target)
+        .await
+    {
+        return response;
+    }
+
+    let message: Message = match Message::new(auth_user.uid, body.body) {
+        Ok(message) => message,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    let repository = &mut lock.message_repository;
+
+    match repository.send(subject, message).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Send message successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => {
+            log::error!("error = {}", error);
+            ApiError::Internal(error.to_string()).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path="/message/read",
+    security(("bearer_auth" = [])),
+    request_body=ReadMessageThreadRequestBody,
+    responses(
+        (status=200, description="Read message thread successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=500, description="Read message thread failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn read_message_thread(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<ReadMessageThreadRequestBody>,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+
+    let tid: ThreadId = match ThreadId::from_str(&body.tid) {
+        Ok(tid) => tid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(
+            Endpoints::ReadMessageThread,
+            &auth_user.uid,
+            Target::Thread(tid.clone()),
+        )
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.message_repository;
+
+    match repository.mark_read(tid, auth_user.uid).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Read message thread successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => {
+            log::error!("error = {}", error);
+            ApiError::Internal(error.to_string()).into_response()
+        }
+    }
+}
+
+/// メッセージは論理削除し, スレッドには削除済みとして表示する
+#[utoipa::path(
+    post,
+    path="/message/delete",
+    security(("bearer_auth" = [])),
+    request_body=DeleteMessageRequestBody,
+    responses(
+        (status=200, description="Delete message successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=410, description="Message is already deleted.", body=WriteApiResponseFailureBody),
+        (status=500, description="Delete message failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn delete_message(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<DeleteMessageRequestBody>,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+
+    let mid: MessageId = match MessageId::from_str(&body.mid) {
+        Ok(mid) => mid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(
+            Endpoints::DeleteMessage,
+            &auth_user.uid,
+            Target::Message(mid.clone()),
+        )
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.message_repository;
+
+    match repository.delete(mid).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Delete message successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => match error.downcast_ref::<MessageError>() {
+            Some(MessageError::AlreadyDeleted) => {
+                log::warn!("error = {}", error);
+                ApiError::Deleted(error.to_string()).into_response()
+            }
+            _ => {
+                log::error!("error = {}", error);
+                ApiError::Internal(error.to_string()).into_response()
+            }
+        },
+    }
+}
@@ -1,12 +1,14 @@
 use std::str::FromStr;
 
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime, Weekday};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use api_error::ApiError;
 use command_repository::user_account::participant::ParticipantUserRepository;
 use domain::model::{
+    availability::{Availability, AvailabilityError, AvailabilityId},
     condition::Condition,
     gender::{gender_from_i8, Gender},
     region::Region,
@@ -19,13 +21,13 @@ use domain::model::{
     },
 };
 
+use crate::auth::AuthUser;
+
 use super::{AppData, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
 
 /// 参加者アカウントの作成時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateParticipantAccountRequestBody {
-    #[schema(required = true)]
-    pub pid: String,
     #[schema(required = true)]
     pub name: String,
     #[schema(required = true)]
@@ -55,8 +57,6 @@ pub struct CreateParticipantAccountRequestBody {
 /// 参加者アカウントの更新時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateParticipantAccountRequestBody {
-    #[schema(required = true)]
-    pub pid: String,
     #[schema(required = true)]
     pub name: String,
     #[schema(required = true)]
@@ -83,54 +83,54 @@ pub struct UpdateParticipantAccountRequestBody {
     pub target_status: String,
 }
 
-/// 参加者アカウントの削除時のリクエストボディを表す構造体
+/// 参加可能な時間帯の登録時のリクエストボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RegisterParticipantAvailabilityRequestBody {
+    /// 曜日(Mon, Tue, Wed, Thu, Fri, Sat, Sun)
+    #[schema(required = true, example = "Sat")]
+    pub weekday: String,
+    /// 開始時刻(日本時間)
+    #[schema(required = true, value_type = String, example = "09:00:00")]
+    pub start_time: NaiveTime,
+    /// 終了時刻(日本時間)
+    #[schema(required = true, value_type = String, example = "12:00:00")]
+    pub finish_time: NaiveTime,
+}
+
+/// 参加可能な時間帯の削除時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct DeleteParticipantAccountRequestBody {
+pub struct DeleteParticipantAvailabilityRequestBody {
     #[schema(required = true)]
-    pub pid: String,
+    pub did: String,
 }
 
 #[utoipa::path(
     post,
     path="/participant-account/create",
+    security(("bearer_auth" = [])),
     request_body=CreateParticipantAccountRequestBody,
     responses(
         (status=200, description="Create participant account successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
         (status=500, description="Create participant account failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn create_participant_account(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<CreateParticipantAccountRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.participant_account_repository;
 
-    let pid: UserId = match UserId::from_str(&body.pid) {
-        Ok(pid) => pid,
-        Err(error) => {
-            log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let pid: UserId = auth_user.uid;
 
     let name: UserName = match UserName::from_str(&body.name) {
         Ok(name) => name,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -138,13 +138,7 @@ pub async fn create_participant_account(
         Ok(furigana) => furigana,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -152,13 +146,7 @@ pub async fn create_participant_account(
         Ok(phone) => phone,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -166,13 +154,7 @@ pub async fn create_participant_account(
         Ok(gender) => gender,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Internal(error.to_string()).into_response();
         }
     };
 
@@ -180,44 +162,73 @@ pub async fn create_participant_account(
 
     let birthday: NaiveDate = body.birthday;
 
-    let region: Vec<Region> = body
+    let region: Vec<Region> = match body
         .region
         .iter()
-        .map(|r: &String| Region::from_str(r).unwrap())
-        .collect::<Vec<Region>>();
+        .map(|r: &String| Region::from_str(r))
+        .collect::<Result<Vec<Region>, _>>()
+    {
+        Ok(region) => region,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    let theme: Vec<Theme> = body
+    let theme: Vec<Theme> = match body
         .theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
-    let required_theme: Vec<Theme> = body
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(theme) => theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_theme: Vec<Theme> = match body
         .required_theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(required_theme) => required_theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    let condition: Vec<Condition> = body
+    let condition: Vec<Condition> = match body
         .condition
         .iter()
-        .map(|c: &String| Condition::from_str(c).unwrap())
-        .collect::<Vec<Condition>>();
-    let required_condition: Vec<Condition> = body
+        .map(|c: &String| Condition::from_str(c))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(condition) => condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_condition: Vec<Condition> = match body
         .required_condition
         .iter()
-        .map(|c: &String| Condition::from_str(c).unwrap())
-        .collect::<Vec<Condition>>();
+        .map(|c: &String| Condition::from_str(c))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(required_condition) => required_condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
     let target_status: TargetStatus = match TargetStatus::from_str(&body.target_status) {
         Ok(target_status) => target_status,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -231,7 +242,7 @@ pub async fn create_participant_account(
     );
 
     match repository
-        .create(pid, name, furigana, phone, gender, birthday, profile, terms)
+        .create(pid, name, furigana, phone, auth_user.email, gender, birthday, profile, terms)
         .await
     {
         Ok(_) => (
@@ -243,13 +254,7 @@ pub async fn create_participant_account(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
@@ -257,44 +262,30 @@ pub async fn create_participant_account(
 #[utoipa::path(
     post,
     path="/participant-account/update",
+    security(("bearer_auth" = [])),
     request_body=UpdateParticipantAccountRequestBody,
     responses(
         (status=200, description="Update participant account successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
         (status=500, description="Update participant account failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn update_participant_account(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<UpdateParticipantAccountRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.participant_account_repository;
 
-    let pid: UserId = match UserId::from_str(&body.pid) {
-        Ok(pid) => pid,
-        Err(error) => {
-            log::warn!("error = {}", error);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let pid: UserId = auth_user.uid;
 
     let name: UserName = match UserName::from_str(&body.name) {
         Ok(name) => name,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Internal(error.to_string()).into_response();
         }
     };
 
@@ -302,13 +293,7 @@ pub async fn update_participant_account(
         Ok(furigana) => furigana,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Internal(error.to_string()).into_response();
         }
     };
 
@@ -316,13 +301,7 @@ pub async fn update_participant_account(
         Ok(phone) => phone,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -330,57 +309,80 @@ pub async fn update_participant_account(
         Ok(gender) => gender,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Internal(error.to_string()).into_response();
         }
     };
 
     let birthday: NaiveDate = body.birthday;
 
     let profile: String = body.profile;
-    let region: Vec<Region> = body
+    let region: Vec<Region> = match body
         .region
         .iter()
-        .map(|r: &String| Region::from_str(r).unwrap())
-        .collect::<Vec<Region>>();
+        .map(|r: &String| Region::from_str(r))
+        .collect::<Result<Vec<Region>, _>>()
+    {
+        Ok(region) => region,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    let theme: Vec<Theme> = body
+    let theme: Vec<Theme> = match body
         .theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
-    let required_theme: Vec<Theme> = body
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(theme) => theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_theme: Vec<Theme> = match body
         .required_theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(required_theme) => required_theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    let condition: Vec<Condition> = body
+    let condition: Vec<Condition> = match body
         .condition
         .iter()
-        .map(|c: &String| Condition::from_str(c).unwrap())
-        .collect::<Vec<Condition>>();
-    let required_condition: Vec<Condition> = body
+        .map(|c: &String| Condition::from_str(c))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(condition) => condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_condition: Vec<Condition> = match body
         .required_condition
         .iter()
-        .map(|c: &String| Condition::from_str(c).unwrap())
-        .collect::<Vec<Condition>>();
+        .map(|c: &String| Condition::from_str(c))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(required_condition) => required_condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
     let target_status: TargetStatus = match TargetStatus::from_str(&body.target_status) {
         Ok(target_status) => target_status,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
@@ -394,7 +396,7 @@ pub async fn update_participant_account(
     );
 
     match repository
-        .update(pid, name, furigana, phone, gender, birthday, profile, terms)
+        .update(pid, name, furigana, phone, auth_user.email, gender, birthday, profile, terms)
         .await
     {
         Ok(_) => (
@@ -406,13 +408,7 @@ pub async fn update_participant_account(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
@@ -420,50 +416,147 @@ pub async fn update_participant_account(
 #[utoipa::path(
     post,
     path="/participant-account/delete",
-    request_body=DeleteParticipantAccountRequestBody,
+    security(("bearer_auth" = [])),
     responses(
         (status=200, description="Delete participant account successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
         (status=500, description="Delete participant account failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn delete_participant_account(
     State(state): State<AppData>,
-    Json(body): Json<DeleteParticipantAccountRequestBody>,
+    auth_user: AuthUser,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+    let repository = &mut lock.participant_account_repository;
+
+    let pid: UserId = auth_user.uid;
+
+    match repository.delete(pid).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Delete participant account successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => {
+            log::error!("error = {}", error);
+            ApiError::Internal(error.to_string()).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path="/participant-account/availability/register",
+    security(("bearer_auth" = [])),
+    request_body=RegisterParticipantAvailabilityRequestBody,
+    responses(
+        (status=200, description="Register participant availability successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=409, description="Overlaps with another availability.", body=WriteApiResponseFailureBody),
+        (status=500, description="Register participant availability failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn register_participant_availability(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<RegisterParticipantAvailabilityRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.participant_account_repository;
 
-    let pid: UserId = match UserId::from_str(&body.pid) {
-        Ok(pid) => pid,
+    let pid: UserId = auth_user.uid;
+    let did: AvailabilityId = AvailabilityId::new();
+
+    let weekday: Weekday = match Weekday::from_str(&body.weekday) {
+        Ok(weekday) => weekday,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
-    match repository.delete(pid).await {
+    let availability: Availability =
+        match Availability::new(weekday, body.start_time, body.finish_time) {
+            Ok(availability) => availability,
+            Err(error) => {
+                log::warn!("error = {}", error);
+                return ApiError::Validation(error.to_string()).into_response();
+            }
+        };
+
+    match repository.register_availability(pid, did, availability).await {
         Ok(_) => (
             StatusCode::OK,
             Json(WriteApiResponseSuccessBody {
-                message: "Delete participant account successfully.".to_string(),
+                message: "Register participant availability successfully.".to_string(),
             }),
         )
             .into_response(),
+        Err(error) => match error.downcast_ref::<AvailabilityError>() {
+            Some(AvailabilityError::Overlapped(_)) => {
+                log::warn!("error = {}", error);
+                ApiError::Conflict(error.to_string()).into_response()
+            }
+            _ => {
+                log::error!("error = {}", error);
+                ApiError::Internal(error.to_string()).into_response()
+            }
+        },
+    }
+}
+
+#[utoipa::path(
+    post,
+    path="/participant-account/availability/delete",
+    security(("bearer_auth" = [])),
+    request_body=DeleteParticipantAvailabilityRequestBody,
+    responses(
+        (status=200, description="Delete participant availability successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=404, description="Availability not found.", body=WriteApiResponseFailureBody),
+        (status=500, description="Delete participant availability failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn delete_participant_availability(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<DeleteParticipantAvailabilityRequestBody>,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+    let repository = &mut lock.participant_account_repository;
+
+    let pid: UserId = auth_user.uid;
+
+    let did: AvailabilityId = match AvailabilityId::from_str(&body.did) {
+        Ok(did) => did,
         Err(error) => {
-            log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
         }
+    };
+
+    match repository.delete_availability(pid, did).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Delete participant availability successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => match error.downcast_ref::<AvailabilityError>() {
+            Some(AvailabilityError::NotFound) => {
+                log::warn!("error = {}", error);
+                ApiError::NotFound(error.to_string()).into_response()
+            }
+            _ => {
+                log::error!("error = {}", error);
+                ApiError::Internal(error.to_string()).into_response()
+            }
+        },
     }
 }
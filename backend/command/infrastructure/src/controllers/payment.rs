@@ -0,0 +1,90 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+
+use api_error::ApiError;
+use command_repository::user_account::billing::BillingRepository;
+use domain::model::{billing::BillingError, payment::{PaymentError, PaymentEvent}};
+
+use super::{AppData, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
+
+/// Webhookの署名を送るヘッダー
+pub const PAYMENT_SIGNATURE_HEADER: &str = "X-Payment-Signature";
+
+#[utoipa::path(
+    post,
+    path="/payment/webhook",
+    params(
+        ("X-Payment-Signature" = String, Header, description = "Hex-encoded HMAC-SHA256 of the raw request body, keyed with the webhook secret.")
+    ),
+    request_body(content=String, description="Payment event sent by the payment provider.", content_type="application/json"),
+    responses(
+        (status=200, description="Process payment event successfully.", body=WriteApiResponseSuccessBody),
+        (status=400, description="Invalid payment event.", body=WriteApiResponseFailureBody),
+        (status=401, description="Invalid signature.", body=WriteApiResponseFailureBody),
+        (status=409, description="Invoice cannot be changed by the event.", body=WriteApiResponseFailureBody),
+        (status=500, description="Process payment event failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn payment_webhook(
+    State(state): State<AppData>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let mut lock = state.write().await;
+    let provider = lock.payment_provider.clone();
+    let repository = &mut lock.billing_repository;
+
+    let signature: Option<&str> = headers
+        .get(PAYMENT_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    let event: PaymentEvent = match provider.parse_event(signature, &body) {
+        Ok(event) => event,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return match error.downcast_ref::<PaymentError>() {
+                Some(PaymentError::InvalidSignature) => {
+                    ApiError::Unauthorized(error.to_string()).into_response()
+                }
+                _ => ApiError::Validation(error.to_string()).into_response(),
+            };
+        }
+    };
+
+    // 決済サービスは同じイベントを再送するため, 処理済みのイベントも成功として返す
+    match repository.apply_payment_event(&event).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Process payment event successfully.".to_string(),
+            }),
+        )
+            .into_response(),
+        Ok(false) => (
+            StatusCode::OK,
+            Json(WriteApiResponseSuccessBody {
+                message: "Payment event already processed.".to_string(),
+            }),
+        )
+            .into_response(),
+        Err(error) => {
+            if let Some(PaymentError::InvalidPayload(_)) = error.downcast_ref::<PaymentError>() {
+                log::warn!("error = {}", error);
+                return ApiError::Validation(error.to_string()).into_response();
+            }
+            if let Some(BillingError::InvalidInvoiceTransition { .. }) =
+                error.downcast_ref::<BillingError>()
+            {
+                log::warn!("error = {}", error);
+                return ApiError::Conflict(error.to_string()).into_response();
+            }
+            log::error!("error = {}", error);
+            ApiError::Internal(error.to_string()).into_response()
+        }
+    }
+}
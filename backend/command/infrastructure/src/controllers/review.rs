@@ -5,17 +5,21 @@ use command_repository::activities::review::ReviewRepository;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use api_error::ApiError;
 use domain::model::{
         user_account::user_id::UserId, volunteer::VolunteerId
     };
 
-use super::{WriteApiResponseFailureBody, WriteApiResponseSuccessBody, AppData};
+use crate::{
+    auth::AuthUser,
+    authz::{Target, WriteApiResponseForbiddenBody},
+};
+
+use super::{WriteApiResponseFailureBody, WriteApiResponseSuccessBody, AppData, Endpoints};
 
 /// 参加者からボランティアへレビュー時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ReviewToVolunteerRequestBody {
-    #[schema(required = true)]
-    pub uid: String,
     #[schema(required = true)]
     pub vid: String,
     #[schema(required = true)]
@@ -38,34 +42,50 @@ pub struct ReviewToParticipantRequestBody {
 #[utoipa::path(
     post,
     path="/review/to-volunteer",
+    security(("bearer_auth" = [])),
     request_body=ReviewToVolunteerRequestBody,
     responses(
         (status=200, description="Create review (to volunteer) successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
         (status=500, description="Create review (to volunteer) failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn review_to_volunteer(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<ReviewToVolunteerRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.review_repository;
 
-    let uid: UserId = match UserId::from_str(&body.uid) {
-        Ok(uid) => uid,
+    let uid: UserId = auth_user.uid;
+
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
+    if let Err(response) = lock
+        .authorizer
+        .enforce(
+            Endpoints::ReviewToVolunteer,
+            &uid,
+            Target::Participation {
+                uid: uid.clone(),
+                vid: vid.clone(),
+            },
+        )
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.review_repository;
+
     let point: u8 = body.point;
     let comment: Option<String> = body.comment;
 
@@ -82,13 +102,7 @@ pub async fn review_to_volunteer(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
@@ -96,34 +110,56 @@ pub async fn review_to_volunteer(
 #[utoipa::path(
     post,
     path="/review/to-participant",
+    security(("bearer_auth" = [])),
     request_body=ReviewToVolunteerRequestBody,
     responses(
         (status=200, description="Create review (to participant) successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
         (status=500, description="Create review (to participant) failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn review_to_participant(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<ReviewToParticipantRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.review_repository;
 
     let uid: UserId = match UserId::from_str(&body.uid) {
         Ok(uid) => uid,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(
+            Endpoints::ReviewToParticipant,
+            &auth_user.uid,
+            Target::Participation {
+                uid: uid.clone(),
+                vid: vid.clone(),
+            },
+        )
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.review_repository;
+
     let point: u8 = body.point;
     let comment: Option<String> = body.comment;
 
@@ -140,13 +176,7 @@ pub async fn review_to_participant(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
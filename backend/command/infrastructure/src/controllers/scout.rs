@@ -4,10 +4,29 @@ use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use command_repository::activities::scout::ScoutRepository;
-use domain::model::{scout::ScoutId, user_account::user_id::UserId, volunteer::VolunteerId};
+use anyhow::Result;
+use api_error::ApiError;
+use command_repository::{
+    activities::scout::ScoutRepository,
+    unit_of_work::{UnitOfWork, UnitOfWorkFactory},
+};
+use domain::model::{
+    apply::ApplyId,
+    schedule::SessionId,
+    scout::{Scout, ScoutError, ScoutId},
+    user_account::user_id::UserId,
+    volunteer::VolunteerId,
+};
 
-use super::{AppData, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
+use crate::{
+    auth::AuthUser,
+    authz::{Target, WriteApiResponseForbiddenBody},
+};
+
+use super::{
+    quota_error_response, session_error_response, AppData, Endpoints, WriteApiResponseFailureBody,
+    WriteApiResponseSuccessBody,
+};
 
 /// スカウト時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -20,23 +39,32 @@ pub struct CreateScoutRequestBody {
     pub message: String,
 }
 
-/// スカウトメール送信済み更新時のリクエストボディを表す構造体
+/// スカウト既読更新時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct UpdateScoutIsSentRequestBody {
+pub struct UpdateScoutIsReadRequestBody {
     #[schema(required = true)]
     pub sid: String,
 }
 
-/// スカウト既読更新時のリクエストボディを表す構造体
+/// スカウト拒否時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct UpdateScoutIsReadRequestBody {
+pub struct UpdateScoutDeniedRequestBody {
     #[schema(required = true)]
     pub sid: String,
 }
 
-/// スカウト拒否時のリクエストボディを表す構造体
+/// スカウト承諾時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct UpdateScoutDeniedRequestBody {
+pub struct AcceptScoutRequestBody {
+    #[schema(required = true)]
+    pub sid: String,
+    /// 応募する回のID. 回が1つだけのボランティアでは省略できる
+    pub vsid: Option<String>,
+}
+
+/// スカウト取り下げ時のリクエストボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WithdrawScoutRequestBody {
     #[schema(required = true)]
     pub sid: String,
 }
@@ -44,172 +72,311 @@ pub struct UpdateScoutDeniedRequestBody {
 #[utoipa::path(
     post,
     path="/scout/create",
+    security(("bearer_auth" = [])),
     request_body=CreateScoutRequestBody,
     responses(
         (status=200, description="Create scout successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=429, description="Monthly scout quota of the plan exceeded.", body=WriteApiResponseFailureBody),
         (status=500, description="Create scout failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn create_scout(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<CreateScoutRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.scout_repository;
 
     let sid: ScoutId = ScoutId::new();
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::CreateScout, &auth_user.uid, Target::Volunteer(vid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.scout_repository;
 
     let uid: UserId = match UserId::from_str(&body.uid) {
         Ok(uid) => uid,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
 
     let message: String = body.message;
 
-    match repository.create(sid, vid, uid, message).await {
-        Ok(_) => (
-            StatusCode::OK,
-            Json(WriteApiResponseSuccessBody {
-                message: "Create scout successfully.".to_string(),
-            }),
-        )
-            .into_response(),
-        Err(error) => {
-            log::error!("error = {}", error);
+    match repository.create(sid.clone(), vid, uid, message).await {
+        Ok(_) => {
+            lock.event_notifier.scout_received(&sid).await;
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
+                StatusCode::OK,
+                Json(WriteApiResponseSuccessBody {
+                    message: "Create scout successfully.".to_string(),
                 }),
             )
                 .into_response()
         }
+        Err(error) => quota_error_response(error),
     }
 }
 
 #[utoipa::path(
     post,
-    path="/scout/update/is-sent",
-    request_body=UpdateScoutIsSentRequestBody,
+    path="/scout/update/is-read",
+    security(("bearer_auth" = [])),
+    request_body=UpdateScoutIsReadRequestBody,
     responses(
-        (status=200, description="Update scout's is_sent successfully.", body=WriteApiResponseSuccessBody),
-        (status=500, description="Update scout's is_sent failed.", body=WriteApiResponseFailureBody)
+        (status=200, description="Update scout's is_read successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=500, description="Update scout's is_read failed.", body=WriteApiResponseFailureBody)
     )
 )]
-pub async fn update_scout_is_sent(
+pub async fn update_scout_is_read(
     State(state): State<AppData>,
-    Json(body): Json<UpdateScoutIsSentRequestBody>,
+    auth_user: AuthUser,
+    Json(body): Json<UpdateScoutIsReadRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.scout_repository;
 
-    let sid: ScoutId = ScoutId::from_str(&body.sid);
+    let sid: ScoutId = match ScoutId::from_str(&body.sid) {
+        Ok(sid) => sid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::UpdateScoutIsRead, &auth_user.uid, Target::Scout(sid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.scout_repository;
 
-    match repository.update_is_sent(sid).await {
+    match repository.update_is_read(sid).await {
         Ok(_) => (
             StatusCode::OK,
             Json(WriteApiResponseSuccessBody {
-                message: "Update scout's is_sent successfully.".to_string(),
+                message: "Update scout's is_read successfully.".to_string(),
             }),
         )
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
 
 #[utoipa::path(
     post,
-    path="/scout/update/is-read",
-    request_body=UpdateScoutIsReadRequestBody,
+    path="/scout/update/denied",
+    security(("bearer_auth" = [])),
+    request_body=UpdateScoutDeniedRequestBody,
     responses(
-        (status=200, description="Update scout's is_read successfully.", body=WriteApiResponseSuccessBody),
-        (status=500, description="Update scout's is_read failed.", body=WriteApiResponseFailureBody)
+        (status=200, description="Update scout's denied successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Invalid status transition.", body=WriteApiResponseFailureBody),
+        (status=500, description="Update scout's denied failed.", body=WriteApiResponseFailureBody)
     )
 )]
-pub async fn update_scout_is_read(
+pub async fn update_scout_denied(
     State(state): State<AppData>,
-    Json(body): Json<UpdateScoutIsReadRequestBody>,
+    auth_user: AuthUser,
+    Json(body): Json<UpdateScoutDeniedRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.scout_repository;
 
-    let sid: ScoutId = ScoutId::from_str(&body.sid);
+    let sid: ScoutId = match ScoutId::from_str(&body.sid) {
+        Ok(sid) => sid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    match repository.update_is_read(sid).await {
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::UpdateScoutDenied, &auth_user.uid, Target::Scout(sid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.scout_repository;
+
+    match repository.update_denied(sid).await {
         Ok(_) => (
             StatusCode::OK,
             Json(WriteApiResponseSuccessBody {
-                message: "Update scout's is_read successfully.".to_string(),
+                message: "Update scout's denied successfully.".to_string(),
             }),
         )
             .into_response(),
+        Err(error) => scout_error_response(error),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path="/scout/accept",
+    security(("bearer_auth" = [])),
+    request_body=AcceptScoutRequestBody,
+    responses(
+        (status=200, description="Accept scout successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Invalid status transition, already applied or session already started.", body=WriteApiResponseFailureBody),
+        (status=500, description="Accept scout failed.", body=WriteApiResponseFailureBody)
+    )
+)]
+pub async fn accept_scout(
+    State(state): State<AppData>,
+    auth_user: AuthUser,
+    Json(body): Json<AcceptScoutRequestBody>,
+) -> impl IntoResponse {
+    let lock = state.write().await;
+
+    let sid: ScoutId = match ScoutId::from_str(&body.sid) {
+        Ok(sid) => sid,
         Err(error) => {
-            log::error!("error = {}", error);
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::AcceptScout, &auth_user.uid, Target::Scout(sid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let vsid: Option<SessionId> = match body.vsid.as_deref().map(SessionId::from_str) {
+        Some(Ok(vsid)) => Some(vsid),
+        Some(Err(error)) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+        None => None,
+    };
+
+    let aid: ApplyId = ApplyId::new();
+
+    match accept_with_apply(&lock.unit_of_work_factory, sid, aid.clone(), vsid).await {
+        Ok(_) => {
+            lock.event_notifier.apply_changed(&aid).await;
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
+                StatusCode::OK,
+                Json(WriteApiResponseSuccessBody {
+                    message: "Accept scout successfully.".to_string(),
                 }),
             )
                 .into_response()
         }
+        Err(error) => scout_error_response(error),
     }
 }
 
 #[utoipa::path(
     post,
-    path="/scout/update/denied",
-    request_body=UpdateScoutDeniedRequestBody,
+    path="/scout/withdraw",
+    security(("bearer_auth" = [])),
+    request_body=WithdrawScoutRequestBody,
     responses(
-        (status=200, description="Update scout's denied successfully.", body=WriteApiResponseSuccessBody),
-        (status=500, description="Update scout's denied failed.", body=WriteApiResponseFailureBody)
+        (status=200, description="Withdraw scout successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Invalid status transition.", body=WriteApiResponseFailureBody),
+        (status=500, description="Withdraw scout failed.", body=WriteApiResponseFailureBody)
     )
 )]
-pub async fn update_scout_denied(
+pub async fn withdraw_scout(
     State(state): State<AppData>,
-    Json(body): Json<UpdateScoutDeniedRequestBody>,
+    auth_user: AuthUser,
+    Json(body): Json<WithdrawScoutRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.scout_repository;
 
-    let sid: ScoutId = ScoutId::from_str(&body.sid);
+    let sid: ScoutId = match ScoutId::from_str(&body.sid) {
+        Ok(sid) => sid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
-    match repository.update_denied(sid).await {
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::WithdrawScout, &auth_user.uid, Target::Scout(sid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.scout_repository;
+
+    match repository.withdraw(sid).await {
         Ok(_) => (
             StatusCode::OK,
             Json(WriteApiResponseSuccessBody {
-                message: "Update scout's denied successfully.".to_string(),
+                message: "Withdraw scout successfully.".to_string(),
             }),
         )
             .into_response(),
-        Err(error) => {
-            log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+        Err(error) => scout_error_response(error),
+    }
+}
+
+/// スカウトの承諾と, 同じボランティアの回への応募の作成を1つのトランザクションで行う
+async fn accept_with_apply(
+    factory: &dyn UnitOfWorkFactory,
+    sid: ScoutId,
+    aid: ApplyId,
+    vsid: Option<SessionId>,
+) -> Result<()> {
+    let uow: Box<dyn UnitOfWork> = factory.begin().await?;
+
+    let scout: Scout = uow.scout_repository().accept(sid.clone()).await?;
+    uow.apply_repository()
+        .create(aid.clone(), scout.sender, vsid, scout.destination, false, None)
+        .await?;
+    uow.scout_repository().link_apply(sid, aid).await?;
+
+    uow.commit().await
+}
+
+/// スカウトの状態遷移のエラーは409, 応募する回のエラーは400または409, それ以外は500のレスポンスに変換する
+fn scout_error_response(error: anyhow::Error) -> axum::response::Response {
+    match error.downcast_ref::<ScoutError>() {
+        Some(ScoutError::InvalidTransition { .. }) | Some(ScoutError::AlreadyApplied) => {
+            log::warn!("error = {}", error);
+            ApiError::Conflict(error.to_string()).into_response()
         }
+        _ => session_error_response(error),
     }
 }
@@ -5,19 +5,28 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use command_repository::activities::volunteer::VolunteerRepository;
+use api_error::ApiError;
+use command_repository::{activities::volunteer::VolunteerRepository, geocoder::Geocoder};
 use domain::model::{
-    condition::Condition, region::Region, target_status::TargetStatus, terms::Terms, theme::Theme,
+    condition::Condition, location::GeoPoint, region::Region,
+    schedule::{RecurrenceRule, Schedule, ScheduleError, VolunteerSession},
+    target_status::TargetStatus, terms::Terms, theme::Theme,
     user_account::user_id::UserId, volunteer::VolunteerId,
 };
 
-use super::{AppData, WriteApiResponseFailureBody, WriteApiResponseSuccessBody};
+use crate::{
+    auth::AuthUser,
+    authz::{Target, WriteApiResponseForbiddenBody},
+};
+
+use super::{
+    quota_error_response, AppData, Endpoints, WriteApiResponseFailureBody,
+    WriteApiResponseSuccessBody,
+};
 
 /// ボランティアの作成時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateVolunteerRequestBody {
-    #[schema(required = true)]
-    pub gid: String,
     #[schema(required = true)]
     pub title: String,
     #[schema(required = true)]
@@ -51,6 +60,10 @@ pub struct CreateVolunteerRequestBody {
     #[schema(required = true)]
     pub target_status: Vec<String>,
     pub photos: Option<Vec<String>>,
+    /// 繰り返しの規則(例: `FREQ=WEEKLY;COUNT=52`). `start_at`と`finish_at`を最初の回とする
+    pub recurrence: Option<String>,
+    /// 回の一覧. 指定した場合は`start_at`と`finish_at`の代わりに使用する
+    pub sessions: Option<Vec<VolunteerSessionRequestBody>>,
 }
 
 /// ボランティアの更新時のリクエストボディを表す構造体
@@ -59,8 +72,6 @@ pub struct UpdateVolunteerRequestBody {
     #[schema(required = true)]
     pub vid: String,
     #[schema(required = true)]
-    pub gid: String,
-    #[schema(required = true)]
     pub title: String,
     #[schema(required = true)]
     pub message: String,
@@ -93,6 +104,21 @@ pub struct UpdateVolunteerRequestBody {
     #[schema(required = true)]
     pub target_status: Vec<String>,
     pub photos: Option<Vec<String>>,
+    /// 繰り返しの規則(例: `FREQ=WEEKLY;COUNT=52`). `start_at`と`finish_at`を最初の回とする
+    pub recurrence: Option<String>,
+    /// 回の一覧. 指定した場合は`start_at`と`finish_at`の代わりに使用する
+    pub sessions: Option<Vec<VolunteerSessionRequestBody>>,
+}
+
+/// ボランティアの回を指定する場合のリクエストボディを表す構造体
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VolunteerSessionRequestBody {
+    #[schema(required = true, value_type = String, format = DateTime, example = "2023-12-17T09:00:00Z")]
+    pub start_at: DateTime<Utc>,
+    #[schema(required = true, value_type = String, format = DateTime, example = "2023-12-17T17:00:00Z")]
+    pub finish_at: DateTime<Utc>,
+    /// 回の募集人数. 指定しない場合はボランティアの`recruited_num`
+    pub recruited_num: Option<u32>,
 }
 
 /// ボランティアの削除時のリクエストボディを表す構造体
@@ -105,8 +131,6 @@ pub struct DeleteVolunteerRequestBody {
 /// お気に入り登録時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RegisterVolunteerFavoriteRequestBody {
-    #[schema(required = true)]
-    pub uid: String,
     #[schema(required = true)]
     pub vid: String
 }
@@ -114,93 +138,187 @@ pub struct RegisterVolunteerFavoriteRequestBody {
 /// お気に入り登録時のリクエストボディを表す構造体
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UnregisterVolunteerFavoriteRequestBody {
-    #[schema(required = true)]
-    pub uid: String,
     #[schema(required = true)]
     pub vid: String
 }
 
+/// 開催場所を緯度経度に変換する. 変換に失敗した場合は緯度経度なしで登録する
+async fn geocode_place(geocoder: &dyn Geocoder, place: &str) -> Option<GeoPoint> {
+    match geocoder.geocode(place).await {
+        Ok(location) => location,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            None
+        }
+    }
+}
+
+/// リクエストから日程を作成する. `recurrence`と`sessions`のどちらも指定しない場合は1回だけのボランティアとする
+fn schedule_from_request(
+    start_at: DateTime<Utc>,
+    finish_at: DateTime<Utc>,
+    recruited_num: u32,
+    recurrence: Option<String>,
+    sessions: Option<Vec<VolunteerSessionRequestBody>>,
+) -> anyhow::Result<Schedule> {
+    match (recurrence, sessions) {
+        (Some(_), Some(_)) => Err(ScheduleError::AmbiguousSchedule.into()),
+        (Some(rule), None) => Schedule::recurring(
+            RecurrenceRule::from_str(&rule)?,
+            start_at,
+            finish_at,
+            recruited_num,
+        ),
+        (None, Some(sessions)) => Schedule::explicit(
+            sessions
+                .into_iter()
+                .map(|session| {
+                    VolunteerSession::new(
+                        session.start_at,
+                        session.finish_at,
+                        session.recruited_num.unwrap_or(recruited_num),
+                    )
+                })
+                .collect::<anyhow::Result<Vec<VolunteerSession>>>()?,
+        ),
+        (None, None) => Schedule::once(start_at, finish_at, recruited_num),
+    }
+}
+
+/// 応募がある回を日程から除く場合は409, それ以外はプランの上限のエラーとして変換する
+fn volunteer_error_response(error: anyhow::Error) -> axum::response::Response {
+    match error.downcast_ref::<ScheduleError>() {
+        Some(_) => {
+            log::warn!("error = {}", error);
+            ApiError::Conflict(error.to_string()).into_response()
+        }
+        None => quota_error_response(error),
+    }
+}
+
 #[utoipa::path(
     post,
     path="/volunteer/create",
+    security(("bearer_auth" = [])),
     request_body=CreateVolunteerRequestBody,
     responses(
         (status=200, description="Create volunteer successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=429, description="Open volunteer or photo limit of the plan exceeded.", body=WriteApiResponseFailureBody),
         (status=500, description="Create volunteer failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn create_volunteer(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<CreateVolunteerRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
+    let geocoder = lock.geocoder.clone();
     let repository = &mut lock.volunteer_repository;
 
     let vid: VolunteerId = VolunteerId::new();
 
-    let gid: UserId = match UserId::from_str(&body.gid) {
-        Ok(gid) => gid,
-        Err(error) => {
-            log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let gid: UserId = auth_user.uid;
 
     let title: String = body.title;
     let message: String = body.message;
     let overview: String = body.overview;
     let recruited_num: u32 = body.recruited_num;
     let place: String = body.place;
-    let start_at: DateTime<Utc> = body.start_at;
-    let finish_at: DateTime<Utc> = body.finish_at;
+    let location: Option<GeoPoint> = geocode_place(geocoder.as_ref(), &place).await;
+    let schedule: Schedule = match schedule_from_request(
+        body.start_at,
+        body.finish_at,
+        recruited_num,
+        body.recurrence,
+        body.sessions,
+    ) {
+        Ok(schedule) => schedule,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
     let deadline_on: NaiveDate = body.deadline_on;
     let as_group: bool = body.as_group;
-    let region: Vec<Region> = body
+    let region: Vec<Region> = match body
         .region
         .iter()
-        .map(|r: &String| Region::from_str(r).unwrap())
-        .collect::<Vec<Region>>();
-    let theme: Vec<Theme> = body
+        .map(|r: &String| Region::from_str(r))
+        .collect::<Result<Vec<Region>, _>>()
+    {
+        Ok(region) => region,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let theme: Vec<Theme> = match body
         .theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
-    let required_theme: Vec<Theme> = body
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(theme) => theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_theme: Vec<Theme> = match body
         .required_theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
-    let condition: Vec<Condition> = body
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(required_theme) => required_theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let condition: Vec<Condition> = match body
         .condition
         .iter()
-        .map(|t: &String| Condition::from_str(t).unwrap())
-        .collect::<Vec<Condition>>();
-    let required_condition: Vec<Condition> = body
+        .map(|t: &String| Condition::from_str(t))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(condition) => condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_condition: Vec<Condition> = match body
         .required_condition
         .iter()
-        .map(|t: &String| Condition::from_str(t).unwrap())
-        .collect::<Vec<Condition>>();
+        .map(|t: &String| Condition::from_str(t))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(required_condition) => required_condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
     let reward: Option<String> = body.reward;
-    let target_status: Vec<TargetStatus> = body
+    let target_status: Vec<TargetStatus> = match body
         .target_status
         .iter()
-        .map(|t: &String| TargetStatus::from_str(t).unwrap())
-        .collect::<Vec<TargetStatus>>();
+        .map(|t: &String| TargetStatus::from_str(t))
+        .collect::<Result<Vec<TargetStatus>, _>>()
+    {
+        Ok(target_status) => target_status,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
     if target_status.len() == 0 {
-        return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: "target status is null".to_string(),
-                }),
-            )
-                .into_response();
+        return ApiError::Validation("target status is null".to_string()).into_response();
     }
 
     let terms: Terms = Terms::new(
@@ -226,8 +344,8 @@ pub async fn create_volunteer(
             overview,
             recruited_num,
             place,
-            start_at,
-            finish_at,
+            location,
+            schedule,
             deadline_on,
             as_group,
             reward,
@@ -243,87 +361,149 @@ pub async fn create_volunteer(
             }),
         )
             .into_response(),
-        Err(error) => {
-            log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
-        }
+        Err(error) => quota_error_response(error),
     }
 }
 
 #[utoipa::path(
     post,
     path="/volunteer/update",
+    security(("bearer_auth" = [])),
     request_body=UpdateVolunteerRequestBody,
     responses(
         (status=200, description="Update volunteer successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
+        (status=409, description="Removed session has pending or approved applies.", body=WriteApiResponseFailureBody),
+        (status=429, description="Photo limit of the plan exceeded.", body=WriteApiResponseFailureBody),
         (status=500, description="Update volunteer failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn update_volunteer(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<UpdateVolunteerRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.volunteer_repository;
 
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::UpdateVolunteer, &auth_user.uid, Target::Volunteer(vid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let geocoder = lock.geocoder.clone();
+    let repository = &mut lock.volunteer_repository;
 
     let title: String = body.title;
     let message: String = body.message;
     let overview: String = body.overview;
     let recruited_num: u32 = body.recruited_num;
     let place: String = body.place;
-
-    let start_at: DateTime<Utc> = body.start_at;
-    let finish_at: DateTime<Utc> = body.finish_at;
+    let location: Option<GeoPoint> = geocode_place(geocoder.as_ref(), &place).await;
+
+    let schedule: Schedule = match schedule_from_request(
+        body.start_at,
+        body.finish_at,
+        recruited_num,
+        body.recurrence,
+        body.sessions,
+    ) {
+        Ok(schedule) => schedule,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
     let deadline_on: NaiveDate = body.deadline_on;
     let as_group: bool = body.as_group;
-    let region: Vec<Region> = body
+    let region: Vec<Region> = match body
         .region
         .iter()
-        .map(|r: &String| Region::from_str(r).unwrap())
-        .collect::<Vec<Region>>();
-    let theme: Vec<Theme> = body
+        .map(|r: &String| Region::from_str(r))
+        .collect::<Result<Vec<Region>, _>>()
+    {
+        Ok(region) => region,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let theme: Vec<Theme> = match body
         .theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
-    let required_theme: Vec<Theme> = body
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(theme) => theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_theme: Vec<Theme> = match body
         .required_theme
         .iter()
-        .map(|t: &String| Theme::from_str(t).unwrap())
-        .collect::<Vec<Theme>>();
-    let condition: Vec<Condition> = body
+        .map(|t: &String| Theme::from_str(t))
+        .collect::<Result<Vec<Theme>, _>>()
+    {
+        Ok(required_theme) => required_theme,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let condition: Vec<Condition> = match body
         .condition
         .iter()
-        .map(|t: &String| Condition::from_str(t).unwrap())
-        .collect::<Vec<Condition>>();
-    let required_condition: Vec<Condition> = body
+        .map(|t: &String| Condition::from_str(t))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(condition) => condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+    let required_condition: Vec<Condition> = match body
         .required_condition
         .iter()
-        .map(|t: &String| Condition::from_str(t).unwrap())
-        .collect::<Vec<Condition>>();
+        .map(|t: &String| Condition::from_str(t))
+        .collect::<Result<Vec<Condition>, _>>()
+    {
+        Ok(required_condition) => required_condition,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
     let reward: Option<String> = body.reward;
-    let target_status: Vec<TargetStatus> = body
+    let target_status: Vec<TargetStatus> = match body
         .target_status
         .iter()
-        .map(|t: &String| TargetStatus::from_str(t).unwrap())
-        .collect::<Vec<TargetStatus>>();
+        .map(|t: &String| TargetStatus::from_str(t))
+        .collect::<Result<Vec<TargetStatus>, _>>()
+    {
+        Ok(target_status) => target_status,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
 
     if target_status.len() == 0 {
-        return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: "target status is null".to_string(),
-                }),
-            )
-                .into_response();
+        return ApiError::Validation("target status is null".to_string()).into_response();
     }
 
     let terms: Terms = Terms::new(
@@ -348,8 +528,8 @@ pub async fn update_volunteer(
             overview,
             recruited_num,
             place,
-            start_at,
-            finish_at,
+            location,
+            schedule,
             deadline_on,
             as_group,
             reward,
@@ -365,36 +545,47 @@ pub async fn update_volunteer(
             }),
         )
             .into_response(),
-        Err(error) => {
-            log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
-        }
+        Err(error) => volunteer_error_response(error),
     }
 }
 
 #[utoipa::path(
     post,
     path="/volunteer/delete",
+    security(("bearer_auth" = [])),
     request_body=DeleteVolunteerRequestBody,
     responses(
         (status=200, description="Delete volunteer successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
+        (status=403, description="Forbidden.", body=WriteApiResponseForbiddenBody),
         (status=500, description="Delete volunteer failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn delete_volunteer(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<DeleteVolunteerRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
-    let repository = &mut lock.volunteer_repository;
 
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
+        Err(error) => {
+            log::warn!("error = {}", error);
+            return ApiError::Validation(error.to_string()).into_response();
+        }
+    };
+
+    if let Err(response) = lock
+        .authorizer
+        .enforce(Endpoints::DeleteVolunteer, &auth_user.uid, Target::Volunteer(vid.clone()))
+        .await
+    {
+        return response;
+    }
+
+    let repository = &mut lock.volunteer_repository;
 
     match repository.delete(vid).await {
         Ok(_) => (
@@ -406,13 +597,7 @@ pub async fn delete_volunteer(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
@@ -420,33 +605,31 @@ pub async fn delete_volunteer(
 #[utoipa::path(
     post,
     path="/volunteer/favorite/register",
+    security(("bearer_auth" = [])),
     request_body=RegisterVolunteerFavoriteRequestBody,
     responses(
         (status=200, description="Register favorite successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
         (status=500, description="Register favorite failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn register_favorite(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<RegisterVolunteerFavoriteRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.volunteer_repository;
 
-    let uid: UserId = match UserId::from_str(&body.uid) {
-        Ok(uid) => uid,
+    let uid: UserId = auth_user.uid;
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
 
     match repository.register_favorite(uid, vid).await {
         Ok(_) => (
@@ -458,13 +641,7 @@ pub async fn register_favorite(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
@@ -472,33 +649,31 @@ pub async fn register_favorite(
 #[utoipa::path(
     post,
     path="/volunteer/favorite/unregister",
+    security(("bearer_auth" = [])),
     request_body=RegisterVolunteerFavoriteRequestBody,
     responses(
         (status=200, description="Unregister favorite successfully.", body=WriteApiResponseSuccessBody),
+        (status=401, description="Unauthorized.", body=WriteApiResponseFailureBody),
+        (status=400, description="Invalid request.", body=WriteApiResponseFailureBody),
         (status=500, description="Unregister favorite failed.", body=WriteApiResponseFailureBody)
     )
 )]
 pub async fn unregister_favorite(
     State(state): State<AppData>,
+    auth_user: AuthUser,
     Json(body): Json<UnregisterVolunteerFavoriteRequestBody>,
 ) -> impl IntoResponse {
     let mut lock = state.write().await;
     let repository = &mut lock.volunteer_repository;
 
-    let uid: UserId = match UserId::from_str(&body.uid) {
-        Ok(uid) => uid,
+    let uid: UserId = auth_user.uid;
+    let vid: VolunteerId = match VolunteerId::from_str(&body.vid) {
+        Ok(vid) => vid,
         Err(error) => {
             log::warn!("error = {}", error);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response();
+            return ApiError::Validation(error.to_string()).into_response();
         }
     };
-    let vid: VolunteerId = VolunteerId::from_str(&body.vid);
 
     match repository.unregister_favorite(uid, vid).await {
         Ok(_) => (
@@ -510,13 +685,7 @@ pub async fn unregister_favorite(
             .into_response(),
         Err(error) => {
             log::error!("error = {}", error);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(WriteApiResponseFailureBody {
-                    message: error.to_string(),
-                }),
-            )
-                .into_response()
+            ApiError::Internal(error.to_string()).into_response()
         }
     }
 }
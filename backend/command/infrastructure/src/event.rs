@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use redis::{AsyncCommands, Client};
+use sqlx::MySqlPool;
+
+use command_repository::event::EventPublisher;
+use domain::model::{
+    apply::{ApplyId, ApplyStatus},
+    event::{activity_channel, ActivityEvent, ACTIVITY_EVENT_RETENTION_SECS},
+    scout::ScoutId,
+};
+
+/// Redisのチャンネルに配信する. Read API Serverが別のプロセスで動く場合に使用する
+pub struct RedisEventPublisher {
+    client: Client,
+}
+
+impl RedisEventPublisher {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for RedisEventPublisher {
+    async fn publish(&self, event: &ActivityEvent) -> Result<()> {
+        let payload: String = serde_json::to_string(event)?;
+        let mut conn = self.client.get_async_connection().await?;
+        for uid in event.recipients() {
+            conn.publish::<_, _, ()>(activity_channel(uid), &payload)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// `activity_event`テーブルに配信する. Redisを使わずにローカルで動かす場合に使用する
+///
+/// Read API Serverは別のプロセスからテーブルをポーリングして受信する.
+/// 保持期間を過ぎた出来事は配信のたびに削除する
+pub struct DatabaseEventPublisher {
+    pool: MySqlPool,
+}
+
+impl DatabaseEventPublisher {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for DatabaseEventPublisher {
+    async fn publish(&self, event: &ActivityEvent) -> Result<()> {
+        let payload: String = serde_json::to_string(event)?;
+        let now: DateTime<Utc> = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        for uid in event.recipients() {
+            sqlx::query!(
+                r#"
+                INSERT INTO activity_event (recipient, payload, created_at)
+                VALUES (?, ?, ?)
+                "#,
+                uid,
+                payload,
+                now
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            DELETE FROM activity_event
+            WHERE created_at < ?
+            "#,
+            now - Duration::seconds(ACTIVITY_EVENT_RETENTION_SECS)
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// 環境変数`REDIS_URL`が設定されている場合はRedis, それ以外は`activity_event`テーブルに配信する
+pub fn create_event_publisher(pool: MySqlPool) -> Result<Arc<dyn EventPublisher>> {
+    match std::env::var("REDIS_URL") {
+        Ok(url) => Ok(Arc::new(RedisEventPublisher::new(Client::open(url)?))),
+        Err(_) => Ok(Arc::new(DatabaseEventPublisher::new(pool))),
+    }
+}
+
+/// コミット後の応募・スカウトから出来事を作成し, 配信する
+///
+/// 書き込みは既にコミットされているため, 配信に失敗してもエラーを返さずにログに出力する
+pub struct EventNotifier {
+    pool: MySqlPool,
+    publisher: Arc<dyn EventPublisher>,
+}
+
+impl EventNotifier {
+    pub fn new(pool: MySqlPool, publisher: Arc<dyn EventPublisher>) -> Self {
+        Self { pool, publisher }
+    }
+
+    /// スカウトを受けたことを参加者に配信する
+    pub async fn scout_received(&self, sid: &ScoutId) {
+        let event: Result<ActivityEvent> = async {
+            let scout = sqlx::query!(
+                r#"
+                SELECT scout.vid, scout.uid, volunteer.gid
+                FROM scout
+                INNER JOIN volunteer ON scout.vid = volunteer.vid
+                WHERE scout.sid = ?
+                "#,
+                sid.to_string()
+            )
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(ActivityEvent::ScoutReceived {
+                sid: sid.to_string(),
+                vid: scout.vid,
+                uid: scout.uid,
+                gid: scout.gid,
+            })
+        }
+        .await;
+
+        self.publish(event).await;
+    }
+
+    /// 応募の作成・取消・承認・棄却を配信する. 応募の現在の状態から出来事を決める
+    pub async fn apply_changed(&self, aid: &ApplyId) {
+        let event: Result<ActivityEvent> = async {
+            let apply = sqlx::query!(
+                r#"
+                SELECT apply.vid, apply.uid, volunteer.gid, apply.allowed_status as "allowed_status: u8"
+                FROM apply
+                INNER JOIN volunteer ON apply.vid = volunteer.vid
+                WHERE apply.aid = ?
+                "#,
+                aid.to_string()
+            )
+            .fetch_one(&self.pool)
+            .await?;
+
+            let (aid, vid, uid, gid) = (aid.to_string(), apply.vid, apply.uid, apply.gid);
+            Ok(match ApplyStatus::from_u8(apply.allowed_status)? {
+                ApplyStatus::Pending => ActivityEvent::ApplyReceived { aid, vid, uid, gid },
+                ApplyStatus::Cancelled => ActivityEvent::ApplyCancelled { aid, vid, uid, gid },
+                status => ActivityEvent::ApplyDecided {
+                    aid,
+                    vid,
+                    uid,
+                    gid,
+                    status,
+                },
+            })
+        }
+        .await;
+
+        self.publish(event).await;
+    }
+
+    async fn publish(&self, event: Result<ActivityEvent>) {
+        let result: Result<()> = match event {
+            Ok(event) => self.publisher.publish(&event).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            log::error!("failed to publish activity event: {}", error);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use command_repository::geocoder::Geocoder;
+use domain::model::location::GeoPoint;
+
+/// 地名と緯度経度の対応表
+///
+/// 市区町村を都道府県より優先するため, より狭い範囲の地名を先に並べる
+const GAZETTEER: [(&str, f64, f64); 67] = [
+    // 政令指定都市(市役所の所在地)
+    ("札幌市", 43.0621, 141.3544),
+    ("仙台市", 38.2682, 140.8694),
+    ("さいたま市", 35.8617, 139.6455),
+    ("千葉市", 35.6073, 140.1063),
+    ("横浜市", 35.4437, 139.6380),
+    ("川崎市", 35.5308, 139.7029),
+    ("相模原市", 35.5714, 139.3733),
+    ("新潟市", 37.9161, 139.0364),
+    ("静岡市", 34.9756, 138.3828),
+    ("浜松市", 34.7108, 137.7261),
+    ("名古屋市", 35.1815, 136.9066),
+    ("京都市", 35.0116, 135.7681),
+    ("大阪市", 34.6937, 135.5023),
+    ("堺市", 34.5733, 135.4828),
+    ("神戸市", 34.6901, 135.1955),
+    ("岡山市", 34.6551, 133.9195),
+    ("広島市", 34.3853, 132.4553),
+    ("北九州市", 33.8834, 130.8752),
+    ("福岡市", 33.5902, 130.4017),
+    ("熊本市", 32.8031, 130.7079),
+    // 都道府県(都道府県庁の所在地)
+    ("北海道", 43.0642, 141.3469),
+    ("青森県", 40.8244, 140.7400),
+    ("岩手県", 39.7036, 141.1527),
+    ("宮城県", 38.2688, 140.8721),
+    ("秋田県", 39.7186, 140.1024),
+    ("山形県", 38.2404, 140.3633),
+    ("福島県", 37.7503, 140.4676),
+    ("茨城県", 36.3418, 140.4468),
+    ("栃木県", 36.5657, 139.8836),
+    ("群馬県", 36.3911, 139.0608),
+    ("埼玉県", 35.8570, 139.6489),
+    ("千葉県", 35.6051, 140.1233),
+    ("東京都", 35.6895, 139.6917),
+    ("神奈川県", 35.4478, 139.6425),
+    ("新潟県", 37.9026, 139.0236),
+    ("富山県", 36.6953, 137.2113),
+    ("石川県", 36.5947, 136.6256),
+    ("福井県", 36.0652, 136.2216),
+    ("山梨県", 35.6642, 138.5684),
+    ("長野県", 36.6513, 138.1810),
+    ("岐阜県", 35.3912, 136.7223),
+    ("静岡県", 34.9769, 138.3831),
+    ("愛知県", 35.1802, 136.9066),
+    ("三重県", 34.7303, 136.5086),
+    ("滋賀県", 35.0045, 135.8686),
+    ("京都府", 35.0213, 135.7556),
+    ("大阪府", 34.6863, 135.5200),
+    ("兵庫県", 34.6913, 135.1830),
+    ("奈良県", 34.6853, 135.8327),
+    ("和歌山県", 34.2261, 135.1675),
+    ("鳥取県", 35.5036, 134.2383),
+    ("島根県", 35.4723, 133.0505),
+    ("岡山県", 34.6618, 133.9344),
+    ("広島県", 34.3966, 132.4596),
+    ("山口県", 34.1859, 131.4714),
+    ("徳島県", 34.0658, 134.5593),
+    ("香川県", 34.3401, 134.0434),
+    ("愛媛県", 33.8416, 132.7657),
+    ("高知県", 33.5597, 133.5311),
+    ("福岡県", 33.6064, 130.4181),
+    ("佐賀県", 33.2494, 130.2988),
+    ("長崎県", 32.7448, 129.8737),
+    ("熊本県", 32.7898, 130.7417),
+    ("大分県", 33.2382, 131.6126),
+    ("宮崎県", 31.9111, 131.4239),
+    ("鹿児島県", 31.5602, 130.5581),
+    ("沖縄県", 26.2124, 127.6809),
+];
+
+/// 外部APIを使わずに, 組み込みの地名表で緯度経度を求めるジオコーダー
+///
+/// 地名に含まれる市区町村・都道府県の代表地点を返すため, 精度は市区町村単位となる
+pub struct GazetteerGeocoder;
+
+impl GazetteerGeocoder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 地名に含まれる最初の地名表の地点を返す
+    pub fn lookup(&self, place: &str) -> Option<GeoPoint> {
+        GAZETTEER
+            .iter()
+            .find(|(name, _, _)| place.contains(name))
+            .map(|(_, latitude, longitude)| GeoPoint {
+                latitude: *latitude,
+                longitude: *longitude,
+            })
+    }
+}
+
+impl Default for GazetteerGeocoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Geocoder for GazetteerGeocoder {
+    async fn geocode(&self, place: &str) -> Result<Option<GeoPoint>> {
+        Ok(self.lookup(place))
+    }
+}
+
+#[cfg(test)]
+mod test_gazetteer_geocoder {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let geocoder = GazetteerGeocoder::new();
+        // 都道府県より市区町村を優先する
+        assert_eq!(
+            geocoder.lookup("神奈川県横浜市中区港町1-1"),
+            Some(GeoPoint {
+                latitude: 35.4437,
+                longitude: 139.6380
+            })
+        );
+        assert_eq!(
+            geocoder.lookup("東京都渋谷区"),
+            Some(GeoPoint {
+                latitude: 35.6895,
+                longitude: 139.6917
+            })
+        );
+        assert_eq!(geocoder.lookup("オンライン"), None);
+    }
+
+    #[test]
+    fn gazetteer_points_are_valid() {
+        for (name, latitude, longitude) in GAZETTEER.iter() {
+            assert!(GeoPoint::new(*latitude, *longitude).is_ok(), "{}", name);
+        }
+    }
+}
@@ -1,3 +1,12 @@
 pub mod controllers;
 pub mod user_account;
 pub mod activities;
+pub mod event;
+pub mod geocoder;
+pub mod mailer;
+pub mod outbox;
+pub mod payment;
+pub mod transaction;
+pub mod auth;
+pub mod authz;
+pub mod plan;
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use command_repository::mailer::Mailer;
+use domain::model::notification::Email;
+
+/// メールの出力先の環境変数が設定されていない場合に使用するディレクトリ
+const DEFAULT_MAIL_DIR: &str = "tmp/mail";
+
+/// SMTPサーバーのポートの環境変数が設定されていない場合に使用するポート (STARTTLS)
+const DEFAULT_SMTP_PORT: u16 = 587;
+
+/// メールをファイルに書き出す場合に環境変数`MAILER`に設定する値. 開発・テスト環境でのみ使用する
+pub const FILE_MAILER: &str = "file";
+
+/// メールをファイルに書き出すメーラー. ローカル環境での動作確認に使用する
+///
+/// メールごとに`<message_id>.eml`を作成するため, 同じメールを再送した場合は上書きされる
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// 環境変数`MAIL_OUTPUT_DIR`を出力先として作成する
+    pub fn from_env() -> Self {
+        Self::new(PathBuf::from(
+            std::env::var("MAIL_OUTPUT_DIR").unwrap_or_else(|_| DEFAULT_MAIL_DIR.to_string()),
+        ))
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: &Email) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let content: String = format!(
+            "Message-ID: <{}>\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=UTF-8\r\n\r\n{}",
+            email.message_id, email.to, email.subject, email.body
+        );
+        std::fs::write(self.dir.join(format!("{}.eml", email.message_id)), content)?;
+        Ok(())
+    }
+}
+
+/// SMTPサーバーに送信するメーラー
+///
+/// STARTTLSで接続し, ユーザー名とパスワードで認証する
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(host: &str, port: u16, credentials: Credentials, from: Mailbox) -> Result<Self> {
+        let transport: AsyncSmtpTransport<Tokio1Executor> =
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+                .port(port)
+                .credentials(credentials)
+                .build();
+        Ok(Self { transport, from })
+    }
+
+    /// 環境変数`SMTP_HOST`, `SMTP_PORT`, `SMTP_USERNAME`, `SMTP_PASSWORD`, `MAIL_FROM`から作成する
+    ///
+    /// `SMTP_PORT`以外が設定されていない場合はエラーを返す
+    pub fn from_env() -> Result<Self> {
+        let var = |key: &str| -> Result<String> {
+            std::env::var(key).map_err(|_| anyhow!("{} is not set", key))
+        };
+        let port: u16 = match std::env::var("SMTP_PORT") {
+            Ok(port) => port.parse()?,
+            Err(_) => DEFAULT_SMTP_PORT,
+        };
+        Self::new(
+            &var("SMTP_HOST")?,
+            port,
+            Credentials::new(var("SMTP_USERNAME")?, var("SMTP_PASSWORD")?),
+            var("MAIL_FROM")?.parse()?,
+        )
+    }
+
+    fn message(&self, email: &Email) -> Result<Message> {
+        Ok(Message::builder()
+            .message_id(Some(format!("<{}>", email.message_id)))
+            .from(self.from.clone())
+            .to(email.to.parse()?)
+            .subject(&email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body.clone())?)
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<()> {
+        self.transport.send(self.message(email)?).await?;
+        Ok(())
+    }
+}
+
+/// 環境変数からメーラーを作成する
+///
+/// `MAILER`が[FILE_MAILER]の場合のみファイルに書き出し, それ以外はSMTPサーバーに送信する
+pub fn create_mailer() -> Result<Box<dyn Mailer>> {
+    match std::env::var("MAILER").as_deref() {
+        Ok(FILE_MAILER) => {
+            log::warn!("writing emails to files. do not use it in production");
+            Ok(Box::new(FileMailer::from_env()))
+        }
+        _ => Ok(Box::new(SmtpMailer::from_env()?)),
+    }
+}
+
+#[cfg(test)]
+mod test_file_mailer {
+    use super::*;
+
+    #[tokio::test]
+    async fn send() {
+        let dir: PathBuf = std::env::temp_dir().join("volunscout-file-mailer-test");
+        let mailer = FileMailer::new(dir.clone());
+        let email = Email {
+            message_id: "outbox-1@volunscout".to_string(),
+            to: "participant@example.com".to_string(),
+            subject: "【VolunScout】スカウトが届きました".to_string(),
+            body: "本文".to_string(),
+        };
+
+        mailer.send(&email).await.unwrap();
+
+        let content = std::fs::read_to_string(dir.join("outbox-1@volunscout.eml")).unwrap();
+        assert!(content.contains("To: participant@example.com\r\n"));
+        assert!(content.contains("Subject: 【VolunScout】スカウトが届きました\r\n"));
+        assert!(content.ends_with("\r\n\r\n本文"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test_smtp_mailer {
+    use super::*;
+
+    fn mailer() -> SmtpMailer {
+        SmtpMailer::new(
+            "smtp.example.com",
+            DEFAULT_SMTP_PORT,
+            Credentials::new("user".to_string(), "password".to_string()),
+            "VolunScout <noreply@example.com>".parse().unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn email(to: &str) -> Email {
+        Email {
+            message_id: "outbox-1@volunscout".to_string(),
+            to: to.to_string(),
+            subject: "【VolunScout】スカウトが届きました".to_string(),
+            body: "本文".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn message() {
+        let message = mailer().message(&email("participant@example.com")).unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+        assert!(formatted.contains("Message-ID: <outbox-1@volunscout>\r\n"));
+        assert!(formatted.contains("To: participant@example.com\r\n"));
+
+        // メールアドレスでない宛先は送信しない
+        assert!(mailer()
+            .message(&email("participant_account000000000"))
+            .is_err());
+    }
+}
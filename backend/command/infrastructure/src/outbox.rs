@@ -0,0 +1,397 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{MySqlConnection, MySqlPool};
+
+use command_repository::{
+    mailer::Mailer,
+    outbox::{OutboxMessage, OutboxRepository},
+};
+use domain::model::notification::{EmailContext, NotificationKind, OutboxStatus, RetryPolicy};
+
+/// 取得した送信待ちのメールを, 他のワーカーが取得できないようにする時間
+const LEASE: Duration = Duration::minutes(5);
+
+/// 送信待ちのメールを追加する
+///
+/// 宛先のメールアドレスが登録されていない場合は追加せず, ログに出力する
+async fn insert_outbox(
+    conn: &mut MySqlConnection,
+    kind: NotificationKind,
+    target_id: &str,
+    recipient: Option<&str>,
+    context: &EmailContext,
+) -> Result<()> {
+    let Some(recipient) = recipient else {
+        log::warn!(
+            "skipped email without recipient address: target_id = {}",
+            target_id
+        );
+        return Ok(());
+    };
+
+    let now: DateTime<Utc> = Utc::now();
+    sqlx::query!(
+        r#"
+        INSERT INTO email_outbox (kind, target_id, recipient, payload, next_attempt_at, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        kind.to_u8(),
+        target_id,
+        recipient,
+        serde_json::to_string(context)?,
+        now,
+        now
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// 応募・応募の承認・応募の棄却・応募の取消のメールを送信待ちに追加する
+///
+/// 応募の書き込みと同じトランザクションで呼び出す
+pub(crate) async fn enqueue_apply_notification(
+    conn: &mut MySqlConnection,
+    kind: NotificationKind,
+    aid: &str,
+) -> Result<()> {
+    let apply = sqlx::query!(
+        r#"
+        SELECT
+            participant_account.email as participant_email, participant_account.name as participant_name,
+            apply.cancel_reason, group_account.email as group_email, group_account.name as group_name,
+            volunteer.title
+        FROM apply
+        INNER JOIN volunteer ON apply.vid = volunteer.vid
+        INNER JOIN group_account ON volunteer.gid = group_account.gid
+        INNER JOIN participant_account ON apply.uid = participant_account.uid
+        WHERE apply.aid = ?
+        "#,
+        aid
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // 応募・取消は団体に, 承認・棄却は参加者に通知する
+    let (recipient, context): (Option<String>, EmailContext) = match kind {
+        NotificationKind::ApplyReceived | NotificationKind::ApplyCancelled => (
+            apply.group_email,
+            EmailContext {
+                recipient_name: apply.group_name,
+                sender_name: apply.participant_name,
+                volunteer_title: apply.title,
+                message: apply.cancel_reason,
+            },
+        ),
+        _ => (
+            apply.participant_email,
+            EmailContext {
+                recipient_name: apply.participant_name,
+                sender_name: apply.group_name,
+                volunteer_title: apply.title,
+                message: None,
+            },
+        ),
+    };
+
+    insert_outbox(conn, kind, aid, recipient.as_deref(), &context).await
+}
+
+/// スカウトのメールを送信待ちに追加する
+///
+/// スカウトの書き込みと同じトランザクションで呼び出す
+pub(crate) async fn enqueue_scout_notification(conn: &mut MySqlConnection, sid: &str) -> Result<()> {
+    let scout = sqlx::query!(
+        r#"
+        SELECT
+            participant_account.email, participant_account.name as participant_name, scout.message,
+            group_account.name as group_name, volunteer.title
+        FROM scout
+        INNER JOIN volunteer ON scout.vid = volunteer.vid
+        INNER JOIN group_account ON volunteer.gid = group_account.gid
+        INNER JOIN participant_account ON scout.uid = participant_account.uid
+        WHERE scout.sid = ?
+        "#,
+        sid
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let context: EmailContext = EmailContext {
+        recipient_name: scout.participant_name,
+        sender_name: scout.group_name,
+        volunteer_title: scout.title,
+        message: Some(scout.message),
+    };
+
+    insert_outbox(
+        conn,
+        NotificationKind::ScoutReceived,
+        sid,
+        scout.email.as_deref(),
+        &context,
+    )
+    .await
+}
+
+pub struct OutboxImpl {
+    pool: MySqlPool,
+}
+
+impl OutboxImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for OutboxImpl {
+    async fn claim_due(&self, limit: u32, lease_until: DateTime<Utc>) -> Result<Vec<OutboxMessage>> {
+        let mut tx = self.pool.begin().await?;
+
+        // 複数のワーカーが同じメールを取得しないよう, 他のワーカーがロック中の行は読み飛ばす
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                oid, kind as "kind: u8", target_id, recipient,
+                CAST(payload AS CHAR) as "payload!: String", attempts as "attempts: u32"
+            FROM email_outbox
+            WHERE status = ? AND next_attempt_at <= ?
+            ORDER BY next_attempt_at, oid
+            LIMIT ?
+            FOR UPDATE SKIP LOCKED
+            "#,
+            OutboxStatus::Pending.to_u8(),
+            Utc::now(),
+            limit
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut messages: Vec<OutboxMessage> = Vec::with_capacity(rows.len());
+        for row in rows {
+            sqlx::query!(
+                "UPDATE email_outbox SET next_attempt_at = ? WHERE oid = ?",
+                lease_until,
+                row.oid
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            messages.push(OutboxMessage {
+                oid: row.oid,
+                kind: NotificationKind::from_u8(row.kind)?,
+                target_id: row.target_id,
+                recipient: row.recipient,
+                context: serde_json::from_str(&row.payload)?,
+                attempts: row.attempts,
+            });
+        }
+
+        tx.commit().await?;
+
+        Ok(messages)
+    }
+
+    async fn mark_sent(&self, message: &OutboxMessage) -> Result<()> {
+        let now: DateTime<Utc> = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE email_outbox SET status = ?, attempts = attempts + 1, sent_at = ? WHERE oid = ?",
+            OutboxStatus::Sent.to_u8(),
+            now,
+            message.oid
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        match message.kind {
+            NotificationKind::ApplyReceived => {
+                sqlx::query!(
+                    "UPDATE apply SET is_sent = ?, sent_at = ? WHERE aid = ?",
+                    true,
+                    now,
+                    message.target_id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            NotificationKind::ScoutReceived => {
+                sqlx::query!(
+                    "UPDATE scout SET is_sent = ?, sent_at = ? WHERE sid = ?",
+                    true,
+                    now,
+                    message.target_id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            NotificationKind::ApplyApproved
+            | NotificationKind::ApplyRejected
+            | NotificationKind::ApplyCancelled => {}
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        message: &OutboxMessage,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let status: OutboxStatus = match next_attempt_at {
+            Some(_) => OutboxStatus::Pending,
+            None => OutboxStatus::Failed,
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE email_outbox
+            SET status = ?, attempts = attempts + 1, last_error = ?, next_attempt_at = COALESCE(?, next_attempt_at)
+            WHERE oid = ?
+            "#,
+            status.to_u8(),
+            error,
+            next_attempt_at,
+            message.oid
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// 送信日時を過ぎた送信待ちのメールを最大`batch_size`件送信し, 送信できた件数を返す
+///
+/// 送信に失敗したメールは`policy`に従って再送する
+pub async fn deliver_due(
+    repository: &dyn OutboxRepository,
+    mailer: &dyn Mailer,
+    policy: &RetryPolicy,
+    batch_size: u32,
+) -> Result<usize> {
+    let messages: Vec<OutboxMessage> = repository
+        .claim_due(batch_size, Utc::now() + LEASE)
+        .await?;
+
+    let mut sent: usize = 0;
+    for message in messages.iter() {
+        match mailer.send(&message.to_email()).await {
+            Ok(_) => {
+                repository.mark_sent(message).await?;
+                sent += 1;
+            }
+            Err(error) => {
+                log::warn!("failed to send email: oid = {}, error = {}", message.oid, error);
+                let next_attempt_at: Option<DateTime<Utc>> =
+                    policy.next_attempt_at(message.attempts + 1, Utc::now());
+                repository
+                    .mark_failed(message, &error.to_string(), next_attempt_at)
+                    .await?;
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod test_deliver_due {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    use domain::model::notification::Email;
+
+    /// 送信済み・失敗の記録を保持するリポジトリ
+    #[derive(Default)]
+    struct MemoryOutbox {
+        messages: Vec<OutboxMessage>,
+        sent: Mutex<Vec<u64>>,
+        failed: Mutex<Vec<(u64, Option<DateTime<Utc>>)>>,
+    }
+
+    #[async_trait]
+    impl OutboxRepository for MemoryOutbox {
+        async fn claim_due(&self, limit: u32, _: DateTime<Utc>) -> Result<Vec<OutboxMessage>> {
+            Ok(self.messages.iter().take(limit as usize).cloned().collect())
+        }
+
+        async fn mark_sent(&self, message: &OutboxMessage) -> Result<()> {
+            self.sent.lock().unwrap().push(message.oid);
+            Ok(())
+        }
+
+        async fn mark_failed(
+            &self,
+            message: &OutboxMessage,
+            _: &str,
+            next_attempt_at: Option<DateTime<Utc>>,
+        ) -> Result<()> {
+            self.failed.lock().unwrap().push((message.oid, next_attempt_at));
+            Ok(())
+        }
+    }
+
+    /// 宛先が`unreachable@example.com`のメールの送信に失敗するメーラー
+    struct FlakyMailer;
+
+    #[async_trait]
+    impl Mailer for FlakyMailer {
+        async fn send(&self, email: &Email) -> Result<()> {
+            if email.to == "unreachable@example.com" {
+                anyhow::bail!("connection refused");
+            }
+            Ok(())
+        }
+    }
+
+    fn message(oid: u64, recipient: &str, attempts: u32) -> OutboxMessage {
+        OutboxMessage {
+            oid,
+            kind: NotificationKind::ScoutReceived,
+            target_id: "01HMAWQDS2Y50QHNGB8GSWJQNW".to_string(),
+            recipient: recipient.to_string(),
+            context: EmailContext {
+                recipient_name: "参加者".to_string(),
+                sender_name: "団体".to_string(),
+                volunteer_title: "清掃".to_string(),
+                message: None,
+            },
+            attempts,
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_until_max_attempts() {
+        let policy = RetryPolicy::default();
+        let outbox = MemoryOutbox {
+            messages: vec![
+                message(1, "participant@example.com", 0),
+                message(2, "unreachable@example.com", 0),
+                message(3, "unreachable@example.com", policy.max_attempts - 1),
+            ],
+            ..Default::default()
+        };
+
+        let sent = deliver_due(&outbox, &FlakyMailer, &policy, 10).await.unwrap();
+
+        assert_eq!(sent, 1);
+        assert_eq!(*outbox.sent.lock().unwrap(), vec![1]);
+        let failed = outbox.failed.lock().unwrap();
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0].0, 2);
+        assert!(failed[0].1.is_some());
+        // 再送の上限に達したメールは再送しない
+        assert_eq!(failed[1], (3, None));
+    }
+}
@@ -0,0 +1,293 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use command_repository::payment::PaymentProvider;
+use domain::{
+    hex::{decode_hex, encode_hex},
+    model::payment::{Charge, ChargeOutcome, ChargeRequest, PaymentError, PaymentEvent},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 請求に失敗させる支払い方法
+pub const MOCK_DECLINED_PAYMENT_METHOD: &str = "pm_mock_declined";
+
+/// 決済サービスのモックを使う場合に環境変数`PAYMENT_PROVIDER`に設定する値. 開発・テスト環境でのみ使用する
+pub const MOCK_PAYMENT_PROVIDER: &str = "mock";
+
+/// Webhookの署名を検証する
+///
+/// 署名はリクエストボディをWebhookの秘密鍵でHMAC-SHA256した値の16進数とし, 定数時間で比較する
+pub struct WebhookVerifier {
+    secret: String,
+}
+
+impl WebhookVerifier {
+    /// 秘密鍵が空の場合はエラーを返す
+    pub fn new(secret: String) -> Result<Self> {
+        if secret.is_empty() {
+            return Err(anyhow!("payment webhook secret is empty"));
+        }
+        Ok(Self { secret })
+    }
+
+    /// 環境変数`PAYMENT_WEBHOOK_SECRET`を秘密鍵として作成する. 設定されていない場合はエラーを返す
+    pub fn from_env() -> Result<Self> {
+        let secret: String = std::env::var("PAYMENT_WEBHOOK_SECRET")
+            .map_err(|_| anyhow!("PAYMENT_WEBHOOK_SECRET is not set"))?;
+        Self::new(secret)
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac: HmacSha256 = HmacSha256::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC can take a key of any size");
+        mac.update(payload);
+        mac
+    }
+
+    /// 署名を16進数の小文字で返す
+    pub fn sign(&self, payload: &[u8]) -> String {
+        encode_hex(&self.mac(payload).finalize().into_bytes())
+    }
+
+    /// 署名を検証し, イベントに変換する
+    pub fn parse_event(&self, signature: Option<&str>, payload: &[u8]) -> Result<PaymentEvent> {
+        let signature: Vec<u8> = signature
+            .and_then(|signature| decode_hex(signature.trim()))
+            .ok_or(PaymentError::InvalidSignature)?;
+        self.mac(payload)
+            .verify_slice(&signature)
+            .map_err(|_| PaymentError::InvalidSignature)?;
+
+        serde_json::from_slice::<PaymentEvent>(payload)
+            .map_err(|error| PaymentError::InvalidPayload(error.to_string()).into())
+    }
+}
+
+/// 決済サービスへの請求のリクエストボディ
+#[derive(Debug, Serialize)]
+struct ChargeRequestBody<'a> {
+    amount: u32,
+    currency: &'a str,
+    payment_method: &'a str,
+    invoice_id: String,
+}
+
+/// 決済サービスからの請求のレスポンスボディ
+#[derive(Debug, Deserialize)]
+struct ChargeResponseBody {
+    charge_id: String,
+    /// `succeeded`または`failed`
+    status: String,
+    reason: Option<String>,
+}
+
+/// 決済サービスのAPIで請求する
+///
+/// 請求書idを冪等キーとして送り, 再送しても二重に請求されないようにする
+pub struct HttpPaymentProvider {
+    client: Client,
+    api_url: String,
+    api_key: String,
+    webhook: WebhookVerifier,
+}
+
+impl HttpPaymentProvider {
+    pub fn new(api_url: String, api_key: String, webhook: WebhookVerifier) -> Self {
+        Self {
+            client: Client::new(),
+            api_url,
+            api_key,
+            webhook,
+        }
+    }
+
+    /// 環境変数`PAYMENT_API_URL`, `PAYMENT_API_KEY`から作成する. 設定されていない場合はエラーを返す
+    pub fn from_env(webhook: WebhookVerifier) -> Result<Self> {
+        let api_url: String =
+            std::env::var("PAYMENT_API_URL").map_err(|_| anyhow!("PAYMENT_API_URL is not set"))?;
+        let api_key: String =
+            std::env::var("PAYMENT_API_KEY").map_err(|_| anyhow!("PAYMENT_API_KEY is not set"))?;
+        Ok(Self::new(api_url, api_key, webhook))
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for HttpPaymentProvider {
+    async fn charge(&self, request: &ChargeRequest) -> Result<Charge> {
+        let invoice_id: String = request.iid.to_string();
+        let response: ChargeResponseBody = self
+            .client
+            .post(format!("{}/charges", self.api_url.trim_end_matches('/')))
+            .bearer_auth(&self.api_key)
+            .header("Idempotency-Key", &invoice_id)
+            .json(&ChargeRequestBody {
+                amount: request.amount,
+                currency: "jpy",
+                payment_method: &request.payment_method,
+                invoice_id,
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let outcome: ChargeOutcome = match response.status.as_str() {
+            "succeeded" => ChargeOutcome::Succeeded,
+            "failed" => ChargeOutcome::Failed {
+                reason: response.reason.unwrap_or_else(|| "unknown".to_string()),
+            },
+            status => return Err(anyhow!("unexpected charge status: {}", status)),
+        };
+
+        Ok(Charge {
+            charge_id: response.charge_id,
+            outcome,
+        })
+    }
+
+    fn parse_event(&self, signature: Option<&str>, payload: &[u8]) -> Result<PaymentEvent> {
+        self.webhook.parse_event(signature, payload)
+    }
+}
+
+/// 決済サービスのモック. 開発・テスト環境での動作確認に使用する
+///
+/// 支払い方法が[MOCK_DECLINED_PAYMENT_METHOD]の場合は支払いに失敗し, それ以外は成功する.
+/// Webhookの署名は本番と同様に検証する
+pub struct MockPaymentProvider {
+    webhook: WebhookVerifier,
+}
+
+impl MockPaymentProvider {
+    pub fn new(webhook: WebhookVerifier) -> Self {
+        Self { webhook }
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for MockPaymentProvider {
+    async fn charge(&self, request: &ChargeRequest) -> Result<Charge> {
+        let outcome: ChargeOutcome = if request.payment_method == MOCK_DECLINED_PAYMENT_METHOD {
+            ChargeOutcome::Failed {
+                reason: "card_declined".to_string(),
+            }
+        } else {
+            ChargeOutcome::Succeeded
+        };
+
+        Ok(Charge {
+            charge_id: format!("ch_mock_{}", request.iid),
+            outcome,
+        })
+    }
+
+    fn parse_event(&self, signature: Option<&str>, payload: &[u8]) -> Result<PaymentEvent> {
+        self.webhook.parse_event(signature, payload)
+    }
+}
+
+/// 環境変数から決済サービスを作成する
+///
+/// `PAYMENT_WEBHOOK_SECRET`は必須とし, 設定されていない場合は起動時にエラーを返す.
+/// `PAYMENT_PROVIDER`が[MOCK_PAYMENT_PROVIDER]の場合のみモックを使用し, それ以外は決済サービスのAPIで請求する
+pub fn create_payment_provider() -> Result<Arc<dyn PaymentProvider>> {
+    let webhook: WebhookVerifier = WebhookVerifier::from_env()?;
+    match std::env::var("PAYMENT_PROVIDER").as_deref() {
+        Ok(MOCK_PAYMENT_PROVIDER) => {
+            log::warn!("using mock payment provider. do not use it in production");
+            Ok(Arc::new(MockPaymentProvider::new(webhook)))
+        }
+        _ => Ok(Arc::new(HttpPaymentProvider::from_env(webhook)?)),
+    }
+}
+
+#[cfg(test)]
+mod test_mock_payment_provider {
+    use super::*;
+
+    use domain::model::{
+        billing::{InvoiceId, InvoiceStatus},
+        payment::PaymentEventKind,
+    };
+
+    fn request(payment_method: &str) -> ChargeRequest {
+        ChargeRequest {
+            iid: InvoiceId::new(),
+            amount: 5_000,
+            payment_method: payment_method.to_string(),
+        }
+    }
+
+    fn verifier() -> WebhookVerifier {
+        WebhookVerifier::new("secret".to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn charge() {
+        let provider = MockPaymentProvider::new(verifier());
+
+        let charge = provider.charge(&request("pm_mock_card")).await.unwrap();
+        assert_eq!(charge.outcome, ChargeOutcome::Succeeded);
+        assert!(charge.charge_id.starts_with("ch_mock_"));
+
+        let charge = provider
+            .charge(&request(MOCK_DECLINED_PAYMENT_METHOD))
+            .await
+            .unwrap();
+        assert_eq!(charge.invoice_status(), InvoiceStatus::Failed);
+    }
+
+    #[test]
+    fn parse_event() {
+        let provider = MockPaymentProvider::new(verifier());
+        let payload = br#"{"event_id": "evt_1", "type": "charge_succeeded", "charge_id": "ch_1"}"#;
+        let signature: String = verifier().sign(payload);
+
+        let event = provider.parse_event(Some(&signature), payload).unwrap();
+        assert_eq!(
+            event.kind,
+            PaymentEventKind::ChargeSucceeded {
+                charge_id: "ch_1".to_string()
+            }
+        );
+        assert!(provider
+            .parse_event(Some(&signature.to_uppercase()), payload)
+            .is_ok());
+
+        // 秘密鍵そのものや, 別のボディの署名は受け付けない
+        for signature in [
+            Some("secret".to_string()),
+            Some(verifier().sign(b"{}")),
+            None,
+        ] {
+            let error = provider
+                .parse_event(signature.as_deref(), payload)
+                .unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<PaymentError>(),
+                Some(&PaymentError::InvalidSignature)
+            );
+        }
+
+        let error = provider
+            .parse_event(Some(&verifier().sign(b"{}")), b"{}")
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PaymentError>(),
+            Some(PaymentError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn empty_secret() {
+        assert!(WebhookVerifier::new(String::new()).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use sqlx::MySqlConnection;
+
+use domain::model::plan::{BillingPeriod, Plan, PlanLimits};
+
+/// 団体のプランを取得し, 同じ団体の書き込みが終わるまで団体の行をロックする
+///
+/// 利用数の確認と書き込みの間に, 同じ団体が上限を超えて書き込めないようにする
+async fn lock_plan(conn: &mut MySqlConnection, gid: &str) -> Result<Plan> {
+    let group = sqlx::query!(
+        r#"SELECT plan as "plan: u8" FROM group_account WHERE gid = ? FOR UPDATE"#,
+        gid
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Plan::from_u8(group.plan)?)
+}
+
+/// ボランティアを募集する団体が, 今の請求期間にさらにスカウトを送信できるか確認する
+pub(crate) async fn ensure_scout_quota(conn: &mut MySqlConnection, vid: &str) -> Result<()> {
+    let volunteer = sqlx::query!("SELECT gid FROM volunteer WHERE vid = ?", vid)
+        .fetch_one(&mut *conn)
+        .await?;
+    let limits: PlanLimits = lock_plan(conn, &volunteer.gid).await?.limits();
+
+    let (start_at, next_start_at): (NaiveDateTime, NaiveDateTime) =
+        BillingPeriod::containing(Utc::now()).utc_range();
+    let used = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS count
+        FROM scout
+        INNER JOIN volunteer ON scout.vid = volunteer.vid
+        WHERE volunteer.gid = ?
+        AND scout.scouted_at >= ? AND scout.scouted_at < ?
+        "#,
+        volunteer.gid,
+        start_at,
+        next_start_at
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    limits.ensure_scout(used.count as u32)?;
+    Ok(())
+}
+
+/// 団体がさらにボランティアを募集できるか, 写真の枚数が上限以内か確認する
+///
+/// 削除されておらず, 募集締切日を過ぎていないボランティアを募集中として数える
+pub(crate) async fn ensure_volunteer_quota(
+    conn: &mut MySqlConnection,
+    gid: &str,
+    photo_count: usize,
+) -> Result<()> {
+    let limits: PlanLimits = lock_plan(conn, gid).await?.limits();
+    limits.ensure_photos(photo_count)?;
+
+    let open = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS count
+        FROM volunteer
+        WHERE gid = ? AND is_deleted = false AND deadline_on >= CURDATE()
+        "#,
+        gid
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    limits.ensure_open_volunteer(open.count as u32)?;
+    Ok(())
+}
+
+/// ボランティアの写真の枚数が, 募集する団体のプランの上限以内か確認する
+pub(crate) async fn ensure_photo_quota(
+    conn: &mut MySqlConnection,
+    vid: &str,
+    photo_count: usize,
+) -> Result<()> {
+    let volunteer = sqlx::query!("SELECT gid FROM volunteer WHERE vid = ?", vid)
+        .fetch_one(&mut *conn)
+        .await?;
+    lock_plan(conn, &volunteer.gid).await?.limits().ensure_photos(photo_count)?;
+    Ok(())
+}
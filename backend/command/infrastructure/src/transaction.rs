@@ -0,0 +1,174 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{MySql, MySqlConnection, MySqlPool, Transaction};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use command_repository::{
+    activities::{
+        apply::ApplyRepository, review::ReviewRepository, scout::ScoutRepository,
+        volunteer::VolunteerRepository,
+    },
+    unit_of_work::{UnitOfWork, UnitOfWorkFactory},
+    user_account::{group::GroupUserRepository, participant::ParticipantUserRepository},
+};
+
+use crate::{
+    activities::{
+        apply::ApplyImpl, review::ReviewImpl, scout::ScoutImpl, volunteer::VolunteerImpl,
+    },
+    user_account::{group::GroupAccountImpl, participant::ParticipantAccountImpl},
+};
+
+/// 複数のリポジトリで共有するトランザクション
+pub type SharedTransaction = Arc<Mutex<Transaction<'static, MySql>>>;
+
+/// リポジトリが書き込みに使用するデータベース
+#[derive(Clone)]
+pub enum Database {
+    /// 呼び出しごとにトランザクションを開始し, 呼び出しの終わりにコミットする
+    Pool(MySqlPool),
+    /// ユニットオブワークのトランザクションに参加する. コミットはユニットオブワークが行う
+    Shared(SharedTransaction),
+}
+
+impl Database {
+    /// トランザクションを開始する
+    pub async fn begin(&self) -> Result<DbTransaction> {
+        match self {
+            Database::Pool(pool) => Ok(DbTransaction::Owned(pool.begin().await?)),
+            Database::Shared(transaction) => Ok(DbTransaction::Shared(
+                transaction.clone().lock_owned().await,
+            )),
+        }
+    }
+}
+
+/// [Database::begin]で開始したトランザクション
+///
+/// コミットせずにドロップされた場合はロールバックされる
+pub enum DbTransaction {
+    Owned(Transaction<'static, MySql>),
+    Shared(OwnedMutexGuard<Transaction<'static, MySql>>),
+}
+
+impl DbTransaction {
+    /// トランザクションをコミットする
+    ///
+    /// ユニットオブワークに参加している場合は何もしない
+    pub async fn commit(self) -> Result<()> {
+        if let DbTransaction::Owned(transaction) = self {
+            transaction.commit().await?;
+        }
+        Ok(())
+    }
+}
+
+impl Deref for DbTransaction {
+    type Target = MySqlConnection;
+
+    fn deref(&self) -> &MySqlConnection {
+        match self {
+            DbTransaction::Owned(transaction) => &**transaction,
+            DbTransaction::Shared(transaction) => &***transaction,
+        }
+    }
+}
+
+impl DerefMut for DbTransaction {
+    fn deref_mut(&mut self) -> &mut MySqlConnection {
+        match self {
+            DbTransaction::Owned(transaction) => &mut **transaction,
+            DbTransaction::Shared(transaction) => &mut ***transaction,
+        }
+    }
+}
+
+/// 1つのMySQLトランザクションを共有するリポジトリの集まり
+pub struct MySqlUnitOfWork {
+    transaction: SharedTransaction,
+    group_account_repository: GroupAccountImpl,
+    participant_account_repository: ParticipantAccountImpl,
+    volunteer_repository: VolunteerImpl,
+    apply_repository: ApplyImpl,
+    scout_repository: ScoutImpl,
+    review_repository: ReviewImpl,
+}
+
+impl MySqlUnitOfWork {
+    pub fn new(transaction: Transaction<'static, MySql>) -> Self {
+        let transaction: SharedTransaction = Arc::new(Mutex::new(transaction));
+        let db: Database = Database::Shared(transaction.clone());
+        Self {
+            transaction,
+            group_account_repository: GroupAccountImpl::with_database(db.clone()),
+            participant_account_repository: ParticipantAccountImpl::with_database(db.clone()),
+            volunteer_repository: VolunteerImpl::with_database(db.clone()),
+            apply_repository: ApplyImpl::with_database(db.clone()),
+            scout_repository: ScoutImpl::with_database(db.clone()),
+            review_repository: ReviewImpl::with_database(db),
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for MySqlUnitOfWork {
+    fn group_user_repository(&self) -> &dyn GroupUserRepository {
+        &self.group_account_repository
+    }
+
+    fn participant_user_repository(&self) -> &dyn ParticipantUserRepository {
+        &self.participant_account_repository
+    }
+
+    fn volunteer_repository(&self) -> &dyn VolunteerRepository {
+        &self.volunteer_repository
+    }
+
+    fn apply_repository(&self) -> &dyn ApplyRepository {
+        &self.apply_repository
+    }
+
+    fn scout_repository(&self) -> &dyn ScoutRepository {
+        &self.scout_repository
+    }
+
+    fn review_repository(&self) -> &dyn ReviewRepository {
+        &self.review_repository
+    }
+
+    async fn commit(self: Box<Self>) -> Result<()> {
+        let transaction: SharedTransaction = self.transaction.clone();
+        // リポジトリが持つトランザクションの参照を解放する
+        drop(self);
+
+        let transaction: Transaction<'static, MySql> = Arc::try_unwrap(transaction)
+            .map_err(|_| anyhow::anyhow!("the transaction is still in use"))?
+            .into_inner();
+        transaction.commit().await?;
+        Ok(())
+    }
+}
+
+/// [MySqlUnitOfWork]を生成する
+pub struct MySqlUnitOfWorkFactory {
+    pool: MySqlPool,
+}
+
+impl MySqlUnitOfWorkFactory {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for MySqlUnitOfWorkFactory {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>> {
+        let transaction: Transaction<'static, MySql> = self.pool.begin().await?;
+        Ok(Box::new(MySqlUnitOfWork::new(transaction)))
+    }
+}
@@ -0,0 +1,518 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{MySqlConnection, MySqlPool};
+
+use command_repository::{
+    payment::PaymentProvider,
+    user_account::billing::{BillingRepository, EndedTrial},
+};
+use domain::model::{
+    billing::{CurrentPlan, Invoice, InvoiceId, InvoiceStatus, PlanChange, PlanStatus},
+    payment::{Charge, ChargeOutcome, ChargeRequest, PaymentError, PaymentEvent},
+    plan::Plan,
+    user_account::user_id::UserId,
+};
+
+use crate::transaction::Database;
+
+pub struct BillingImpl {
+    db: Database,
+}
+
+impl BillingImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { db: Database::Pool(pool) }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+/// 現在のプランの変更履歴を終了し, 新しいプランを記録する
+async fn insert_plan(
+    conn: &mut MySqlConnection,
+    gid: &str,
+    plan: Plan,
+    status: PlanStatus,
+    payment_method: Option<String>,
+    trial_ends_at: Option<DateTime<Utc>>,
+) -> Result<()> {
+    let now: DateTime<Utc> = Utc::now();
+
+    sqlx::query!(
+        "UPDATE group_plan_history SET effective_until = ? WHERE gid = ? AND effective_until IS NULL",
+        now,
+        gid
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO group_plan_history (gid, plan, status, payment_method, trial_ends_at, effective_from) VALUES (?, ?, ?, ?, ?, ?)",
+        gid,
+        plan.to_u8(),
+        status.to_u8(),
+        payment_method,
+        trial_ends_at,
+        now
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE group_account SET plan = ?, is_paid = ? WHERE gid = ?",
+        plan.to_u8(),
+        plan.is_paid(),
+        gid
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// 請求書の支払い状態を更新する. 支払済になった場合は支払日時を記録する
+async fn update_invoice_status(
+    conn: &mut MySqlConnection,
+    iid: &str,
+    status: InvoiceStatus,
+    charge_id: Option<&str>,
+) -> Result<()> {
+    let paid_at: Option<DateTime<Utc>> = match status {
+        InvoiceStatus::Paid => Some(Utc::now()),
+        _ => None,
+    };
+    sqlx::query!(
+        "UPDATE invoice SET status = ?, charge_id = COALESCE(?, charge_id), paid_at = COALESCE(?, paid_at) WHERE iid = ?",
+        status.to_u8(),
+        charge_id,
+        paid_at,
+        iid
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl BillingRepository for BillingImpl {
+    async fn find_current_plan(&self, gid: &UserId) -> Result<CurrentPlan> {
+        let mut tx = self.db.begin().await?;
+        let current = sqlx::query!(
+            r#"
+            SELECT
+                plan as "plan: u8",
+                (
+                    SELECT status FROM group_plan_history
+                    WHERE gid = group_account.gid AND effective_until IS NULL
+                ) as "status: u8",
+                EXISTS(
+                    SELECT hid FROM group_plan_history
+                    WHERE gid = group_account.gid AND trial_ends_at IS NOT NULL
+                ) as "has_trialed: bool"
+            FROM group_account
+            WHERE gid = ? AND is_deleted = false
+            "#,
+            gid.to_string()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(CurrentPlan {
+            plan: Plan::from_u8(current.plan)?,
+            // 変更履歴がない場合は, 作成時の無料プランを契約中とする
+            status: match current.status {
+                Some(status) => PlanStatus::from_u8(status)?,
+                None => PlanStatus::Active,
+            },
+            has_trialed: current.has_trialed,
+        })
+    }
+
+    async fn change_plan(
+        &self,
+        gid: &UserId,
+        plan: Plan,
+        status: PlanStatus,
+        payment_method: Option<String>,
+        trial_ends_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        insert_plan(&mut tx, &gid.to_string(), plan, status, payment_method, trial_ends_at).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn create_invoice(&self, invoice: &Invoice) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "INSERT INTO invoice (iid, gid, plan, amount, period_start_on, period_end_on, status, issued_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            invoice.iid.to_string(),
+            invoice.gid.to_string(),
+            invoice.plan.to_u8(),
+            invoice.amount,
+            invoice.period.start_on,
+            invoice.period.end_on,
+            invoice.status.to_u8(),
+            invoice.issued_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn record_charge(&self, iid: &InvoiceId, charge: &Charge) -> Result<()> {
+        let iid: String = iid.to_string();
+        let mut tx = self.db.begin().await?;
+        let invoice = sqlx::query!(
+            r#"SELECT status as "status: u8" FROM invoice WHERE iid = ? FOR UPDATE"#,
+            iid
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let status: InvoiceStatus =
+            InvoiceStatus::from_u8(invoice.status)?.transition_to(charge.invoice_status())?;
+        update_invoice_status(&mut tx, &iid, status, Some(&charge.charge_id)).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn apply_payment_event(&self, event: &PaymentEvent) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        let received = sqlx::query!(
+            "INSERT IGNORE INTO payment_event (event_id, type, received_at) VALUES (?, ?, ?)",
+            event.event_id,
+            event.kind.to_string(),
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+        if received.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        let charge_id: &str = event.kind.charge_id();
+        let invoice = sqlx::query!(
+            r#"SELECT iid, gid, plan as "plan: u8", status as "status: u8" FROM invoice WHERE charge_id = ? FOR UPDATE"#,
+            charge_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| PaymentError::InvalidPayload(format!("unknown charge_id: {}", charge_id)))?;
+
+        let current: InvoiceStatus = InvoiceStatus::from_u8(invoice.status)?;
+        let next: InvoiceStatus = event.kind.invoice_status();
+        // 請求時の結果と同じ通知は, 請求書に記録済みのため何もしない
+        if current != next {
+            current.transition_to(next)?;
+            update_invoice_status(&mut tx, &invoice.iid, next, None).await?;
+
+            let plan: Plan = Plan::from_u8(invoice.plan)?;
+            let contract = sqlx::query!(
+                r#"
+                SELECT plan as "plan: u8"
+                FROM group_plan_history
+                WHERE gid = ? AND effective_until IS NULL
+                FOR UPDATE
+                "#,
+                invoice.gid
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            let is_current_plan: bool = contract
+                .as_ref()
+                .is_some_and(|contract| contract.plan == plan.to_u8());
+
+            match next {
+                // 再請求で支払われた場合は, 未払いを解消するか, 請求したプランに変更する
+                InvoiceStatus::Paid if is_current_plan => {
+                    sqlx::query!(
+                        "UPDATE group_plan_history SET status = ? WHERE gid = ? AND effective_until IS NULL",
+                        PlanStatus::Active.to_u8(),
+                        invoice.gid
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                InvoiceStatus::Paid => {
+                    insert_plan(&mut tx, &invoice.gid, plan, PlanStatus::Active, None, None).await?;
+                }
+                InvoiceStatus::Failed if is_current_plan => {
+                    sqlx::query!(
+                        "UPDATE group_plan_history SET status = ? WHERE gid = ? AND effective_until IS NULL",
+                        PlanStatus::PastDue.to_u8(),
+                        invoice.gid
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                // 返金ではプランを変更しない. プランの変更は団体が行う
+                _ => {}
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn find_ended_trials(&self) -> Result<Vec<EndedTrial>> {
+        let mut tx = self.db.begin().await?;
+        let trials = sqlx::query!(
+            r#"
+            SELECT gid, plan as "plan: u8", payment_method as "payment_method!"
+            FROM group_plan_history
+            WHERE effective_until IS NULL AND status = ? AND trial_ends_at <= ?
+            AND payment_method IS NOT NULL
+            "#,
+            PlanStatus::Trialing.to_u8(),
+            Utc::now()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        trials
+            .into_iter()
+            .map(|trial| {
+                Ok(EndedTrial {
+                    gid: UserId::from_str(&trial.gid)?,
+                    plan: Plan::from_u8(trial.plan)?,
+                    payment_method: trial.payment_method,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 請求書を発行して請求し, 結果を請求書に記録する
+pub async fn charge_invoice(
+    repository: &dyn BillingRepository,
+    provider: &dyn PaymentProvider,
+    invoice: &Invoice,
+    payment_method: &str,
+) -> Result<Charge> {
+    repository.create_invoice(invoice).await?;
+
+    let charge: Charge = provider
+        .charge(&ChargeRequest {
+            iid: invoice.iid.clone(),
+            amount: invoice.amount,
+            payment_method: payment_method.to_string(),
+        })
+        .await?;
+    repository.record_charge(&invoice.iid, &charge).await?;
+
+    Ok(charge)
+}
+
+/// 団体のプランを変更し, 変更後の契約状態を返す
+///
+/// 有料プランへの変更は, 無料体験を開始するか, 請求して支払いに成功した場合に行う
+pub async fn change_plan(
+    repository: &dyn BillingRepository,
+    provider: &dyn PaymentProvider,
+    gid: &UserId,
+    plan: Plan,
+    payment_method: Option<String>,
+) -> Result<PlanStatus> {
+    let current: CurrentPlan = repository.find_current_plan(gid).await?;
+
+    match PlanChange::decide(gid, &current, plan, Utc::now())? {
+        PlanChange::Downgrade => {
+            repository
+                .change_plan(gid, plan, PlanStatus::Active, None, None)
+                .await?;
+            Ok(PlanStatus::Active)
+        }
+        PlanChange::Trial { trial_ends_at } => {
+            let payment_method: String =
+                payment_method.ok_or(PaymentError::PaymentMethodRequired)?;
+            repository
+                .change_plan(
+                    gid,
+                    plan,
+                    PlanStatus::Trialing,
+                    Some(payment_method),
+                    Some(trial_ends_at),
+                )
+                .await?;
+            Ok(PlanStatus::Trialing)
+        }
+        PlanChange::Charge(invoice) => {
+            let payment_method: String =
+                payment_method.ok_or(PaymentError::PaymentMethodRequired)?;
+            let charge: Charge =
+                charge_invoice(repository, provider, &invoice, &payment_method).await?;
+            match charge.outcome {
+                ChargeOutcome::Succeeded => {
+                    repository
+                        .change_plan(gid, plan, PlanStatus::Active, Some(payment_method), None)
+                        .await?;
+                    Ok(PlanStatus::Active)
+                }
+                ChargeOutcome::Failed { reason } => Err(PaymentError::ChargeFailed { reason }.into()),
+            }
+        }
+    }
+}
+
+/// 無料体験が終了した団体に請求し, 請求を終えた団体の数を返す
+///
+/// 支払いに成功した場合は契約中にし, 失敗した場合は無料プランに戻す.
+/// 1つの団体の請求でエラーが発生しても, ログに出力して残りの団体の請求を続ける
+pub async fn settle_ended_trials(
+    repository: &dyn BillingRepository,
+    provider: &dyn PaymentProvider,
+) -> Result<usize> {
+    let trials: Vec<EndedTrial> = repository.find_ended_trials().await?;
+
+    let mut settled: usize = 0;
+    for trial in trials.iter() {
+        match settle_trial(repository, provider, trial).await {
+            Ok(_) => settled += 1,
+            Err(error) => log::error!("failed to settle trial of {}: {}", trial.gid, error),
+        }
+    }
+
+    Ok(settled)
+}
+
+/// 無料体験が終了した1つの団体に請求する
+async fn settle_trial(
+    repository: &dyn BillingRepository,
+    provider: &dyn PaymentProvider,
+    trial: &EndedTrial,
+) -> Result<()> {
+    let invoice: Invoice = Invoice::new(trial.gid.clone(), trial.plan, Utc::now());
+    let charge: Charge =
+        charge_invoice(repository, provider, &invoice, &trial.payment_method).await?;
+    match charge.outcome {
+        ChargeOutcome::Succeeded => {
+            repository
+                .change_plan(
+                    &trial.gid,
+                    trial.plan,
+                    PlanStatus::Active,
+                    Some(trial.payment_method.clone()),
+                    None,
+                )
+                .await
+        }
+        ChargeOutcome::Failed { .. } => {
+            repository
+                .change_plan(&trial.gid, Plan::Free, PlanStatus::Active, None, None)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_settle_ended_trials {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use anyhow::anyhow;
+
+    use crate::payment::{MockPaymentProvider, WebhookVerifier, MOCK_DECLINED_PAYMENT_METHOD};
+
+    /// 請求書の発行に失敗する団体を指定できる請求リポジトリ
+    struct FakeBillingRepository {
+        trials: Vec<EndedTrial>,
+        failing_gid: String,
+        changes: Mutex<Vec<(String, Plan)>>,
+    }
+
+    #[async_trait]
+    impl BillingRepository for FakeBillingRepository {
+        async fn find_current_plan(&self, _gid: &UserId) -> Result<CurrentPlan> {
+            Err(anyhow!("not used in this test"))
+        }
+
+        async fn change_plan(
+            &self,
+            gid: &UserId,
+            plan: Plan,
+            _status: PlanStatus,
+            _payment_method: Option<String>,
+            _trial_ends_at: Option<DateTime<Utc>>,
+        ) -> Result<()> {
+            self.changes.lock().unwrap().push((gid.to_string(), plan));
+            Ok(())
+        }
+
+        async fn create_invoice(&self, invoice: &Invoice) -> Result<()> {
+            if invoice.gid.to_string() == self.failing_gid {
+                return Err(anyhow!("connection reset"));
+            }
+            Ok(())
+        }
+
+        async fn record_charge(&self, _iid: &InvoiceId, _charge: &Charge) -> Result<()> {
+            Ok(())
+        }
+
+        async fn apply_payment_event(&self, _event: &PaymentEvent) -> Result<bool> {
+            Err(anyhow!("not used in this test"))
+        }
+
+        async fn find_ended_trials(&self) -> Result<Vec<EndedTrial>> {
+            Ok(self
+                .trials
+                .iter()
+                .map(|trial| EndedTrial {
+                    gid: trial.gid.clone(),
+                    plan: trial.plan,
+                    payment_method: trial.payment_method.clone(),
+                })
+                .collect())
+        }
+    }
+
+    fn trial(gid: &str, payment_method: &str) -> EndedTrial {
+        EndedTrial {
+            gid: UserId::from_str(gid).unwrap(),
+            plan: Plan::Standard,
+            payment_method: payment_method.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn continue_after_error() {
+        let repository = FakeBillingRepository {
+            trials: vec![
+                trial("group_account000000000000001", "pm_mock_card"),
+                trial("group_account000000000000002", "pm_mock_card"),
+                trial("group_account000000000000003", MOCK_DECLINED_PAYMENT_METHOD),
+            ],
+            failing_gid: "group_account000000000000001".to_string(),
+            changes: Mutex::new(vec![]),
+        };
+        let provider =
+            MockPaymentProvider::new(WebhookVerifier::new("secret".to_string()).unwrap());
+
+        let settled = settle_ended_trials(&repository, &provider).await.unwrap();
+
+        assert_eq!(settled, 2);
+        assert_eq!(
+            *repository.changes.lock().unwrap(),
+            vec![
+                ("group_account000000000000002".to_string(), Plan::Standard),
+                ("group_account000000000000003".to_string(), Plan::Free),
+            ]
+        );
+    }
+}
@@ -1,6 +1,5 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::future;
 use chrono::Utc;
 use sqlx::MySqlPool;
 
@@ -10,13 +9,19 @@ use domain::model::user_account::{
     user_phone::UserPhone,
 };
 
+use crate::transaction::Database;
+
 pub struct GroupAccountImpl {
-    pool: MySqlPool,
+    db: Database,
 }
 
 impl GroupAccountImpl {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self { db: Database::Pool(pool) }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
     }
 }
 
@@ -30,38 +35,39 @@ impl GroupUserRepository for GroupAccountImpl {
         representative_name: UserName,
         representative_furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         address: String,
         contents: String,
         s3_keys: Vec<String>
     ) -> Result<()> {
         let id: String = gid.to_string();
+        let mut tx = self.db.begin().await?;
         sqlx::query!(
-            "INSERT INTO group_account (gid, name, furigana, representative_name, representative_furigana, phone, address, contents) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO group_account (gid, name, furigana, representative_name, representative_furigana, phone, email, address, contents) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
             id,
             name.to_string(),
             furigana.to_string(),
             representative_name.to_string(),
             representative_furigana.to_string(),
             phone.to_string(),
+            email,
             address,
             contents
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let insert_photo_query: Vec<_> = s3_keys
-            .iter()
-            .map(|p: &String| {
-                sqlx::query!(
-                    "INSERT INTO group_photo VALUES (?, ?)",
-                    p,
-                    id
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
+        for p in s3_keys.iter() {
+            sqlx::query!(
+                "INSERT INTO group_photo VALUES (?, ?)",
+                p,
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
 
-        future::try_join_all(insert_photo_query.into_iter()).await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -74,57 +80,47 @@ impl GroupUserRepository for GroupAccountImpl {
         representative_name: UserName,
         representative_furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         address: String,
         contents: String,
         s3_keys: Vec<String>
     ) -> Result<()> {
         let id: String = gid.to_string();
+        let mut tx = self.db.begin().await?;
         sqlx::query!(
-            "UPDATE group_account SET name = ?, furigana = ?, representative_name = ?, representative_furigana = ?, phone = ?, address = ?, contents = ? WHERE gid = ?",
+            "UPDATE group_account SET name = ?, furigana = ?, representative_name = ?, representative_furigana = ?, phone = ?, email = COALESCE(?, email), address = ?, contents = ? WHERE gid = ?",
             name.to_string(),
             furigana.to_string(),
             representative_name.to_string(),
             representative_furigana.to_string(),
             phone.to_string(),
+            email,
             address,
             contents,
             id
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
         sqlx::query!(
             "DELETE FROM group_photo WHERE gid = ?",
             id
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let insert_photo_query: Vec<_> = s3_keys
-            .iter()
-            .map(|p: &String| {
-                sqlx::query!(
-                    "INSERT INTO group_photo VALUES (?, ?)",
-                    p,
-                    id
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        future::try_join_all(insert_photo_query.into_iter()).await?;
+        for p in s3_keys.iter() {
+            sqlx::query!(
+                "INSERT INTO group_photo VALUES (?, ?)",
+                p,
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
 
-        Ok(())
-    }
+        tx.commit().await?;
 
-    async fn switch_plan(&self, gid: UserId, is_paid: bool) -> Result<()> {
-        sqlx::query!(
-            "UPDATE group_account SET is_paid = ? WHERE gid = ?",
-            is_paid,
-            gid.to_string()
-        )
-        .execute(&self.pool)
-        .await?;
         Ok(())
     }
 
@@ -134,26 +130,29 @@ impl GroupUserRepository for GroupAccountImpl {
             is_deleted: bool
         }
 
+        let mut tx = self.db.begin().await?;
+
         let is_deleted = sqlx::query_as!(
             IsExists,
             r#"
-            SELECT is_deleted as "is_deleted: bool" FROM group_account WHERE gid = ?
+            SELECT is_deleted as "is_deleted: bool" FROM group_account WHERE gid = ? FOR UPDATE
             "#,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
         if is_deleted.is_deleted {
             Err(anyhow::anyhow!("This group_account is already deleted".to_string()))
         } else {
             sqlx::query!(
-                "UPDATE group_account SET is_deleted = true, deleted_at = ? WHERE gid = ?",
+                "UPDATE group_account SET is_deleted = true, deleted_at = ?, email = NULL WHERE gid = ?",
                 Utc::now(),
                 gid.to_string()
             )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+            tx.commit().await?;
             Ok(())
         }
     }
@@ -1,32 +1,77 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{NaiveDate, Utc};
-use futures::future;
-use sqlx::MySqlPool;
+use sqlx::{MySqlConnection, MySqlPool};
 
 use command_repository::user_account::participant::ParticipantUserRepository;
 use domain::model::{
-    condition::Condition,
+    availability::{Availability, AvailabilityError, AvailabilityId},
     gender::Gender,
-    region::Region,
     terms::Terms,
-    theme::Theme,
     user_account::{
         user_id::UserId, user_name::UserName, user_name_furigana::UserNameFurigana,
         user_phone::UserPhone,
     },
 };
 
+use crate::transaction::Database;
+
 pub struct ParticipantAccountImpl {
-    pool: MySqlPool,
+    db: Database,
 }
 
 impl ParticipantAccountImpl {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self { db: Database::Pool(pool) }
+    }
+
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
     }
 }
 
+/// 参加者の地域・要素を登録する
+async fn insert_terms(conn: &mut MySqlConnection, uid: &str, terms: &Terms) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO participant_element (uid, eid) VALUES (?, ?)",
+        uid,
+        terms.target_status[0].to_id()
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    for r in terms.regions.iter() {
+        sqlx::query!(
+            "INSERT INTO participant_region (uid, rid) VALUES (?, ?)",
+            uid,
+            r.to_uint()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let elements = terms
+        .themes
+        .iter()
+        .map(|t| (t.to_id(), false))
+        .chain(terms.required_themes.iter().map(|t| (t.to_id(), true)))
+        .chain(terms.conditions.iter().map(|c| (c.to_id(), false)))
+        .chain(terms.required_conditions.iter().map(|c| (c.to_id(), true)));
+
+    for (eid, is_need) in elements {
+        sqlx::query!(
+            "INSERT INTO participant_element (uid, eid, is_need) VALUES (?, ?, ?)",
+            uid,
+            eid,
+            is_need
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
 #[async_trait]
 impl ParticipantUserRepository for ParticipantAccountImpl {
     async fn create(
@@ -35,6 +80,7 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
         name: UserName,
         furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         gender: Gender,
         birthday: NaiveDate,
         profile: String,
@@ -42,100 +88,22 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
     ) -> Result<()> {
         let id: String = pid.to_string();
 
-        sqlx::query!("INSERT INTO participant_account (uid, name, furigana, gender, birthday, phone, profile) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!("INSERT INTO participant_account (uid, name, furigana, gender, birthday, phone, email, profile) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
             id,
             name.to_string(),
             furigana.to_string(),
             gender as u8,
             birthday,
             phone.to_string(),
+            email,
             profile
-        ).execute(&self.pool).await?;
+        ).execute(&mut *tx).await?;
 
-        sqlx::query!(
-            "INSERT INTO participant_element (uid, eid) VALUES (?, ?)",
-            id,
-            terms.target_status[0].to_id()
-        )
-        .execute(&self.pool)
-        .await?;
+        insert_terms(&mut tx, &id, &terms).await?;
 
-        let insert_region_query: Vec<_> = terms
-            .regions
-            .iter()
-            .map(|r: &Region| {
-                sqlx::query!(
-                    "INSERT INTO participant_region (uid, rid) VALUES (?, ?)",
-                    id,
-                    r.to_uint()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_query: Vec<_> = terms
-            .themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid) VALUES (?, ?)",
-                    id,
-                    t.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_required_query: Vec<_> = terms
-            .required_themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    t.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_query: Vec<_> = terms
-            .conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid) VALUES (?, ?)",
-                    id,
-                    c.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_required_query: Vec<_> = terms
-            .required_conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    c.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        future::try_join_all(
-            insert_region_query
-                .into_iter()
-                .chain(insert_theme_query)
-                .chain(insert_theme_required_query)
-                .chain(insert_condition_query)
-                .chain(insert_condition_required_query),
-        )
-        .await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -146,123 +114,47 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
         name: UserName,
         furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         gender: Gender,
         birthday: NaiveDate,
         profile: String,
         terms: Terms,
     ) -> Result<()> {
-        let update_query = sqlx::query!(
-            "UPDATE participant_account SET name = ?,furigana = ?, phone = ?, gender = ?, birthday = ?, profile = ? WHERE uid = ?",
+        let id: String = pid.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            "UPDATE participant_account SET name = ?,furigana = ?, phone = ?, email = COALESCE(?, email), gender = ?, birthday = ?, profile = ? WHERE uid = ?",
             name.to_string(),
             furigana.to_string(),
             phone.to_string(),
+            email,
             gender as u8,
             birthday,
             profile,
-            pid.to_string()
+            id
         )
-        .execute(&self.pool);
+        .execute(&mut *tx)
+        .await?;
 
-        let delete_region_query = sqlx::query!(
+        sqlx::query!(
             "DELETE FROM participant_region WHERE uid = ?",
-            pid.to_string()
-        )
-        .execute(&self.pool);
-
-        let delete_element_query = sqlx::query!(
-            "DELETE FROM participant_element WHERE uid = ?",
-            pid.to_string()
+            id
         )
-        .execute(&self.pool);
-
-        future::try_join3(update_query, delete_region_query, delete_element_query).await?;
-
-        let id: String = pid.to_string();
+        .execute(&mut *tx)
+        .await?;
 
         sqlx::query!(
-            "INSERT INTO participant_element (uid, eid) VALUES (?, ?)",
-            id,
-            terms.target_status[0].to_id()
+            "DELETE FROM participant_element WHERE uid = ?",
+            id
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let insert_region_query: Vec<_> = terms
-            .regions
-            .iter()
-            .map(|r: &Region| {
-                sqlx::query!(
-                    "INSERT INTO participant_region (uid, rid) VALUES (?, ?)",
-                    id,
-                    r.to_uint()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_query: Vec<_> = terms
-            .themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid) VALUES (?, ?)",
-                    id,
-                    t.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_theme_required_query: Vec<_> = terms
-            .required_themes
-            .iter()
-            .map(|t: &Theme| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    t.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_query: Vec<_> = terms
-            .conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid) VALUES (?, ?)",
-                    id,
-                    c.to_id()
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        let insert_condition_required_query: Vec<_> = terms
-            .required_conditions
-            .iter()
-            .map(|c: &Condition| {
-                sqlx::query!(
-                    "INSERT INTO participant_element (uid, eid, is_need) VALUES (?, ?, ?)",
-                    id,
-                    c.to_id(),
-                    true
-                )
-                .execute(&self.pool)
-            })
-            .collect::<Vec<_>>();
-
-        future::try_join_all(
-            insert_region_query
-                .into_iter()
-                .chain(insert_theme_query)
-                .chain(insert_theme_required_query)
-                .chain(insert_condition_query)
-                .chain(insert_condition_required_query),
-        )
-        .await?;
+        insert_terms(&mut tx, &id, &terms).await?;
+
+        tx.commit().await?;
 
         Ok(())
     }
@@ -273,27 +165,97 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
             is_deleted: bool
         }
 
+        let mut tx = self.db.begin().await?;
+
         let is_deleted = sqlx::query_as!(
             IsExists,
             r#"
-            SELECT is_deleted as "is_deleted: bool" FROM participant_account WHERE uid = ?
+            SELECT is_deleted as "is_deleted: bool" FROM participant_account WHERE uid = ? FOR UPDATE
             "#,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
         if is_deleted.is_deleted {
             Err(anyhow::anyhow!("This participant_account is already deleted".to_string()))
         } else {
             sqlx::query!(
-                "UPDATE participant_account SET is_deleted = true, deleted_at = ? WHERE uid = ?",
+                "UPDATE participant_account SET is_deleted = true, deleted_at = ?, email = NULL WHERE uid = ?",
                 Utc::now(),
                 pid.to_string()
             )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+            tx.commit().await?;
             Ok(())
         }
     }
+
+    async fn register_availability(
+        &self,
+        pid: UserId,
+        did: AvailabilityId,
+        availability: Availability,
+    ) -> Result<()> {
+        let weekday: String = availability.weekday.to_string();
+
+        let mut tx = self.db.begin().await?;
+
+        // 同じ曜日の時間帯をロックし, 重なる時間帯が登録されないようにする
+        let registered = sqlx::query!(
+            "SELECT start_time, finish_time FROM volunteer_dates WHERE uid = ? AND dates = ? FOR UPDATE",
+            pid.to_string(),
+            weekday
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let is_overlapped: bool = registered.into_iter().any(|r| {
+            availability.overlaps(&Availability {
+                weekday: availability.weekday,
+                start_time: r.start_time,
+                finish_time: r.finish_time,
+            })
+        });
+        if is_overlapped {
+            return Err(AvailabilityError::Overlapped(availability.weekday).into());
+        }
+
+        sqlx::query!(
+            "INSERT INTO volunteer_dates (did, uid, dates, start_time, finish_time) VALUES (?, ?, ?, ?, ?)",
+            did.to_string(),
+            pid.to_string(),
+            weekday,
+            availability.start_time,
+            availability.finish_time
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_availability(&self, pid: UserId, did: AvailabilityId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query!(
+            "DELETE FROM volunteer_dates WHERE did = ? AND uid = ?",
+            did.to_string(),
+            pid.to_string()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // 他の参加者の時間帯は削除できない
+        if result.rows_affected() == 0 {
+            return Err(AvailabilityError::NotFound.into());
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
 }
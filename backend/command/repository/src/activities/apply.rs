@@ -2,7 +2,8 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use domain::model::{
-    apply::ApplyId,
+    apply::{ApplyId, ApplyStatus, CancelReason},
+    schedule::SessionId,
     volunteer::VolunteerId,
     user_account::user_id::UserId, group_participants::GroupParticipants
 };
@@ -10,23 +11,32 @@ use domain::model::{
 
 #[async_trait]
 pub trait ApplyRepository: Send + Sync {
-    /// ボランティアに応募する
+    /// ボランティアの回に応募する
+    ///
+    /// 回を指定しない場合は, 回が1つだけのボランティアのみその回に応募する.
+    /// 回がボランティアにない場合, 回が開始済みの場合はエラーを返す
     async fn create(
         &self,
         aid: ApplyId,
         vid: VolunteerId,
+        vsid: Option<SessionId>,
         user_id: UserId,
         as_group: bool,
         members: Option<Vec<GroupParticipants>>
     ) -> Result<()>;
 
     /// 応募の承認を更新する
+    ///
+    /// 状態遷移が不正な場合, 承認により回の募集人数を超える場合はエラーを返す
     async fn update_allowed_status(
         &self,
         aid: ApplyId,
-        allowed_status: u8,
+        allowed_status: ApplyStatus,
     ) -> Result<()>;
 
-    // 応募メールの送信を送信済みにする
-    async fn update_is_sent(&self, aid: ApplyId) -> Result<()>;
+    /// 参加者が応募を取り消す
+    ///
+    /// 承認済の応募を取り消した場合は, 同じ回の空いた枠に未承認の応募を応募順に繰り上げて承認し, 繰り上げた応募のIDを返す.
+    /// 状態遷移が不正な場合, 取消の期限を過ぎている場合はエラーを返す
+    async fn cancel(&self, aid: ApplyId, reason: CancelReason) -> Result<Vec<ApplyId>>;
 }
@@ -0,0 +1,26 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use domain::model::{
+    apply::ApplyId,
+    attendance::{AttendanceRecord, CheckInCode},
+    volunteer::VolunteerId,
+};
+
+#[async_trait]
+pub trait AttendanceRepository: Send + Sync {
+    /// ボランティアのチェックインコードを発行する. 以前に発行したコードは無効になる
+    ///
+    /// チェックインの受付期間外の場合はエラーを返す
+    async fn issue_code(&self, vid: VolunteerId, code: CheckInCode) -> Result<()>;
+
+    /// 参加者がチェックインコードでチェックインし, 応募を出席にする
+    ///
+    /// 承認済の応募でない場合, 受付期間外の場合, コードが一致しない・期限切れの場合はエラーを返す
+    async fn check_in(&self, aid: ApplyId, code: String) -> Result<()>;
+
+    /// 団体が応募または集団応募のメンバーの出欠を記録する
+    ///
+    /// 承認済の応募でない場合, ボランティアの開始前に欠席を記録した場合はエラーを返す
+    async fn record(&self, aid: ApplyId, record: AttendanceRecord) -> Result<()>;
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use domain::model::{
+    message::{Message, MessageId, ThreadId, ThreadSubject},
+    user_account::user_id::UserId,
+};
+
+#[async_trait]
+pub trait MessageRepository: Send + Sync {
+    /// 応募・スカウトのスレッドにメッセージを送信する. スレッドがない場合は作成する
+    ///
+    /// 送信者はスレッドのメッセージを全て読んだものとする
+    async fn send(&self, subject: ThreadSubject, message: Message) -> Result<ThreadId>;
+
+    /// スレッドのメッセージを全て既読にする
+    async fn mark_read(&self, tid: ThreadId, uid: UserId) -> Result<()>;
+
+    /// メッセージを削除する. 削除済みの場合はエラーを返す
+    async fn delete(&self, mid: MessageId) -> Result<()>;
+}
@@ -2,8 +2,9 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use domain::model::{
+    apply::ApplyId,
     volunteer::VolunteerId,
-    user_account::user_id::UserId, scout::ScoutId
+    user_account::user_id::UserId, scout::{Scout, ScoutId}
 };
 
 
@@ -18,13 +19,23 @@ pub trait ScoutRepository: Send + Sync {
         message: String
     ) -> Result<()>;
 
-    // スカウトメールの送信を送信済みにする
-    async fn update_is_sent(&self, sid: ScoutId) -> Result<()>;
-
     /// スカウトを既読状態にする
     async fn update_is_read(&self, sid: ScoutId) -> Result<()>;
 
     // スカウトを辞退する
     async fn update_denied(&self, sid: ScoutId) -> Result<()>;
 
+    /// スカウトを承諾済にする
+    ///
+    /// 状態遷移が不正な場合(募集締切日を過ぎた場合を含む), 既に同じボランティアに応募している場合はエラーを返す
+    async fn accept(&self, sid: ScoutId) -> Result<Scout>;
+
+    /// 承諾したスカウトと, スカウトから作成した応募を紐づける
+    async fn link_apply(&self, sid: ScoutId, aid: ApplyId) -> Result<()>;
+
+    /// 団体がスカウトを取り下げる
+    async fn withdraw(&self, sid: ScoutId) -> Result<()>;
+
+    /// 募集締切日を過ぎた返答待ちのスカウトを期限切れにし, 期限切れにした件数を返す
+    async fn expire_overdue(&self) -> Result<u64>;
 }
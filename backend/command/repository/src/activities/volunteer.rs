@@ -1,8 +1,10 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::NaiveDate;
 use domain::model::{
+    location::GeoPoint,
+    schedule::Schedule,
     volunteer::VolunteerId,
     user_account::user_id::UserId,
     terms::Terms
@@ -11,7 +13,7 @@ use domain::model::{
 
 #[async_trait]
 pub trait VolunteerRepository: Send + Sync {
-    /// ボランティアを作成する
+    /// ボランティアを作成する. 日程の回も作成する
     async fn create(
         &self,
         vid: VolunteerId,
@@ -21,8 +23,8 @@ pub trait VolunteerRepository: Send + Sync {
         overview: String,
         recruited_num: u32,
         place: String,
-        start_at: DateTime<Utc>,
-        finish_at: DateTime<Utc>,
+        location: Option<GeoPoint>,
+        schedule: Schedule,
         deadline_on: NaiveDate,
         as_group: bool,
         reward: Option<String>,
@@ -31,6 +33,8 @@ pub trait VolunteerRepository: Send + Sync {
     ) -> Result<()>;
 
     /// ボランティアを更新する
+    ///
+    /// 開始日時が同じ回は回IDと応募を引き継ぐ. 未承認・承認済の応募がある回を日程から除く場合はエラーを返す
     async fn update(
         &self,
         vid: VolunteerId,
@@ -39,8 +43,8 @@ pub trait VolunteerRepository: Send + Sync {
         overview: String,
         recruited_num: u32,
         place: String,
-        start_at: DateTime<Utc>,
-        finish_at: DateTime<Utc>,
+        location: Option<GeoPoint>,
+        schedule: Schedule,
         deadline_on: NaiveDate,
         as_group: bool,
         reward: Option<String>,
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use domain::model::event::ActivityEvent;
+
+/// 応募・スカウトの出来事の配信手段
+///
+/// Redisなどのメッセージブローカーに差し替えられるようにトレイトとして定義する
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    /// 出来事を関係するユーザーに配信する
+    async fn publish(&self, event: &ActivityEvent) -> Result<()>;
+}
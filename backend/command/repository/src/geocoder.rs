@@ -0,0 +1,13 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use domain::model::location::GeoPoint;
+
+/// 住所や地名を緯度経度に変換する
+///
+/// 外部のジオコーディングAPIに差し替えられるようにトレイトとして定義する
+#[async_trait]
+pub trait Geocoder: Send + Sync {
+    /// 地名を緯度経度に変換する. 該当する地点がない場合は`None`を返す
+    async fn geocode(&self, place: &str) -> Result<Option<GeoPoint>>;
+}
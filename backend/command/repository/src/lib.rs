@@ -1,2 +1,8 @@
-pub mod user_account;
 pub mod activities;
+pub mod event;
+pub mod geocoder;
+pub mod mailer;
+pub mod outbox;
+pub mod payment;
+pub mod unit_of_work;
+pub mod user_account;
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use domain::model::notification::Email;
+
+/// メールの送信手段
+///
+/// SMTPサーバーや外部のメール配信サービスに差し替えられるようにトレイトとして定義する
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// メールを送信する
+    async fn send(&self, email: &Email) -> Result<()>;
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use domain::model::notification::{Email, EmailContext, NotificationKind};
+
+/// 送信待ちのメール
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutboxMessage {
+    pub oid: u64,
+    pub kind: NotificationKind,
+    /// 応募id または スカウトid
+    pub target_id: String,
+    /// 宛先のメールアドレス
+    pub recipient: String,
+    pub context: EmailContext,
+    /// これまでに送信を試みた回数
+    pub attempts: u32,
+}
+
+impl OutboxMessage {
+    /// テンプレートから送信するメールを作成する
+    pub fn to_email(&self) -> Email {
+        let (subject, body) = self.kind.render(&self.context);
+        Email {
+            message_id: format!("outbox-{}@volunscout", self.oid),
+            to: self.recipient.clone(),
+            subject,
+            body,
+        }
+    }
+}
+
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// 送信日時を過ぎた送信待ちのメールを最大`limit`件取得する
+    ///
+    /// 取得したメールは`lease_until`まで他のワーカーに取得されないようにする
+    async fn claim_due(&self, limit: u32, lease_until: DateTime<Utc>) -> Result<Vec<OutboxMessage>>;
+
+    /// 送信済みにする. 応募・スカウトのメールの場合は応募・スカウトも送信済みにする
+    async fn mark_sent(&self, message: &OutboxMessage) -> Result<()>;
+
+    /// 送信の失敗を記録する
+    ///
+    /// `next_attempt_at`がNoneの場合は再送せず, 送信失敗にする
+    async fn mark_failed(
+        &self,
+        message: &OutboxMessage,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()>;
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use domain::model::payment::{Charge, ChargeRequest, PaymentEvent};
+
+/// 決済サービス
+///
+/// 外部の決済サービスに差し替えられるようにトレイトとして定義する
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    /// 請求する
+    ///
+    /// 決済サービスが請求を受け付けた場合は, 支払いに失敗しても結果を`Ok`で返す
+    async fn charge(&self, request: &ChargeRequest) -> Result<Charge>;
+
+    /// Webhookのリクエストの署名を検証し, イベントに変換する
+    fn parse_event(&self, signature: Option<&str>, payload: &[u8]) -> Result<PaymentEvent>;
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{
+    activities::{
+        apply::ApplyRepository, review::ReviewRepository, scout::ScoutRepository,
+        volunteer::VolunteerRepository,
+    },
+    user_account::{group::GroupUserRepository, participant::ParticipantUserRepository},
+};
+
+/// 複数のリポジトリ操作を1つのトランザクションで実行する
+///
+/// [UnitOfWork::commit]を呼ばずにドロップした場合, 全ての操作はロールバックされる
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    fn group_user_repository(&self) -> &dyn GroupUserRepository;
+
+    fn participant_user_repository(&self) -> &dyn ParticipantUserRepository;
+
+    fn volunteer_repository(&self) -> &dyn VolunteerRepository;
+
+    fn apply_repository(&self) -> &dyn ApplyRepository;
+
+    fn scout_repository(&self) -> &dyn ScoutRepository;
+
+    fn review_repository(&self) -> &dyn ReviewRepository;
+
+    /// トランザクションをコミットする
+    async fn commit(self: Box<Self>) -> Result<()>;
+}
+
+#[async_trait]
+pub trait UnitOfWorkFactory: Send + Sync {
+    /// トランザクションを開始する
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>>;
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use domain::model::{
+    billing::{CurrentPlan, Invoice, InvoiceId, PlanStatus},
+    payment::{Charge, PaymentEvent},
+    plan::Plan,
+    user_account::user_id::UserId,
+};
+
+/// 無料体験が終了した団体
+#[derive(Debug, Clone)]
+pub struct EndedTrial {
+    pub gid: UserId,
+    pub plan: Plan,
+    pub payment_method: String,
+}
+
+#[async_trait]
+pub trait BillingRepository: Send + Sync {
+    /// 団体の現在のプランを取得する
+    async fn find_current_plan(&self, gid: &UserId) -> Result<CurrentPlan>;
+
+    /// 団体のプランを変更し, 変更履歴に記録する
+    async fn change_plan(
+        &self,
+        gid: &UserId,
+        plan: Plan,
+        status: PlanStatus,
+        payment_method: Option<String>,
+        trial_ends_at: Option<DateTime<Utc>>,
+    ) -> Result<()>;
+
+    /// 請求書を発行する
+    async fn create_invoice(&self, invoice: &Invoice) -> Result<()>;
+
+    /// 決済サービスでの請求の結果を請求書に記録する
+    async fn record_charge(&self, iid: &InvoiceId, charge: &Charge) -> Result<()>;
+
+    /// 決済サービスのイベントを処理する
+    ///
+    /// 処理済みのイベントの場合は何もせず`false`を返す
+    async fn apply_payment_event(&self, event: &PaymentEvent) -> Result<bool>;
+
+    /// 無料体験が終了した団体を取得する
+    async fn find_ended_trials(&self) -> Result<Vec<EndedTrial>>;
+}
@@ -9,6 +9,8 @@ use domain::model::user_account::{
 #[async_trait]
 pub trait GroupUserRepository: Send + Sync {
     /// 団体アカウントを作成する
+    ///
+    /// `email`は応募のメールの宛先とする
     async fn create(
         &self,
         gid: UserId,
@@ -17,12 +19,13 @@ pub trait GroupUserRepository: Send + Sync {
         representative_name: UserName,
         representative_furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         address: String,
         contents: String,
         photo_keys: Vec<String>
     ) -> Result<()>;
 
-    /// 団体アカウントを更新する
+    /// 団体アカウントを更新する. `email`が`None`の場合はメールアドレスを変更しない
     async fn update(
         &self,
         gid: UserId,
@@ -31,18 +34,12 @@ pub trait GroupUserRepository: Send + Sync {
         representative_name: UserName,
         representative_furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         address: String,
         contents: String,
         photo_keys: Vec<String>
     ) -> Result<()>;
 
-    /// 団体アカウントの有償・無償プランを変更する
-    async fn switch_plan(
-        &self,
-        gid: UserId,
-        is_paid: bool
-    ) -> Result<()>;
-
     /// 団体アカウントを削除する
     async fn delete(&self, gid: UserId) -> Result<()>;
 }
@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use chrono::NaiveDate;
 
 use domain::model::{
+    availability::{Availability, AvailabilityId},
     gender::Gender,
     user_account::{
         user_id::UserId, user_name::UserName, user_name_furigana::UserNameFurigana,
@@ -13,25 +14,29 @@ use domain::model::{
 #[async_trait]
 pub trait ParticipantUserRepository: Send + Sync {
     /// 参加者アカウントを作成する
+    ///
+    /// `email`は応募・スカウトのメールの宛先とする
     async fn create(
         &self,
         pid: UserId,
         name: UserName,
         furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         gender: Gender,
         birthday: NaiveDate,
         profile: String,
         terms: Terms
     ) -> Result<()>;
 
-    /// 参加者アカウントを更新する
+    /// 参加者アカウントを更新する. `email`が`None`の場合はメールアドレスを変更しない
     async fn update(
         &self,
         pid: UserId,
         name: UserName,
         furigana: UserNameFurigana,
         phone: UserPhone,
+        email: Option<String>,
         gender: Gender,
         birthday: NaiveDate,
         profile: String,
@@ -40,4 +45,15 @@ pub trait ParticipantUserRepository: Send + Sync {
 
     /// 参加者アカウントを削除する
     async fn delete(&self, pid: UserId) -> Result<()>;
+
+    /// 参加可能な時間帯を登録する. 同じ曜日の時間帯と重なる場合はエラーとなる
+    async fn register_availability(
+        &self,
+        pid: UserId,
+        did: AvailabilityId,
+        availability: Availability,
+    ) -> Result<()>;
+
+    /// 参加可能な時間帯を削除する
+    async fn delete_availability(&self, pid: UserId, did: AvailabilityId) -> Result<()>;
 }
@@ -0,0 +1,36 @@
+/// バイト列を16進数の小文字にする
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 16進数の文字列をバイト列にする. 大文字・小文字は区別せず, 16進数でない場合は`None`
+pub fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    // from_str_radixは先頭の`+`を受け付けるため, 16進数の文字のみか先に確認する
+    if !value.len().is_multiple_of(2) || !value.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test_hex {
+    use super::*;
+
+    #[test]
+    fn encode() {
+        assert_eq!(encode_hex(&[0x00, 0x0f, 0xab, 0xff]), "000fabff");
+        assert_eq!(encode_hex(&[]), "");
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(decode_hex("000fABff"), Some(vec![0x00, 0x0f, 0xab, 0xff]));
+        assert_eq!(decode_hex(""), Some(vec![]));
+        for value in ["abc", "zz", "+1", "あい"] {
+            assert_eq!(decode_hex(value), None);
+        }
+    }
+}
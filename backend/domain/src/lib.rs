@@ -1,2 +1,3 @@
 pub mod consts;
+pub mod hex;
 pub mod model;
@@ -3,11 +3,22 @@ use serde::{Serialize, Deserialize};
 use self::{volunteer::VolunteerId, user_account::user_id::UserId};
 
 pub mod apply;
+pub mod attendance;
+pub mod availability;
+pub mod billing;
+pub mod certificate;
 pub mod condition;
+pub mod event;
 pub mod gender;
+pub mod location;
+pub mod message;
 pub mod group_account;
+pub mod notification;
 pub mod participant_account;
+pub mod payment;
+pub mod plan;
 pub mod region;
+pub mod schedule;
 pub mod scout;
 pub mod target_status;
 pub mod terms;
@@ -1,11 +1,19 @@
 use std::str::FromStr;
 
-use chrono::{DateTime, Utc};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use ulid_generator_rs::{ULIDGenerator, ULID};
 
 use crate::model::{user_account::user_id::UserId, volunteer::VolunteerId};
 
+/// 承認済の応募を取り消せる期限. ボランティアの開始日時の何時間前までか
+pub const APPROVED_CANCEL_CUTOFF_HOURS: i64 = 24;
+
+/// 取消理由の最大文字数
+pub const MAX_CANCEL_REASON_LENGTH: usize = 500;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Apply {
     pub aid: ApplyId,
@@ -13,8 +21,7 @@ pub struct Apply {
     pub user_id: UserId,
     pub applied_at: DateTime<Utc>,
     pub as_group: bool,
-    /// 0:未承認 1:承認済 2:棄却済
-    pub allowed_status: u8,
+    pub allowed_status: ApplyStatus,
     pub decided_at: Option<DateTime<Utc>>,
     pub is_sent: bool
 }
@@ -28,10 +35,234 @@ impl ApplyId {
         let value: ULID = generator.generate().unwrap();
         ApplyId(value)
     }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ApplyIdError {
+    #[error("invalid aid")]
+    Invalid,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for ApplyId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(ApplyId(value)),
+            Err(_) => Err(ApplyIdError::Invalid.into()),
+        }
+    }
+}
+
+/// 応募の承認状態
+///
+/// 状態遷移は以下のみ許可する
+/// - 未承認 -> 承認済 / 棄却済 / 取消済
+/// - 承認済 -> 取消済
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApplyStatus {
+    /// 未承認
+    Pending = 0,
+    /// 承認済
+    Approved = 1,
+    /// 棄却済
+    Rejected = 2,
+    /// 取消済(参加者による辞退)
+    Cancelled = 3,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ApplyError {
+    #[error("allowed_status not found")]
+    StatusNotFound,
+    #[error("cannot change allowed_status from {from} to {to}")]
+    InvalidTransition { from: ApplyStatus, to: ApplyStatus },
+    #[error("recruited_num exceeded (recruited: {recruited_num}, approved: {approved_num}, requested: {requested_num})")]
+    CapacityExceeded {
+        recruited_num: u32,
+        approved_num: u32,
+        requested_num: u32,
+    },
+    #[error("cancellation is closed at {deadline}")]
+    CancellationClosed { deadline: DateTime<Utc> },
+    #[error("cancel reason is empty")]
+    CancelReasonEmpty,
+    #[error("cancel reason must be at most {max} characters (actual: {actual})")]
+    CancelReasonTooLong { max: usize, actual: usize },
+}
+
+impl ApplyStatus {
+    pub fn from_u8(value: u8) -> Result<ApplyStatus> {
+        match value {
+            0 => Ok(ApplyStatus::Pending),
+            1 => Ok(ApplyStatus::Approved),
+            2 => Ok(ApplyStatus::Rejected),
+            3 => Ok(ApplyStatus::Cancelled),
+            _ => Err(ApplyError::StatusNotFound.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// 指定した状態へ遷移できるか
+    pub fn can_transition_to(&self, next: ApplyStatus) -> bool {
+        matches!(
+            (self, next),
+            (ApplyStatus::Pending, ApplyStatus::Approved)
+                | (ApplyStatus::Pending, ApplyStatus::Rejected)
+                | (ApplyStatus::Pending, ApplyStatus::Cancelled)
+                | (ApplyStatus::Approved, ApplyStatus::Cancelled)
+        )
+    }
+
+    /// 指定した状態へ遷移する. 許可されていない遷移の場合はエラーを返す
+    pub fn transition_to(&self, next: ApplyStatus) -> Result<ApplyStatus> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(ApplyError::InvalidTransition {
+                from: *self,
+                to: next,
+            }
+            .into())
+        }
+    }
+
+    /// 承認する
+    pub fn approve(&self) -> Result<ApplyStatus> {
+        self.transition_to(ApplyStatus::Approved)
+    }
+
+    /// 棄却する
+    pub fn reject(&self) -> Result<ApplyStatus> {
+        self.transition_to(ApplyStatus::Rejected)
+    }
+
+    /// 参加者が取り消す
+    pub fn cancel(&self) -> Result<ApplyStatus> {
+        self.transition_to(ApplyStatus::Cancelled)
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for ApplyStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            ApplyStatus::Pending => "Pending",
+            ApplyStatus::Approved => "Approved",
+            ApplyStatus::Rejected => "Rejected",
+            ApplyStatus::Cancelled => "Cancelled",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// 応募を承認した場合に募集人数を超えないか確認する
+///
+/// ## 引数
+/// - `recruited_num` - 応募した回の募集人数
+/// - `approved_num` - 承認済の人数(集団応募はメンバー数で数える)
+/// - `requested_num` - 承認しようとしている応募の人数
+pub fn ensure_capacity(recruited_num: u32, approved_num: u32, requested_num: u32) -> Result<()> {
+    if approved_num + requested_num > recruited_num {
+        Err(ApplyError::CapacityExceeded {
+            recruited_num,
+            approved_num,
+            requested_num,
+        }
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+/// 応募を取り消せる期限を返す
+///
+/// - 未承認: 応募した回の開始日時まで
+/// - 承認済: 団体が代わりの参加者を確保できるよう, 開始日時の[APPROVED_CANCEL_CUTOFF_HOURS]時間前まで
+pub fn cancel_deadline(status: ApplyStatus, start_at: DateTime<Utc>) -> DateTime<Utc> {
+    match status {
+        ApplyStatus::Approved => start_at - Duration::hours(APPROVED_CANCEL_CUTOFF_HOURS),
+        _ => start_at,
+    }
+}
+
+/// 参加者が応募を取り消せるか確認し, 取消済の状態を返す
+///
+/// 取り消せない状態の場合, 期限を過ぎている場合はエラーを返す
+pub fn ensure_cancellable(
+    current: ApplyStatus,
+    start_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<ApplyStatus> {
+    let next: ApplyStatus = current.cancel()?;
+    let deadline: DateTime<Utc> = cancel_deadline(current, start_at);
+    if now >= deadline {
+        return Err(ApplyError::CancellationClosed { deadline }.into());
+    }
+    Ok(next)
+}
+
+/// 繰り上げを待つ未承認の応募
+#[derive(Debug, Clone)]
+pub struct WaitlistEntry {
+    pub aid: ApplyId,
+    /// 応募の人数(集団応募はメンバー数で数える)
+    pub headcount: u32,
+}
+
+/// 承認済の応募の取消で空いた枠に繰り上げる応募を選ぶ
+///
+/// `waitlist`は応募の古い順に並べる. 古い順に残りの枠に収まる応募を選び,
+/// 枠に収まらない集団応募は飛ばして後ろの応募で枠を埋める
+pub fn select_promotions(
+    recruited_num: u32,
+    approved_num: u32,
+    waitlist: &[WaitlistEntry],
+) -> Vec<ApplyId> {
+    let mut remaining: u32 = recruited_num.saturating_sub(approved_num);
+    let mut promoted: Vec<ApplyId> = vec![];
+    for entry in waitlist {
+        if remaining == 0 {
+            break;
+        }
+        if entry.headcount <= remaining {
+            remaining -= entry.headcount;
+            promoted.push(entry.aid.clone());
+        }
+    }
+    promoted
+}
+
+/// 参加者が応募を取り消す理由
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelReason(String);
 
-    pub fn from_str(value: &str) -> ApplyId {
-        let aid = ULID::from_str(&value).unwrap();
-        ApplyId(aid)
+impl CancelReason {
+    /// 理由が空白のみ, または[MAX_CANCEL_REASON_LENGTH]文字を超える場合はエラーを返す
+    pub fn new(reason: &str) -> Result<CancelReason> {
+        if reason.trim().is_empty() {
+            return Err(ApplyError::CancelReasonEmpty.into());
+        }
+        let length: usize = reason.chars().count();
+        if length > MAX_CANCEL_REASON_LENGTH {
+            return Err(ApplyError::CancelReasonTooLong {
+                max: MAX_CANCEL_REASON_LENGTH,
+                actual: length,
+            }
+            .into());
+        }
+        Ok(CancelReason(reason.to_string()))
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for CancelReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -49,7 +280,7 @@ impl Apply {
             user_id,
             applied_at,
             as_group,
-            allowed_status: 0,
+            allowed_status: ApplyStatus::Pending,
             decided_at: None,
             is_sent: false
         }
@@ -62,3 +293,171 @@ impl std::fmt::Display for ApplyId {
         write!(f, "{}", self.0.to_string())
     }
 }
+
+#[cfg(test)]
+mod test_apply_status {
+    use super::*;
+
+    #[test]
+    fn from_u8() {
+        assert_eq!(ApplyStatus::from_u8(0).unwrap(), ApplyStatus::Pending);
+        assert_eq!(ApplyStatus::from_u8(1).unwrap(), ApplyStatus::Approved);
+        assert_eq!(ApplyStatus::from_u8(2).unwrap(), ApplyStatus::Rejected);
+        assert_eq!(ApplyStatus::from_u8(3).unwrap(), ApplyStatus::Cancelled);
+        assert_eq!(ApplyStatus::from_u8(4).is_err(), true);
+    }
+
+    #[test]
+    fn pending_to_decided() {
+        assert_eq!(ApplyStatus::Pending.approve().unwrap(), ApplyStatus::Approved);
+        assert_eq!(ApplyStatus::Pending.reject().unwrap(), ApplyStatus::Rejected);
+        assert_eq!(ApplyStatus::Pending.cancel().unwrap(), ApplyStatus::Cancelled);
+    }
+
+    #[test]
+    fn approved_to_cancelled() {
+        assert_eq!(ApplyStatus::Approved.cancel().unwrap(), ApplyStatus::Cancelled);
+    }
+
+    #[test]
+    fn rejected_to_approved() {
+        let error = ApplyStatus::Rejected.approve().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ApplyError>(),
+            Some(&ApplyError::InvalidTransition {
+                from: ApplyStatus::Rejected,
+                to: ApplyStatus::Approved
+            })
+        );
+    }
+
+    #[test]
+    fn approved_to_rejected() {
+        assert_eq!(ApplyStatus::Approved.reject().is_err(), true);
+    }
+
+    #[test]
+    fn cancelled_is_terminal() {
+        assert_eq!(ApplyStatus::Cancelled.approve().is_err(), true);
+        assert_eq!(ApplyStatus::Cancelled.reject().is_err(), true);
+        assert_eq!(ApplyStatus::Cancelled.cancel().is_err(), true);
+    }
+
+    #[test]
+    fn same_status() {
+        assert_eq!(ApplyStatus::Approved.approve().is_err(), true);
+        assert_eq!(ApplyStatus::Pending.transition_to(ApplyStatus::Pending).is_err(), true);
+    }
+
+    #[test]
+    fn capacity_ok() {
+        assert_eq!(ensure_capacity(5, 3, 2).is_ok(), true);
+    }
+
+    #[test]
+    fn capacity_exceeded() {
+        let error = ensure_capacity(5, 4, 2).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ApplyError>(),
+            Some(&ApplyError::CapacityExceeded {
+                recruited_num: 5,
+                approved_num: 4,
+                requested_num: 2
+            })
+        );
+    }
+
+    fn start_at() -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn cancel_pending_until_start() {
+        let now = start_at() - Duration::hours(1);
+        assert_eq!(
+            ensure_cancellable(ApplyStatus::Pending, start_at(), now).unwrap(),
+            ApplyStatus::Cancelled
+        );
+        let error = ensure_cancellable(ApplyStatus::Pending, start_at(), start_at()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ApplyError>(),
+            Some(&ApplyError::CancellationClosed { deadline: start_at() })
+        );
+    }
+
+    #[test]
+    fn cancel_approved_before_cutoff() {
+        let deadline = start_at() - Duration::hours(APPROVED_CANCEL_CUTOFF_HOURS);
+        assert_eq!(
+            ensure_cancellable(ApplyStatus::Approved, start_at(), deadline - Duration::minutes(1))
+                .unwrap(),
+            ApplyStatus::Cancelled
+        );
+        let error = ensure_cancellable(ApplyStatus::Approved, start_at(), deadline).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ApplyError>(),
+            Some(&ApplyError::CancellationClosed { deadline })
+        );
+    }
+
+    #[test]
+    fn cancel_rejected() {
+        let now = start_at() - Duration::days(7);
+        let error = ensure_cancellable(ApplyStatus::Rejected, start_at(), now).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ApplyError>(),
+            Some(&ApplyError::InvalidTransition {
+                from: ApplyStatus::Rejected,
+                to: ApplyStatus::Cancelled
+            })
+        );
+    }
+
+    #[test]
+    fn cancel_reason() {
+        assert_eq!(CancelReason::new("体調不良のため").unwrap().to_string(), "体調不良のため");
+        assert!(CancelReason::new(" ").is_err());
+        assert!(CancelReason::new(&"あ".repeat(MAX_CANCEL_REASON_LENGTH)).is_ok());
+        assert!(CancelReason::new(&"あ".repeat(MAX_CANCEL_REASON_LENGTH + 1)).is_err());
+    }
+
+    fn waitlist(headcounts: &[u32]) -> (Vec<ApplyId>, Vec<WaitlistEntry>) {
+        let aids: Vec<ApplyId> = headcounts.iter().map(|_| ApplyId::new()).collect();
+        let entries = aids
+            .iter()
+            .zip(headcounts)
+            .map(|(aid, headcount)| WaitlistEntry {
+                aid: aid.clone(),
+                headcount: *headcount,
+            })
+            .collect();
+        (aids, entries)
+    }
+
+    fn ids(aids: &[ApplyId]) -> Vec<String> {
+        aids.iter().map(|aid| aid.to_string()).collect()
+    }
+
+    #[test]
+    fn promote_in_applied_order() {
+        let (aids, entries) = waitlist(&[1, 1, 1]);
+        assert_eq!(ids(&select_promotions(5, 3, &entries)), ids(&aids[..2]));
+    }
+
+    #[test]
+    fn promote_skips_group_that_does_not_fit() {
+        let (aids, entries) = waitlist(&[3, 1, 2]);
+        assert_eq!(
+            ids(&select_promotions(5, 3, &entries)),
+            ids(&[aids[1].clone()])
+        );
+    }
+
+    #[test]
+    fn promote_nothing_when_full() {
+        let (_, entries) = waitlist(&[1]);
+        assert!(select_promotions(5, 5, &entries).is_empty());
+        assert!(select_promotions(5, 6, &entries).is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid_generator_rs::{ULIDGenerator, ULID};
+
+/// 参加者がチェックインできるようになる, ボランティアの開始日時の何分前か
+pub const CHECK_IN_OPENS_BEFORE_MINUTES: i64 = 60;
+
+/// チェックインコードの有効期間(分)
+pub const CHECK_IN_CODE_TTL_MINUTES: i64 = 30;
+
+/// チェックインコードの文字数
+pub const CHECK_IN_CODE_LENGTH: usize = 8;
+
+/// 応募・集団応募のメンバーの出欠
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttendanceStatus {
+    /// 未記録
+    Unrecorded = 0,
+    /// 出席
+    Attended = 1,
+    /// 欠席(無断欠席を含む)
+    NoShow = 2,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AttendanceError {
+    #[error("attendance_status not found")]
+    StatusNotFound,
+    #[error("only approved applies can be checked in")]
+    NotApproved,
+    #[error("check-in has not opened yet (opens at {opens_at})")]
+    CheckInNotOpen { opens_at: DateTime<Utc> },
+    #[error("check-in is closed")]
+    CheckInClosed,
+    #[error("check-in code is invalid")]
+    InvalidCode,
+    #[error("check-in code is expired")]
+    CodeExpired,
+    #[error("no-show cannot be recorded before the volunteer starts")]
+    NoShowBeforeStart,
+    #[error("members can be recorded only for group applies")]
+    NotGroupApply,
+    #[error("group participant not found (serial: {serial})")]
+    MemberNotFound { serial: u16 },
+}
+
+impl AttendanceStatus {
+    pub fn from_u8(value: u8) -> Result<AttendanceStatus> {
+        match value {
+            0 => Ok(AttendanceStatus::Unrecorded),
+            1 => Ok(AttendanceStatus::Attended),
+            2 => Ok(AttendanceStatus::NoShow),
+            _ => Err(AttendanceError::StatusNotFound.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for AttendanceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            AttendanceStatus::Unrecorded => "Unrecorded",
+            AttendanceStatus::Attended => "Attended",
+            AttendanceStatus::NoShow => "NoShow",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// 集団応募のメンバーの出欠
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberAttendance {
+    /// 集団応募のメンバーのシリアル
+    pub serial: u16,
+    pub status: AttendanceStatus,
+}
+
+/// 団体が記録する出欠
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttendanceRecord {
+    /// 応募単位で記録する
+    Apply(AttendanceStatus),
+    /// 集団応募のメンバーごとに記録する. 応募の出欠はメンバーの出欠から決める
+    Members(Vec<MemberAttendance>),
+}
+
+impl AttendanceRecord {
+    /// 欠席を含む記録か
+    pub fn has_no_show(&self) -> bool {
+        match self {
+            AttendanceRecord::Apply(status) => *status == AttendanceStatus::NoShow,
+            AttendanceRecord::Members(members) => members
+                .iter()
+                .any(|member| member.status == AttendanceStatus::NoShow),
+        }
+    }
+}
+
+/// 集団応募のメンバーの出欠から応募の出欠を決める
+///
+/// 1人でも出席していれば出席, 全員が欠席であれば欠席, それ以外は未記録とする
+pub fn summarize(members: &[AttendanceStatus]) -> AttendanceStatus {
+    if members.contains(&AttendanceStatus::Attended) {
+        AttendanceStatus::Attended
+    } else if !members.is_empty() && members.iter().all(|s| *s == AttendanceStatus::NoShow) {
+        AttendanceStatus::NoShow
+    } else {
+        AttendanceStatus::Unrecorded
+    }
+}
+
+/// 参加者がチェックインできる期間か確認する
+///
+/// ボランティアの開始日時の[CHECK_IN_OPENS_BEFORE_MINUTES]分前から終了日時まで受け付ける
+pub fn ensure_check_in_open(
+    start_at: DateTime<Utc>,
+    finish_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let opens_at: DateTime<Utc> = start_at - Duration::minutes(CHECK_IN_OPENS_BEFORE_MINUTES);
+    if now < opens_at {
+        Err(AttendanceError::CheckInNotOpen { opens_at }.into())
+    } else if now >= finish_at {
+        Err(AttendanceError::CheckInClosed.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// 団体が出欠を記録できるか確認する. 欠席はボランティアの開始後のみ記録できる
+pub fn ensure_recordable(
+    record: &AttendanceRecord,
+    start_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if record.has_no_show() && now < start_at {
+        Err(AttendanceError::NoShowBeforeStart.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// 参加者がチェックインに使用するワンタイムコード
+///
+/// 団体がボランティアの会場でコードまたはQRコードを提示し, 参加者が入力・読み取りする
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckInCode {
+    pub code: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl CheckInCode {
+    /// コードを発行する. コードはULIDのランダムな部分から作成する
+    pub fn new(now: DateTime<Utc>) -> CheckInCode {
+        let mut generator: ULIDGenerator = ULIDGenerator::new();
+        let value: ULID = generator.generate().unwrap();
+        let value: String = value.to_string();
+        CheckInCode {
+            code: value[value.len() - CHECK_IN_CODE_LENGTH..].to_string(),
+            issued_at: now,
+            expires_at: now + Duration::minutes(CHECK_IN_CODE_TTL_MINUTES),
+        }
+    }
+
+    /// 入力されたコードを確認する. 大文字・小文字と前後の空白は区別しない
+    pub fn verify(&self, input: &str, now: DateTime<Utc>) -> Result<()> {
+        if !self.code.eq_ignore_ascii_case(input.trim()) {
+            Err(AttendanceError::InvalidCode.into())
+        } else if now >= self.expires_at {
+            Err(AttendanceError::CodeExpired.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_attendance {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    fn start_at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap()
+    }
+
+    fn finish_at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn from_u8() {
+        for value in 0..3 {
+            assert_eq!(AttendanceStatus::from_u8(value).unwrap().to_u8(), value);
+        }
+        assert!(AttendanceStatus::from_u8(3).is_err());
+    }
+
+    #[test]
+    fn summarize_members() {
+        use AttendanceStatus::*;
+        assert_eq!(summarize(&[NoShow, Attended, Unrecorded]), Attended);
+        assert_eq!(summarize(&[NoShow, NoShow]), NoShow);
+        assert_eq!(summarize(&[NoShow, Unrecorded]), Unrecorded);
+        assert_eq!(summarize(&[]), Unrecorded);
+    }
+
+    #[test]
+    fn check_in_window() {
+        let opens_at = start_at() - Duration::minutes(CHECK_IN_OPENS_BEFORE_MINUTES);
+        assert!(ensure_check_in_open(start_at(), finish_at(), opens_at).is_ok());
+        assert!(
+            ensure_check_in_open(start_at(), finish_at(), finish_at() - Duration::seconds(1))
+                .is_ok()
+        );
+
+        let error = ensure_check_in_open(start_at(), finish_at(), opens_at - Duration::seconds(1))
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AttendanceError>(),
+            Some(&AttendanceError::CheckInNotOpen { opens_at })
+        );
+        let error = ensure_check_in_open(start_at(), finish_at(), finish_at()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AttendanceError>(),
+            Some(&AttendanceError::CheckInClosed)
+        );
+    }
+
+    #[test]
+    fn no_show_after_start() {
+        let before = start_at() - Duration::minutes(10);
+        let no_show = AttendanceRecord::Members(vec![
+            MemberAttendance {
+                serial: 0,
+                status: AttendanceStatus::Attended,
+            },
+            MemberAttendance {
+                serial: 1,
+                status: AttendanceStatus::NoShow,
+            },
+        ]);
+        assert!(ensure_recordable(
+            &AttendanceRecord::Apply(AttendanceStatus::Attended),
+            start_at(),
+            before
+        )
+        .is_ok());
+        assert!(ensure_recordable(&no_show, start_at(), start_at()).is_ok());
+        let error = ensure_recordable(&no_show, start_at(), before).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AttendanceError>(),
+            Some(&AttendanceError::NoShowBeforeStart)
+        );
+    }
+
+    #[test]
+    fn verify_code() {
+        let code = CheckInCode::new(start_at());
+        assert_eq!(code.code.len(), CHECK_IN_CODE_LENGTH);
+        assert!(code
+            .verify(&format!(" {} ", code.code.to_lowercase()), start_at())
+            .is_ok());
+
+        let error = code.verify("00000000", start_at()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AttendanceError>(),
+            Some(&AttendanceError::InvalidCode)
+        );
+        let error = code.verify(&code.code, code.expires_at).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AttendanceError>(),
+            Some(&AttendanceError::CodeExpired)
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid_generator_rs::{ULIDGenerator, ULID};
+
+/// 参加者の毎週の参加可能な時間帯
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Availability {
+    /// 曜日. `Mon`のように英語の3文字で保存する
+    pub weekday: Weekday,
+    pub start_time: NaiveTime,
+    pub finish_time: NaiveTime,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AvailabilityError {
+    #[error("start_time must be earlier than finish_time")]
+    InvalidTimeRange,
+    #[error("availability overlaps with another availability on {0}")]
+    Overlapped(Weekday),
+    #[error("availability not found")]
+    NotFound,
+}
+
+impl Availability {
+    pub fn new(
+        weekday: Weekday,
+        start_time: NaiveTime,
+        finish_time: NaiveTime,
+    ) -> Result<Availability> {
+        if start_time >= finish_time {
+            return Err(AvailabilityError::InvalidTimeRange.into());
+        }
+        Ok(Availability {
+            weekday,
+            start_time,
+            finish_time,
+        })
+    }
+
+    /// 同じ曜日で時間帯が重なっているか. 終了時刻と開始時刻が等しい場合は重ならない
+    pub fn overlaps(&self, other: &Availability) -> bool {
+        self.weekday == other.weekday
+            && self.start_time < other.finish_time
+            && other.start_time < self.finish_time
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityId(pub ULID);
+
+impl AvailabilityId {
+    pub fn new() -> AvailabilityId {
+        let mut generator: ULIDGenerator = ULIDGenerator::new();
+        let value: ULID = generator.generate().unwrap();
+        AvailabilityId(value)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AvailabilityIdError {
+    #[error("invalid did")]
+    Invalid,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for AvailabilityId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(AvailabilityId(value)),
+            Err(_) => Err(AvailabilityIdError::Invalid.into()),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for AvailabilityId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod test_availability {
+    use super::*;
+
+    fn time(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn new() {
+        assert!(Availability::new(Weekday::Mon, time(9), time(12)).is_ok());
+        let availability = Availability::new(Weekday::Mon, time(12), time(12));
+        assert_eq!(
+            availability.unwrap_err().downcast_ref::<AvailabilityError>(),
+            Some(&AvailabilityError::InvalidTimeRange)
+        );
+    }
+
+    #[test]
+    fn overlaps() {
+        let morning = Availability::new(Weekday::Sat, time(9), time(12)).unwrap();
+        let noon = Availability::new(Weekday::Sat, time(11), time(14)).unwrap();
+        let afternoon = Availability::new(Weekday::Sat, time(12), time(17)).unwrap();
+        let sunday = Availability::new(Weekday::Sun, time(9), time(12)).unwrap();
+        assert!(morning.overlaps(&noon));
+        assert!(noon.overlaps(&afternoon));
+        assert!(!morning.overlaps(&afternoon));
+        assert!(!morning.overlaps(&sunday));
+    }
+
+    #[test]
+    fn weekday() {
+        assert_eq!(Weekday::Mon.to_string(), "Mon");
+        assert_eq!(Weekday::from_str("Sat"), Ok(Weekday::Sat));
+    }
+}
+
+#[cfg(test)]
+mod test_availability_id {
+    use super::*;
+
+    #[test]
+    fn ok() {
+        let did = AvailabilityId::from_str("01H5Z3A2Q4Y8X7W6V5T4S3R2P1").unwrap();
+        assert_eq!(did.to_string(), "01H5Z3A2Q4Y8X7W6V5T4S3R2P1");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(AvailabilityId::from_str("invalid").is_err());
+    }
+}
@@ -0,0 +1,308 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid_generator_rs::{ULIDGenerator, ULID};
+
+use crate::model::{
+    plan::{BillingPeriod, Plan, PlanError},
+    user_account::user_id::UserId,
+};
+
+/// 団体のプランの契約状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlanStatus {
+    /// 無料体験中
+    Trialing = 0,
+    /// 契約中
+    Active = 1,
+    /// 支払いに失敗し, 未払いの請求がある
+    PastDue = 2,
+}
+
+/// 請求書の支払い状態
+///
+/// 状態遷移は以下のみ許可する
+/// - 未払い -> 支払済 / 支払失敗
+/// - 支払失敗 -> 支払済(決済サービスでの再請求)
+/// - 支払済 -> 返金済
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvoiceStatus {
+    /// 未払い
+    Pending = 0,
+    /// 支払済
+    Paid = 1,
+    /// 支払失敗
+    Failed = 2,
+    /// 返金済
+    Refunded = 3,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BillingError {
+    #[error("plan status not found")]
+    PlanStatusNotFound,
+    #[error("invoice status not found")]
+    InvoiceStatusNotFound,
+    #[error("cannot change invoice status from {from} to {to}")]
+    InvalidInvoiceTransition {
+        from: InvoiceStatus,
+        to: InvoiceStatus,
+    },
+}
+
+impl PlanStatus {
+    pub fn from_u8(value: u8) -> Result<PlanStatus> {
+        match value {
+            0 => Ok(PlanStatus::Trialing),
+            1 => Ok(PlanStatus::Active),
+            2 => Ok(PlanStatus::PastDue),
+            _ => Err(BillingError::PlanStatusNotFound.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for PlanStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlanStatus::Trialing => write!(f, "Trialing"),
+            PlanStatus::Active => write!(f, "Active"),
+            PlanStatus::PastDue => write!(f, "PastDue"),
+        }
+    }
+}
+
+impl InvoiceStatus {
+    pub fn from_u8(value: u8) -> Result<InvoiceStatus> {
+        match value {
+            0 => Ok(InvoiceStatus::Pending),
+            1 => Ok(InvoiceStatus::Paid),
+            2 => Ok(InvoiceStatus::Failed),
+            3 => Ok(InvoiceStatus::Refunded),
+            _ => Err(BillingError::InvoiceStatusNotFound.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// `next`に遷移できるか
+    pub fn can_transition_to(&self, next: InvoiceStatus) -> bool {
+        matches!(
+            (self, next),
+            (InvoiceStatus::Pending, InvoiceStatus::Paid)
+                | (InvoiceStatus::Pending, InvoiceStatus::Failed)
+                | (InvoiceStatus::Failed, InvoiceStatus::Paid)
+                | (InvoiceStatus::Paid, InvoiceStatus::Refunded)
+        )
+    }
+
+    /// `next`に遷移する. 遷移できない場合はエラーを返す
+    pub fn transition_to(&self, next: InvoiceStatus) -> Result<InvoiceStatus> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(BillingError::InvalidInvoiceTransition {
+                from: *self,
+                to: next,
+            }
+            .into())
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for InvoiceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InvoiceStatus::Pending => write!(f, "Pending"),
+            InvoiceStatus::Paid => write!(f, "Paid"),
+            InvoiceStatus::Failed => write!(f, "Failed"),
+            InvoiceStatus::Refunded => write!(f, "Refunded"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvoiceId(pub ULID);
+
+impl InvoiceId {
+    pub fn new() -> InvoiceId {
+        let mut generator: ULIDGenerator = ULIDGenerator::new();
+        let value: ULID = generator.generate().unwrap();
+        InvoiceId(value)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum InvoiceIdError {
+    #[error("invalid iid")]
+    Invalid,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for InvoiceId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(InvoiceId(value)),
+            Err(_) => Err(InvoiceIdError::Invalid.into()),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for InvoiceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// 請求書
+#[derive(Debug, Clone)]
+pub struct Invoice {
+    pub iid: InvoiceId,
+    pub gid: UserId,
+    pub plan: Plan,
+    /// 請求額(円, 税込)
+    pub amount: u32,
+    pub period: BillingPeriod,
+    pub status: InvoiceStatus,
+    pub issued_at: DateTime<Utc>,
+}
+
+impl Invoice {
+    /// `now`を含む請求期間のプランの料金を請求する
+    pub fn new(gid: UserId, plan: Plan, now: DateTime<Utc>) -> Invoice {
+        Invoice {
+            iid: InvoiceId::new(),
+            gid,
+            plan,
+            amount: plan.monthly_price(),
+            period: BillingPeriod::containing(now),
+            status: InvoiceStatus::Pending,
+            issued_at: now,
+        }
+    }
+}
+
+/// 団体の現在のプラン
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentPlan {
+    pub plan: Plan,
+    pub status: PlanStatus,
+    /// 過去に無料体験を利用したか
+    pub has_trialed: bool,
+}
+
+/// プラン変更の内容
+#[derive(Debug, Clone)]
+pub enum PlanChange {
+    /// 無料プランに変更する. 請求しない
+    Downgrade,
+    /// 無料体験を開始する. 無料体験の終了時に請求する
+    Trial { trial_ends_at: DateTime<Utc> },
+    /// 請求し, 支払いに成功した場合に変更する
+    Charge(Invoice),
+}
+
+impl PlanChange {
+    /// 現在のプランから`next`に変更する場合の内容を決める
+    ///
+    /// 無料体験は団体ごとに1回のみ利用できる
+    pub fn decide(
+        gid: &UserId,
+        current: &CurrentPlan,
+        next: Plan,
+        now: DateTime<Utc>,
+    ) -> Result<PlanChange, PlanError> {
+        if current.plan == next {
+            return Err(PlanError::SamePlan(next));
+        }
+        if !next.is_paid() {
+            return Ok(PlanChange::Downgrade);
+        }
+        if !current.has_trialed && next.trial_days() > 0 {
+            return Ok(PlanChange::Trial {
+                trial_ends_at: now + Duration::days(next.trial_days() as i64),
+            });
+        }
+        Ok(PlanChange::Charge(Invoice::new(gid.clone(), next, now)))
+    }
+}
+
+#[cfg(test)]
+mod test_billing {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    fn gid() -> UserId {
+        UserId::new("abcdefghijklmnopqrstuvwxyz12").unwrap()
+    }
+
+    #[test]
+    fn invoice_transition() {
+        assert!(InvoiceStatus::Pending.transition_to(InvoiceStatus::Paid).is_ok());
+        assert!(InvoiceStatus::Failed.transition_to(InvoiceStatus::Paid).is_ok());
+        assert!(InvoiceStatus::Paid.transition_to(InvoiceStatus::Refunded).is_ok());
+        assert!(InvoiceStatus::Paid.transition_to(InvoiceStatus::Failed).is_err());
+        assert!(InvoiceStatus::Refunded.transition_to(InvoiceStatus::Paid).is_err());
+        for value in 0..4 {
+            assert_eq!(InvoiceStatus::from_u8(value).unwrap().to_u8(), value);
+        }
+        assert!(InvoiceStatus::from_u8(4).is_err());
+    }
+
+    #[test]
+    fn decide_plan_change() {
+        let now = Utc.with_ymd_and_hms(2024, 2, 10, 0, 0, 0).unwrap();
+        let free = CurrentPlan {
+            plan: Plan::Free,
+            status: PlanStatus::Active,
+            has_trialed: false,
+        };
+
+        match PlanChange::decide(&gid(), &free, Plan::Standard, now).unwrap() {
+            PlanChange::Trial { trial_ends_at } => {
+                assert_eq!(trial_ends_at, now + Duration::days(14))
+            }
+            change => panic!("unexpected change: {:?}", change),
+        }
+
+        let trialed = CurrentPlan {
+            has_trialed: true,
+            ..free
+        };
+        match PlanChange::decide(&gid(), &trialed, Plan::Premium, now).unwrap() {
+            PlanChange::Charge(invoice) => {
+                assert_eq!(invoice.amount, Plan::Premium.monthly_price());
+                assert_eq!(invoice.period, BillingPeriod::containing(now));
+                assert_eq!(invoice.status, InvoiceStatus::Pending);
+            }
+            change => panic!("unexpected change: {:?}", change),
+        }
+
+        let standard = CurrentPlan {
+            plan: Plan::Standard,
+            ..trialed
+        };
+        assert!(matches!(
+            PlanChange::decide(&gid(), &standard, Plan::Free, now),
+            Ok(PlanChange::Downgrade)
+        ));
+        assert_eq!(
+            PlanChange::decide(&gid(), &standard, Plan::Standard, now).unwrap_err(),
+            PlanError::SamePlan(Plan::Standard)
+        );
+    }
+}
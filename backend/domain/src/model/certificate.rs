@@ -0,0 +1,218 @@
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::model::attendance::AttendanceStatus;
+
+/// 証明書の日時を表示するタイムゾーン(日本時間)の UTC からの差(秒)
+const CERTIFICATE_UTC_OFFSET_SECONDS: i32 = 9 * 60 * 60;
+
+/// 証明書に記載する活動
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertifiedActivity {
+    /// 証明書ID. 出席した応募の応募ID
+    pub certificate_id: String,
+    /// 参加者ID
+    pub uid: String,
+    /// 団体ID
+    pub gid: String,
+    /// ボランティアID
+    pub vid: String,
+    pub participant_name: String,
+    pub group_name: String,
+    /// 団体の代表者名
+    pub representative_name: String,
+    pub volunteer_title: String,
+    pub start_at: DateTime<Utc>,
+    pub finish_at: DateTime<Utc>,
+}
+
+/// ボランティア活動証明書
+///
+/// 出席した応募ごとに発行する
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Certificate {
+    pub activity: CertifiedActivity,
+    /// 発行日(日本時間)
+    pub issued_on: NaiveDate,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CertificateError {
+    #[error("certificate can be issued only for attended applies")]
+    NotAttended,
+    #[error("certificate can be issued after the volunteer finishes")]
+    NotFinished,
+}
+
+/// 日本時間の日時に変換する
+pub fn to_jst(at: DateTime<Utc>) -> DateTime<FixedOffset> {
+    at.with_timezone(&FixedOffset::east_opt(CERTIFICATE_UTC_OFFSET_SECONDS).unwrap())
+}
+
+/// ボランティアの活動時間(分). 終了日時が開始日時以前の場合は0
+pub fn volunteer_minutes(start_at: DateTime<Utc>, finish_at: DateTime<Utc>) -> i64 {
+    std::cmp::max((finish_at - start_at).num_minutes(), 0)
+}
+
+/// 活動時間を`3時間30分`の形式の文字列にする
+pub fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (hours, 0) => format!("{}時間", hours),
+        (0, minutes) => format!("{}分", minutes),
+        (hours, minutes) => format!("{}時間{}分", hours, minutes),
+    }
+}
+
+impl Certificate {
+    /// 証明書を発行する
+    ///
+    /// 出席していない場合, ボランティアが終了していない場合はエラーを返す
+    pub fn issue(
+        activity: CertifiedActivity,
+        attendance: AttendanceStatus,
+        now: DateTime<Utc>,
+    ) -> Result<Certificate> {
+        if attendance != AttendanceStatus::Attended {
+            return Err(CertificateError::NotAttended.into());
+        }
+        if now < activity.finish_at {
+            return Err(CertificateError::NotFinished.into());
+        }
+
+        Ok(Certificate {
+            activity,
+            issued_on: to_jst(now).date_naive(),
+        })
+    }
+
+    /// 活動時間(分)
+    pub fn minutes(&self) -> i64 {
+        volunteer_minutes(self.activity.start_at, self.activity.finish_at)
+    }
+
+    /// 署名する内容
+    ///
+    /// アカウント名やボランティア名は後から変更できるため, 変わらないIDと日時・活動時間のみ含める.
+    /// 同じ活動の証明書は発行日によらず同じ署名になるよう, 発行日も含めない
+    pub fn signing_payload(&self) -> String {
+        [
+            self.activity.certificate_id.clone(),
+            self.activity.uid.clone(),
+            self.activity.gid.clone(),
+            self.activity.vid.clone(),
+            self.activity.start_at.to_rfc3339(),
+            self.activity.finish_at.to_rfc3339(),
+            self.minutes().to_string(),
+        ]
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test_certificate {
+    use super::*;
+
+    use chrono::{Duration, TimeZone};
+
+    fn start_at() -> DateTime<Utc> {
+        // 日本時間 2024-03-10 09:00
+        Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap()
+    }
+
+    fn activity() -> CertifiedActivity {
+        CertifiedActivity {
+            certificate_id: "01HPAY3C1A1Z1XQ6Z2J6S0V7KX".to_string(),
+            uid: "participant_account000000000".to_string(),
+            gid: "group_account000000000000000".to_string(),
+            vid: "01HKXVVVKBR6G8240N7HWSPR7M".to_string(),
+            participant_name: "山田太郎".to_string(),
+            group_name: "ボランティア団体".to_string(),
+            representative_name: "鈴木花子".to_string(),
+            volunteer_title: "公園の清掃".to_string(),
+            start_at: start_at(),
+            finish_at: start_at() + Duration::minutes(210),
+        }
+    }
+
+    fn issue(attendance: AttendanceStatus, now: DateTime<Utc>) -> Result<Certificate> {
+        Certificate::issue(activity(), attendance, now)
+    }
+
+    #[test]
+    fn minutes() {
+        assert_eq!(
+            volunteer_minutes(start_at(), start_at() + Duration::minutes(210)),
+            210
+        );
+        assert_eq!(
+            volunteer_minutes(start_at(), start_at() - Duration::minutes(1)),
+            0
+        );
+        assert_eq!(format_minutes(210), "3時間30分");
+        assert_eq!(format_minutes(120), "2時間");
+        assert_eq!(format_minutes(45), "45分");
+    }
+
+    #[test]
+    fn issue_attended() {
+        // 日本時間の日付で発行日を決める
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 16, 0, 0).unwrap();
+        let certificate = issue(AttendanceStatus::Attended, now).unwrap();
+        assert_eq!(certificate.minutes(), 210);
+        assert_eq!(
+            certificate.issued_on,
+            NaiveDate::from_ymd_opt(2024, 3, 11).unwrap()
+        );
+    }
+
+    #[test]
+    fn issue_not_attended_or_not_finished() {
+        let finished = start_at() + Duration::days(1);
+        let error = issue(AttendanceStatus::NoShow, finished).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CertificateError>(),
+            Some(&CertificateError::NotAttended)
+        );
+        let error = issue(AttendanceStatus::Attended, start_at()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CertificateError>(),
+            Some(&CertificateError::NotFinished)
+        );
+    }
+
+    #[test]
+    fn signing_payload_ignores_issued_on() {
+        let first = issue(AttendanceStatus::Attended, start_at() + Duration::days(1)).unwrap();
+        let second = issue(AttendanceStatus::Attended, start_at() + Duration::days(30)).unwrap();
+        assert_ne!(first.issued_on, second.issued_on);
+        assert_eq!(first.signing_payload(), second.signing_payload());
+    }
+
+    #[test]
+    fn signing_payload_ignores_names() {
+        let now = start_at() + Duration::days(1);
+        let renamed = CertifiedActivity {
+            participant_name: "山田次郎".to_string(),
+            group_name: "清掃の会".to_string(),
+            representative_name: "佐藤一郎".to_string(),
+            volunteer_title: "河川敷の清掃".to_string(),
+            ..activity()
+        };
+        let renamed = Certificate::issue(renamed, AttendanceStatus::Attended, now).unwrap();
+        let original = issue(AttendanceStatus::Attended, now).unwrap();
+        assert_eq!(original.signing_payload(), renamed.signing_payload());
+
+        let other_participant = CertifiedActivity {
+            uid: "participant_account000000001".to_string(),
+            ..activity()
+        };
+        let other_participant =
+            Certificate::issue(other_participant, AttendanceStatus::Attended, now).unwrap();
+        assert_ne!(
+            original.signing_payload(),
+            other_participant.signing_payload()
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::apply::ApplyStatus;
+
+/// Redisを使わない場合に, 出来事を配信用のテーブルに残しておく時間 (秒)
+pub const ACTIVITY_EVENT_RETENTION_SECS: i64 = 60 * 60;
+
+/// 応募・スカウトの出来事. 書き込みのコミット後に, 関係するユーザーに配信する
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActivityEvent {
+    /// 参加者がスカウトを受けた
+    ScoutReceived {
+        sid: String,
+        vid: String,
+        uid: String,
+        gid: String,
+    },
+    /// 参加者がボランティアに応募した
+    ApplyReceived {
+        aid: String,
+        vid: String,
+        uid: String,
+        gid: String,
+    },
+    /// 参加者が応募を取り消した
+    ApplyCancelled {
+        aid: String,
+        vid: String,
+        uid: String,
+        gid: String,
+    },
+    /// 団体が応募を承認・棄却した
+    ApplyDecided {
+        aid: String,
+        vid: String,
+        uid: String,
+        gid: String,
+        status: ApplyStatus,
+    },
+}
+
+impl ActivityEvent {
+    /// 出来事を配信するユーザーのid
+    ///
+    /// スカウトは参加者に, 応募・取消は団体に, 承認・棄却は参加者と団体の両方に配信する
+    pub fn recipients(&self) -> Vec<&str> {
+        match self {
+            ActivityEvent::ScoutReceived { uid, .. } => vec![uid],
+            ActivityEvent::ApplyReceived { gid, .. }
+            | ActivityEvent::ApplyCancelled { gid, .. } => {
+                vec![gid]
+            }
+            ActivityEvent::ApplyDecided { uid, gid, .. } => vec![uid, gid],
+        }
+    }
+
+    /// `uid`のユーザーに配信する出来事か
+    pub fn is_addressed_to(&self, uid: &str) -> bool {
+        self.recipients().contains(&uid)
+    }
+}
+
+/// ユーザーごとの配信チャンネル名
+pub fn activity_channel(uid: &str) -> String {
+    format!("activity:{}", uid)
+}
+
+#[cfg(test)]
+mod test_activity_event {
+    use super::*;
+
+    fn decided(status: ApplyStatus) -> ActivityEvent {
+        ActivityEvent::ApplyDecided {
+            aid: "01HKXZS5TT1GMJD8PVC5RKJH42".to_string(),
+            vid: "01HKXVVVKBR6G8240N7HWSPR7M".to_string(),
+            uid: "participant_account000000000".to_string(),
+            gid: "group_account000000000000000".to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn recipients() {
+        let event = decided(ApplyStatus::Approved);
+        assert!(event.is_addressed_to("participant_account000000000"));
+        assert!(event.is_addressed_to("group_account000000000000000"));
+        assert!(!event.is_addressed_to("participant_account000000001"));
+
+        let event = ActivityEvent::ApplyCancelled {
+            aid: "01HKXZS5TT1GMJD8PVC5RKJH42".to_string(),
+            vid: "01HKXVVVKBR6G8240N7HWSPR7M".to_string(),
+            uid: "participant_account000000000".to_string(),
+            gid: "group_account000000000000000".to_string(),
+        };
+        assert_eq!(event.recipients(), vec!["group_account000000000000000"]);
+    }
+
+    #[test]
+    fn serialize() {
+        let json = serde_json::to_string(&decided(ApplyStatus::Rejected)).unwrap();
+        assert!(json.contains(r#""type":"apply_decided""#));
+        assert!(json.contains(r#""status":"Rejected""#));
+        assert_eq!(
+            serde_json::from_str::<ActivityEvent>(&json).unwrap(),
+            decided(ApplyStatus::Rejected)
+        );
+        assert_eq!(
+            activity_channel("participant_account000000000"),
+            "activity:participant_account000000000"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 地球の半径(km)
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// 緯度経度で表す地点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum GeoPointError {
+    #[error("latitude must be between -90 and 90")]
+    InvalidLatitude,
+    #[error("longitude must be between -180 and 180")]
+    InvalidLongitude,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> Result<GeoPoint> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(GeoPointError::InvalidLatitude.into());
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(GeoPointError::InvalidLongitude.into());
+        }
+        Ok(GeoPoint {
+            latitude,
+            longitude,
+        })
+    }
+
+    /// 2地点間の距離(km)を球面三角法(ハバーサイン公式)で求める
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let d_lat = (other.latitude - self.latitude).to_radians();
+        let d_lng = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2)
+            + self.latitude.to_radians().cos()
+                * other.latitude.to_radians().cos()
+                * (d_lng / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    /// 半径`radius_km`の円を囲む矩形の南西端と北東端を求める
+    ///
+    /// 空間インデックスで候補を絞り込むために使用する
+    pub fn bounding_box(&self, radius_km: f64) -> (GeoPoint, GeoPoint) {
+        let angle = radius_km / EARTH_RADIUS_KM;
+        let d_lat = angle.to_degrees();
+        // 円に接する経線までの経度差. 極を含む場合は全ての経度を対象とする
+        let ratio = angle.sin() / self.latitude.to_radians().cos();
+        let d_lng = if ratio < 1.0 {
+            ratio.asin().to_degrees()
+        } else {
+            180.0
+        };
+        (
+            GeoPoint {
+                latitude: (self.latitude - d_lat).max(-90.0),
+                longitude: (self.longitude - d_lng).max(-180.0),
+            },
+            GeoPoint {
+                latitude: (self.latitude + d_lat).min(90.0),
+                longitude: (self.longitude + d_lng).min(180.0),
+            },
+        )
+    }
+
+    /// 経度, 緯度の順のWKT(`POINT(経度 緯度)`)に変換する
+    pub fn to_wkt(&self) -> String {
+        format!("POINT({} {})", self.longitude, self.latitude)
+    }
+}
+
+#[cfg(test)]
+mod test_geo_point {
+    use super::*;
+
+    #[test]
+    fn new() {
+        assert!(GeoPoint::new(35.681236, 139.767125).is_ok());
+        assert!(GeoPoint::new(90.1, 139.767125).is_err());
+        assert!(GeoPoint::new(35.681236, -180.1).is_err());
+    }
+
+    #[test]
+    fn distance_km() {
+        // 東京駅から新大阪駅までは約400km
+        let tokyo = GeoPoint::new(35.681236, 139.767125).unwrap();
+        let shin_osaka = GeoPoint::new(34.733165, 135.500214).unwrap();
+        let distance = tokyo.distance_km(&shin_osaka);
+        assert!((395.0..405.0).contains(&distance));
+        assert_eq!(tokyo.distance_km(&tokyo), 0.0);
+    }
+
+    #[test]
+    fn bounding_box() {
+        let tokyo = GeoPoint::new(35.681236, 139.767125).unwrap();
+        let (south_west, north_east) = tokyo.bounding_box(10.0);
+        let north = GeoPoint::new(north_east.latitude, tokyo.longitude).unwrap();
+        let east = GeoPoint::new(tokyo.latitude, north_east.longitude).unwrap();
+        assert!((tokyo.distance_km(&north) - 10.0).abs() < 0.01);
+        assert!(tokyo.distance_km(&east) >= 10.0);
+        assert!(tokyo.distance_km(&east) < 10.1);
+        assert!(
+            south_west.latitude < tokyo.latitude && south_west.longitude < tokyo.longitude
+        );
+    }
+
+    #[test]
+    fn to_wkt() {
+        let point = GeoPoint::new(35.5, 139.25).unwrap();
+        assert_eq!(point.to_wkt(), "POINT(139.25 35.5)");
+    }
+}
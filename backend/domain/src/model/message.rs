@@ -0,0 +1,191 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid_generator_rs::{ULIDGenerator, ULID};
+
+use crate::model::{apply::ApplyId, scout::ScoutId, user_account::user_id::UserId};
+
+/// メッセージ本文の最大文字数
+pub const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// 団体と参加者のメッセージのやり取り. 応募またはスカウトごとに1つ作成する
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageThread {
+    pub tid: ThreadId,
+    pub subject: ThreadSubject,
+}
+
+/// スレッドの対象
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ThreadSubject {
+    Apply(ApplyId),
+    Scout(ScoutId),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadId(pub ULID);
+
+impl ThreadId {
+    pub fn new() -> ThreadId {
+        let mut generator: ULIDGenerator = ULIDGenerator::new();
+        let value: ULID = generator.generate().unwrap();
+        ThreadId(value)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ThreadIdError {
+    #[error("invalid tid")]
+    Invalid,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for ThreadId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(ThreadId(value)),
+            Err(_) => Err(ThreadIdError::Invalid.into()),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for ThreadId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0.to_string())
+    }
+}
+
+impl MessageThread {
+    pub fn new(subject: ThreadSubject) -> MessageThread {
+        MessageThread {
+            tid: ThreadId::new(),
+            subject,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub mid: MessageId,
+    pub sender: UserId,
+    pub body: String,
+    pub sent_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageId(pub ULID);
+
+impl MessageId {
+    pub fn new() -> MessageId {
+        let mut generator: ULIDGenerator = ULIDGenerator::new();
+        let value: ULID = generator.generate().unwrap();
+        MessageId(value)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MessageError {
+    #[error("invalid mid")]
+    InvalidId,
+    #[error("message body is empty")]
+    Empty,
+    #[error("message body must be at most {max} characters (actual: {actual})")]
+    TooLong { max: usize, actual: usize },
+    #[error("message not found")]
+    NotFound,
+    #[error("message is already deleted")]
+    AlreadyDeleted,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for MessageId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(MessageId(value)),
+            Err(_) => Err(MessageError::InvalidId.into()),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for MessageId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0.to_string())
+    }
+}
+
+impl Message {
+    /// 本文が空白のみ, または[MAX_MESSAGE_LENGTH]文字を超える場合はエラーを返す
+    pub fn new(sender: UserId, body: String) -> Result<Message> {
+        if body.trim().is_empty() {
+            return Err(MessageError::Empty.into());
+        }
+        let length: usize = body.chars().count();
+        if length > MAX_MESSAGE_LENGTH {
+            return Err(MessageError::TooLong {
+                max: MAX_MESSAGE_LENGTH,
+                actual: length,
+            }
+            .into());
+        }
+
+        Ok(Message {
+            mid: MessageId::new(),
+            sender,
+            body,
+            sent_at: Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_message {
+    use super::*;
+
+    fn sender() -> UserId {
+        UserId::from_str("participant_account000000000").unwrap()
+    }
+
+    #[test]
+    fn new() {
+        let message = Message::new(sender(), "集合場所はどこですか?".to_string()).unwrap();
+        assert_eq!(message.body, "集合場所はどこですか?");
+        assert_eq!(message.sender.to_string(), "participant_account000000000");
+    }
+
+    #[test]
+    fn empty() {
+        let error = Message::new(sender(), " \n".to_string()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<MessageError>(),
+            Some(&MessageError::Empty)
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        assert!(Message::new(sender(), "あ".repeat(MAX_MESSAGE_LENGTH)).is_ok());
+        let error = Message::new(sender(), "あ".repeat(MAX_MESSAGE_LENGTH + 1)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<MessageError>(),
+            Some(&MessageError::TooLong {
+                max: MAX_MESSAGE_LENGTH,
+                actual: MAX_MESSAGE_LENGTH + 1
+            })
+        );
+    }
+
+    #[test]
+    fn id_from_str() {
+        assert!(ThreadId::from_str("01HMAWQDS2Y50QHNGB8GSWJQNW").is_ok());
+        assert!(MessageId::from_str("invalid").is_err());
+    }
+}
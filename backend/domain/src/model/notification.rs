@@ -0,0 +1,253 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// メールで通知する出来事
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationKind {
+    /// 団体にボランティアへの応募を通知する
+    ApplyReceived = 0,
+    /// 参加者に応募の承認を通知する
+    ApplyApproved = 1,
+    /// 参加者に応募の棄却を通知する
+    ApplyRejected = 2,
+    /// 参加者にスカウトを通知する
+    ScoutReceived = 3,
+    /// 団体に参加者による応募の取消を通知する
+    ApplyCancelled = 4,
+}
+
+/// 送信待ちのメールの送信状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxStatus {
+    /// 送信待ち. 送信に失敗した場合も再送までこの状態のまま
+    Pending = 0,
+    /// 送信済
+    Sent = 1,
+    /// 再送の上限に達したため送信を諦めた
+    Failed = 2,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum NotificationError {
+    #[error("notification kind not found")]
+    KindNotFound,
+    #[error("outbox status not found")]
+    OutboxStatusNotFound,
+}
+
+impl NotificationKind {
+    pub fn from_u8(value: u8) -> Result<NotificationKind> {
+        match value {
+            0 => Ok(NotificationKind::ApplyReceived),
+            1 => Ok(NotificationKind::ApplyApproved),
+            2 => Ok(NotificationKind::ApplyRejected),
+            3 => Ok(NotificationKind::ScoutReceived),
+            4 => Ok(NotificationKind::ApplyCancelled),
+            _ => Err(NotificationError::KindNotFound.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// メールの件名と本文を作成する
+    pub fn render(&self, context: &EmailContext) -> (String, String) {
+        let EmailContext {
+            recipient_name,
+            sender_name,
+            volunteer_title,
+            message,
+        } = context;
+
+        match self {
+            NotificationKind::ApplyReceived => (
+                format!("【VolunScout】「{}」に応募がありました", volunteer_title),
+                format!(
+                    "{} 様\n\n{} さんから「{}」への応募がありました.\nVolunScoutにログインし, 応募内容を確認してください.\n",
+                    recipient_name, sender_name, volunteer_title
+                ),
+            ),
+            NotificationKind::ApplyApproved => (
+                format!("【VolunScout】「{}」への応募が承認されました", volunteer_title),
+                format!(
+                    "{} 様\n\n{} が「{}」への応募を承認しました.\n当日の詳細はVolunScoutで確認してください.\n",
+                    recipient_name, sender_name, volunteer_title
+                ),
+            ),
+            NotificationKind::ApplyRejected => (
+                format!("【VolunScout】「{}」への応募結果のお知らせ", volunteer_title),
+                format!(
+                    "{} 様\n\n誠に残念ながら, {} の「{}」への応募は見送りとなりました.\nまたのご応募をお待ちしております.\n",
+                    recipient_name, sender_name, volunteer_title
+                ),
+            ),
+            NotificationKind::ScoutReceived => (
+                format!("【VolunScout】{} からスカウトが届きました", sender_name),
+                format!(
+                    "{} 様\n\n{} から「{}」へのスカウトが届きました.\n\n{}\n\nVolunScoutにログインし, スカウトに返答してください.\n",
+                    recipient_name,
+                    sender_name,
+                    volunteer_title,
+                    message.as_deref().unwrap_or_default()
+                ),
+            ),
+            NotificationKind::ApplyCancelled => (
+                format!("【VolunScout】「{}」への応募が取り消されました", volunteer_title),
+                format!(
+                    "{} 様\n\n{} さんが「{}」への応募を取り消しました.\n\n取消理由: {}\n\n承認済の応募の場合, 空いた枠には未承認の応募を応募順に自動で繰り上げて承認します.\n",
+                    recipient_name,
+                    sender_name,
+                    volunteer_title,
+                    message.as_deref().unwrap_or_default()
+                ),
+            ),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NotificationKind::ApplyReceived => write!(f, "ApplyReceived"),
+            NotificationKind::ApplyApproved => write!(f, "ApplyApproved"),
+            NotificationKind::ApplyRejected => write!(f, "ApplyRejected"),
+            NotificationKind::ScoutReceived => write!(f, "ScoutReceived"),
+            NotificationKind::ApplyCancelled => write!(f, "ApplyCancelled"),
+        }
+    }
+}
+
+impl OutboxStatus {
+    pub fn from_u8(value: u8) -> Result<OutboxStatus> {
+        match value {
+            0 => Ok(OutboxStatus::Pending),
+            1 => Ok(OutboxStatus::Sent),
+            2 => Ok(OutboxStatus::Failed),
+            _ => Err(NotificationError::OutboxStatusNotFound.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// メールの本文に埋め込む値
+///
+/// 送信時に名前などが変更されていても, 出来事が起きた時点の値で送信する
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailContext {
+    /// 宛先のユーザーの名前
+    pub recipient_name: String,
+    /// 応募した参加者, またはボランティアを募集する団体の名前
+    pub sender_name: String,
+    pub volunteer_title: String,
+    /// スカウトのメッセージ, または応募の取消理由
+    pub message: Option<String>,
+}
+
+/// 送信するメール
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    /// 送信待ちのメールごとに一意なid. 送信に成功したが記録に失敗した場合の重複送信の判別に使用する
+    pub message_id: String,
+    /// 宛先のメールアドレス
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// 送信に失敗したメールの再送の間隔
+///
+/// 再送の間隔は失敗するごとに2倍にし, 上限を超えないようにする
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 送信を試みる回数の上限
+    pub max_attempts: u32,
+    /// 1回目の失敗後の再送までの間隔
+    pub base_delay: Duration,
+    /// 再送までの間隔の上限
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 8,
+            base_delay: Duration::minutes(1),
+            max_delay: Duration::hours(6),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// `attempts`回失敗した後に再送する日時を返す. 上限に達した場合はNone
+    pub fn next_attempt_at(&self, attempts: u32, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let factor: i32 = 2_i32.saturating_pow(attempts.saturating_sub(1));
+        let delay: Duration = std::cmp::min(self.base_delay * factor, self.max_delay);
+        Some(now + delay)
+    }
+}
+
+#[cfg(test)]
+mod test_notification {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    fn context() -> EmailContext {
+        EmailContext {
+            recipient_name: "山田太郎".to_string(),
+            sender_name: "ボランティア団体".to_string(),
+            volunteer_title: "公園の清掃".to_string(),
+            message: Some("ぜひご参加ください".to_string()),
+        }
+    }
+
+    #[test]
+    fn render() {
+        let (subject, body) = NotificationKind::ScoutReceived.render(&context());
+        assert_eq!(subject, "【VolunScout】ボランティア団体 からスカウトが届きました");
+        assert!(body.starts_with("山田太郎 様"));
+        assert!(body.contains("ぜひご参加ください"));
+
+        let (subject, _) = NotificationKind::ApplyApproved.render(&context());
+        assert_eq!(subject, "【VolunScout】「公園の清掃」への応募が承認されました");
+
+        let (_, body) = NotificationKind::ApplyCancelled.render(&EmailContext {
+            message: Some("体調不良のため".to_string()),
+            ..context()
+        });
+        assert!(body.contains("取消理由: 体調不良のため"));
+
+        for value in 0..5 {
+            assert_eq!(NotificationKind::from_u8(value).unwrap().to_u8(), value);
+        }
+        assert!(NotificationKind::from_u8(5).is_err());
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let now = Utc.with_ymd_and_hms(2024, 2, 10, 0, 0, 0).unwrap();
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.next_attempt_at(1, now), Some(now + Duration::minutes(1)));
+        assert_eq!(policy.next_attempt_at(2, now), Some(now + Duration::minutes(2)));
+        assert_eq!(policy.next_attempt_at(4, now), Some(now + Duration::minutes(8)));
+        assert_eq!(policy.next_attempt_at(7, now), Some(now + Duration::minutes(64)));
+        // 間隔は上限を超えない
+        let capped = RetryPolicy {
+            max_delay: Duration::minutes(10),
+            ..policy
+        };
+        assert_eq!(capped.next_attempt_at(7, now), Some(now + Duration::minutes(10)));
+        assert_eq!(policy.next_attempt_at(8, now), None);
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::model::billing::{InvoiceId, InvoiceStatus};
+
+/// 決済サービスへの請求の依頼
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChargeRequest {
+    /// 請求書id. 決済サービスでの二重請求を防ぐために使用する
+    pub iid: InvoiceId,
+    /// 請求額(円, 税込)
+    pub amount: u32,
+    /// 決済サービスで登録した支払い方法
+    pub payment_method: String,
+}
+
+/// 決済サービスでの請求の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Charge {
+    /// 決済サービスでの請求id
+    pub charge_id: String,
+    pub outcome: ChargeOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChargeOutcome {
+    Succeeded,
+    Failed { reason: String },
+}
+
+impl Charge {
+    /// 請求の結果に対応する請求書の支払い状態
+    pub fn invoice_status(&self) -> InvoiceStatus {
+        match self.outcome {
+            ChargeOutcome::Succeeded => InvoiceStatus::Paid,
+            ChargeOutcome::Failed { .. } => InvoiceStatus::Failed,
+        }
+    }
+}
+
+/// 決済サービスからWebhookで通知されるイベント
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentEvent {
+    /// 決済サービスでのイベントid. 同じイベントが複数回通知されても1回だけ処理する
+    pub event_id: String,
+    #[serde(flatten)]
+    pub kind: PaymentEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PaymentEventKind {
+    /// 請求に成功した. 支払いに失敗した請求の再請求を含む
+    ChargeSucceeded { charge_id: String },
+    /// 請求に失敗した
+    ChargeFailed { charge_id: String, reason: String },
+    /// 返金した
+    ChargeRefunded { charge_id: String },
+}
+
+impl PaymentEventKind {
+    pub fn charge_id(&self) -> &str {
+        match self {
+            PaymentEventKind::ChargeSucceeded { charge_id }
+            | PaymentEventKind::ChargeFailed { charge_id, .. }
+            | PaymentEventKind::ChargeRefunded { charge_id } => charge_id,
+        }
+    }
+
+    /// イベントに対応する請求書の支払い状態
+    pub fn invoice_status(&self) -> InvoiceStatus {
+        match self {
+            PaymentEventKind::ChargeSucceeded { .. } => InvoiceStatus::Paid,
+            PaymentEventKind::ChargeFailed { .. } => InvoiceStatus::Failed,
+            PaymentEventKind::ChargeRefunded { .. } => InvoiceStatus::Refunded,
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for PaymentEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PaymentEventKind::ChargeSucceeded { .. } => write!(f, "charge_succeeded"),
+            PaymentEventKind::ChargeFailed { .. } => write!(f, "charge_failed"),
+            PaymentEventKind::ChargeRefunded { .. } => write!(f, "charge_refunded"),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PaymentError {
+    #[error("payment_method is required for paid plans")]
+    PaymentMethodRequired,
+    #[error("payment failed: {reason}")]
+    ChargeFailed { reason: String },
+    #[error("invalid webhook signature")]
+    InvalidSignature,
+    #[error("invalid webhook payload: {0}")]
+    InvalidPayload(String),
+}
+
+#[cfg(test)]
+mod test_payment_event {
+    use super::*;
+
+    #[test]
+    fn deserialize() {
+        let event: PaymentEvent = serde_json::from_str(
+            r#"{"event_id": "evt_1", "type": "charge_failed", "charge_id": "ch_1", "reason": "card_declined"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.event_id, "evt_1");
+        assert_eq!(event.kind.charge_id(), "ch_1");
+        assert_eq!(event.kind.invoice_status(), InvoiceStatus::Failed);
+        assert_eq!(event.kind.to_string(), "charge_failed");
+
+        let unknown = serde_json::from_str::<PaymentEvent>(
+            r#"{"event_id": "evt_2", "type": "customer_created"}"#,
+        );
+        assert!(unknown.is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 請求期間の区切りに使用するタイムゾーン(日本時間)の UTC からの差(秒)
+const BILLING_UTC_OFFSET_SECONDS: i32 = 9 * 60 * 60;
+
+/// 団体の料金プラン
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Plan {
+    /// 無料プラン
+    Free = 0,
+    /// スタンダードプラン
+    Standard = 1,
+    /// プレミアムプラン
+    Premium = 2,
+}
+
+/// 選択できるプランの一覧
+pub const PLAN_CATALOG: [Plan; 3] = [Plan::Free, Plan::Standard, Plan::Premium];
+
+/// プランごとの利用上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanLimits {
+    /// 請求期間あたりに送信できるスカウトの数
+    pub monthly_scouts: u32,
+    /// 同時に募集できるボランティアの数
+    pub open_volunteers: u32,
+    /// ボランティア1件あたりに登録できる写真の数
+    pub volunteer_photos: u32,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PlanError {
+    #[error("plan not found")]
+    PlanNotFound,
+    #[error("the group is already on the {0} plan")]
+    SamePlan(Plan),
+    #[error("monthly scout quota of {limit} has been reached")]
+    ScoutQuotaExceeded { limit: u32 },
+    #[error("open volunteer limit of {limit} has been reached")]
+    OpenVolunteerLimitExceeded { limit: u32 },
+    #[error("volunteer photos must be at most {limit}")]
+    PhotoLimitExceeded { limit: u32 },
+}
+
+impl Plan {
+    pub fn from_u8(value: u8) -> Result<Plan, PlanError> {
+        match value {
+            0 => Ok(Plan::Free),
+            1 => Ok(Plan::Standard),
+            2 => Ok(Plan::Premium),
+            _ => Err(PlanError::PlanNotFound),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// 有料プランか
+    ///
+    /// 検索結果の並び順に使用する`group_account.is_paid`と対応する
+    pub fn is_paid(&self) -> bool {
+        *self != Plan::Free
+    }
+
+    /// 1か月あたりの料金(円, 税込)
+    pub fn monthly_price(&self) -> u32 {
+        match self {
+            Plan::Free => 0,
+            Plan::Standard => 5_000,
+            Plan::Premium => 15_000,
+        }
+    }
+
+    /// 初めて有料プランに変更した場合の無料体験期間(日)
+    pub fn trial_days(&self) -> u32 {
+        match self {
+            Plan::Free => 0,
+            Plan::Standard | Plan::Premium => 14,
+        }
+    }
+
+    pub fn limits(&self) -> PlanLimits {
+        match self {
+            Plan::Free => PlanLimits {
+                monthly_scouts: 10,
+                open_volunteers: 3,
+                volunteer_photos: 3,
+            },
+            Plan::Standard => PlanLimits {
+                monthly_scouts: 200,
+                open_volunteers: 30,
+                volunteer_photos: 10,
+            },
+            Plan::Premium => PlanLimits {
+                monthly_scouts: 1_000,
+                open_volunteers: 100,
+                volunteer_photos: 20,
+            },
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Plan::Free => write!(f, "Free"),
+            Plan::Standard => write!(f, "Standard"),
+            Plan::Premium => write!(f, "Premium"),
+        }
+    }
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for Plan {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "Free" => Ok(Plan::Free),
+            "Standard" => Ok(Plan::Standard),
+            "Premium" => Ok(Plan::Premium),
+            _ => Err(PlanError::PlanNotFound.into()),
+        }
+    }
+}
+
+impl PlanLimits {
+    /// 請求期間内に`used`件送信済みの場合に, さらにスカウトを送信できるか確認する
+    pub fn ensure_scout(&self, used: u32) -> Result<(), PlanError> {
+        if used >= self.monthly_scouts {
+            return Err(PlanError::ScoutQuotaExceeded {
+                limit: self.monthly_scouts,
+            });
+        }
+        Ok(())
+    }
+
+    /// `open`件募集中の場合に, さらにボランティアを募集できるか確認する
+    pub fn ensure_open_volunteer(&self, open: u32) -> Result<(), PlanError> {
+        if open >= self.open_volunteers {
+            return Err(PlanError::OpenVolunteerLimitExceeded {
+                limit: self.open_volunteers,
+            });
+        }
+        Ok(())
+    }
+
+    /// ボランティアに`count`枚の写真を登録できるか確認する
+    pub fn ensure_photos(&self, count: usize) -> Result<(), PlanError> {
+        if count > self.volunteer_photos as usize {
+            return Err(PlanError::PhotoLimitExceeded {
+                limit: self.volunteer_photos,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 請求期間
+///
+/// 日本時間の暦月の初日から翌月の初日の前日まで
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BillingPeriod {
+    /// 開始日(日本時間)
+    pub start_on: NaiveDate,
+    /// 終了日(日本時間)
+    pub end_on: NaiveDate,
+}
+
+impl BillingPeriod {
+    /// `now`を含む請求期間を求める
+    pub fn containing(now: DateTime<Utc>) -> BillingPeriod {
+        let offset = FixedOffset::east_opt(BILLING_UTC_OFFSET_SECONDS).unwrap();
+        let today: NaiveDate = now.with_timezone(&offset).date_naive();
+        let start_on = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap();
+        let next_start_on = if today.month() == 12 {
+            NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)
+        }
+        .unwrap();
+        BillingPeriod {
+            start_on,
+            end_on: next_start_on - Duration::days(1),
+        }
+    }
+
+    /// 請求期間の開始日時と, 次の請求期間の開始日時をUTCで求める
+    ///
+    /// UTCで保存している日時のカラムを`開始日時 <= カラム < 次の開始日時`で絞り込むために使用する
+    pub fn utc_range(&self) -> (NaiveDateTime, NaiveDateTime) {
+        let offset = Duration::seconds(BILLING_UTC_OFFSET_SECONDS as i64);
+        let start_at = self.start_on.and_hms_opt(0, 0, 0).unwrap() - offset;
+        let next_start_at =
+            (self.end_on + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap() - offset;
+        (start_at, next_start_at)
+    }
+}
+
+#[cfg(test)]
+mod test_plan {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn limits() {
+        let free = Plan::Free.limits();
+        assert!(free.ensure_scout(free.monthly_scouts - 1).is_ok());
+        assert_eq!(
+            free.ensure_scout(free.monthly_scouts),
+            Err(PlanError::ScoutQuotaExceeded {
+                limit: free.monthly_scouts
+            })
+        );
+        assert!(free.ensure_open_volunteer(free.open_volunteers).is_err());
+        assert!(free.ensure_photos(free.volunteer_photos as usize).is_ok());
+        assert!(free.ensure_photos(free.volunteer_photos as usize + 1).is_err());
+
+        let paid = Plan::Standard.limits();
+        assert!(paid.ensure_scout(free.monthly_scouts).is_ok());
+        assert!(paid.ensure_open_volunteer(free.open_volunteers).is_ok());
+    }
+
+    #[test]
+    fn catalog() {
+        for plan in PLAN_CATALOG {
+            assert_eq!(Plan::from_u8(plan.to_u8()).unwrap(), plan);
+            assert_eq!(Plan::from_str(&plan.to_string()).unwrap(), plan);
+            assert_eq!(plan.is_paid(), plan.monthly_price() > 0);
+        }
+        assert_eq!(Plan::from_u8(3), Err(PlanError::PlanNotFound));
+        assert!(Plan::from_str("Paid").is_err());
+        assert!(Plan::Premium.limits().monthly_scouts > Plan::Standard.limits().monthly_scouts);
+    }
+
+    #[test]
+    fn billing_period() {
+        // 日本時間では2024年2月1日
+        let now = Utc.with_ymd_and_hms(2024, 1, 31, 15, 0, 0).unwrap();
+        let period = BillingPeriod::containing(now);
+        assert_eq!(period.start_on, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+        assert_eq!(period.end_on, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+
+        let (start_at, next_start_at) = period.utc_range();
+        assert_eq!(start_at, now.naive_utc());
+        assert_eq!(
+            next_start_at,
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap().and_hms_opt(15, 0, 0).unwrap()
+        );
+
+        let period = BillingPeriod::containing(Utc.with_ymd_and_hms(2023, 12, 20, 0, 0, 0).unwrap());
+        assert_eq!(period.end_on, NaiveDate::from_ymd_opt(2023, 12, 31).unwrap());
+    }
+}
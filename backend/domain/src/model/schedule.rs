@@ -0,0 +1,635 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Days, Duration, FixedOffset, Months, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid_generator_rs::{ULIDGenerator, ULID};
+
+use crate::model::certificate::to_jst;
+
+/// 1つのボランティアに登録できる回の最大数
+pub const MAX_SESSIONS: usize = 100;
+
+/// 繰り返しの間隔(週・か月)の最大値
+pub const MAX_INTERVAL: u32 = 99;
+
+/// ボランティアの回. 回ごとに日時と募集人数を持ち, 応募は回に対して行う
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolunteerSession {
+    pub vsid: SessionId,
+    pub start_at: DateTime<Utc>,
+    pub finish_at: DateTime<Utc>,
+    pub recruited_num: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionId(pub ULID);
+
+impl SessionId {
+    pub fn new() -> SessionId {
+        let mut generator: ULIDGenerator = ULIDGenerator::new();
+        let value: ULID = generator.generate().unwrap();
+        SessionId(value)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    #[error("invalid vsid")]
+    InvalidSessionId,
+    #[error("invalid recurrence rule: {0}")]
+    InvalidRule(String),
+    #[error("recurrence rule must have either COUNT or UNTIL")]
+    UnboundedRule,
+    #[error("recurrence and sessions cannot be specified together")]
+    AmbiguousSchedule,
+    #[error("schedule has no sessions")]
+    Empty,
+    #[error("schedule must have at most {max} sessions (actual: {actual})")]
+    TooManySessions { max: usize, actual: usize },
+    #[error("session must finish after it starts")]
+    InvalidPeriod,
+    #[error("recruited_num of a session must be at least 1")]
+    NoCapacity,
+    #[error("sessions must not start at the same time ({start_at})")]
+    DuplicateStart { start_at: DateTime<Utc> },
+    #[error("session not found")]
+    SessionNotFound,
+    #[error("vsid is required to apply to a volunteer with multiple sessions")]
+    SessionRequired,
+    #[error("session has already started")]
+    SessionStarted,
+    #[error("session starting at {start_at} has pending or approved applies")]
+    SessionHasApplies { start_at: DateTime<Utc> },
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for SessionId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(SessionId(value)),
+            Err(_) => Err(ScheduleError::InvalidSessionId.into()),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for SessionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl VolunteerSession {
+    /// 終了日時が開始日時以前の場合, 募集人数が0の場合はエラーを返す
+    pub fn new(
+        start_at: DateTime<Utc>,
+        finish_at: DateTime<Utc>,
+        recruited_num: u32,
+    ) -> Result<VolunteerSession> {
+        if finish_at <= start_at {
+            return Err(ScheduleError::InvalidPeriod.into());
+        }
+        if recruited_num == 0 {
+            return Err(ScheduleError::NoCapacity.into());
+        }
+
+        Ok(VolunteerSession {
+            vsid: SessionId::new(),
+            start_at,
+            finish_at,
+            recruited_num,
+        })
+    }
+}
+
+/// 繰り返しの頻度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+    Weekly,
+    Monthly,
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for Frequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Frequency::Weekly => write!(f, "WEEKLY"),
+            Frequency::Monthly => write!(f, "MONTHLY"),
+        }
+    }
+}
+
+/// 繰り返しの規則. iCalendarのRRULEの`FREQ`, `INTERVAL`, `COUNT`, `UNTIL`に対応する
+///
+/// 例: `FREQ=WEEKLY;COUNT=52`, `FREQ=MONTHLY;INTERVAL=2;UNTIL=20241231`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    /// 何週・何か月ごとに繰り返すか
+    pub interval: u32,
+    /// 繰り返す回数. 最初の回を含む
+    pub count: Option<u32>,
+    /// 最後の回の開始日(日本時間)
+    pub until: Option<NaiveDate>,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for RecurrenceRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = |reason: &str| -> anyhow::Error {
+            ScheduleError::InvalidRule(reason.to_string()).into()
+        };
+
+        let s: &str = s.trim();
+        let s: &str = s.strip_prefix("RRULE:").unwrap_or(s);
+        let mut frequency: Option<Frequency> = None;
+        let mut interval: Option<u32> = None;
+        let mut count: Option<u32> = None;
+        let mut until: Option<NaiveDate> = None;
+
+        for part in s.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| invalid(&format!("{} is not KEY=VALUE", part)))?;
+            let duplicated: bool = match key.to_ascii_uppercase().as_str() {
+                "FREQ" => frequency
+                    .replace(match value.to_ascii_uppercase().as_str() {
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(invalid(&format!("FREQ={} is not supported", value))),
+                    })
+                    .is_some(),
+                "INTERVAL" => interval
+                    .replace(match value.parse::<u32>() {
+                        Ok(value) if (1..=MAX_INTERVAL).contains(&value) => value,
+                        _ => {
+                            return Err(invalid(&format!(
+                                "INTERVAL must be an integer from 1 to {}",
+                                MAX_INTERVAL
+                            )))
+                        }
+                    })
+                    .is_some(),
+                "COUNT" => count
+                    .replace(match value.parse::<u32>() {
+                        Ok(value) if value > 0 => value,
+                        _ => return Err(invalid("COUNT must be a positive integer")),
+                    })
+                    .is_some(),
+                // 日時の形式(20241231T000000Z)の場合は日付のみ使用する
+                "UNTIL" => until
+                    .replace(
+                        value
+                            .get(..8)
+                            .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+                            .ok_or_else(|| invalid("UNTIL must be YYYYMMDD"))?,
+                    )
+                    .is_some(),
+                _ => return Err(invalid(&format!("{} is not supported", key))),
+            };
+            if duplicated {
+                return Err(invalid(&format!("{} is specified more than once", key)));
+            }
+        }
+
+        let frequency: Frequency = frequency.ok_or_else(|| invalid("FREQ is required"))?;
+        match (count, until) {
+            (None, None) => Err(ScheduleError::UnboundedRule.into()),
+            (Some(_), Some(_)) => Err(invalid("COUNT and UNTIL cannot be specified together")),
+            _ => Ok(RecurrenceRule {
+                frequency,
+                interval: interval.unwrap_or(1),
+                count,
+                until,
+            }),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "FREQ={};INTERVAL={}", self.frequency, self.interval)?;
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        Ok(())
+    }
+}
+
+impl RecurrenceRule {
+    /// 最初の回から繰り返す回の開始・終了日時を求める
+    ///
+    /// 日付は日本時間で繰り返す. 毎月の繰り返しで同じ日がない月(31日など)は飛ばす.
+    /// 日付が表せる範囲を超えた場合はそこで打ち切る
+    pub fn occurrences(
+        &self,
+        start_at: DateTime<Utc>,
+        finish_at: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+        if let Some(count) = self.count {
+            if count as usize > MAX_SESSIONS {
+                return Err(ScheduleError::TooManySessions {
+                    max: MAX_SESSIONS,
+                    actual: count as usize,
+                }
+                .into());
+            }
+        }
+
+        let first: DateTime<FixedOffset> = to_jst(start_at);
+        let duration: Duration = finish_at - start_at;
+        let mut occurrences: Vec<(DateTime<Utc>, DateTime<Utc>)> = vec![];
+
+        for k in 0u32.. {
+            let Some(step) = k.checked_mul(self.interval) else {
+                break;
+            };
+            let date: Option<NaiveDate> = match self.frequency {
+                Frequency::Weekly => {
+                    match first
+                        .date_naive()
+                        .checked_add_days(Days::new(u64::from(step) * 7))
+                    {
+                        Some(date) => Some(date),
+                        None => break,
+                    }
+                }
+                // 月の初日で月を進めてから日を合わせ, 同じ日がない月は`None`にする
+                Frequency::Monthly => match first
+                    .date_naive()
+                    .with_day(1)
+                    .and_then(|date| date.checked_add_months(Months::new(step)))
+                {
+                    Some(month) => month.with_day(first.day()),
+                    None => break,
+                },
+            };
+            let date: NaiveDate = match date {
+                Some(date) => date,
+                None => continue,
+            };
+
+            if self.until.is_some_and(|until| date > until)
+                || self
+                    .count
+                    .is_some_and(|count| occurrences.len() >= count as usize)
+            {
+                break;
+            }
+            // 件数の上限を超えた時点で打ち切り, 残りは数えない
+            if occurrences.len() == MAX_SESSIONS {
+                return Err(ScheduleError::TooManySessions {
+                    max: MAX_SESSIONS,
+                    actual: MAX_SESSIONS + 1,
+                }
+                .into());
+            }
+
+            let start_at: DateTime<Utc> = first
+                .timezone()
+                .from_local_datetime(&date.and_time(first.time()))
+                .unwrap()
+                .with_timezone(&Utc);
+            occurrences.push((start_at, start_at + duration));
+        }
+
+        Ok(occurrences)
+    }
+}
+
+/// ボランティアの日程. 繰り返しの規則から求めた回, または指定された回の一覧
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// 繰り返しの規則. 回を指定した場合は`None`
+    pub recurrence: Option<RecurrenceRule>,
+    /// 開始日時の早い順の回
+    pub sessions: Vec<VolunteerSession>,
+}
+
+/// 日程の更新で回に行う変更
+///
+/// 開始日時が同じ回は同じ回として回IDを引き継ぎ, 応募を残す
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionChanges {
+    pub inserted: Vec<VolunteerSession>,
+    pub updated: Vec<VolunteerSession>,
+    pub removed: Vec<VolunteerSession>,
+}
+
+impl Schedule {
+    /// 1回だけのボランティアの日程
+    pub fn once(
+        start_at: DateTime<Utc>,
+        finish_at: DateTime<Utc>,
+        recruited_num: u32,
+    ) -> Result<Schedule> {
+        Schedule::new(
+            None,
+            vec![VolunteerSession::new(start_at, finish_at, recruited_num)?],
+        )
+    }
+
+    /// 最初の回の日時から繰り返す日程. 全ての回の募集人数は`recruited_num`とする
+    pub fn recurring(
+        rule: RecurrenceRule,
+        start_at: DateTime<Utc>,
+        finish_at: DateTime<Utc>,
+        recruited_num: u32,
+    ) -> Result<Schedule> {
+        let sessions: Vec<VolunteerSession> = rule
+            .occurrences(start_at, finish_at)?
+            .into_iter()
+            .map(|(start_at, finish_at)| VolunteerSession::new(start_at, finish_at, recruited_num))
+            .collect::<Result<Vec<VolunteerSession>>>()?;
+        Schedule::new(Some(rule), sessions)
+    }
+
+    /// 回を指定した日程
+    pub fn explicit(sessions: Vec<VolunteerSession>) -> Result<Schedule> {
+        Schedule::new(None, sessions)
+    }
+
+    fn new(
+        recurrence: Option<RecurrenceRule>,
+        mut sessions: Vec<VolunteerSession>,
+    ) -> Result<Schedule> {
+        if sessions.is_empty() {
+            return Err(ScheduleError::Empty.into());
+        }
+        if sessions.len() > MAX_SESSIONS {
+            return Err(ScheduleError::TooManySessions {
+                max: MAX_SESSIONS,
+                actual: sessions.len(),
+            }
+            .into());
+        }
+        sessions.sort_by_key(|session| session.start_at);
+        if let Some(pair) = sessions
+            .windows(2)
+            .find(|pair| pair[0].start_at == pair[1].start_at)
+        {
+            return Err(ScheduleError::DuplicateStart {
+                start_at: pair[0].start_at,
+            }
+            .into());
+        }
+
+        Ok(Schedule {
+            recurrence,
+            sessions,
+        })
+    }
+
+    /// 最初の回の開始日時
+    pub fn start_at(&self) -> DateTime<Utc> {
+        self.sessions[0].start_at
+    }
+
+    /// 最後に終了する回の終了日時
+    pub fn finish_at(&self) -> DateTime<Utc> {
+        self.sessions
+            .iter()
+            .map(|session| session.finish_at)
+            .max()
+            .unwrap()
+    }
+
+    /// 登録済みの回からこの日程にするための変更を求める
+    pub fn changes_from(&self, registered: &[VolunteerSession]) -> SessionChanges {
+        let mut inserted: Vec<VolunteerSession> = vec![];
+        let mut updated: Vec<VolunteerSession> = vec![];
+        for session in &self.sessions {
+            match registered.iter().find(|r| r.start_at == session.start_at) {
+                Some(r) => updated.push(VolunteerSession {
+                    vsid: r.vsid.clone(),
+                    ..session.clone()
+                }),
+                None => inserted.push(session.clone()),
+            }
+        }
+        let removed: Vec<VolunteerSession> = registered
+            .iter()
+            .filter(|r| {
+                !self
+                    .sessions
+                    .iter()
+                    .any(|session| session.start_at == r.start_at)
+            })
+            .cloned()
+            .collect();
+
+        SessionChanges {
+            inserted,
+            updated,
+            removed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_schedule {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn parse_rule() {
+        let rule = RecurrenceRule::from_str("RRULE:FREQ=WEEKLY;COUNT=52").unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 1);
+        assert_eq!(rule.count, Some(52));
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;INTERVAL=1;COUNT=52");
+
+        let rule =
+            RecurrenceRule::from_str("freq=monthly;interval=2;until=20241231T000000Z").unwrap();
+        assert_eq!(rule.until, NaiveDate::from_ymd_opt(2024, 12, 31));
+        assert_eq!(rule.to_string(), "FREQ=MONTHLY;INTERVAL=2;UNTIL=20241231");
+    }
+
+    #[test]
+    fn invalid_rule() {
+        let error = RecurrenceRule::from_str("FREQ=WEEKLY").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScheduleError>(),
+            Some(&ScheduleError::UnboundedRule)
+        );
+        for rule in [
+            "FREQ=DAILY;COUNT=3",
+            "FREQ=WEEKLY;INTERVAL=0;COUNT=3",
+            "FREQ=WEEKLY;INTERVAL=100;COUNT=3",
+            "FREQ=MONTHLY;INTERVAL=4294967295;COUNT=3",
+            "FREQ=WEEKLY;COUNT=3;UNTIL=20241231",
+            "FREQ=WEEKLY;FREQ=MONTHLY;COUNT=3",
+            "FREQ=WEEKLY;BYDAY=MO;COUNT=3",
+            "COUNT=3",
+        ] {
+            let error = RecurrenceRule::from_str(rule).unwrap_err();
+            assert!(
+                matches!(
+                    error.downcast_ref::<ScheduleError>(),
+                    Some(ScheduleError::InvalidRule(_))
+                ),
+                "{}",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn weekly() {
+        let rule = RecurrenceRule::from_str("FREQ=WEEKLY;INTERVAL=2;COUNT=3").unwrap();
+        let schedule = Schedule::recurring(rule, at(2024, 4, 6, 0), at(2024, 4, 6, 3), 5).unwrap();
+        let starts: Vec<DateTime<Utc>> = schedule.sessions.iter().map(|s| s.start_at).collect();
+        assert_eq!(
+            starts,
+            vec![at(2024, 4, 6, 0), at(2024, 4, 20, 0), at(2024, 5, 4, 0)]
+        );
+        assert_eq!(schedule.finish_at(), at(2024, 5, 4, 3));
+        assert!(schedule.sessions.iter().all(|s| s.recruited_num == 5));
+    }
+
+    #[test]
+    fn monthly_skips_missing_days() {
+        // 日本時間の1月31日9時
+        let rule = RecurrenceRule::from_str("FREQ=MONTHLY;UNTIL=20240531").unwrap();
+        let occurrences = rule
+            .occurrences(at(2024, 1, 31, 0), at(2024, 1, 31, 2))
+            .unwrap();
+        let starts: Vec<DateTime<Utc>> = occurrences.iter().map(|(start, _)| *start).collect();
+        assert_eq!(
+            starts,
+            vec![at(2024, 1, 31, 0), at(2024, 3, 31, 0), at(2024, 5, 31, 0)]
+        );
+    }
+
+    #[test]
+    fn monthly_uses_jst_date() {
+        // UTCの3月31日15時は日本時間の4月1日
+        let rule = RecurrenceRule::from_str("FREQ=MONTHLY;COUNT=2").unwrap();
+        let occurrences = rule
+            .occurrences(at(2024, 3, 31, 15), at(2024, 3, 31, 18))
+            .unwrap();
+        assert_eq!(occurrences[1], (at(2024, 4, 30, 15), at(2024, 4, 30, 18)));
+    }
+
+    #[test]
+    fn stops_at_max_date() {
+        // 表せる日付を超える回は作成せずに打ち切る
+        let rule = RecurrenceRule::from_str("FREQ=MONTHLY;INTERVAL=99;COUNT=100").unwrap();
+        let start_at: DateTime<Utc> = NaiveDate::from_ymd_opt(262_000, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        let occurrences = rule
+            .occurrences(start_at, start_at + Duration::hours(1))
+            .unwrap();
+        assert!(!occurrences.is_empty() && occurrences.len() < 100);
+
+        let rule = RecurrenceRule::from_str("FREQ=WEEKLY;INTERVAL=99;COUNT=100").unwrap();
+        let occurrences = rule
+            .occurrences(start_at, start_at + Duration::hours(1))
+            .unwrap();
+        assert!(!occurrences.is_empty() && occurrences.len() < 100);
+    }
+
+    #[test]
+    fn too_many_sessions() {
+        let rule = RecurrenceRule::from_str("FREQ=WEEKLY;UNTIL=20991231").unwrap();
+        let error = rule
+            .occurrences(at(2024, 1, 1, 0), at(2024, 1, 1, 1))
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScheduleError>(),
+            Some(&ScheduleError::TooManySessions {
+                max: MAX_SESSIONS,
+                actual: MAX_SESSIONS + 1
+            })
+        );
+        let rule = RecurrenceRule::from_str("FREQ=WEEKLY;COUNT=100").unwrap();
+        assert_eq!(
+            rule.occurrences(at(2024, 1, 1, 0), at(2024, 1, 1, 1))
+                .unwrap()
+                .len(),
+            100
+        );
+    }
+
+    #[test]
+    fn explicit() {
+        let late = VolunteerSession::new(at(2024, 5, 1, 0), at(2024, 5, 1, 8), 3).unwrap();
+        let early = VolunteerSession::new(at(2024, 4, 1, 0), at(2024, 4, 1, 2), 10).unwrap();
+        let schedule = Schedule::explicit(vec![late.clone(), early.clone()]).unwrap();
+        assert_eq!(schedule.sessions, vec![early.clone(), late]);
+        assert_eq!(schedule.recurrence, None);
+        assert_eq!(schedule.start_at(), at(2024, 4, 1, 0));
+
+        let error = Schedule::explicit(vec![early.clone(), early]).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScheduleError>(),
+            Some(&ScheduleError::DuplicateStart {
+                start_at: at(2024, 4, 1, 0)
+            })
+        );
+        let error = Schedule::explicit(vec![]).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScheduleError>(),
+            Some(&ScheduleError::Empty)
+        );
+    }
+
+    #[test]
+    fn invalid_session() {
+        let error = VolunteerSession::new(at(2024, 4, 1, 2), at(2024, 4, 1, 2), 1).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScheduleError>(),
+            Some(&ScheduleError::InvalidPeriod)
+        );
+        let error = VolunteerSession::new(at(2024, 4, 1, 0), at(2024, 4, 1, 2), 0).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScheduleError>(),
+            Some(&ScheduleError::NoCapacity)
+        );
+    }
+
+    #[test]
+    fn changes_from() {
+        let kept = VolunteerSession::new(at(2024, 4, 1, 0), at(2024, 4, 1, 2), 5).unwrap();
+        let dropped = VolunteerSession::new(at(2024, 4, 8, 0), at(2024, 4, 8, 2), 5).unwrap();
+        let schedule = Schedule::explicit(vec![
+            VolunteerSession::new(at(2024, 4, 1, 0), at(2024, 4, 1, 3), 8).unwrap(),
+            VolunteerSession::new(at(2024, 4, 15, 0), at(2024, 4, 15, 2), 5).unwrap(),
+        ])
+        .unwrap();
+
+        let changes = schedule.changes_from(&[kept.clone(), dropped.clone()]);
+        assert_eq!(changes.updated.len(), 1);
+        assert_eq!(changes.updated[0].vsid, kept.vsid);
+        assert_eq!(changes.updated[0].finish_at, at(2024, 4, 1, 3));
+        assert_eq!(changes.updated[0].recruited_num, 8);
+        assert_eq!(changes.inserted, vec![schedule.sessions[1].clone()]);
+        assert_eq!(changes.removed, vec![dropped]);
+    }
+
+    #[test]
+    fn session_id_from_str() {
+        assert!(SessionId::from_str("01HN22FWVK9949XC4KP8P517C3").is_ok());
+        let error = SessionId::from_str("invalid").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScheduleError>(),
+            Some(&ScheduleError::InvalidSessionId)
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use std::str::FromStr;
 
-use chrono::{DateTime, Utc};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use ulid_generator_rs::{ULIDGenerator, ULID};
 
 use crate::model::{user_account::user_id::UserId, volunteer::VolunteerId};
@@ -15,6 +17,7 @@ pub struct Scout {
     pub send_at: DateTime<Utc>,
     pub is_sent: bool,
     pub is_read: bool,
+    pub status: ScoutStatus,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,10 +29,156 @@ impl ScoutId {
         let value: ulid_generator_rs::ULID = generator.generate().unwrap();
         ScoutId(value)
     }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScoutIdError {
+    #[error("invalid sid")]
+    Invalid,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for ScoutId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(ScoutId(value)),
+            Err(_) => Err(ScoutIdError::Invalid.into()),
+        }
+    }
+}
+
+/// スカウトの状態
+///
+/// 状態遷移は以下のみ許可する
+/// - 送信済 -> 既読 / 承諾済 / 辞退済 / 期限切れ / 取下済
+/// - 既読 -> 承諾済 / 辞退済 / 期限切れ / 取下済
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoutStatus {
+    /// 送信済
+    Sent = 0,
+    /// 既読
+    Read = 1,
+    /// 承諾済(応募に変換済)
+    Accepted = 2,
+    /// 辞退済
+    Declined = 3,
+    /// 期限切れ(ボランティアの募集締切日を過ぎた)
+    Expired = 4,
+    /// 取下済(団体による取り下げ)
+    Withdrawn = 5,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScoutError {
+    #[error("status not found")]
+    StatusNotFound,
+    #[error("cannot change status from {from} to {to}")]
+    InvalidTransition { from: ScoutStatus, to: ScoutStatus },
+    #[error("the participant has already applied to the volunteer")]
+    AlreadyApplied,
+}
+
+impl ScoutStatus {
+    pub fn from_u8(value: u8) -> Result<ScoutStatus> {
+        match value {
+            0 => Ok(ScoutStatus::Sent),
+            1 => Ok(ScoutStatus::Read),
+            2 => Ok(ScoutStatus::Accepted),
+            3 => Ok(ScoutStatus::Declined),
+            4 => Ok(ScoutStatus::Expired),
+            5 => Ok(ScoutStatus::Withdrawn),
+            _ => Err(ScoutError::StatusNotFound.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// 返答を待っている状態か
+    pub fn is_open(&self) -> bool {
+        matches!(self, ScoutStatus::Sent | ScoutStatus::Read)
+    }
+
+    /// 指定した状態へ遷移できるか
+    pub fn can_transition_to(&self, next: ScoutStatus) -> bool {
+        matches!(
+            (self, next),
+            (ScoutStatus::Sent, ScoutStatus::Read)
+                | (
+                    ScoutStatus::Sent | ScoutStatus::Read,
+                    ScoutStatus::Accepted
+                        | ScoutStatus::Declined
+                        | ScoutStatus::Expired
+                        | ScoutStatus::Withdrawn
+                )
+        )
+    }
+
+    /// 指定した状態へ遷移する. 許可されていない遷移の場合はエラーを返す
+    pub fn transition_to(&self, next: ScoutStatus) -> Result<ScoutStatus> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(ScoutError::InvalidTransition {
+                from: *self,
+                to: next,
+            }
+            .into())
+        }
+    }
+
+    /// 参加者が既読にする
+    pub fn read(&self) -> Result<ScoutStatus> {
+        self.transition_to(ScoutStatus::Read)
+    }
 
-    pub fn from_str(value: &str) -> ScoutId {
-        let sid = ULID::from_str(&value).unwrap();
-        ScoutId(sid)
+    /// 参加者が承諾する
+    pub fn accept(&self) -> Result<ScoutStatus> {
+        self.transition_to(ScoutStatus::Accepted)
+    }
+
+    /// 参加者が辞退する
+    pub fn decline(&self) -> Result<ScoutStatus> {
+        self.transition_to(ScoutStatus::Declined)
+    }
+
+    /// 募集締切日を過ぎたため期限切れにする
+    pub fn expire(&self) -> Result<ScoutStatus> {
+        self.transition_to(ScoutStatus::Expired)
+    }
+
+    /// 団体が取り下げる
+    pub fn withdraw(&self) -> Result<ScoutStatus> {
+        self.transition_to(ScoutStatus::Withdrawn)
+    }
+
+    /// 募集締切日を考慮した状態
+    ///
+    /// 返答を待っている状態で`today`が募集締切日を過ぎている場合は期限切れとする
+    pub fn effective(&self, deadline_on: NaiveDate, today: NaiveDate) -> ScoutStatus {
+        if self.is_open() && deadline_on < today {
+            ScoutStatus::Expired
+        } else {
+            *self
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for ScoutStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            ScoutStatus::Sent => "Sent",
+            ScoutStatus::Read => "Read",
+            ScoutStatus::Accepted => "Accepted",
+            ScoutStatus::Declined => "Declined",
+            ScoutStatus::Expired => "Expired",
+            ScoutStatus::Withdrawn => "Withdrawn",
+        };
+        write!(f, "{}", status)
     }
 }
 
@@ -45,6 +194,7 @@ impl Scout {
             send_at,
             is_sent: false,
             is_read: false,
+            status: ScoutStatus::Sent,
         }
     }
 }
@@ -55,3 +205,59 @@ impl std::fmt::Display for ScoutId {
         write!(f, "{}", self.0.to_string())
     }
 }
+
+#[cfg(test)]
+mod test_scout_status {
+    use super::*;
+
+    #[test]
+    fn from_u8() {
+        assert_eq!(ScoutStatus::from_u8(0).unwrap(), ScoutStatus::Sent);
+        assert_eq!(ScoutStatus::from_u8(5).unwrap(), ScoutStatus::Withdrawn);
+        assert_eq!(ScoutStatus::from_u8(6).is_err(), true);
+    }
+
+    #[test]
+    fn sent_to_read() {
+        assert_eq!(ScoutStatus::Sent.read().unwrap(), ScoutStatus::Read);
+        assert_eq!(ScoutStatus::Read.read().is_err(), true);
+    }
+
+    #[test]
+    fn open_to_decided() {
+        for status in [ScoutStatus::Sent, ScoutStatus::Read] {
+            assert_eq!(status.accept().unwrap(), ScoutStatus::Accepted);
+            assert_eq!(status.decline().unwrap(), ScoutStatus::Declined);
+            assert_eq!(status.expire().unwrap(), ScoutStatus::Expired);
+            assert_eq!(status.withdraw().unwrap(), ScoutStatus::Withdrawn);
+        }
+    }
+
+    #[test]
+    fn decided_is_terminal() {
+        let error = ScoutStatus::Declined.accept().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ScoutError>(),
+            Some(&ScoutError::InvalidTransition {
+                from: ScoutStatus::Declined,
+                to: ScoutStatus::Accepted
+            })
+        );
+        assert_eq!(ScoutStatus::Accepted.withdraw().is_err(), true);
+        assert_eq!(ScoutStatus::Expired.read().is_err(), true);
+        assert_eq!(ScoutStatus::Withdrawn.decline().is_err(), true);
+    }
+
+    #[test]
+    fn effective() {
+        let deadline_on = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let on_deadline = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let after_deadline = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        assert_eq!(ScoutStatus::Read.effective(deadline_on, on_deadline), ScoutStatus::Read);
+        assert_eq!(ScoutStatus::Sent.effective(deadline_on, after_deadline), ScoutStatus::Expired);
+        assert_eq!(
+            ScoutStatus::Accepted.effective(deadline_on, after_deadline),
+            ScoutStatus::Accepted
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::str::FromStr;
 
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use ulid_generator_rs::{ULIDGenerator, ULID};
 
 use crate::model::{terms::Terms, user_account::user_id::UserId};
@@ -35,12 +36,26 @@ impl VolunteerId {
         let value: ULID = generator.generate().unwrap();
         VolunteerId(value)
     }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum VolunteerIdError {
+    #[error("invalid vid")]
+    Invalid,
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for VolunteerId {
+    type Err = anyhow::Error;
 
-    pub fn from_str(value: &str) -> VolunteerId {
-        let vid = ULID::from_str(&value).unwrap();
-        VolunteerId(vid)
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match ULID::from_str(s) {
+            Ok(value) => Ok(VolunteerId(value)),
+            Err(_) => Err(VolunteerIdError::Invalid.into()),
+        }
     }
 }
+
 // Displayを実装することで, to_string()で文字列に変換できるようになる
 impl std::fmt::Display for VolunteerId {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -87,3 +102,26 @@ impl Volunteer {
         }
     }
 }
+
+#[cfg(test)]
+mod test_volunteer_id {
+    use super::*;
+
+    #[test]
+    fn ok() {
+        let vid = VolunteerId::from_str("01H5Z3A2Q4Y8X7W6V5T4S3R2P1").unwrap();
+        assert_eq!(vid.to_string(), "01H5Z3A2Q4Y8X7W6V5T4S3R2P1");
+    }
+
+    #[test]
+    fn invalid() {
+        let vid = VolunteerId::from_str("invalid");
+        assert_eq!(vid.is_err(), true);
+    }
+
+    #[test]
+    fn empty() {
+        let vid = VolunteerId::from_str("");
+        assert_eq!(vid.is_err(), true);
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::MySqlPool;
+
+use domain::model::{
+    apply::{ApplyId, ApplyStatus},
+    scout::ScoutStatus,
+    user_account::user_id::UserId,
+};
+use query_repository::access::{AccessRepository, Applicant};
+
+pub struct AccessImpl {
+    pool: MySqlPool,
+}
+
+impl AccessImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AccessRepository for AccessImpl {
+    async fn has_accepted_relationship(&self, gid: &UserId, uid: &UserId) -> Result<bool> {
+        // 承認済みの応募か, 参加者が承諾したスカウトがあれば関係があるとする
+        let relationship = sqlx::query!(
+            r#"
+            SELECT (
+                EXISTS(
+                    SELECT 1
+                    FROM apply
+                    INNER JOIN volunteer ON apply.vid = volunteer.vid
+                    WHERE volunteer.gid = ? AND apply.uid = ? AND apply.allowed_status = ?
+                )
+                OR EXISTS(
+                    SELECT 1
+                    FROM scout
+                    INNER JOIN volunteer ON scout.vid = volunteer.vid
+                    WHERE volunteer.gid = ? AND scout.uid = ? AND scout.status = ?
+                )
+            ) AS "exists: bool"
+            "#,
+            gid.to_string(),
+            uid.to_string(),
+            ApplyStatus::Approved.to_u8(),
+            gid.to_string(),
+            uid.to_string(),
+            ScoutStatus::Accepted.to_u8()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(relationship.exists)
+    }
+
+    async fn find_applicant(&self, aid: &ApplyId) -> Result<Option<Applicant>> {
+        let applicant: Option<Applicant> = sqlx::query_as!(
+            Applicant,
+            r#"
+            SELECT apply.uid, volunteer.gid
+            FROM apply
+            INNER JOIN volunteer ON apply.vid = volunteer.vid
+            WHERE apply.aid = ?
+            "#,
+            aid.to_string()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(applicant)
+    }
+}
@@ -3,8 +3,10 @@ use async_trait::async_trait;
 use sqlx::MySqlPool;
 
 use domain::model::{apply::ApplyId, user_account::user_id::UserId, volunteer::VolunteerId};
-use query_repository::activities::apply::{
-    Apply, ApplyRepository, PastVolunteerParticipantReadModel,
+use query_repository::{
+    activities::apply::{Apply, ApplyRepository, PastVolunteerParticipantReadModel},
+    error::QueryError,
+    pagination::{Page, PageRequest},
 };
 
 pub struct ApplyImpl {
@@ -19,69 +21,127 @@ impl ApplyImpl {
 
 #[async_trait]
 impl ApplyRepository for ApplyImpl {
-    async fn find_by_sid(&self, aid: &ApplyId) -> Result<Apply> {
-        let apply: Apply = sqlx::query_as!(
+    async fn find_by_sid(&self, aid: &ApplyId) -> Result<Apply, QueryError> {
+        let apply: Option<Apply> = sqlx::query_as!(
             Apply,
             r#"
             SELECT
-                aid, vid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool"
+                aid, vid, vsid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool", cancel_reason, cancelled_at, attendance_status, checked_in_at
             FROM apply
             WHERE aid = ?
             "#,
             aid.to_string()
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
-        Ok(apply)
+        apply.ok_or(QueryError::NotFound("apply"))
     }
 
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<Apply>> {
+    async fn find_by_gid(&self, gid: &UserId, page: &PageRequest) -> Result<Page<Apply>> {
         let apply = sqlx::query_as!(
             Apply,
             r#"
             SELECT
-                aid, vid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool"
+                aid, vid, vsid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool", cancel_reason, cancelled_at, attendance_status, checked_in_at
             FROM apply
             WHERE vid IN
                 (select vid from volunteer where gid = ?)
+            AND (? IS NULL OR aid < ?)
+            ORDER BY aid DESC
+            LIMIT ?
             "#,
-            gid.to_string()
+            gid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(apply)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM apply
+            WHERE vid IN
+                (select vid from volunteer where gid = ?)
+            "#,
+            gid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(apply, page, total_count, |apply| apply.aid.clone()))
     }
 
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Apply>> {
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Apply>> {
         let apply = sqlx::query_as!(
             Apply,
             r#"
             SELECT
-                aid, vid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool"
+                aid, vid, vsid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool", cancel_reason, cancelled_at, attendance_status, checked_in_at
             FROM apply
             WHERE uid = ?
+            AND (? IS NULL OR aid < ?)
+            ORDER BY aid DESC
+            LIMIT ?
             "#,
-            uid.to_string()
+            uid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(apply)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM apply
+            WHERE uid = ?
+            "#,
+            uid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(apply, page, total_count, |apply| apply.aid.clone()))
     }
 
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<Apply>> {
+    async fn find_by_vid(&self, vid: &VolunteerId, page: &PageRequest) -> Result<Page<Apply>> {
         let apply = sqlx::query_as!(
             Apply,
             r#"
             SELECT
-                aid, vid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool"
+                aid, vid, vsid, uid, applied_at, as_group as "as_group: bool", allowed_status, decided_at, is_sent as "is_sent: bool", cancel_reason, cancelled_at, attendance_status, checked_in_at
             FROM apply
             WHERE vid = ?
+            AND (? IS NULL OR aid < ?)
+            ORDER BY aid DESC
+            LIMIT ?
             "#,
-            vid.to_string()
+            vid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(apply)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM apply
+            WHERE vid = ?
+            "#,
+            vid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(apply, page, total_count, |apply| apply.aid.clone()))
     }
 
     async fn find_past_volunteer_participants(
@@ -91,12 +151,12 @@ impl ApplyRepository for ApplyImpl {
         let participants = sqlx::query_as!(
             PastVolunteerParticipantReadModel,
             r#"
-            SELECT uid, name, gender as "gender: u8", birthday
+            SELECT uid, name, gender as "gender: u8", birthday as "birthday?"
             FROM participant_account
             WHERE uid IN (
                 SELECT uid
                 FROM apply
-                WHERE vid = ? AND allowed_status = 1
+                WHERE vid = ? AND attendance_status = 1
             )
             "#,
             vid.to_string()
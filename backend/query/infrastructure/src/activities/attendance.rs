@@ -0,0 +1,61 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::MySqlPool;
+
+use domain::model::{user_account::user_id::UserId, volunteer::VolunteerId};
+use query_repository::{
+    activities::attendance::{AttendanceRepository, CheckInCode, ParticipantReliability},
+    error::QueryError,
+};
+
+pub struct AttendanceImpl {
+    pool: MySqlPool,
+}
+
+impl AttendanceImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AttendanceRepository for AttendanceImpl {
+    async fn find_check_in_code(&self, vid: &VolunteerId) -> Result<CheckInCode, QueryError> {
+        let code: Option<CheckInCode> = sqlx::query_as!(
+            CheckInCode,
+            r#"
+            SELECT c.vid, v.gid, c.code, c.issued_at, c.expires_at
+            FROM check_in_code as c
+            INNER JOIN volunteer as v ON c.vid = v.vid
+            WHERE c.vid = ?
+            "#,
+            vid.to_string()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        code.ok_or(QueryError::NotFound("check_in_code"))
+    }
+
+    async fn find_reliability(&self, uid: &UserId) -> Result<ParticipantReliability> {
+        let counts = sqlx::query!(
+            r#"
+            SELECT
+                CAST(COALESCE(SUM(attendance_status = 1), 0) AS SIGNED) as "attended_count!: i64",
+                CAST(COALESCE(SUM(attendance_status = 2), 0) AS SIGNED) as "no_show_count!: i64",
+                CAST(COALESCE(SUM(allowed_status = 3), 0) AS SIGNED) as "cancelled_count!: i64"
+            FROM apply
+            WHERE uid = ?
+            "#,
+            uid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(ParticipantReliability::new(
+            uid.to_string(),
+            counts.attended_count,
+            counts.no_show_count,
+            counts.cancelled_count,
+        ))
+    }
+}
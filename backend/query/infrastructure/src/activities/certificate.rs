@@ -0,0 +1,80 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::MySqlPool;
+
+use domain::model::{apply::ApplyId, user_account::user_id::UserId};
+use query_repository::{
+    activities::certificate::{CertificateRepository, CertificateSource, VolunteerHours},
+    error::QueryError,
+};
+
+pub struct CertificateImpl {
+    pool: MySqlPool,
+}
+
+impl CertificateImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CertificateRepository for CertificateImpl {
+    async fn find_source(&self, aid: &ApplyId) -> Result<CertificateSource, QueryError> {
+        let source: Option<CertificateSource> = sqlx::query_as!(
+            CertificateSource,
+            r#"
+            SELECT
+                apply.aid, apply.uid, volunteer.gid, volunteer.vid, participant_account.name as participant_name,
+                group_account.name as group_name, group_account.representative_name,
+                volunteer.title as volunteer_title, volunteer_session.start_at, volunteer_session.finish_at,
+                apply.attendance_status as "attendance_status: u8"
+            FROM apply
+            INNER JOIN volunteer ON apply.vid = volunteer.vid
+            INNER JOIN volunteer_session ON apply.vsid = volunteer_session.vsid
+            INNER JOIN group_account ON volunteer.gid = group_account.gid
+            INNER JOIN participant_account ON apply.uid = participant_account.uid
+            WHERE apply.aid = ?
+            "#,
+            aid.to_string()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        source.ok_or(QueryError::NotFound("apply"))
+    }
+
+    async fn find_hours(&self, uid: &UserId) -> Result<Vec<VolunteerHours>> {
+        let activities = sqlx::query!(
+            r#"
+            SELECT
+                apply.aid, volunteer.vid, volunteer.title, group_account.name as group_name,
+                volunteer_session.start_at, volunteer_session.finish_at
+            FROM apply
+            INNER JOIN volunteer ON apply.vid = volunteer.vid
+            INNER JOIN volunteer_session ON apply.vsid = volunteer_session.vsid
+            INNER JOIN group_account ON volunteer.gid = group_account.gid
+            WHERE apply.uid = ?
+            AND apply.attendance_status = 1
+            AND volunteer_session.finish_at < now()
+            ORDER BY volunteer_session.start_at DESC
+            "#,
+            uid.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(activities
+            .into_iter()
+            .map(|activity| {
+                VolunteerHours::new(
+                    activity.aid,
+                    activity.vid,
+                    activity.title,
+                    activity.group_name,
+                    activity.start_at,
+                    activity.finish_at,
+                )
+            })
+            .collect())
+    }
+}
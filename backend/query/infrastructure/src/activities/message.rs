@@ -0,0 +1,185 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::MySqlPool;
+
+use domain::model::{message::ThreadId, user_account::user_id::UserId};
+use query_repository::{
+    activities::message::{Message, MessageRepository, MessageThread},
+    error::QueryError,
+    pagination::{Page, PageRequest},
+};
+
+pub struct MessageImpl {
+    pool: MySqlPool,
+}
+
+impl MessageImpl {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MessageRepository for MessageImpl {
+    async fn find_thread(
+        &self,
+        tid: &ThreadId,
+        viewer: &UserId,
+    ) -> Result<MessageThread, QueryError> {
+        // 未読数は, 相手が送信した削除されていないメッセージのうち, 最後に読んだメッセージより後のもの
+        let thread: Option<MessageThread> = sqlx::query_as!(
+            MessageThread,
+            r#"
+            SELECT
+                t.tid, t.aid, t.sid, t.vid, t.gid, t.uid, t.created_at, t.last_message_at,
+                (
+                    SELECT COUNT(*)
+                    FROM message as m
+                    LEFT JOIN message_read as r ON r.tid = m.tid AND r.uid = ?
+                    WHERE m.tid = t.tid AND m.sender <> ? AND m.is_deleted = false
+                    AND (r.last_read_mid IS NULL OR m.mid > r.last_read_mid)
+                ) as "unread_count!: i64"
+            FROM message_thread as t
+            WHERE t.tid = ?
+            "#,
+            viewer.to_string(),
+            viewer.to_string(),
+            tid.to_string()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        thread.ok_or(QueryError::NotFound("message_thread"))
+    }
+
+    async fn find_threads(
+        &self,
+        viewer: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<MessageThread>> {
+        let threads = sqlx::query_as!(
+            MessageThread,
+            r#"
+            SELECT
+                t.tid, t.aid, t.sid, t.vid, t.gid, t.uid, t.created_at, t.last_message_at,
+                (
+                    SELECT COUNT(*)
+                    FROM message as m
+                    LEFT JOIN message_read as r ON r.tid = m.tid AND r.uid = ?
+                    WHERE m.tid = t.tid AND m.sender <> ? AND m.is_deleted = false
+                    AND (r.last_read_mid IS NULL OR m.mid > r.last_read_mid)
+                ) as "unread_count!: i64"
+            FROM message_thread as t
+            WHERE (t.gid = ? OR t.uid = ?)
+            AND (? IS NULL OR t.tid < ?)
+            ORDER BY t.tid DESC
+            LIMIT ?
+            "#,
+            viewer.to_string(),
+            viewer.to_string(),
+            viewer.to_string(),
+            viewer.to_string(),
+            page.after,
+            page.after,
+            page.limit()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM message_thread
+            WHERE gid = ? OR uid = ?
+            "#,
+            viewer.to_string(),
+            viewer.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(threads, page, total_count, |thread| {
+            thread.tid.clone()
+        }))
+    }
+
+    async fn find_messages(&self, tid: &ThreadId, page: &PageRequest) -> Result<Page<Message>> {
+        // 既読は, スレッドの相手が最後に読んだメッセージ以前かどうか
+        let messages = sqlx::query!(
+            r#"
+            SELECT
+                m.mid, m.tid, m.sender, m.body, m.sent_at, m.is_deleted as "is_deleted: bool", m.deleted_at,
+                EXISTS(
+                    SELECT 1
+                    FROM message_read as r
+                    WHERE r.tid = m.tid AND r.uid <> m.sender AND r.last_read_mid >= m.mid
+                ) as "is_read!: bool"
+            FROM message as m
+            WHERE m.tid = ?
+            AND (? IS NULL OR m.mid < ?)
+            ORDER BY m.mid DESC
+            LIMIT ?
+            "#,
+            tid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM message
+            WHERE tid = ?
+            "#,
+            tid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        let messages: Vec<Message> = messages
+            .into_iter()
+            .map(|m| {
+                Message::new(
+                    m.mid,
+                    m.tid,
+                    m.sender,
+                    m.body,
+                    m.sent_at,
+                    m.is_deleted,
+                    m.deleted_at,
+                    m.is_read,
+                )
+            })
+            .collect();
+        Ok(Page::new(messages, page, total_count, |message| {
+            message.mid.clone()
+        }))
+    }
+
+    async fn count_unread(&self, viewer: &UserId) -> Result<i64> {
+        let unread_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM message as m
+            INNER JOIN message_thread as t ON m.tid = t.tid
+            LEFT JOIN message_read as r ON r.tid = m.tid AND r.uid = ?
+            WHERE (t.gid = ? OR t.uid = ?)
+            AND m.sender <> ? AND m.is_deleted = false
+            AND (r.last_read_mid IS NULL OR m.mid > r.last_read_mid)
+            "#,
+            viewer.to_string(),
+            viewer.to_string(),
+            viewer.to_string(),
+            viewer.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(unread_count)
+    }
+}
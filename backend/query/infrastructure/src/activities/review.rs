@@ -1,10 +1,17 @@
+use std::str::FromStr;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use sqlx::{MySqlPool, Row};
 
 use domain::model::{user_account::user_id::UserId, volunteer::VolunteerId};
-use query_repository::activities::review::{
-    ParticipantReviewPointAverage, ParticipantReviewRepository, Review, VolunteerReviewRepository,
+use query_repository::{
+    activities::review::{
+        ParticipantReviewPointAverage, ParticipantReviewRepository, Review, ReviewCursor,
+        VolunteerReviewRepository,
+    },
+    error::QueryError,
+    pagination::{Page, PageRequest},
 };
 
 pub struct ReviewImpl {
@@ -19,8 +26,8 @@ impl ReviewImpl {
 
 #[async_trait]
 impl ParticipantReviewRepository for ReviewImpl {
-    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review> {
-        let review: Review = sqlx::query_as!(
+    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review, QueryError> {
+        let review: Option<Review> = sqlx::query_as!(
             Review,
             r#"
             SELECT * FROM participant_review WHERE uid = ? and vid = ?
@@ -28,35 +35,75 @@ impl ParticipantReviewRepository for ReviewImpl {
             uid.to_string(),
             vid.to_string()
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
-        Ok(review)
+        review.ok_or(QueryError::NotFound("review"))
     }
 
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Review>> {
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Review>> {
+        let after: Option<ReviewCursor> =
+            page.after.as_deref().map(ReviewCursor::from_str).transpose()?;
         let review = sqlx::query_as!(
             Review,
             r#"
             SELECT * FROM participant_review WHERE uid = ?
+            AND (? IS NULL OR (vid, uid) < (?, ?))
+            ORDER BY vid DESC, uid DESC
+            LIMIT ?
             "#,
-            uid.to_string()
+            uid.to_string(),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.uid.clone()),
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(review)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM participant_review WHERE uid = ?
+            "#,
+            uid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(review, page, total_count, |review| ReviewCursor::of(review).to_string()))
     }
 
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<Review>> {
+    async fn find_by_vid(&self, vid: &VolunteerId, page: &PageRequest) -> Result<Page<Review>> {
+        let after: Option<ReviewCursor> =
+            page.after.as_deref().map(ReviewCursor::from_str).transpose()?;
         let review = sqlx::query_as!(
             Review,
             r#"
             SELECT * FROM participant_review WHERE vid = ?
+            AND (? IS NULL OR (vid, uid) < (?, ?))
+            ORDER BY vid DESC, uid DESC
+            LIMIT ?
             "#,
-            vid.to_string()
+            vid.to_string(),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.uid.clone()),
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(review)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM participant_review WHERE vid = ?
+            "#,
+            vid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(review, page, total_count, |review| ReviewCursor::of(review).to_string()))
     }
 
     async fn find_by_uids(&self, uids: &[UserId]) -> Result<Vec<ParticipantReviewPointAverage>> {
@@ -102,8 +149,8 @@ impl ParticipantReviewRepository for ReviewImpl {
 
 #[async_trait]
 impl VolunteerReviewRepository for ReviewImpl {
-    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review> {
-        let review: Review = sqlx::query_as!(
+    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review, QueryError> {
+        let review: Option<Review> = sqlx::query_as!(
             Review,
             r#"
             SELECT * FROM volunteer_review WHERE uid = ? and vid = ?
@@ -111,49 +158,111 @@ impl VolunteerReviewRepository for ReviewImpl {
             uid.to_string(),
             vid.to_string()
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
-        Ok(review)
+        review.ok_or(QueryError::NotFound("review"))
     }
 
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Review>> {
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Review>> {
+        let after: Option<ReviewCursor> =
+            page.after.as_deref().map(ReviewCursor::from_str).transpose()?;
         let review = sqlx::query_as!(
             Review,
             r#"
             SELECT * FROM volunteer_review WHERE uid = ?
+            AND (? IS NULL OR (vid, uid) < (?, ?))
+            ORDER BY vid DESC, uid DESC
+            LIMIT ?
             "#,
-            uid.to_string()
+            uid.to_string(),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.uid.clone()),
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(review)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM volunteer_review WHERE uid = ?
+            "#,
+            uid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(review, page, total_count, |review| ReviewCursor::of(review).to_string()))
     }
 
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<Review>> {
+    async fn find_by_vid(&self, vid: &VolunteerId, page: &PageRequest) -> Result<Page<Review>> {
+        let after: Option<ReviewCursor> =
+            page.after.as_deref().map(ReviewCursor::from_str).transpose()?;
         let review = sqlx::query_as!(
             Review,
             r#"
             SELECT * FROM volunteer_review WHERE vid = ?
+            AND (? IS NULL OR (vid, uid) < (?, ?))
+            ORDER BY vid DESC, uid DESC
+            LIMIT ?
             "#,
-            vid.to_string()
+            vid.to_string(),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.uid.clone()),
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(review)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM volunteer_review WHERE vid = ?
+            "#,
+            vid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(review, page, total_count, |review| ReviewCursor::of(review).to_string()))
     }
 
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<Review>> {
+    async fn find_by_gid(&self, gid: &UserId, page: &PageRequest) -> Result<Page<Review>> {
+        let after: Option<ReviewCursor> =
+            page.after.as_deref().map(ReviewCursor::from_str).transpose()?;
         let review = sqlx::query_as!(
             Review,
             r#"
             SELECT * FROM volunteer_review WHERE vid IN (
                 SELECT vid FROM volunteer WHERE gid = ?
-            );
+            )
+            AND (? IS NULL OR (vid, uid) < (?, ?))
+            ORDER BY vid DESC, uid DESC
+            LIMIT ?
             "#,
-            gid.to_string()
+            gid.to_string(),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.vid.clone()),
+            after.as_ref().map(|cursor| cursor.uid.clone()),
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(review)
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM volunteer_review WHERE vid IN (
+                SELECT vid FROM volunteer WHERE gid = ?
+            )
+            "#,
+            gid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(review, page, total_count, |review| ReviewCursor::of(review).to_string()))
     }
 }
@@ -1,9 +1,60 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveDateTime};
 use sqlx::MySqlPool;
 
-use domain::model::{scout::ScoutId, user_account::user_id::UserId, volunteer::VolunteerId};
-use query_repository::activities::scout::{Scout, ScoutFromGroup, ScoutRepository};
+use domain::model::{
+    scout::{ScoutId, ScoutStatus},
+    user_account::user_id::UserId,
+    volunteer::VolunteerId,
+};
+use query_repository::{
+    activities::scout::{Scout, ScoutFromGroup, ScoutRepository},
+    error::QueryError,
+    pagination::{Page, PageRequest},
+};
+
+/// 締切日を考慮する前のスカウトの行
+struct ScoutRow {
+    sid: String,
+    vid: String,
+    uid: String,
+    message: String,
+    scouted_at: NaiveDateTime,
+    is_read: bool,
+    is_sent: bool,
+    sent_at: Option<NaiveDateTime>,
+    is_denied: bool,
+    denied_at: Option<NaiveDateTime>,
+    status: u8,
+    deadline_on: NaiveDate,
+    today: NaiveDate,
+}
+
+impl ScoutRow {
+    fn into_scout(self) -> Result<Scout> {
+        Ok(Scout::new(
+            self.sid,
+            self.vid,
+            self.uid,
+            self.message,
+            self.scouted_at,
+            self.is_read,
+            self.is_sent,
+            self.sent_at,
+            self.is_denied,
+            self.denied_at,
+            effective_status(self.status, self.deadline_on, self.today)?,
+        ))
+    }
+}
+
+/// 返答待ちのまま募集締切日を過ぎたスカウトを期限切れとした状態の文字列
+fn effective_status(status: u8, deadline_on: NaiveDate, today: NaiveDate) -> Result<String> {
+    Ok(ScoutStatus::from_u8(status)?
+        .effective(deadline_on, today)
+        .to_string())
+}
 
 pub struct ScoutImpl {
     pool: MySqlPool,
@@ -17,98 +68,180 @@ impl ScoutImpl {
 
 #[async_trait]
 impl ScoutRepository for ScoutImpl {
-    async fn find_by_sid(&self, sid: &ScoutId) -> Result<Scout> {
-        let scout: Scout = sqlx::query_as!(
-            Scout,
+    async fn find_by_sid(&self, sid: &ScoutId) -> Result<Scout, QueryError> {
+        let scout: Option<ScoutRow> = sqlx::query_as!(
+            ScoutRow,
             r#"
             SELECT
-                sid, vid, uid, message, scouted_at, is_read as "is_read: bool", is_sent as "is_sent: bool", sent_at, is_denied as "is_denied: bool", denied_at
+                sid, scout.vid, uid, scout.message, scouted_at, is_read as "is_read: bool", is_sent as "is_sent: bool", sent_at, is_denied as "is_denied: bool", denied_at,
+                scout.status as "status: u8", volunteer.deadline_on, CURDATE() as "today!: NaiveDate"
             FROM scout
+            INNER JOIN volunteer ON scout.vid = volunteer.vid
             WHERE sid = ?
             "#,
             sid.to_string()
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
-        Ok(scout)
+        Ok(scout.ok_or(QueryError::NotFound("scout"))?.into_scout()?)
     }
 
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<Scout>> {
-        let scout = sqlx::query_as!(
-            Scout,
+    async fn find_by_gid(&self, gid: &UserId, page: &PageRequest) -> Result<Page<Scout>> {
+        let scout: Vec<Scout> = sqlx::query_as!(
+            ScoutRow,
             r#"
             SELECT
-                sid, vid, uid, message, scouted_at, is_read as "is_read: bool", is_sent as "is_sent: bool", sent_at, is_denied as "is_denied: bool", denied_at
+                sid, scout.vid, uid, scout.message, scouted_at, is_read as "is_read: bool", is_sent as "is_sent: bool", sent_at, is_denied as "is_denied: bool", denied_at,
+                scout.status as "status: u8", volunteer.deadline_on, CURDATE() as "today!: NaiveDate"
+            FROM scout
+            INNER JOIN volunteer ON scout.vid = volunteer.vid
+            WHERE volunteer.gid = ?
+            AND (? IS NULL OR sid < ?)
+            ORDER BY sid DESC
+            LIMIT ?
+            "#,
+            gid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(ScoutRow::into_scout)
+        .collect::<Result<_>>()?;
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
             FROM scout
             WHERE vid IN
                 (select vid from volunteer where gid = ?)
             "#,
             gid.to_string()
         )
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(scout)
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(scout, page, total_count, |scout| scout.sid.clone()))
     }
 
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Scout>> {
-        let scout = sqlx::query_as!(
-            Scout,
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Scout>> {
+        let scout: Vec<Scout> = sqlx::query_as!(
+            ScoutRow,
             r#"
             SELECT
-                sid, vid, uid, message, scouted_at, is_read as "is_read: bool", is_sent as "is_sent: bool", sent_at, is_denied as "is_denied: bool", denied_at
+                sid, scout.vid, uid, scout.message, scouted_at, is_read as "is_read: bool", is_sent as "is_sent: bool", sent_at, is_denied as "is_denied: bool", denied_at,
+                scout.status as "status: u8", volunteer.deadline_on, CURDATE() as "today!: NaiveDate"
             FROM scout
+            INNER JOIN volunteer ON scout.vid = volunteer.vid
             WHERE uid = ?
             AND is_denied = false
+            AND scout.status <> ?
+            AND (? IS NULL OR sid < ?)
+            ORDER BY sid DESC
+            LIMIT ?
             "#,
-            uid.to_string()
+            uid.to_string(),
+            ScoutStatus::Withdrawn.to_u8(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
-        .await?;
-        Ok(scout)
+        .await?
+        .into_iter()
+        .map(ScoutRow::into_scout)
+        .collect::<Result<_>>()?;
+
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM scout
+            WHERE uid = ?
+            AND is_denied = false
+            AND status <> ?
+            "#,
+            uid.to_string(),
+            ScoutStatus::Withdrawn.to_u8()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(Page::new(scout, page, total_count, |scout| scout.sid.clone()))
     }
 
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<ScoutFromGroup>> {
+    async fn find_by_vid(
+        &self,
+        vid: &VolunteerId,
+        page: &PageRequest,
+    ) -> Result<Page<ScoutFromGroup>> {
         let scout = sqlx::query!(
             r#"
             SELECT
-                s.sid, s.vid, s.uid, s.message, s.scouted_at, s.is_read as "is_read: bool", s.is_sent as "is_sent: bool", s.sent_at, s.is_denied as "is_denied: bool", s.denied_at, p.name, p.gender as "gender: u8", p.birthday, AVG(r.point) as point
+                s.sid, s.vid, s.uid, s.message, s.scouted_at, s.is_read as "is_read: bool", s.is_sent as "is_sent: bool", s.sent_at, s.is_denied as "is_denied: bool", s.denied_at,
+                s.status as "status: u8", v.deadline_on, CURDATE() as "today!: NaiveDate",
+                p.name, p.gender as "gender: u8", p.birthday, AVG(r.point) as point
             FROM scout as s
+            INNER JOIN volunteer as v ON s.vid = v.vid
             INNER JOIN participant_account as p ON s.uid = p.uid
             LEFT JOIN participant_review as r ON p.uid = r.uid
             WHERE s.vid = ?
+            AND (? IS NULL OR s.sid < ?)
             GROUP BY s.sid
+            ORDER BY s.sid DESC
+            LIMIT ?
             "#,
-            vid.to_string()
+            vid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM scout as s
+            INNER JOIN participant_account as p ON s.uid = p.uid
+            WHERE s.vid = ?
+            "#,
+            vid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
         let scout = scout
             .into_iter()
-            .map(|s|{
-                println!("aaa");
-                ScoutFromGroup {
-                sid: s.sid,
-                vid: s.vid,
-                uid: s.uid,
-                message: s.message,
-                scouted_at: s.scouted_at,
-                is_read: s.is_read,
-                is_sent: s.is_sent,
-                sent_at: s.sent_at,
-                is_denied: s.is_denied,
-                denied_at: s.denied_at,
-                name: s.name,
-                gender: s.gender,
-                birthday: s.birthday,
-                point: match s.point {
-                    Some(p) => {
-                        Some((p.to_string().parse::<f32>().unwrap() * 100.0).round() / 100.0)
-                    }
-                    None => None,
-                },
-            }})
-            .collect();
-        Ok(scout)
+            .map(|s| {
+                Ok(ScoutFromGroup {
+                    sid: s.sid,
+                    vid: s.vid,
+                    uid: s.uid,
+                    message: s.message,
+                    scouted_at: s.scouted_at,
+                    is_read: s.is_read,
+                    is_sent: s.is_sent,
+                    sent_at: s.sent_at,
+                    is_denied: s.is_denied,
+                    denied_at: s.denied_at,
+                    status: effective_status(s.status, s.deadline_on, s.today)?,
+                    name: s.name,
+                    gender: s.gender,
+                    birthday: Some(s.birthday),
+                    point: match s.point {
+                        Some(p) => {
+                            Some((p.to_string().parse::<f32>().unwrap() * 100.0).round() / 100.0)
+                        }
+                        None => None,
+                    },
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Page::new(scout, page, total_count, |scout| scout.sid.clone()))
     }
 }
@@ -2,22 +2,26 @@ use std::str::FromStr;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use domain::{
-    consts::{
-        conditions::ConditionMap, region::RegionMap, target_status::TargetStatusMap,
-        themes::ThemeMap,
-    },
-    model::{
-        condition::Condition, region::Region, target_status::TargetStatus, theme::Theme,
-        user_account::user_id::UserId, volunteer::VolunteerId,
-    },
+use domain::model::{
+    apply::ApplyStatus, location::GeoPoint, user_account::user_id::UserId,
+    volunteer::VolunteerId,
 };
 use futures::future;
-use query_repository::activities::volunteer::{
-    VolunteerElementsReadModel, VolunteerQueryRepository, VolunteerReadModel,
+use query_repository::{
+    activities::volunteer::{
+        RecommendationCursor, RecommendationProfile, RecommendationSignal, RecommendedVolunteer,
+        SessionActivityReadModel, VolunteerElementsReadModel, VolunteerQueryRepository,
+        VolunteerReadModel, VolunteerSearchCursor, VolunteerSearchInput, VolunteerSessionReadModel,
+    },
+    error::QueryError,
+    pagination::{Page, PageRequest},
+};
+use sqlx::{mysql::MySqlRow, types::Json, MySql, MySqlPool, QueryBuilder, Row};
+
+use crate::search::{
+    local_time, push_availability_overlap, push_in, push_weight_sum, region_names, ElementNames,
+    ElementRow, SearchFilter, VOLUNTEER_TABLES,
 };
-use serde_json::Value;
-use sqlx::{query, MySqlPool, Row};
 
 pub struct VolunteerQueryRepositoryImpl {
     pool: MySqlPool,
@@ -27,6 +31,194 @@ impl VolunteerQueryRepositoryImpl {
     pub fn new(pool: MySqlPool) -> Self {
         Self { pool }
     }
+
+    /// ボランティアIDのページから, ボランティア情報のページを取得する
+    async fn find_page(&self, vids: Page<String>) -> Result<Page<VolunteerReadModel>> {
+        let volunteers = future::try_join_all(vids.edges.iter().map(|(_, vid)| async move {
+            let vid: VolunteerId = VolunteerId::from_str(vid)?;
+            Ok::<VolunteerReadModel, anyhow::Error>(self.find_by_id(&vid).await?)
+        }))
+        .await?;
+
+        Ok(Page {
+            edges: vids
+                .edges
+                .into_iter()
+                .map(|(cursor, _)| cursor)
+                .zip(volunteers)
+                .collect(),
+            has_next_page: vids.has_next_page,
+            total_count: vids.total_count,
+        })
+    }
+
+    /// 回IDで一致する回を取得する
+    async fn find_session(&self, vsid: &str) -> Result<VolunteerSessionReadModel> {
+        let session = sqlx::query!(
+            r#"
+            SELECT
+                vsid, vid, start_at, finish_at, recruited_num,
+                CAST(COALESCE((
+                    SELECT SUM(CASE WHEN apply.as_group THEN GREATEST((SELECT COUNT(*) FROM group_participants WHERE gpid = apply.aid), 1) ELSE 1 END)
+                    FROM apply WHERE apply.vsid = volunteer_session.vsid AND apply.allowed_status = ?
+                ), 0) AS UNSIGNED) as "approved_num!: u64"
+            FROM volunteer_session WHERE vsid = ?
+            "#,
+            ApplyStatus::Approved.to_u8(),
+            vsid
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(QueryError::NotFound("volunteer_session"))?;
+
+        Ok(VolunteerSessionReadModel {
+            vsid: session.vsid,
+            vid: session.vid,
+            start_at: session.start_at,
+            finish_at: session.finish_at,
+            recruited_num: session.recruited_num,
+            approved_num: session.approved_num as u32,
+        })
+    }
+
+    /// 回IDのページから, 回とボランティア情報のページを取得する
+    async fn find_session_page(&self, vsids: Page<String>) -> Result<Page<SessionActivityReadModel>> {
+        let activities = future::try_join_all(vsids.edges.iter().map(|(_, vsid)| async move {
+            let session: VolunteerSessionReadModel = self.find_session(vsid).await?;
+            let vid: VolunteerId = VolunteerId::from_str(&session.vid)?;
+            let volunteer: VolunteerReadModel = self.find_by_id(&vid).await?;
+            Ok::<SessionActivityReadModel, anyhow::Error>(SessionActivityReadModel { session, volunteer })
+        }))
+        .await?;
+
+        Ok(Page {
+            edges: vsids
+                .edges
+                .into_iter()
+                .map(|(cursor, _)| cursor)
+                .zip(activities)
+                .collect(),
+            has_next_page: vsids.has_next_page,
+            total_count: vsids.total_count,
+        })
+    }
+
+    /// 参加者が登録した地域・要素と, お気に入り, 応募, 評価したボランティアの地域・要素から嗜好を求める
+    async fn find_recommendation_profile(&self, pid: &UserId) -> Result<RecommendationProfile> {
+        let preferred_elements = sqlx::query!(
+            r#"
+            SELECT eid FROM participant_element WHERE uid = ?
+            "#,
+            pid.to_string()
+        )
+        .fetch_all(&self.pool);
+        let favorite_elements = sqlx::query!(
+            r#"
+            SELECT volunteer_element.eid
+            FROM favorite INNER JOIN volunteer_element ON favorite.vid = volunteer_element.vid
+            WHERE favorite.uid = ?
+            "#,
+            pid.to_string()
+        )
+        .fetch_all(&self.pool);
+        // 棄却・辞退した応募は嗜好に含めない
+        let applied_elements = sqlx::query!(
+            r#"
+            SELECT volunteer_element.eid
+            FROM apply INNER JOIN volunteer_element ON apply.vid = volunteer_element.vid
+            WHERE apply.uid = ? AND apply.allowed_status IN (?, ?)
+            "#,
+            pid.to_string(),
+            ApplyStatus::Pending.to_u8(),
+            ApplyStatus::Approved.to_u8()
+        )
+        .fetch_all(&self.pool);
+        let reviewed_elements = sqlx::query!(
+            r#"
+            SELECT volunteer_element.eid, volunteer_review.point
+            FROM volunteer_review INNER JOIN volunteer_element ON volunteer_review.vid = volunteer_element.vid
+            WHERE volunteer_review.uid = ?
+            "#,
+            pid.to_string()
+        )
+        .fetch_all(&self.pool);
+        let (preferred_elements, favorite_elements, applied_elements, reviewed_elements) =
+            future::try_join4(
+                preferred_elements,
+                favorite_elements,
+                applied_elements,
+                reviewed_elements,
+            )
+            .await?;
+
+        let preferred_regions = sqlx::query!(
+            r#"
+            SELECT rid FROM participant_region WHERE uid = ?
+            "#,
+            pid.to_string()
+        )
+        .fetch_all(&self.pool);
+        let favorite_regions = sqlx::query!(
+            r#"
+            SELECT volunteer_region.rid
+            FROM favorite INNER JOIN volunteer_region ON favorite.vid = volunteer_region.vid
+            WHERE favorite.uid = ?
+            "#,
+            pid.to_string()
+        )
+        .fetch_all(&self.pool);
+        let applied_regions = sqlx::query!(
+            r#"
+            SELECT volunteer_region.rid
+            FROM apply INNER JOIN volunteer_region ON apply.vid = volunteer_region.vid
+            WHERE apply.uid = ? AND apply.allowed_status IN (?, ?)
+            "#,
+            pid.to_string(),
+            ApplyStatus::Pending.to_u8(),
+            ApplyStatus::Approved.to_u8()
+        )
+        .fetch_all(&self.pool);
+        let reviewed_regions = sqlx::query!(
+            r#"
+            SELECT volunteer_region.rid, volunteer_review.point
+            FROM volunteer_review INNER JOIN volunteer_region ON volunteer_review.vid = volunteer_region.vid
+            WHERE volunteer_review.uid = ?
+            "#,
+            pid.to_string()
+        )
+        .fetch_all(&self.pool);
+        let (preferred_regions, favorite_regions, applied_regions, reviewed_regions) =
+            future::try_join4(preferred_regions, favorite_regions, applied_regions, reviewed_regions)
+                .await?;
+
+        let mut profile: RecommendationProfile = RecommendationProfile::default();
+        for element in preferred_elements {
+            profile.add_element(element.eid, RecommendationSignal::Preference);
+        }
+        for element in favorite_elements {
+            profile.add_element(element.eid, RecommendationSignal::Favorite);
+        }
+        for element in applied_elements {
+            profile.add_element(element.eid, RecommendationSignal::Apply);
+        }
+        for element in reviewed_elements {
+            profile.add_element(element.eid, RecommendationSignal::Review(element.point as i64));
+        }
+        for region in preferred_regions {
+            profile.add_region(region.rid as u8, RecommendationSignal::Preference);
+        }
+        for region in favorite_regions {
+            profile.add_region(region.rid as u8, RecommendationSignal::Favorite);
+        }
+        for region in applied_regions {
+            profile.add_region(region.rid as u8, RecommendationSignal::Apply);
+        }
+        for region in reviewed_regions {
+            profile.add_region(region.rid as u8, RecommendationSignal::Review(region.point as i64));
+        }
+
+        Ok(profile)
+    }
 }
 
 #[async_trait]
@@ -51,100 +243,43 @@ impl VolunteerQueryRepository for VolunteerQueryRepositoryImpl {
 
         let (regions, elements) = future::try_join(region_query, element_query).await?;
 
-        let regions_map = RegionMap::new().regions_index_to_name;
-        let regions = regions
-            .iter()
-            .map(|r| regions_map.get(&(r.rid as usize)).unwrap().to_string())
-            .collect();
-
-        let themes_map = ThemeMap::new().themes_id_to_name;
-        let conditions_map = ConditionMap::new().conditions_id_to_name;
-        let target_status_map = TargetStatusMap::new().target_statuses_index_to_name;
-
-        let themes = elements
-            .iter()
-            .filter_map(|e| {
-                if !e.is_need && themes_map.contains_key(&e.eid) {
-                    Some(themes_map.get(&e.eid).unwrap().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let required_themes = elements
-            .iter()
-            .filter_map(|e| {
-                if e.is_need && themes_map.contains_key(&e.eid) {
-                    Some(themes_map.get(&e.eid).unwrap().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let conditions = elements
-            .iter()
-            .filter_map(|e| {
-                if !e.is_need && conditions_map.contains_key(&e.eid) {
-                    Some(conditions_map.get(&e.eid).unwrap().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let required_conditions = elements
-            .iter()
-            .filter_map(|e| {
-                if e.is_need && conditions_map.contains_key(&e.eid) {
-                    Some(conditions_map.get(&e.eid).unwrap().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let target_status = elements
-            .iter()
-            .filter_map(|e| {
-                if target_status_map.contains_key(&e.eid) {
-                    Some(target_status_map.get(&e.eid).unwrap().to_string())
-                } else {
-                    None
-                }
-            })
+        let rids: Vec<u8> = regions.iter().map(|r| r.rid as u8).collect();
+        let elements: Vec<ElementRow> = elements
+            .into_iter()
+            .map(|e| ElementRow::new(e.eid, e.is_need))
             .collect();
+        let elements: ElementNames = ElementNames::new(&elements);
 
         let volunteer_elements = VolunteerElementsReadModel::new(
             vid.to_string(),
-            regions,
+            region_names(&rids),
             None,
-            themes,
-            required_themes,
-            conditions,
-            required_conditions,
-            target_status,
+            elements.themes,
+            elements.required_themes,
+            elements.conditions,
+            elements.required_conditions,
+            elements.target_status,
         );
 
         Ok(volunteer_elements)
     }
 
     ///vidで一致するボランティア情報の取得
-    async fn find_by_id(&self, vid: &VolunteerId) -> Result<VolunteerReadModel> {
+    async fn find_by_id(&self, vid: &VolunteerId) -> Result<VolunteerReadModel, QueryError> {
         let volunteer = sqlx::query!(
             r#"
             SELECT
-                vid, gid, title, message, overview, recruited_num, place, reward, start_at, finish_at, deadline_on, as_group as "as_group: bool", is_deleted as "is_deleted: bool", deleted_at, registered_at, updated_at
+                vid, gid, title, message, overview, recruited_num, place, reward, start_at, finish_at, recurrence, deadline_on, as_group as "as_group: bool", is_deleted as "is_deleted: bool", deleted_at, registered_at, updated_at
             FROM volunteer WHERE vid = ?
             "#,
             vid.to_string()
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(QueryError::NotFound("volunteer"))?;
 
         if volunteer.is_deleted {
-            return Err(anyhow::anyhow!("the volunteer is deleted"));
+            return Err(QueryError::Deleted("volunteer"));
         }
 
         let elements: VolunteerElementsReadModel = self.find_elements_by_id(&vid).await?;
@@ -156,6 +291,15 @@ impl VolunteerQueryRepository for VolunteerQueryRepositoryImpl {
         )
         .fetch_all(&self.pool)
         .await?;
+        let location = sqlx::query!(
+            r#"
+            SELECT ST_Latitude(location) AS "latitude!: f64", ST_Longitude(location) AS "longitude!: f64"
+            FROM volunteer_location WHERE vid = ?
+            "#,
+            vid.to_string()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
 
         let volunteer = VolunteerReadModel::new(
             volunteer.vid,
@@ -165,9 +309,12 @@ impl VolunteerQueryRepository for VolunteerQueryRepositoryImpl {
             volunteer.overview,
             volunteer.recruited_num.try_into().unwrap(),
             volunteer.place,
+            location.as_ref().map(|location| location.latitude),
+            location.as_ref().map(|location| location.longitude),
             volunteer.reward,
             volunteer.start_at,
             volunteer.finish_at,
+            volunteer.recurrence,
             volunteer.deadline_on,
             volunteer.as_group,
             volunteer.is_deleted,
@@ -190,503 +337,853 @@ impl VolunteerQueryRepository for VolunteerQueryRepositoryImpl {
         Ok(volunteer)
     }
 
+    ///vidで一致するボランティアの回の取得
+    async fn find_sessions(&self, vid: &VolunteerId) -> Result<Vec<VolunteerSessionReadModel>> {
+        let vsids = sqlx::query!(
+            r#"
+            SELECT vsid FROM volunteer_session WHERE vid = ? AND is_deleted = false
+            ORDER BY start_at
+            "#,
+            vid.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        future::try_join_all(vsids.iter().map(|v| self.find_session(&v.vsid))).await
+    }
+
     ///ボランティアの検索
     async fn find_by_elements(
         &self,
-        elements: &VolunteerElementsReadModel,
-        search_words: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        // OR条件の要素一覧
-        let mut or_elements: Vec<String> = Vec::new();
-
-        // OR条件の地域一覧
-        let mut or_regions = elements
-            .regions
-            .clone()
-            .iter()
-            .map(|r: &String| Region::from_str(r).unwrap().to_uint())
-            .collect::<Vec<u8>>();
-        let req_regions: Vec<u8> = match elements.required_regions.clone() {
-            Some(regions) => regions
-                .clone()
-                .iter()
-                .map(|r: &String| Region::from_str(r).unwrap().to_uint())
-                .collect::<Vec<u8>>(),
-            None => Vec::new(),
+        input: &VolunteerSearchInput,
+        uid: Option<&UserId>,
+        page: &PageRequest,
+    ) -> Result<Page<VolunteerReadModel>> {
+        let filter: SearchFilter = SearchFilter::new(&input.elements())?;
+        let near: Option<Near> = input
+            .near
+            .map(|near| {
+                Ok::<Near, anyhow::Error>(Near {
+                    center: near.center()?,
+                    radius_km: near.radius_km,
+                })
+            })
+            .transpose()?;
+        let uid: Option<String> = uid.map(|uid| uid.to_string());
+        let search_words = input.search_words();
+        // 検索ワードは全文検索の検索式としてバインドする
+        let search_query: Option<String> = if search_words.is_empty() {
+            None
+        } else {
+            Some(search_words.to_boolean_query())
         };
-        or_elements.extend(
-            elements
-                .themes
-                .iter()
-                .map(|r: &String| Theme::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
-        );
-        or_elements.extend(
-            elements
-                .conditions
-                .iter()
-                .map(|r: &String| Condition::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
+        let after: Option<VolunteerSearchCursor> = page
+            .after
+            .as_deref()
+            .map(VolunteerSearchCursor::from_str)
+            .transpose()?;
+
+        let mut count_query: QueryBuilder<MySql> =
+            QueryBuilder::new("SELECT COUNT(*) AS count FROM (");
+        push_ranked_query(
+            &mut count_query,
+            input,
+            &filter,
+            search_query.as_deref(),
+            &near,
+            uid.as_deref(),
         );
-        or_elements.extend(
-            elements
-                .target_status
-                .iter()
-                .map(|r: &String| TargetStatus::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
-        );
-
-        let mut req_elements: Vec<String> = Vec::new();
-        req_elements.extend(
-            elements
-                .required_themes
-                .iter()
-                .map(|r: &String| Theme::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
+        count_query.push(") AS ranked");
+        let total_count: i64 = count_query
+            .build()
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+
+        let mut page_query: QueryBuilder<MySql> = QueryBuilder::new("SELECT * FROM (");
+        push_ranked_query(
+            &mut page_query,
+            input,
+            &filter,
+            search_query.as_deref(),
+            &near,
+            uid.as_deref(),
         );
-        req_elements.extend(
-            elements
-                .required_conditions
-                .iter()
-                .map(|r: &String| Condition::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
-        );
-        if or_elements.len() == 0 {
-            or_elements.push("".to_string())
-        }
-        if or_regions.len() == 0 {
-            or_regions.push(100 as u8)
+        page_query.push(") AS ranked");
+        if let Some(cursor) = &after {
+            page_query.push(" WHERE ");
+            push_search_keyset(&mut page_query, cursor);
         }
+        page_query.push(" ORDER BY ");
+        page_query.push(order_by(&SEARCH_ORDER));
+        page_query.push(" LIMIT ");
+        page_query.push_bind(page.limit());
 
-        let search_words = search_words.replace("\\", "\\\\").replace("\"", "\\\"").replace("\'", "\\\'").replace("%", "\\%").replace("_", "\\_");
+        let volunteers = page_query.build().fetch_all(&self.pool).await?;
 
-        let query_str = format!(
-            r#"
-                SELECT
-                    volunteer.vid,
-                    volunteer.gid, title, message, overview, recruited_num, place, start_at, finish_at, as_group, reward, volunteer.is_deleted,
-                    volunteer.deleted_at, deadline_on, registered_at, updated_at, is_paid, GROUP_CONCAT(DISTINCT volunteer_photo.s3_key) AS s3_keys,
-                    GROUP_CONCAT(DISTINCT
-                        JSON_OBJECT(
-                            'eid', volunteer_element.eid,
-                            'is_need', volunteer_element.is_need
-                        )
-                    ) as eids,
-                    GROUP_CONCAT(DISTINCT
-                            volunteer_region.rid
-                    ) as rids,
-                    COUNT(DISTINCT CASE WHEN volunteer_element.eid IN ({}) THEN volunteer_element.eid END) AS eid_match_count,
-                    COUNT(DISTINCT CASE WHEN volunteer_region.rid IN ({}) THEN volunteer_region.rid END) AS rid_match_count,
-                    COUNT(DISTINCT CASE WHEN volunteer_element.is_need = true AND volunteer_element.eid IN ({}) THEN volunteer_element.eid END) AS req_eid_match_count
-                FROM
-                    volunteer
-                LEFT JOIN
-                    volunteer_element ON volunteer.vid = volunteer_element.vid
-                LEFT JOIN
-                    volunteer_region ON volunteer.vid = volunteer_region.vid
-                LEFT JOIN
-                    group_account ON volunteer.gid = group_account.gid
-                LEFT JOIN
-                    volunteer_photo ON volunteer.vid = volunteer_photo.vid
-                WHERE
-                    volunteer.vid IN (
-                        SELECT volunteer.vid
-                        FROM volunteer
-                        LEFT JOIN volunteer_element ON volunteer.vid = volunteer_element.vid
-                        LEFT JOIN volunteer_region ON volunteer.vid = volunteer_region.vid
-                        {}
-                        GROUP BY volunteer.vid
-                        {}
-                    )
-                AND NOT volunteer.is_deleted
-                AND deadline_on >= NOW()
-                AND title LIKE "%{}%"
-                GROUP BY
-                    volunteer.vid
-                ORDER BY
-                    eid_match_count + rid_match_count DESC, is_paid DESC, req_eid_match_count DESC, deadline_on ASC, registered_at DESC, vid;
-            "#,
-            format!("?{}", ", ?".repeat(or_elements.len() - 1)),
-            format!("?{}", ", ?".repeat(or_regions.len() - 1)),
-            format!(
-                "?{}",
-                ", ?".repeat(or_elements.len() + req_elements.len() - 1)
-            ),
-            if req_elements.len() > 0 && req_regions.len() > 0 {
-                format!(
-                    "WHERE volunteer_element.eid IN ({}) AND volunteer_region.rid IN ({})",
-                    format!("?{}", ", ?".repeat(req_elements.len() - 1)),
-                    format!("?{}", ", ?".repeat(req_regions.len() - 1))
-                )
-            } else if req_elements.len() > 0 {
-                format!(
-                    "WHERE volunteer_element.eid IN ({})",
-                    format!("?{}", ", ?".repeat(req_elements.len() - 1))
-                )
-            } else if req_regions.len() > 0 {
-                format!(
-                    "WHERE volunteer_region.rid IN ({})",
-                    format!("?{}", ", ?".repeat(req_regions.len() - 1))
-                )
-            } else {
-                "".to_string()
-            },
-            if req_elements.len() > 0 && req_regions.len() > 0 {
-                format!(
-                    "HAVING COUNT(DISTINCT volunteer_element.eid) = {} AND COUNT(DISTINCT volunteer_region.rid) = {}",
-                    req_elements.len(),
-                    req_regions.len()
-                )
-            } else if req_elements.len() > 0 {
-                format!(
-                    "HAVING COUNT(DISTINCT volunteer_element.eid) = {}",
-                    req_elements.len()
-                )
-            } else if req_regions.len() > 0 {
-                format!(
-                    "HAVING COUNT(DISTINCT volunteer_region.rid) = {}",
-                    req_regions.len()
-                )
-            } else {
-                "".to_string()
-            },
-            search_words
-        );
-        let mut query = query(&query_str);
+        let volunteers = volunteers
+            .into_iter()
+            .map(|volunteer| {
+                let cursor: VolunteerSearchCursor = VolunteerSearchCursor {
+                    distance: volunteer.try_get("distance")?,
+                    match_count: volunteer.try_get::<i64, _>("eid_match_count")?
+                        + volunteer.try_get::<i64, _>("rid_match_count")?,
+                    availability_overlap: volunteer.try_get("availability_overlap")?,
+                    relevance: volunteer.try_get("relevance")?,
+                    is_paid: volunteer.try_get::<Option<bool>, _>("is_paid")?.unwrap_or(false),
+                    required_match_count: volunteer.try_get("req_eid_match_count")?,
+                    deadline_on: volunteer.try_get("deadline_on")?,
+                    registered_at: volunteer.try_get("registered_at")?,
+                    vid: volunteer.try_get("vid")?,
+                };
 
-        for or_element in or_elements.clone() {
-            query = query.bind(or_element);
-        }
-        for or_region in or_regions {
-            query = query.bind(or_region);
-        }
+                Ok((cursor.to_string(), volunteer_from_row(&volunteer)?))
+            })
+            .collect::<Result<Vec<(String, VolunteerReadModel)>, sqlx::Error>>()?;
 
-        for or_element in or_elements {
-            query = query.bind(or_element.to_string());
-        }
-        for req_element in req_elements.clone() {
-            query = query.bind(req_element.to_string());
-        }
+        Ok(Page::new(volunteers, page, total_count, |(cursor, _)| cursor.clone())
+            .map(|(_, volunteer)| volunteer))
+    }
 
-        for req_element in req_elements.clone() {
-            query = query.bind(req_element.to_string());
+    ///参加者へのおすすめのボランティアの取得
+    async fn find_recommended(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<RecommendedVolunteer>> {
+        let profile: RecommendationProfile = self.find_recommendation_profile(pid).await?;
+        if profile.is_empty() {
+            return Ok(Page::new(Vec::new(), page, 0, |_: &RecommendedVolunteer| {
+                String::new()
+            }));
         }
-        for req_region in req_regions.clone() {
-            query = query.bind(req_region);
+        let uid: String = pid.to_string();
+        let after: Option<RecommendationCursor> = page
+            .after
+            .as_deref()
+            .map(RecommendationCursor::from_str)
+            .transpose()?;
+
+        let mut count_query: QueryBuilder<MySql> =
+            QueryBuilder::new("SELECT COUNT(*) AS count FROM (");
+        push_recommended_query(&mut count_query, &profile, &uid);
+        count_query.push(") AS recommended WHERE score > 0");
+        let total_count: i64 = count_query
+            .build()
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+
+        let mut page_query: QueryBuilder<MySql> = QueryBuilder::new("SELECT * FROM (");
+        push_recommended_query(&mut page_query, &profile, &uid);
+        page_query.push(") AS recommended WHERE score > 0");
+        if let Some(cursor) = &after {
+            page_query.push(" AND ");
+            push_recommendation_keyset(&mut page_query, cursor);
         }
+        page_query.push(" ORDER BY ");
+        page_query.push(order_by(&RECOMMENDATION_ORDER));
+        page_query.push(" LIMIT ");
+        page_query.push_bind(page.limit());
 
-        let volunteers = query.fetch_all(&self.pool).await?;
-
-        let regions_map = RegionMap::new().regions_index_to_name;
-        let themes_map = ThemeMap::new().themes_id_to_name;
-        let conditions_map = ConditionMap::new().conditions_id_to_name;
-        let target_status_map = TargetStatusMap::new().target_statuses_index_to_name;
+        let volunteers = page_query.build().fetch_all(&self.pool).await?;
 
+        // 嗜好の重みが正の要素・地域のうち, ボランティアが持つものを一致した理由とする
+        let preferred_elements: Vec<String> = profile.preferred_elements();
+        let preferred_regions: Vec<u8> = profile.preferred_regions();
         let volunteers = volunteers
             .into_iter()
             .map(|volunteer| {
-                let elements: Vec<Value> = volunteer
-                    .get::<String, _>("eids")
-                    .split("},{")
-                    .map(|s| {
-                        // 文字列をJSONオブジェクトにパースする
-                        let new_s = {
-                            let first_char = s.chars().next().unwrap();
-                            let last_char = s.chars().last().unwrap();
-                            if last_char != '}' && first_char != '{' {
-                                format!("{}{}{}", "{", s, "}")
-                            } else if last_char != '}' {
-                                format!("{}{}", s, "}")
-                            } else if first_char != '{' {
-                                format!("{}{}", "{", s)
-                            } else {
-                                s.to_owned()
-                            }
-                        };
-                        serde_json::from_str(&new_s)
-                            .unwrap_or_else(|_| panic!("Failed to parse JSON: {}", s))
-                    })
-                    .collect();
-
-                let themes: Vec<String> = elements
-                    .iter()
-                    .filter_map(|e| {
-                        // オブジェクトからeidとis_needを取得する
-                        let eid = e.get("eid").and_then(|v| v.as_str()).unwrap();
-                        let is_need = e.get("is_need").and_then(|v| v.as_u64()).unwrap() == 1;
-
-                        // themes_mapにeidが含まれており、かつis_needがfalseであればテーマを取得する
-                        if let Some(theme) = themes_map.get(eid) {
-                            if !is_need {
-                                Some(theme.to_string())
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-
-                let required_themes: Vec<String> = elements
-                    .iter()
-                    .filter_map(|e| {
-                        // オブジェクトからeidとis_needを取得する
-                        let eid = e.get("eid").and_then(|v| v.as_str()).unwrap();
-                        let is_need = e.get("is_need").and_then(|v| v.as_u64()).unwrap() == 1;
-
-                        // themes_mapにeidが含まれており、かつis_needがtrueであればテーマを取得する
-                        if let Some(theme) = themes_map.get(eid) {
-                            if is_need {
-                                Some(theme.to_string())
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-
-                let conditions: Vec<String> = elements
-                    .iter()
-                    .filter_map(|e| {
-                        // オブジェクトからeidとis_needを取得する
-                        let eid = e.get("eid").and_then(|v| v.as_str()).unwrap();
-                        let is_need = e.get("is_need").and_then(|v| v.as_u64()).unwrap() == 1;
-
-                        // conditions_mapにeidが含まれており、かつis_needがfalseであればテーマを取得する
-                        if let Some(condition) = conditions_map.get(eid) {
-                            if !is_need {
-                                Some(condition.to_string())
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-
-                let required_conditions: Vec<String> = elements
-                    .iter()
-                    .filter_map(|e| {
-                        // オブジェクトからeidとis_needを取得する
-                        let eid = e.get("eid").and_then(|v| v.as_str()).unwrap();
-                        let is_need = e.get("is_need").and_then(|v| v.as_u64()).unwrap() == 1;
-
-                        // conditions_mapにeidが含まれており、かつis_needがtrueであればテーマを取得する
-                        if let Some(condition) = conditions_map.get(eid) {
-                            if is_need {
-                                Some(condition.to_string())
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
+                let cursor: RecommendationCursor = RecommendationCursor {
+                    score: volunteer.try_get("score")?,
+                    is_paid: volunteer.try_get::<Option<bool>, _>("is_paid")?.unwrap_or(false),
+                    deadline_on: volunteer.try_get("deadline_on")?,
+                    registered_at: volunteer.try_get("registered_at")?,
+                    vid: volunteer.try_get("vid")?,
+                };
 
-                let target_statuses: Vec<String> = elements
-                    .iter()
-                    .filter_map(|e| {
-                        // オブジェクトからeidとis_needを取得する
-                        let eid = e.get("eid").and_then(|v| v.as_str()).unwrap();
-                        let is_need = e.get("is_need").and_then(|v| v.as_u64()).unwrap() == 1;
-
-                        // target_statuss_mapにeidが含まれており、かつis_needがfalseであればテーマを取得する
-                        if let Some(target_status) = target_status_map.get(eid) {
-                            if !is_need {
-                                Some(target_status.to_string())
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
+                let matched_elements: Vec<ElementRow> = element_rows(&volunteer)?
+                    .into_iter()
+                    .filter(|element| preferred_elements.contains(&element.eid))
                     .collect();
-
-                let regions: Vec<String> = volunteer
-                    .get::<String, _>("rids")
-                    .split(',')
-                    .map(|rid| {
-                        let rid = rid.trim();
-                        regions_map
-                            .get(&rid.parse::<usize>().unwrap())
-                            .unwrap()
-                            .to_string()
-                    })
+                let matched_elements: ElementNames = ElementNames::new(&matched_elements);
+                let matched_regions: Vec<u8> = region_ids(&volunteer)?
+                    .into_iter()
+                    .filter(|rid| preferred_regions.contains(rid))
                     .collect();
 
-                let photo_urls: Vec<String> = match volunteer.get::<Option<String>, _>("s3_keys") {
-                    Some(keys) => keys.split(',').map(|key: &str| key.to_string()).collect(),
-                    None => Vec::new(),
+                let recommended = RecommendedVolunteer {
+                    volunteer: volunteer_from_row(&volunteer)?,
+                    score: cursor.score,
+                    matched_regions: region_names(&matched_regions),
+                    matched_themes: [matched_elements.themes, matched_elements.required_themes]
+                        .concat(),
+                    matched_conditions: [
+                        matched_elements.conditions,
+                        matched_elements.required_conditions,
+                    ]
+                    .concat(),
+                    matched_target_status: matched_elements.target_status,
                 };
-
-                VolunteerReadModel {
-                    vid: volunteer.get("vid"),
-                    gid: volunteer.get("gid"),
-                    title: volunteer.get("title"),
-                    message: volunteer.get("message"),
-                    overview: volunteer.get("overview"),
-                    recruited_num: volunteer.get::<u32, _>("recruited_num"),
-                    place: volunteer.get("place"),
-                    reward: volunteer.get("reward"),
-                    start_at: volunteer.get("start_at"),
-                    finish_at: volunteer.get("finish_at"),
-                    deadline_on: volunteer.get("deadline_on"),
-                    as_group: volunteer.get("as_group"),
-                    is_deleted: volunteer.get("is_deleted"),
-                    deleted_at: volunteer.get("deleted_at"),
-                    registered_at: volunteer.get("registered_at"),
-                    updated_at: volunteer.get("updated_at"),
-                    photo_urls: photo_urls,
-                    themes: themes,
-                    regions: regions,
-                    conditions: conditions,
-                    required_themes: required_themes,
-                    required_conditions: required_conditions,
-                    target_status: target_statuses,
-                }
+                Ok((cursor.to_string(), recommended))
             })
-            .collect();
+            .collect::<Result<Vec<(String, RecommendedVolunteer)>, sqlx::Error>>()?;
 
-        Ok(volunteers)
+        Ok(Page::new(volunteers, page, total_count, |(cursor, _)| cursor.clone())
+            .map(|(_, volunteer)| volunteer))
     }
 
     ///gidが一致する団体が登録したボランティア情報の取得
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<VolunteerReadModel>> {
+    async fn find_by_gid(
+        &self,
+        gid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<VolunteerReadModel>> {
         let vids = sqlx::query!(
             r#"
             SELECT vid FROM volunteer WHERE gid = ? AND is_deleted = false
+            AND (? IS NULL OR vid < ?)
+            ORDER BY vid DESC
+            LIMIT ?
             "#,
-            gid.to_string()
+            gid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let vids: Vec<VolunteerId> = vids.iter().map(|v| VolunteerId::from_str(&v.vid)).collect();
-
-        let volunteers = future::try_join_all(vids.iter().map(|vid| self.find_by_id(&vid))).await?;
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM volunteer WHERE gid = ? AND is_deleted = false
+            "#,
+            gid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
 
-        Ok(volunteers)
+        let vids: Vec<String> = vids.into_iter().map(|v| v.vid).collect();
+        self.find_page(Page::new(vids, page, total_count, |vid| vid.clone())).await
     }
 
     ///uidが一致する参加者のお気に入りボランティア情報取得
-    async fn find_favorite_by_id(&self, pid: &UserId) -> Result<Vec<VolunteerReadModel>> {
+    async fn find_favorite_by_id(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<VolunteerReadModel>> {
         let vids = sqlx::query!(
             r#"
             SELECT vid FROM favorite WHERE uid = ?
+            AND (? IS NULL OR vid < ?)
+            ORDER BY vid DESC
+            LIMIT ?
             "#,
-            pid.to_string()
+            pid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let vids: Vec<VolunteerId> = vids.iter().map(|v| VolunteerId::from_str(&v.vid)).collect();
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM favorite WHERE uid = ?
+            "#,
+            pid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
 
-        let volunteers = future::try_join_all(vids.iter().map(|vid| self.find_by_id(&vid))).await?;
-        Ok(volunteers)
+        let vids: Vec<String> = vids.into_iter().map(|v| v.vid).collect();
+        self.find_page(Page::new(vids, page, total_count, |vid| vid.clone())).await
     }
 
-    ///uidが一致する参加者の活動履歴ボランティア情報の取得
-    async fn find_activity_by_id(&self, pid: &UserId) -> Result<Vec<VolunteerReadModel>> {
-        let vids = sqlx::query!(
+    ///uidが一致する参加者の活動履歴の回の取得. 出席した回のみ
+    async fn find_activity_by_id(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>> {
+        let vsids = sqlx::query!(
             r#"
-            SELECT
-            volunteer.vid FROM volunteer, apply WHERE
-                volunteer.vid = apply.vid
+            SELECT DISTINCT
+            volunteer_session.vsid FROM volunteer_session, apply WHERE
+                volunteer_session.vsid = apply.vsid
                 AND apply.uid = ?
-                AND apply.allowed_status = 1
-                AND volunteer.finish_at < now();
+                AND apply.attendance_status = 1
+                AND volunteer_session.finish_at < now()
+                AND (? IS NULL OR volunteer_session.vsid < ?)
+            ORDER BY volunteer_session.vsid DESC
+            LIMIT ?
             "#,
-            pid.to_string()
+            pid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let vids: Vec<VolunteerId> = vids.iter().map(|v| VolunteerId::from_str(&v.vid)).collect();
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT
+            COUNT(DISTINCT volunteer_session.vsid) AS count FROM volunteer_session, apply WHERE
+                volunteer_session.vsid = apply.vsid
+                AND apply.uid = ?
+                AND apply.attendance_status = 1
+                AND volunteer_session.finish_at < now()
+            "#,
+            pid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
 
-        let volunteers = future::try_join_all(vids.iter().map(|vid| self.find_by_id(&vid))).await?;
-        Ok(volunteers)
+        let vsids: Vec<String> = vsids.into_iter().map(|v| v.vsid).collect();
+        self.find_session_page(Page::new(vsids, page, total_count, |vsid| vsid.clone())).await
     }
 
-    ///uidが一致する参加者の活動予定ボランティア情報の取得
-    async fn find_scheduled_activity_by_id(&self, pid: &UserId) -> Result<Vec<VolunteerReadModel>> {
-        let vids = sqlx::query!(
+    ///uidが一致する参加者の活動予定の回の取得
+    async fn find_scheduled_activity_by_id(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>> {
+        let vsids = sqlx::query!(
             r#"
-            SELECT
-            volunteer.vid FROM volunteer, apply WHERE
-                volunteer.vid = apply.vid
+            SELECT DISTINCT
+            volunteer_session.vsid FROM volunteer_session, apply WHERE
+                volunteer_session.vsid = apply.vsid
                 AND apply.uid = ?
                 AND apply.allowed_status = 1
-                AND volunteer.finish_at >= now();
+                AND volunteer_session.finish_at >= now()
+                AND volunteer_session.is_deleted = false
+                AND (? IS NULL OR volunteer_session.vsid < ?)
+            ORDER BY volunteer_session.vsid DESC
+            LIMIT ?
             "#,
-            pid.to_string()
+            pid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let vids: Vec<VolunteerId> = vids.iter().map(|v| VolunteerId::from_str(&v.vid)).collect();
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT
+            COUNT(DISTINCT volunteer_session.vsid) AS count FROM volunteer_session, apply WHERE
+                volunteer_session.vsid = apply.vsid
+                AND apply.uid = ?
+                AND apply.allowed_status = 1
+                AND volunteer_session.finish_at >= now()
+                AND volunteer_session.is_deleted = false
+            "#,
+            pid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
 
-        let volunteers = future::try_join_all(vids.iter().map(|vid| self.find_by_id(&vid))).await?;
-        Ok(volunteers)
+        let vsids: Vec<String> = vsids.into_iter().map(|v| v.vsid).collect();
+        self.find_session_page(Page::new(vsids, page, total_count, |vsid| vsid.clone())).await
     }
 
-    ///uidが一致する参加者の未承認予定ボランティア情報の取得
+    ///uidが一致する参加者の未承認の回の取得
     async fn find_not_allowed_activity_by_id(
         &self,
         pid: &UserId,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let vids = sqlx::query!(
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>> {
+        let vsids = sqlx::query!(
             r#"
-            SELECT
-            volunteer.vid FROM volunteer, apply WHERE
-                volunteer.vid = apply.vid
+            SELECT DISTINCT
+            volunteer_session.vsid FROM volunteer, volunteer_session, apply WHERE
+                volunteer.vid = volunteer_session.vid
+                AND volunteer_session.vsid = apply.vsid
                 AND apply.uid = ?
                 AND apply.allowed_status = 0
-                AND volunteer.deadline_on >= NOW();
+                AND volunteer.deadline_on >= NOW()
+                AND volunteer_session.is_deleted = false
+                AND (? IS NULL OR volunteer_session.vsid < ?)
+            ORDER BY volunteer_session.vsid DESC
+            LIMIT ?
             "#,
-            pid.to_string()
+            pid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let vids: Vec<VolunteerId> = vids.iter().map(|v| VolunteerId::from_str(&v.vid)).collect();
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT
+            COUNT(DISTINCT volunteer_session.vsid) AS count FROM volunteer, volunteer_session, apply WHERE
+                volunteer.vid = volunteer_session.vid
+                AND volunteer_session.vsid = apply.vsid
+                AND apply.uid = ?
+                AND apply.allowed_status = 0
+                AND volunteer.deadline_on >= NOW()
+                AND volunteer_session.is_deleted = false
+            "#,
+            pid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
 
-        let volunteers = future::try_join_all(vids.iter().map(|vid| self.find_by_id(&vid))).await?;
-        Ok(volunteers)
+        let vsids: Vec<String> = vsids.into_iter().map(|v| v.vsid).collect();
+        self.find_session_page(Page::new(vsids, page, total_count, |vsid| vsid.clone())).await
     }
 
-    async fn find_activity_by_gid(&self, gid: &UserId) -> Result<Vec<VolunteerReadModel>> {
-        let vids = sqlx::query!(
+    async fn find_activity_by_gid(
+        &self,
+        gid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>> {
+        let vsids = sqlx::query!(
             r#"
-            SELECT vid FROM volunteer WHERE gid = ? AND finish_at < now() AND is_deleted = false
+            SELECT volunteer_session.vsid FROM volunteer, volunteer_session
+            WHERE volunteer.vid = volunteer_session.vid AND volunteer.gid = ?
+            AND volunteer_session.finish_at < now() AND volunteer.is_deleted = false AND volunteer_session.is_deleted = false
+            AND (? IS NULL OR volunteer_session.vsid < ?)
+            ORDER BY volunteer_session.vsid DESC
+            LIMIT ?
             "#,
-            gid.to_string()
+            gid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let vids: Vec<VolunteerId> = vids.iter().map(|v| VolunteerId::from_str(&v.vid)).collect();
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM volunteer, volunteer_session
+            WHERE volunteer.vid = volunteer_session.vid AND volunteer.gid = ?
+            AND volunteer_session.finish_at < now() AND volunteer.is_deleted = false AND volunteer_session.is_deleted = false
+            "#,
+            gid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
 
-        let volunteers = future::try_join_all(vids.iter().map(|vid| self.find_by_id(&vid))).await?;
-        Ok(volunteers)
+        let vsids: Vec<String> = vsids.into_iter().map(|v| v.vsid).collect();
+        self.find_session_page(Page::new(vsids, page, total_count, |vsid| vsid.clone())).await
     }
 
     async fn find_scheduled_activity_by_gid(
         &self,
         gid: &UserId,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let vids = sqlx::query!(
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>> {
+        let vsids = sqlx::query!(
             r#"
-            SELECT vid FROM volunteer WHERE gid = ? AND finish_at > now() AND is_deleted = false
+            SELECT volunteer_session.vsid FROM volunteer, volunteer_session
+            WHERE volunteer.vid = volunteer_session.vid AND volunteer.gid = ?
+            AND volunteer_session.finish_at > now() AND volunteer.is_deleted = false AND volunteer_session.is_deleted = false
+            AND (? IS NULL OR volunteer_session.vsid < ?)
+            ORDER BY volunteer_session.vsid DESC
+            LIMIT ?
             "#,
-            gid.to_string()
+            gid.to_string(),
+            page.after,
+            page.after,
+            page.limit()
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let vids: Vec<VolunteerId> = vids.iter().map(|v| VolunteerId::from_str(&v.vid)).collect();
+        let total_count: i64 = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS count FROM volunteer, volunteer_session
+            WHERE volunteer.vid = volunteer_session.vid AND volunteer.gid = ?
+            AND volunteer_session.finish_at > now() AND volunteer.is_deleted = false AND volunteer_session.is_deleted = false
+            "#,
+            gid.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        let vsids: Vec<String> = vsids.into_iter().map(|v| v.vsid).collect();
+        self.find_session_page(Page::new(vsids, page, total_count, |vsid| vsid.clone())).await
+    }
+}
+
+/// 全文検索の対象カラムに対する検索式の一致
+const FULLTEXT_MATCH: &str =
+    "MATCH(volunteer.title, volunteer.overview, volunteer.message, volunteer.place) AGAINST (";
+
+/// ボランティア情報のカラム. `group_account`と`volunteer_location`を結合して使用する
+const VOLUNTEER_COLUMNS: &str = r#"
+    volunteer.vid, volunteer.gid, title, message, overview, recruited_num, place, start_at, finish_at, recurrence, as_group, reward,
+    volunteer.is_deleted, volunteer.deleted_at, deadline_on, registered_at, updated_at, is_paid,
+    (SELECT JSON_ARRAYAGG(s3_key) FROM volunteer_photo WHERE volunteer_photo.vid = volunteer.vid) AS s3_keys,
+    (
+        SELECT JSON_ARRAYAGG(JSON_OBJECT('eid', eid, 'is_need', is_need))
+        FROM volunteer_element WHERE volunteer_element.vid = volunteer.vid
+    ) AS elements,
+    (SELECT JSON_ARRAYAGG(rid) FROM volunteer_region WHERE volunteer_region.vid = volunteer.vid) AS rids,
+    ST_Latitude(volunteer_location.location) AS latitude,
+    ST_Longitude(volunteer_location.location) AS longitude
+"#;
+
+/// 検索結果の並び順. 末尾のvidで順序を一意にする
+///
+/// 距離と参加可能な時間帯と重なる時間は, 検索地点や参加者を指定しない場合は全て0となり, 並び順に影響しない
+const SEARCH_ORDER: [(&str, &str); 9] = [
+    ("distance", "ASC"),
+    ("eid_match_count + rid_match_count", "DESC"),
+    ("availability_overlap", "DESC"),
+    ("relevance", "DESC"),
+    ("is_paid", "DESC"),
+    ("req_eid_match_count", "DESC"),
+    ("deadline_on", "ASC"),
+    ("registered_at", "DESC"),
+    ("vid", "ASC"),
+];
+
+/// 距離検索の検索地点と半径
+struct Near {
+    center: GeoPoint,
+    radius_km: f64,
+}
 
-        let volunteers = future::try_join_all(vids.iter().map(|vid| self.find_by_id(&vid))).await?;
-        Ok(volunteers)
+/// 空間インデックスで絞り込む矩形の余白の倍率
+///
+/// SRID 4326の多角形の辺は測地線となり, 緯線より極側に膨らむため, 矩形を広げて取りこぼしを防ぐ
+const BOUNDING_BOX_MARGIN: f64 = 1.05;
+
+/// 半径の円を囲む矩形をWKTの多角形に変換する
+fn bounding_polygon(near: &Near) -> String {
+    let (south_west, north_east) = near.center.bounding_box(near.radius_km * BOUNDING_BOX_MARGIN);
+    format!(
+        "POLYGON(({west} {south}, {east} {south}, {east} {north}, {west} {north}, {west} {south}))",
+        west = south_west.longitude,
+        south = south_west.latitude,
+        east = north_east.longitude,
+        north = north_east.latitude
+    )
+}
+
+/// 開催場所と検索地点の距離(m)の式を追加する
+fn push_distance(builder: &mut QueryBuilder<'_, MySql>, near: &Near) {
+    builder.push("ST_Distance_Sphere(volunteer_location.location, ST_PointFromText(");
+    builder.push_bind(near.center.to_wkt());
+    builder.push(", 4326, 'axis-order=long-lat'))");
+}
+
+/// 一致数, 関連度, 距離, 参加可能な時間帯と重なる時間を付与した, 検索条件に一致するボランティアのクエリを追加する
+fn push_ranked_query(
+    builder: &mut QueryBuilder<'_, MySql>,
+    input: &VolunteerSearchInput,
+    filter: &SearchFilter,
+    search_query: Option<&str>,
+    near: &Option<Near>,
+    uid: Option<&str>,
+) {
+    builder.push("SELECT ");
+    builder.push(VOLUNTEER_COLUMNS);
+    builder.push(", ");
+    match near {
+        Some(near) => {
+            builder.push("CAST(ROUND(");
+            push_distance(builder, near);
+            builder.push(") AS SIGNED)");
+        }
+        None => {
+            builder.push("0");
+        }
     }
+    builder.push(" AS distance, ");
+    match uid {
+        Some(uid) => {
+            let uid: String = uid.to_string();
+            push_availability_overlap(builder, "volunteer", |builder| {
+                builder.push_bind(uid);
+            });
+        }
+        None => {
+            builder.push("0");
+        }
+    }
+    builder.push(" AS availability_overlap, ");
+    match search_query {
+        Some(search_query) => {
+            builder.push(format!("CAST(ROUND({}", FULLTEXT_MATCH));
+            builder.push_bind(search_query.to_string());
+            builder.push(" IN BOOLEAN MODE) * 1000) AS SIGNED)");
+        }
+        None => {
+            builder.push("0");
+        }
+    }
+    builder.push(" AS relevance, ");
+    filter.push_match_counts(builder, &VOLUNTEER_TABLES);
+    builder.push(
+        r#"
+        FROM volunteer
+        LEFT JOIN group_account ON volunteer.gid = group_account.gid
+        LEFT JOIN volunteer_location ON volunteer.vid = volunteer_location.vid
+        WHERE NOT volunteer.is_deleted
+        AND deadline_on >= NOW()
+        AND "#,
+    );
+    filter.push_required_condition(builder, &VOLUNTEER_TABLES);
+    push_schedule_condition(builder, input);
+    if let Some(search_query) = search_query {
+        builder.push(format!(" AND {}", FULLTEXT_MATCH));
+        builder.push_bind(search_query.to_string());
+        builder.push(" IN BOOLEAN MODE)");
+    }
+    if let Some(near) = near {
+        // 空間インデックスで矩形内に絞り込んでから, 球面上の距離で判定する
+        builder.push(" AND MBRContains(ST_PolygonFromText(");
+        builder.push_bind(bounding_polygon(near));
+        builder.push(", 4326, 'axis-order=long-lat'), volunteer_location.location) AND ");
+        push_distance(builder, near);
+        builder.push(" <= ");
+        builder.push_bind(near.radius_km * 1000.0);
+    }
+}
+
+/// 日時, 募集締切日, 団体参加, 残りの募集人数の条件を追加する
+fn push_schedule_condition(builder: &mut QueryBuilder<'_, MySql>, input: &VolunteerSearchInput) {
+    let ranges = [
+        ("start_at >= ", input.start_from),
+        ("start_at <= ", input.start_until),
+        ("finish_at >= ", input.finish_from),
+        ("finish_at <= ", input.finish_until),
+    ];
+    for (condition, value) in ranges {
+        if let Some(value) = value {
+            builder.push(format!(" AND {}", condition));
+            builder.push_bind(value);
+        }
+    }
+    if !input.days_of_week.is_empty() {
+        builder.push(" AND ");
+        let days: Vec<u8> = input.days_of_week.iter().map(|day| day.to_mysql()).collect();
+        push_in(builder, &format!("DAYOFWEEK({})", local_time("start_at")), &days);
+    }
+    if let Some(time_from) = input.time_from {
+        builder.push(format!(" AND TIME({}) >= ", local_time("start_at")));
+        builder.push_bind(time_from);
+    }
+    if let Some(time_until) = input.time_until {
+        builder.push(format!(" AND TIME({}) <= ", local_time("finish_at")));
+        builder.push_bind(time_until);
+    }
+    if let Some(deadline_from) = input.deadline_from {
+        builder.push(" AND deadline_on >= ");
+        builder.push_bind(deadline_from);
+    }
+    if let Some(deadline_until) = input.deadline_until {
+        builder.push(" AND deadline_on <= ");
+        builder.push_bind(deadline_until);
+    }
+    if let Some(as_group) = input.as_group {
+        builder.push(" AND as_group = ");
+        builder.push_bind(as_group);
+    }
+    if let Some(min_remaining) = input.min_remaining {
+        // 承認済の応募の人数を募集人数から引く. 団体での応募は団体の参加者数を数える
+        builder.push(
+            r#"
+            AND CAST(recruited_num AS SIGNED) - (
+                SELECT COALESCE(SUM(
+                    IF(apply.as_group, (SELECT COUNT(*) FROM group_participants WHERE gpid = apply.aid), 1)
+                ), 0)
+                FROM apply WHERE apply.vid = volunteer.vid AND apply.allowed_status = "#,
+        );
+        builder.push_bind(ApplyStatus::Approved.to_u8());
+        builder.push(") >= ");
+        builder.push_bind(min_remaining);
+    }
+}
+
+/// 並び順でカーソルより後ろにある行を取得する条件を追加する
+///
+/// `bind`はj番目のキーのカーソルの値をバインドする
+fn push_keyset<'args, F>(
+    builder: &mut QueryBuilder<'args, MySql>,
+    orders: &[(&str, &str)],
+    mut bind: F,
+) where
+    F: FnMut(&mut QueryBuilder<'args, MySql>, usize),
+{
+    // i番目の条件は, i番目より前のキーがカーソルと等しく, i番目のキーがカーソルより後ろにある行
+    builder.push("(");
+    for i in 0..orders.len() {
+        if i > 0 {
+            builder.push(" OR ");
+        }
+        builder.push("(");
+        for (j, (column, order)) in orders.iter().enumerate().take(i + 1) {
+            if j > 0 {
+                builder.push(" AND ");
+            }
+            let operator: &str = match (j < i, *order) {
+                (true, _) => "=",
+                (false, "DESC") => "<",
+                (false, _) => ">",
+            };
+            builder.push(format!("{} {} ", column, operator));
+            bind(builder, j);
+        }
+        builder.push(")");
+    }
+    builder.push(")");
+}
+
+/// 並び順を`ORDER BY`の式に変換する
+fn order_by(orders: &[(&str, &str)]) -> String {
+    orders
+        .iter()
+        .map(|(column, order)| format!("{} {}", column, order))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// 検索結果でカーソルより後ろにある行を取得する条件を追加する
+fn push_search_keyset(builder: &mut QueryBuilder<'_, MySql>, cursor: &VolunteerSearchCursor) {
+    push_keyset(builder, &SEARCH_ORDER, |builder, j| {
+        match j {
+            0 => builder.push_bind(cursor.distance),
+            1 => builder.push_bind(cursor.match_count),
+            2 => builder.push_bind(cursor.availability_overlap),
+            3 => builder.push_bind(cursor.relevance),
+            4 => builder.push_bind(cursor.is_paid),
+            5 => builder.push_bind(cursor.required_match_count),
+            6 => builder.push_bind(cursor.deadline_on),
+            7 => builder.push_bind(cursor.registered_at),
+            _ => builder.push_bind(cursor.vid.clone()),
+        };
+    });
+}
+
+/// おすすめの並び順. 一致度が同じ場合は検索と同様に有料団体のボランティアを優先する
+const RECOMMENDATION_ORDER: [(&str, &str); 5] = [
+    ("score", "DESC"),
+    ("is_paid", "DESC"),
+    ("deadline_on", "ASC"),
+    ("registered_at", "DESC"),
+    ("vid", "ASC"),
+];
+
+/// おすすめでカーソルより後ろにある行を取得する条件を追加する
+fn push_recommendation_keyset(builder: &mut QueryBuilder<'_, MySql>, cursor: &RecommendationCursor) {
+    push_keyset(builder, &RECOMMENDATION_ORDER, |builder, j| {
+        match j {
+            0 => builder.push_bind(cursor.score),
+            1 => builder.push_bind(cursor.is_paid),
+            2 => builder.push_bind(cursor.deadline_on),
+            3 => builder.push_bind(cursor.registered_at),
+            _ => builder.push_bind(cursor.vid.clone()),
+        };
+    });
+}
+
+/// 参加者の嗜好との一致度(`score`)を付与した, 募集中で未応募のボランティアのクエリを追加する
+fn push_recommended_query(
+    builder: &mut QueryBuilder<'_, MySql>,
+    profile: &RecommendationProfile,
+    uid: &str,
+) {
+    let elements: Vec<(String, i64)> = profile
+        .elements
+        .iter()
+        .filter(|(_, weight)| **weight != 0)
+        .map(|(eid, weight)| (eid.clone(), *weight))
+        .collect();
+    let regions: Vec<(u8, i64)> = profile
+        .regions
+        .iter()
+        .filter(|(_, weight)| **weight != 0)
+        .map(|(rid, weight)| (*rid, *weight))
+        .collect();
+
+    builder.push("SELECT ");
+    builder.push(VOLUNTEER_COLUMNS);
+    builder.push(", CAST(");
+    push_weight_sum(builder, &VOLUNTEER_TABLES, VOLUNTEER_TABLES.element, "eid", &elements);
+    builder.push(" + ");
+    push_weight_sum(builder, &VOLUNTEER_TABLES, VOLUNTEER_TABLES.region, "rid", &regions);
+    builder.push(
+        r#" AS SIGNED) AS score
+        FROM volunteer
+        LEFT JOIN group_account ON volunteer.gid = group_account.gid
+        LEFT JOIN volunteer_location ON volunteer.vid = volunteer_location.vid
+        WHERE NOT volunteer.is_deleted
+        AND deadline_on >= NOW()
+        AND NOT EXISTS (SELECT aid FROM apply WHERE apply.vid = volunteer.vid AND apply.uid = "#,
+    );
+    builder.push_bind(uid.to_string());
+    builder.push(")");
+}
+
+/// 検索結果の行から要素を取得する
+fn element_rows(row: &MySqlRow) -> Result<Vec<ElementRow>, sqlx::Error> {
+    Ok(row
+        .try_get::<Option<Json<Vec<ElementRow>>>, _>("elements")?
+        .map(|elements| elements.0)
+        .unwrap_or_default())
+}
+
+/// 検索結果の行から地域IDを取得する
+fn region_ids(row: &MySqlRow) -> Result<Vec<u8>, sqlx::Error> {
+    Ok(row
+        .try_get::<Option<Json<Vec<u8>>>, _>("rids")?
+        .map(|rids| rids.0)
+        .unwrap_or_default())
+}
+
+/// [VOLUNTEER_COLUMNS]を含む行からボランティア情報を作成する
+fn volunteer_from_row(volunteer: &MySqlRow) -> Result<VolunteerReadModel, sqlx::Error> {
+    let elements: ElementNames = ElementNames::new(&element_rows(volunteer)?);
+    let rids: Vec<u8> = region_ids(volunteer)?;
+    let photo_urls: Vec<String> = volunteer
+        .try_get::<Option<Json<Vec<String>>>, _>("s3_keys")?
+        .map(|keys| keys.0)
+        .unwrap_or_default();
+
+    Ok(VolunteerReadModel {
+        vid: volunteer.try_get("vid")?,
+        gid: volunteer.try_get("gid")?,
+        title: volunteer.try_get("title")?,
+        message: volunteer.try_get("message")?,
+        overview: volunteer.try_get("overview")?,
+        recruited_num: volunteer.try_get::<u32, _>("recruited_num")?,
+        place: volunteer.try_get("place")?,
+        latitude: volunteer.try_get("latitude")?,
+        longitude: volunteer.try_get("longitude")?,
+        reward: volunteer.try_get("reward")?,
+        start_at: volunteer.try_get("start_at")?,
+        finish_at: volunteer.try_get("finish_at")?,
+        recurrence: volunteer.try_get("recurrence")?,
+        deadline_on: volunteer.try_get("deadline_on")?,
+        as_group: volunteer.try_get("as_group")?,
+        is_deleted: volunteer.try_get("is_deleted")?,
+        deleted_at: volunteer.try_get("deleted_at")?,
+        registered_at: volunteer.try_get("registered_at")?,
+        updated_at: volunteer.try_get("updated_at")?,
+        photo_urls,
+        themes: elements.themes,
+        regions: region_names(&rids),
+        conditions: elements.conditions,
+        required_themes: elements.required_themes,
+        required_conditions: elements.required_conditions,
+        target_status: elements.target_status,
+    })
 }
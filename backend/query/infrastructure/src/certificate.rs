@@ -0,0 +1,350 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use domain::{
+    hex::{decode_hex, encode_hex},
+    model::certificate::{format_minutes, to_jst, Certificate},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A4の幅(pt)
+const PAGE_WIDTH: f64 = 595.0;
+/// A4の高さ(pt)
+const PAGE_HEIGHT: f64 = 842.0;
+/// 左右の余白(pt)
+const MARGIN: f64 = 72.0;
+/// 項目名から値までの幅(pt)
+const LABEL_WIDTH: f64 = 90.0;
+
+/// 証明書の内容にHMAC-SHA256で署名する
+///
+/// 署名は証明書に記載し, 証明書IDと署名から証明書が改ざんされていないか確認できるようにする
+pub struct CertificateSigner {
+    secret: String,
+}
+
+impl CertificateSigner {
+    /// 秘密鍵が空の場合はエラーを返す
+    pub fn new(secret: String) -> Result<Self> {
+        if secret.is_empty() {
+            return Err(anyhow!("certificate signing secret is empty"));
+        }
+        Ok(Self { secret })
+    }
+
+    /// 環境変数`CERTIFICATE_SIGNING_SECRET`を秘密鍵として作成する. 設定されていない場合はエラーを返す
+    pub fn from_env() -> Result<Self> {
+        let secret: String = std::env::var("CERTIFICATE_SIGNING_SECRET")
+            .map_err(|_| anyhow!("CERTIFICATE_SIGNING_SECRET is not set"))?;
+        Self::new(secret)
+    }
+
+    fn mac(&self, certificate: &Certificate) -> HmacSha256 {
+        let mut mac: HmacSha256 = HmacSha256::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC can take a key of any size");
+        mac.update(certificate.signing_payload().as_bytes());
+        mac
+    }
+
+    /// 署名を16進数の大文字で返す
+    pub fn sign(&self, certificate: &Certificate) -> String {
+        encode_hex(&self.mac(certificate).finalize().into_bytes()).to_uppercase()
+    }
+
+    /// 署名が証明書の内容と一致するか確認する. 大文字・小文字と前後の空白は区別しない
+    pub fn verify(&self, certificate: &Certificate, signature: &str) -> bool {
+        match decode_hex(signature.trim()) {
+            Some(bytes) => self.mac(certificate).verify_slice(&bytes).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// 文字列をフォントの文字コード(UTF-16BE)の16進数にする
+fn encode_text(text: &str) -> String {
+    text.encode_utf16()
+        .map(|unit| format!("{:04X}", unit))
+        .collect()
+}
+
+/// 文字列の幅(pt). 半角は1文字の半分, 全角は1文字の幅とする
+fn text_width(text: &str, size: f64) -> f64 {
+    text.chars()
+        .map(|c| if c.is_ascii() { 0.5 } else { 1.0 })
+        .sum::<f64>()
+        * size
+}
+
+/// 幅に収まるよう文字列を折り返す
+fn wrap(text: &str, size: f64, max_width: f64) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut line: String = String::new();
+    for c in text.chars() {
+        line.push(c);
+        if text_width(&line, size) > max_width {
+            line.pop();
+            lines.push(std::mem::take(&mut line));
+            line.push(c);
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+fn format_date(at: DateTime<FixedOffset>) -> String {
+    at.format("%Y年%-m月%-d日").to_string()
+}
+
+/// 活動日時. 同じ日に終了する場合は終了日を省略する
+fn format_period(certificate: &Certificate) -> String {
+    let start_at: DateTime<FixedOffset> = to_jst(certificate.activity.start_at);
+    let finish_at: DateTime<FixedOffset> = to_jst(certificate.activity.finish_at);
+    if start_at.date_naive() == finish_at.date_naive() {
+        format!(
+            "{} {} 〜 {}",
+            format_date(start_at),
+            start_at.format("%-H:%M"),
+            finish_at.format("%-H:%M")
+        )
+    } else {
+        format!(
+            "{} {} 〜 {} {}",
+            format_date(start_at),
+            start_at.format("%-H:%M"),
+            format_date(finish_at),
+            finish_at.format("%-H:%M")
+        )
+    }
+}
+
+/// ページに文字列を描画する命令
+struct Page {
+    content: String,
+}
+
+impl Page {
+    fn text(&mut self, x: f64, y: f64, size: f64, text: &str) {
+        self.content.push_str(&format!(
+            "BT /F1 {} Tf 1 0 0 1 {:.2} {:.2} Tm <{}> Tj ET\n",
+            size,
+            x,
+            y,
+            encode_text(text)
+        ));
+    }
+
+    fn centered(&mut self, y: f64, size: f64, text: &str) {
+        self.text((PAGE_WIDTH - text_width(text, size)) / 2.0, y, size, text);
+    }
+
+    /// 項目名と値を描画し, 次の行のy座標を返す. 値は折り返す
+    fn item(&mut self, y: f64, size: f64, label: &str, value: &str) -> f64 {
+        self.text(MARGIN, y, size, label);
+        let max_width: f64 = PAGE_WIDTH - MARGIN * 2.0 - LABEL_WIDTH;
+        let mut y: f64 = y;
+        for line in wrap(value, size, max_width) {
+            self.text(MARGIN + LABEL_WIDTH, y, size, &line);
+            y -= size * 1.5;
+        }
+        y - size
+    }
+}
+
+/// 証明書のページの描画命令を作成する
+fn certificate_content(certificate: &Certificate, signature: &str) -> String {
+    let mut page: Page = Page {
+        content: String::from("0.5 w 36 36 523 770 re S 1.5 w 42 42 511 758 re S\n"),
+    };
+
+    page.centered(730.0, 28.0, "ボランティア活動証明書");
+    page.text(
+        MARGIN,
+        660.0,
+        16.0,
+        &format!("{} 様", certificate.activity.participant_name),
+    );
+    page.text(
+        MARGIN,
+        620.0,
+        12.0,
+        "下記のとおりボランティア活動に参加したことを証明します.",
+    );
+
+    let mut y: f64 = 570.0;
+    y = page.item(y, 12.0, "活動名", &certificate.activity.volunteer_title);
+    y = page.item(y, 12.0, "活動日時", &format_period(certificate));
+    y = page.item(y, 12.0, "活動時間", &format_minutes(certificate.minutes()));
+    page.item(y, 12.0, "証明書ID", &certificate.activity.certificate_id);
+
+    page.text(
+        MARGIN,
+        330.0,
+        12.0,
+        &format!("発行日 {}", certificate.issued_on.format("%Y年%-m月%-d日")),
+    );
+    let mut y: f64 = 280.0;
+    y = page.item(y, 14.0, "団体名", &certificate.activity.group_name);
+    page.item(y, 14.0, "代表者", &certificate.activity.representative_name);
+
+    page.text(MARGIN, 92.0, 8.0, &format!("署名: {}", signature));
+    page.text(
+        MARGIN,
+        78.0,
+        8.0,
+        "この証明書の内容は, 証明書IDと署名によりVolunScoutで確認できます.",
+    );
+
+    page.content
+}
+
+/// 証明書をA4・1ページのPDFにする
+///
+/// 日本語はPDFビューアが標準で持つ明朝体(HeiseiMin-W3)で表示し, フォントは埋め込まない
+pub fn render_pdf(certificate: &Certificate, signature: &str) -> Vec<u8> {
+    let content: String = certificate_content(certificate, signature);
+    let objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ),
+        // 半角の英数字は半角の字形(CID 231〜)で表示する
+        "<< /Type /Font /Subtype /Type0 /BaseFont /HeiseiMin-W3 /Encoding /UniJIS-UCS2-HW-H /DescendantFonts [6 0 R] >>".to_string(),
+        "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /HeiseiMin-W3 /CIDSystemInfo << /Registry (Adobe) /Ordering (Japan1) /Supplement 2 >> /FontDescriptor 7 0 R /DW 1000 /W [231 389 500] >>".to_string(),
+        "<< /Type /FontDescriptor /FontName /HeiseiMin-W3 /Flags 6 /FontBBox [-123 -257 1001 910] /ItalicAngle 0 /Ascent 723 /Descent -241 /CapHeight 709 /StemV 69 >>".to_string(),
+        format!(
+            "<< /Title <FEFF{}> /Producer (VolunScout) >>",
+            encode_text("ボランティア活動証明書")
+        ),
+    ];
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+    let mut offsets: Vec<usize> = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+
+    let xref_offset: usize = pdf.len();
+    let mut xref: String = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        xref.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.extend_from_slice(xref.as_bytes());
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            objects.len(),
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+#[cfg(test)]
+mod test_certificate {
+    use super::*;
+
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+
+    use domain::model::certificate::CertifiedActivity;
+
+    fn certificate() -> Certificate {
+        let start_at = Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap();
+        Certificate {
+            activity: CertifiedActivity {
+                certificate_id: "01HPAY3C1A1Z1XQ6Z2J6S0V7KX".to_string(),
+                uid: "participant_account000000000".to_string(),
+                gid: "group_account000000000000000".to_string(),
+                vid: "01HKXVVVKBR6G8240N7HWSPR7M".to_string(),
+                participant_name: "山田太郎".to_string(),
+                group_name: "ボランティア団体".to_string(),
+                representative_name: "鈴木花子".to_string(),
+                volunteer_title: "公園の清掃".to_string(),
+                start_at,
+                finish_at: start_at + Duration::minutes(210),
+            },
+            issued_on: NaiveDate::from_ymd_opt(2024, 3, 20).unwrap(),
+        }
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let signer = CertificateSigner::new("secret".to_string()).unwrap();
+        let signature = signer.sign(&certificate());
+        assert_eq!(signature.len(), 64);
+        assert!(signer.verify(&certificate(), &signature));
+        assert!(signer.verify(&certificate(), &format!(" {} ", signature.to_lowercase())));
+
+        let mut tampered = certificate();
+        tampered.activity.finish_at += Duration::hours(1);
+        assert!(!signer.verify(&tampered, &signature));
+        assert!(!CertificateSigner::new("other".to_string())
+            .unwrap()
+            .verify(&certificate(), &signature));
+        assert!(!signer.verify(&certificate(), "invalid"));
+    }
+
+    #[test]
+    fn empty_secret() {
+        assert!(CertificateSigner::new(String::new()).is_err());
+    }
+
+    #[test]
+    fn period() {
+        assert_eq!(format_period(&certificate()), "2024年3月10日 9:00 〜 12:30");
+        let mut overnight = certificate();
+        overnight.activity.finish_at = overnight.activity.start_at + Duration::hours(20);
+        assert_eq!(
+            format_period(&overnight),
+            "2024年3月10日 9:00 〜 2024年3月11日 5:00"
+        );
+    }
+
+    #[test]
+    fn wrap_long_text() {
+        let lines = wrap(&"あ".repeat(30), 12.0, 120.0);
+        assert_eq!(
+            lines,
+            vec!["あ".repeat(10), "あ".repeat(10), "あ".repeat(10)]
+        );
+        assert_eq!(wrap("", 12.0, 120.0), vec![String::new()]);
+    }
+
+    #[test]
+    fn pdf_structure() {
+        let pdf = render_pdf(&certificate(), "ABCDEF");
+        let text = String::from_utf8(pdf.clone()).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains(&encode_text("山田太郎 様")));
+        assert!(text.contains(&encode_text("3時間30分")));
+
+        // 相互参照表の位置にオブジェクトがあること
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(text[startxref..].starts_with("xref\n"));
+        for (i, line) in text[startxref..].lines().skip(3).take(8).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+}
@@ -1,16 +1,183 @@
-use async_graphql::http::GraphiQLSource;
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use std::{str::FromStr, sync::Arc};
+
+use async_graphql::{
+    http::{GraphiQLSource, ALL_WEBSOCKET_PROTOCOLS},
+    Data, ErrorExtensions, ServerError,
+};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
-    extract::Extension, http::StatusCode, response::{self, IntoResponse, Response}, routing::{get, get_service}, Router, body::Body
+    extract::{ws::WebSocketUpgrade, Extension, Path}, http::{header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE}, HeaderMap, StatusCode}, response::{self, IntoResponse, Response}, routing::{get, get_service}, Router, body::Body
 };
+use api_error::ApiError;
+use chrono::Utc;
+use domain::model::{
+    apply::ApplyId,
+    certificate::{Certificate, CertificateError},
+};
+use firebase_auth::verifier::TokenVerifier;
 use sqlx::MySqlPool;
 use tower_http::services::ServeDir;
 
-use crate::resolvers::{create_schema, ApiSchema};
+use query_repository::{
+    access::Viewer,
+    activities::certificate::{CertificateRepository, CertificateSource},
+    event::EventSubscriber,
+};
+
+use crate::{
+    activities::certificate::CertificateImpl,
+    certificate::{render_pdf, CertificateSigner},
+    resolvers::{create_schema, ApiSchema},
+};
+
+/// Bearerトークンを検証し, リクエストしたユーザーを[Viewer]として返す
+///
+/// トークンがない場合はログインしていない[Viewer]を返す
+async fn authenticate(verifier: &TokenVerifier, token: Option<&str>) -> Result<Viewer, ApiError> {
+    match token {
+        None => Ok(Viewer::default()),
+        Some(token) => match verifier.verify(token).await {
+            Ok(uid) => Ok(Viewer {
+                uid: Some(uid.to_string()),
+            }),
+            Err(error) => Err(ApiError::Unauthorized(error.to_string())),
+        },
+    }
+}
+
+/// `Bearer <token>`の形式の値からトークンを取り出す
+fn bearer_token(value: &str) -> Option<&str> {
+    value.strip_prefix("Bearer ").map(|token| token.trim())
+}
 
 /// GraphQLのリクエストを受け付けるエンドポイント
-async fn graphql_handler(schema: Extension<ApiSchema>, req: GraphQLRequest) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+///
+/// Bearerトークンがある場合は検証し, リクエストしたユーザーを[Viewer]としてContextに追加する
+async fn graphql_handler(
+    schema: Extension<ApiSchema>,
+    verifier: Extension<Arc<TokenVerifier>>,
+    headers: HeaderMap,
+    req: GraphQLRequest,
+) -> GraphQLResponse {
+    let token: Option<&str> = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token);
+
+    let viewer: Viewer = match authenticate(&verifier, token).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            let error: ServerError = error.extend().into_server_error(Default::default());
+            return async_graphql::Response::from_errors(vec![error]).into();
+        }
+    };
+
+    schema.execute(req.into_inner().data(viewer)).await.into()
+}
+
+/// GraphQLのサブスクリプションを受け付けるWebSocketのエンドポイント
+///
+/// 接続時のペイロードの`Authorization`にBearerトークンがある場合は検証し, [Viewer]としてContextに追加する
+async fn graphql_ws_handler(
+    Extension(schema): Extension<ApiSchema>,
+    Extension(verifier): Extension<Arc<TokenVerifier>>,
+    protocol: GraphQLProtocol,
+    websocket: WebSocketUpgrade,
+) -> Response {
+    websocket
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .on_connection_init(move |payload: serde_json::Value| async move {
+                    let token: Option<&str> = payload
+                        .get("Authorization")
+                        .and_then(|value| value.as_str())
+                        .and_then(bearer_token);
+                    let viewer: Viewer = authenticate(&verifier, token)
+                        .await
+                        .map_err(|error| error.extend())?;
+
+                    let mut data: Data = Data::default();
+                    data.insert(viewer);
+                    Ok(data)
+                })
+                .serve()
+        })
+}
+
+/// 応募した参加者本人の証明書を発行し, PDFにする
+async fn issue_certificate(
+    verifier: &TokenVerifier,
+    certificate_dao: &dyn CertificateRepository,
+    signer: &CertificateSigner,
+    aid: &ApplyId,
+    headers: &HeaderMap,
+) -> Result<Vec<u8>, ApiError> {
+    let token: Option<&str> = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token);
+    let uid: String = match authenticate(verifier, token).await?.uid {
+        Some(uid) => uid,
+        None => {
+            return Err(ApiError::Unauthorized(
+                "login is required to download a certificate".to_string(),
+            ))
+        }
+    };
+
+    let source: CertificateSource = certificate_dao
+        .find_source(aid)
+        .await
+        .map_err(|error| ApiError::from(&error))?;
+    if source.uid != uid {
+        return Err(ApiError::Forbidden(
+            "only the participant of the apply can download the certificate".to_string(),
+        ));
+    }
+
+    let certificate: Certificate = match source.issue(Utc::now()) {
+        Ok(certificate) => certificate,
+        Err(error) => match error.downcast_ref::<CertificateError>() {
+            Some(_) => return Err(ApiError::Conflict(error.to_string())),
+            None => return Err(ApiError::Internal(error.to_string())),
+        },
+    };
+    let signature: String = signer.sign(&certificate);
+
+    Ok(render_pdf(&certificate, &signature))
+}
+
+/// 出席したボランティアの活動時間の証明書をPDFでダウンロードするエンドポイント
+///
+/// 証明書IDは応募IDで, 応募した参加者本人のみダウンロードできる
+async fn certificate_handler(
+    Extension(verifier): Extension<Arc<TokenVerifier>>,
+    Extension(certificate_dao): Extension<Arc<dyn CertificateRepository>>,
+    Extension(signer): Extension<Arc<CertificateSigner>>,
+    Path(aid): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let aid: ApplyId = match ApplyId::from_str(&aid) {
+        Ok(aid) => aid,
+        Err(error) => return ApiError::Validation(error.to_string()).into_response(),
+    };
+
+    match issue_certificate(&verifier, certificate_dao.as_ref(), &signer, &aid, &headers).await {
+        Ok(pdf) => (
+            StatusCode::OK,
+            [
+                (CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"certificate-{}.pdf\"", aid),
+                ),
+            ],
+            pdf,
+        )
+            .into_response(),
+        Err(error) => error.into_response(),
+    }
 }
 
 /// GraphQL IDEのためのエンドポイント
@@ -18,12 +185,15 @@ async fn graphql() -> impl IntoResponse {
     response::Html(
         GraphiQLSource::build()
             .endpoint(Endpoints::GraphQL.as_str())
+            .subscription_endpoint(Endpoints::GraphQLWebSocket.as_str())
             .finish(),
     )
 }
 
 pub enum Endpoints {
     GraphQL,
+    GraphQLWebSocket,
+    Certificate,
     Assets,
 }
 
@@ -31,14 +201,22 @@ impl Endpoints {
     pub fn as_str(&self) -> &'static str {
         match *self {
             Endpoints::GraphQL => "/graphql",
+            Endpoints::GraphQLWebSocket => "/graphql/ws",
+            Endpoints::Certificate => "/certificate/:aid",
             Endpoints::Assets => "/assets",
         }
     }
 }
 
 /// [Router]を生成する関数
-pub fn create_router(pool: MySqlPool) -> Router {
-    let schema = create_schema(pool);
+pub fn create_router(
+    pool: MySqlPool,
+    verifier: Arc<TokenVerifier>,
+    subscriber: Arc<dyn EventSubscriber>,
+    signer: Arc<CertificateSigner>,
+) -> Router {
+    let schema = create_schema(pool.clone(), subscriber, signer.clone());
+    let certificate_dao: Arc<dyn CertificateRepository> = Arc::new(CertificateImpl::new(pool));
     let serve_dir = ServeDir::new(&Endpoints::Assets.as_str()[1..]);
     let service = get_service(serve_dir);
 
@@ -46,7 +224,10 @@ pub fn create_router(pool: MySqlPool) -> Router {
         .route(
             Endpoints::GraphQL.as_str(),
             get(graphql).post(graphql_handler),
-        ).route(
+        )
+        .route(Endpoints::GraphQLWebSocket.as_str(), get(graphql_ws_handler))
+        .route(Endpoints::Certificate.as_str(), get(certificate_handler))
+        .route(
             "/health",
             get(|| async {
             Response::builder()
@@ -57,4 +238,7 @@ pub fn create_router(pool: MySqlPool) -> Router {
         )
         .nest_service(Endpoints::Assets.as_str(), service)
         .layer(Extension(schema))
+        .layer(Extension(verifier))
+        .layer(Extension(certificate_dao))
+        .layer(Extension(signer))
 }
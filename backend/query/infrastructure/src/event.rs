@@ -0,0 +1,198 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, StreamExt};
+use redis::Client;
+use sqlx::MySqlPool;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use domain::model::event::{activity_channel, ActivityEvent};
+use query_repository::event::EventSubscriber;
+
+/// Redisのチャンネルから購読する. Write API Serverが別のプロセスで動く場合に使用する
+pub struct RedisEventSubscriber {
+    client: Client,
+}
+
+impl RedisEventSubscriber {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for RedisEventSubscriber {
+    async fn subscribe(&self, uid: &str) -> Result<BoxStream<'static, ActivityEvent>> {
+        let mut pubsub = self.client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(activity_channel(uid)).await?;
+
+        Ok(pubsub
+            .into_on_message()
+            .filter_map(|message| async move {
+                let payload: String = message.get_payload().ok()?;
+                serde_json::from_str::<ActivityEvent>(&payload).ok()
+            })
+            .boxed())
+    }
+}
+
+/// `activity_event`テーブルをポーリングする間隔
+const POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// ポーリングで遡る時間 (秒)
+///
+/// idは挿入時に採番されるため, 小さいidの出来事が後からコミットされることがある.
+/// この時間内に作成された出来事を毎回取得し, 受信済のidを除いて配信する
+const RECEIVE_WINDOW_SECS: i64 = 30;
+
+/// 購読者が受信できずに溜められる出来事の数
+const SUBSCRIBER_CAPACITY: usize = 256;
+
+/// 配信先のユーザーのidと出来事
+type Delivery = (String, ActivityEvent);
+
+/// `activity_event`テーブルをポーリングして購読する. Redisを使わずにローカルで動かす場合に使用する
+///
+/// プロセスごとに1つのタスクがポーリングし, 購読者に振り分ける. 購読者がいない間はポーリングしない.
+/// 購読を開始した時点より後に受信した出来事のみ配信する
+pub struct DatabaseEventSubscriber {
+    sender: broadcast::Sender<Delivery>,
+}
+
+impl DatabaseEventSubscriber {
+    /// ポーリングするタスクを開始する. tokioのランタイム内で呼び出す
+    pub fn new(pool: MySqlPool) -> Self {
+        let sender: broadcast::Sender<Delivery> = broadcast::channel(SUBSCRIBER_CAPACITY).0;
+        tokio::spawn(poll(pool, sender.clone()));
+        Self { sender }
+    }
+}
+
+/// 遡る時間内に受信した出来事のid
+#[derive(Debug, Default)]
+struct ReceivedEvents {
+    received: HashMap<u64, DateTime<Utc>>,
+}
+
+impl ReceivedEvents {
+    /// 初めて受信した出来事の場合はtrue
+    fn insert(&mut self, eid: u64, created_at: DateTime<Utc>) -> bool {
+        self.received.insert(eid, created_at).is_none()
+    }
+
+    /// 遡る時間より前に作成された出来事を忘れる
+    fn forget_before(&mut self, since: DateTime<Utc>) {
+        self.received.retain(|_, created_at| *created_at >= since);
+    }
+}
+
+/// 購読者がいる間, 遡る時間内の出来事を取得し, 受信済でない出来事を配信する
+async fn poll(pool: MySqlPool, sender: broadcast::Sender<Delivery>) {
+    let mut received: ReceivedEvents = ReceivedEvents::default();
+    let mut subscribed_at: DateTime<Utc> = Utc::now();
+    let mut interval = tokio::time::interval(POLLING_INTERVAL);
+    loop {
+        interval.tick().await;
+        if sender.receiver_count() == 0 {
+            received = ReceivedEvents::default();
+            subscribed_at = Utc::now();
+            continue;
+        }
+
+        let since: DateTime<Utc> =
+            subscribed_at.max(Utc::now() - chrono::Duration::seconds(RECEIVE_WINDOW_SECS));
+        received.forget_before(since);
+        // 取得に失敗した場合はログに出力し, 次のポーリングで再試行する
+        if let Err(error) = poll_since(&pool, &sender, &mut received, since).await {
+            log::error!("failed to poll activity events: {}", error);
+        }
+    }
+}
+
+async fn poll_since(
+    pool: &MySqlPool,
+    sender: &broadcast::Sender<Delivery>,
+    received: &mut ReceivedEvents,
+    since: DateTime<Utc>,
+) -> Result<()> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT eid, recipient, CAST(payload AS CHAR) as "payload!: String", created_at as "created_at: DateTime<Utc>"
+        FROM activity_event
+        WHERE created_at >= ?
+        ORDER BY eid
+        "#,
+        since
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        if !received.insert(row.eid, row.created_at) {
+            continue;
+        }
+        match serde_json::from_str::<ActivityEvent>(&row.payload) {
+            // 購読者がいない場合はエラーになるが, 配信先がいないだけなので無視する
+            Ok(event) => {
+                let _ = sender.send((row.recipient, event));
+            }
+            Err(error) => log::warn!("skipped invalid activity event {}: {}", row.eid, error),
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl EventSubscriber for DatabaseEventSubscriber {
+    async fn subscribe(&self, uid: &str) -> Result<BoxStream<'static, ActivityEvent>> {
+        let receiver: broadcast::Receiver<Delivery> = self.sender.subscribe();
+        let uid: String = uid.to_string();
+
+        Ok(
+            futures::stream::unfold((receiver, uid), |(mut receiver, uid)| async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok((recipient, event)) if recipient == uid => {
+                            return Some((event, (receiver, uid)))
+                        }
+                        Ok(_) => continue,
+                        // 受信が遅れて溢れた出来事は読み飛ばし, 購読を続ける
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            })
+            .boxed(),
+        )
+    }
+}
+
+/// 環境変数`REDIS_URL`が設定されている場合はRedis, それ以外は`activity_event`テーブルから購読する
+///
+/// Write API Serverは別のプロセスで動くため, Redisを使わない場合もデータベースを経由して受信する
+pub fn create_event_subscriber(pool: MySqlPool) -> Result<Arc<dyn EventSubscriber>> {
+    match std::env::var("REDIS_URL") {
+        Ok(url) => Ok(Arc::new(RedisEventSubscriber::new(Client::open(url)?))),
+        Err(_) => Ok(Arc::new(DatabaseEventSubscriber::new(pool))),
+    }
+}
+
+#[cfg(test)]
+mod test_received_events {
+    use super::*;
+
+    #[test]
+    fn late_commit() {
+        let now: DateTime<Utc> = Utc::now();
+        let mut received = ReceivedEvents::default();
+        assert!(received.insert(2, now));
+        // 後からコミットされた小さいidの出来事も受信する
+        assert!(received.insert(1, now));
+        assert!(!received.insert(2, now));
+
+        received.forget_before(now + chrono::Duration::seconds(1));
+        assert!(received.insert(2, now));
+    }
+}
@@ -1,4 +1,8 @@
+pub mod access;
 pub mod activities;
+pub mod certificate;
 pub mod controllers;
+pub mod event;
 pub mod resolvers;
+pub mod search;
 pub mod user_account;
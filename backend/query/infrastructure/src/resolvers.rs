@@ -1,40 +1,71 @@
 use std::{str::FromStr, sync::Arc};
 
-use anyhow::Result;
 use async_graphql::{
-    futures_util::{Stream, StreamExt},
-    Context, EmptyMutation, Object, Schema, SchemaBuilder, Subscription,
+    connection::{Connection, Edge},
+    futures_util::{stream::BoxStream, Stream, StreamExt},
+    Context, EmptyMutation, ErrorExtensions, Object, OutputType, Result, ResultExt, Schema,
+    SchemaBuilder, Subscription,
 };
-use redis::Client;
+use chrono::{NaiveDate, Utc};
 use sqlx::MySqlPool;
 
+use api_error::ApiError;
 use domain::model::{
-    apply::ApplyId, scout::ScoutId, user_account::user_id::UserId, volunteer::VolunteerId,
+    apply::ApplyId,
+    condition::Condition,
+    event::ActivityEvent,
+    message::{MessageId, ThreadId},
+    plan::{BillingPeriod, PLAN_CATALOG},
+    region::Region,
+    schedule::SessionId,
+    scout::ScoutId,
+    target_status::TargetStatus, theme::Theme, user_account::user_id::UserId,
+    volunteer::VolunteerId,
 };
 use query_repository::{
+    access::{AccessRepository, ApplyMembersGuard, Viewer},
     activities::{
         apply::{Apply, ApplyRepository, PastVolunteerParticipantReadModel},
+        attendance::{AttendanceRepository, CheckInCode, ParticipantReliability},
+        certificate::{
+            CertificateRepository, CertificateSource, VolunteerHours, VolunteerHoursSummary,
+        },
+        message::{Message, MessageRepository, MessageThread},
         review::{
-            ParticipantReviewPointAverage, ParticipantReviewRepository, Review,
+            ParticipantReviewPointAverage, ParticipantReviewRepository, Review, ReviewCursor,
             VolunteerReviewRepository,
         },
         scout::{Scout, ScoutFromGroup, ScoutRepository},
-        volunteer::{VolunteerElementsReadModel, VolunteerQueryRepository, VolunteerReadModel},
+        volunteer::{
+            RecommendationCursor, RecommendedVolunteer, SessionActivityReadModel,
+            VolunteerElementsReadModel, VolunteerQueryRepository, VolunteerReadModel,
+            VolunteerSearchCursor, VolunteerSearchInput, VolunteerSessionReadModel,
+        },
     },
+    error::QueryError,
+    event::{ApplyEvent, EventSubscriber, ScoutReceivedEvent},
+    pagination::{ConnectionFields, Page, PageRequest},
     user_account::{
-        group::{GroupAccount, GroupUserRepository},
+        group::{
+            GroupAccount, GroupInvoice, GroupUserRepository, PlanCatalogItem, PlanHistory,
+            PlanUsage,
+        },
         participant::{
-            GroupParticipant, ParticipantAccount, ParticipantCondition, ParticipantRegion,
-            ParticipantTargetStatus, ParticipantTheme, ParticipantUserRepository, ScoutParticipant,
+            GroupParticipant, ParticipantAccount, ParticipantAvailability, ParticipantCondition,
+            ParticipantRegion, ParticipantTargetStatus, ParticipantTheme, ParticipantUserRepository,
+            ScoutCandidateFilter, ScoutParticipant,
         },
     },
 };
 
 use crate::{
+    access::AccessImpl,
     activities::{
-        apply::ApplyImpl, review::ReviewImpl, scout::ScoutImpl,
+        apply::ApplyImpl, attendance::AttendanceImpl, certificate::CertificateImpl,
+        message::MessageImpl, review::ReviewImpl, scout::ScoutImpl,
         volunteer::VolunteerQueryRepositoryImpl,
     },
+    certificate::CertificateSigner,
     user_account::{group::GroupAccountImpl, participant::ParticipantAccountImpl},
 };
 
@@ -46,6 +77,9 @@ pub struct ServiceContext {
     volunteer_dao: Arc<dyn VolunteerQueryRepository>,
     participant_review_dao: Arc<dyn ParticipantReviewRepository>,
     volunteer_review_dao: Arc<dyn VolunteerReviewRepository>,
+    message_dao: Arc<dyn MessageRepository>,
+    attendance_dao: Arc<dyn AttendanceRepository>,
+    certificate_dao: Arc<dyn CertificateRepository>,
 }
 
 impl ServiceContext {
@@ -57,6 +91,9 @@ impl ServiceContext {
         volunteer_dao: Arc<dyn VolunteerQueryRepository>,
         participant_review_dao: Arc<dyn ParticipantReviewRepository>,
         volunteer_review_dao: Arc<dyn VolunteerReviewRepository>,
+        message_dao: Arc<dyn MessageRepository>,
+        attendance_dao: Arc<dyn AttendanceRepository>,
+        certificate_dao: Arc<dyn CertificateRepository>,
     ) -> Self {
         Self {
             group_account_dao,
@@ -66,6 +103,9 @@ impl ServiceContext {
             volunteer_dao,
             participant_review_dao,
             volunteer_review_dao,
+            message_dao,
+            attendance_dao,
+            certificate_dao,
         }
     }
 }
@@ -87,9 +127,9 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         gid: String,
     ) -> Result<GroupAccount> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid: UserId = UserId::new(&gid).unwrap();
-        let group_account: GroupAccount = ctx.group_account_dao.find_by_id(&gid).await?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
+        let group_account: GroupAccount = ctx.group_account_dao.find_by_id(&gid).await.extend()?;
 
         Ok(group_account)
     }
@@ -106,11 +146,11 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         gids: Vec<String>,
     ) -> Result<Vec<GroupAccount>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
         let gids: Vec<UserId> = gids
             .iter()
-            .map(|gid| UserId::from_str(gid).unwrap())
-            .collect();
+            .map(|gid| parse_id(gid))
+            .collect::<Result<Vec<UserId>>>()?;
 
         let group_accounts: Vec<GroupAccount> = ctx.group_account_dao.find_by_ids(&gids).await?;
 
@@ -122,7 +162,7 @@ impl QueryRoot {
     /// ## 返り値
     /// - `Vec<GroupAccount>` - グループアカウント情報の配列
     async fn get_all_group_accounts<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<GroupAccount>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
         let group_accounts: Vec<GroupAccount> = ctx.group_account_dao.find_all().await?;
 
         Ok(group_accounts)
@@ -136,13 +176,64 @@ impl QueryRoot {
     /// ## 返り値
     /// - `bool` - 存在する場合はtrue
     async fn exists_group_account<'ctx>(&self, ctx: &Context<'ctx>, gid: String) -> Result<bool> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid: UserId = UserId::new(&gid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
         let exists: bool = ctx.group_account_dao.exists(&gid).await?;
 
         Ok(exists)
     }
 
+    /// ログイン中の団体のプランの利用状況を取得する
+    ///
+    /// 請求期間は日本時間の今月
+    ///
+    /// ## 返り値
+    /// - `PlanUsage` - プランの上限と, 請求期間内のスカウト数・募集中のボランティア数
+    async fn get_plan_usage<'ctx>(&self, ctx: &Context<'ctx>) -> Result<PlanUsage> {
+        let gid: UserId = login_user(ctx, "get plan usage")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let period: BillingPeriod = BillingPeriod::containing(Utc::now());
+        let usage: PlanUsage = ctx
+            .group_account_dao
+            .find_plan_usage(&gid, &period)
+            .await
+            .extend()?;
+
+        Ok(usage)
+    }
+
+    /// 選択できるプランの一覧を取得する
+    ///
+    /// ## 返り値
+    /// - `Vec<PlanCatalogItem>` - プランごとの料金・無料体験の日数・利用上限
+    async fn get_plan_catalog(&self) -> Vec<PlanCatalogItem> {
+        PLAN_CATALOG.into_iter().map(PlanCatalogItem::new).collect()
+    }
+
+    /// ログイン中の団体のプランの変更履歴を取得する
+    ///
+    /// ## 返り値
+    /// - `Vec<PlanHistory>` - プランの変更履歴(新しい順)
+    async fn get_plan_history<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<PlanHistory>> {
+        let gid: UserId = login_user(ctx, "get plan history")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let history: Vec<PlanHistory> = ctx.group_account_dao.find_plan_history(&gid).await?;
+
+        Ok(history)
+    }
+
+    /// ログイン中の団体の請求書を取得する
+    ///
+    /// ## 返り値
+    /// - `Vec<GroupInvoice>` - 請求書(新しい順)
+    async fn get_invoices<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<GroupInvoice>> {
+        let gid: UserId = login_user(ctx, "get invoices")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let invoices: Vec<GroupInvoice> = ctx.group_account_dao.find_invoices(&gid).await?;
+
+        Ok(invoices)
+    }
+
     /// 指定されたuidのアカウント情報を取得する
     ///
     /// ## 引数
@@ -155,10 +246,10 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uid: String,
     ) -> Result<ParticipantAccount> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
         let participant_account: ParticipantAccount =
-            ctx.participant_account_dao.find_by_id(&uid).await?;
+            ctx.participant_account_dao.find_by_id(&uid).await.extend()?;
 
         Ok(participant_account)
     }
@@ -175,11 +266,11 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uids: Vec<String>,
     ) -> Result<Vec<ParticipantAccount>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
         let uids: Vec<UserId> = uids
             .iter()
-            .map(|uid| UserId::from_str(uid).unwrap())
-            .collect();
+            .map(|uid| parse_id(uid))
+            .collect::<Result<Vec<UserId>>>()?;
 
         let participant_accounts: Vec<ParticipantAccount> =
             ctx.participant_account_dao.find_by_ids(&uids).await?;
@@ -199,8 +290,8 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uid: String,
     ) -> Result<Vec<ParticipantRegion>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
         let regions: Vec<ParticipantRegion> =
             ctx.participant_account_dao.find_region_by_id(&uid).await?;
 
@@ -219,8 +310,8 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uid: String,
     ) -> Result<Vec<ParticipantTheme>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
         let themes: Vec<ParticipantTheme> =
             ctx.participant_account_dao.find_theme_by_id(&uid).await?;
 
@@ -239,8 +330,8 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uid: String,
     ) -> Result<Vec<ParticipantCondition>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
         let conditions: Vec<ParticipantCondition> = ctx
             .participant_account_dao
             .find_condition_by_id(&uid)
@@ -261,16 +352,37 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uid: String,
     ) -> Result<ParticipantTargetStatus> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
         let target_status: ParticipantTargetStatus = ctx
             .participant_account_dao
             .find_target_status_by_id(&uid)
-            .await?;
+            .await
+            .extend()?;
 
         Ok(target_status)
     }
 
+    /// 指定されたuidの参加可能な時間帯を取得する
+    ///
+    /// ## 引数
+    /// - `uid` - uid
+    ///
+    /// ## 返り値
+    /// - `Vec<ParticipantAvailability>` - 曜日, 開始時刻順の参加可能な時間帯の配列
+    async fn get_participant_availabilities<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        uid: String,
+    ) -> Result<Vec<ParticipantAvailability>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let availabilities: Vec<ParticipantAvailability> =
+            ctx.participant_account_dao.find_availability_by_id(&uid).await?;
+
+        Ok(availabilities)
+    }
+
     /// 参加者アカウントの存在チェックをする
     ///
     /// ## 引数
@@ -283,8 +395,8 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uid: String,
     ) -> Result<bool> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
         let exists: bool = ctx.participant_account_dao.exists(&uid).await?;
 
         Ok(exists)
@@ -297,13 +409,16 @@ impl QueryRoot {
     ///
     /// ## 返り値
     /// - `Vec<GroupParticipant>` - 集団応募者の詳細情報の配列
+    ///
+    /// 応募者本人と応募先の団体のみ取得できる
+    #[graphql(guard = "ApplyMembersGuard::new(&aid)")]
     async fn get_group_participants<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         aid: String,
     ) -> Result<Vec<GroupParticipant>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let aid: ApplyId = ApplyId::from_str(&aid);
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let aid: ApplyId = parse_id(&aid)?;
         let participants: Vec<GroupParticipant> = ctx
             .participant_account_dao
             .find_group_participants(&aid)
@@ -320,9 +435,9 @@ impl QueryRoot {
     /// ## 返り値
     /// - `Scout` - スカウト情報
     async fn get_scout_by_sid<'ctx>(&self, ctx: &Context<'ctx>, sid: String) -> Result<Scout> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let sid: ScoutId = ScoutId::from_str(&sid);
-        let scout: Scout = ctx.scout_dao.find_by_sid(&sid).await?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let sid: ScoutId = parse_id(&sid)?;
+        let scout: Scout = ctx.scout_dao.find_by_sid(&sid).await.extend()?;
 
         Ok(scout)
     }
@@ -331,49 +446,77 @@ impl QueryRoot {
     ///
     /// ## 引数
     /// - `gid` - gid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<Scout>` - スカウト情報の配列
-    async fn get_scout_by_gid<'ctx>(&self, ctx: &Context<'ctx>, gid: String) -> Result<Vec<Scout>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid: UserId = UserId::new(&gid).unwrap();
-        let scout: Vec<Scout> = ctx.scout_dao.find_by_gid(&gid).await?;
-
-        Ok(scout)
+    /// - `Connection<Scout>` - スカウト情報のページ
+    async fn get_scout_by_gid<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        gid: String,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Scout, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
+        let page: PageRequest = page_request::<ScoutId>(first, after)?;
+        let scouts: Page<Scout> = ctx.scout_dao.find_by_gid(&gid, &page).await?;
+
+        Ok(connection(scouts, &page))
     }
 
-    /// 指定されたvidのスカウト情報を取得する
+    /// 指定されたvidのスカウト情報を取得する. ボランティアを所有する団体のみ取得できる
     ///
     /// ## 引数
     /// - `vid` - vid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<Scout>` - スカウト情報の配列
+    /// - `Connection<ScoutFromGroup>` - スカウト情報のページ
     async fn get_scout_by_vid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         vid: String,
-    ) -> Result<Vec<ScoutFromGroup>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid: VolunteerId = VolunteerId::from_str(&vid);
-        let scout: Vec<ScoutFromGroup> = ctx.scout_dao.find_by_vid(&vid).await?;
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, ScoutFromGroup, ConnectionFields>> {
+        let vid: VolunteerId = parse_id(&vid)?;
+        let gid: UserId = volunteer_owner(ctx, &vid, "get scouts of the volunteer").await?;
+        let access: &Arc<dyn AccessRepository> = ctx.data::<Arc<dyn AccessRepository>>()?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let page: PageRequest = page_request::<ScoutId>(first, after)?;
+        let mut scouts: Page<ScoutFromGroup> = ctx.scout_dao.find_by_vid(&vid, &page).await?;
+        for (_, scout) in scouts.edges.iter_mut() {
+            hide_birthday(access.as_ref(), &gid, &scout.uid, &mut scout.birthday).await?;
+        }
 
-        Ok(scout)
+        Ok(connection(scouts, &page))
     }
 
     /// 指定されたuidに送られたスカウト情報を取得する
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<Scout>` - スカウト情報の配列
-    async fn get_scout_by_uid<'ctx>(&self, ctx: &Context<'ctx>, uid: String) -> Result<Vec<Scout>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
-        let scout: Vec<Scout> = ctx.scout_dao.find_by_uid(&uid).await?;
-
-        Ok(scout)
+    /// - `Connection<Scout>` - スカウト情報のページ
+    async fn get_scout_by_uid<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        uid: String,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Scout, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<ScoutId>(first, after)?;
+        let scouts: Page<Scout> = ctx.scout_dao.find_by_uid(&uid, &page).await?;
+
+        Ok(connection(scouts, &page))
     }
 
     /// 指定されたaidの応募情報を取得する
@@ -384,9 +527,9 @@ impl QueryRoot {
     /// ## 返り値
     /// - `Apply` - 応募情報
     async fn get_apply_by_aid<'ctx>(&self, ctx: &Context<'ctx>, aid: String) -> Result<Apply> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let aid: ApplyId = ApplyId::from_str(&aid);
-        let apply: Apply = ctx.apply_dao.find_by_sid(&aid).await?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let aid: ApplyId = parse_id(&aid)?;
+        let apply: Apply = ctx.apply_dao.find_by_sid(&aid).await.extend()?;
 
         Ok(apply)
     }
@@ -395,45 +538,72 @@ impl QueryRoot {
     ///
     /// ## 引数
     /// - `gid` - gid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<Apply>` - 応募情報の配列
-    async fn get_apply_by_gid<'ctx>(&self, ctx: &Context<'ctx>, gid: String) -> Result<Vec<Apply>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid: UserId = UserId::new(&gid).unwrap();
-        let scout: Vec<Apply> = ctx.apply_dao.find_by_gid(&gid).await?;
-
-        Ok(scout)
+    /// - `Connection<Apply>` - 応募情報のページ
+    async fn get_apply_by_gid<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        gid: String,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Apply, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
+        let page: PageRequest = page_request::<ApplyId>(first, after)?;
+        let applies: Page<Apply> = ctx.apply_dao.find_by_gid(&gid, &page).await?;
+
+        Ok(connection(applies, &page))
     }
 
     /// 指定されたvidの応募情報を取得する
     ///
     /// ## 引数
     /// - `vid` - vid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<Apply>` - 応募情報の配列
-    async fn get_apply_by_vid<'ctx>(&self, ctx: &Context<'ctx>, vid: String) -> Result<Vec<Apply>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid: VolunteerId = VolunteerId::from_str(&vid);
-        let scout: Vec<Apply> = ctx.apply_dao.find_by_vid(&vid).await?;
-
-        Ok(scout)
+    /// - `Connection<Apply>` - 応募情報のページ
+    async fn get_apply_by_vid<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        vid: String,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Apply, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let page: PageRequest = page_request::<ApplyId>(first, after)?;
+        let applies: Page<Apply> = ctx.apply_dao.find_by_vid(&vid, &page).await?;
+
+        Ok(connection(applies, &page))
     }
 
     /// 指定されたuidに送られた応募情報を取得する
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<Apply>` - 応募情報の配列
-    async fn get_apply_by_uid<'ctx>(&self, ctx: &Context<'ctx>, uid: String) -> Result<Vec<Apply>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid: UserId = UserId::new(&uid).unwrap();
-        let scout: Vec<Apply> = ctx.apply_dao.find_by_uid(&uid).await?;
-
-        Ok(scout)
+    /// - `Connection<Apply>` - 応募情報のページ
+    async fn get_apply_by_uid<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        uid: String,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Apply, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<ApplyId>(first, after)?;
+        let applies: Page<Apply> = ctx.apply_dao.find_by_uid(&uid, &page).await?;
+
+        Ok(connection(applies, &page))
     }
 
     /// 指定されたvidのボランティアに指定されたuidが応募しているかどうかを確認する
@@ -450,15 +620,15 @@ impl QueryRoot {
         vid: String,
         uid: String,
     ) -> Result<bool> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid: VolunteerId = VolunteerId::from_str(&vid);
-        let uid: UserId = UserId::new(&uid).unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let uid: UserId = parse_id(&uid)?;
         let exists: bool = ctx.apply_dao.exists_apply(&vid, &uid).await?;
 
         Ok(exists)
     }
 
-    /// 指定されたvidのボランティアに参加した参加者情報を取得する
+    /// 指定されたvidのボランティアに出席した参加者情報を取得する. ボランティアを所有する団体のみ取得できる
     ///
     /// ## 引数
     /// - `vid` - vid
@@ -470,10 +640,16 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         vid: String,
     ) -> Result<Vec<PastVolunteerParticipantReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid = VolunteerId::from_str(&vid);
-        let participants: Vec<PastVolunteerParticipantReadModel> =
+        let vid: VolunteerId = parse_id(&vid)?;
+        let gid: UserId = volunteer_owner(ctx, &vid, "get participants of the volunteer").await?;
+        let access: &Arc<dyn AccessRepository> = ctx.data::<Arc<dyn AccessRepository>>()?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let mut participants: Vec<PastVolunteerParticipantReadModel> =
             ctx.apply_dao.find_past_volunteer_participants(&vid).await?;
+        for participant in participants.iter_mut() {
+            hide_birthday(access.as_ref(), &gid, &participant.uid, &mut participant.birthday)
+                .await?;
+        }
 
         Ok(participants)
     }
@@ -490,8 +666,8 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         vid: String,
     ) -> Result<VolunteerElementsReadModel> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid = VolunteerId::from_str(&vid);
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
         let volunteer: VolunteerElementsReadModel =
             ctx.volunteer_dao.find_elements_by_id(&vid).await?;
 
@@ -501,49 +677,65 @@ impl QueryRoot {
     /// ボランティアを検索する
     ///
     /// ## 引数
-    /// - `regions` - OR検索の地域: Vec<String>
-    /// - `required_regions` - AND検索の地域(東京23区外のボランティアを除外する等の場合で使用を推奨): Vec<String>,
-    /// - `themes` - OR検索のテーマ: Vec<String>,
-    /// - `required_themes` - AND検索のテーマ: Vec<String>,
-    /// - `conditions` - OR検索の条件: Vec<String>,
-    /// - `required_conditions` - AND検索の条件: Vec<String>,
-    /// - `target_status` - 対象者: Vec<String>,
-    /// - `search_words` - 検索ワード: String
+    /// - `input` - 要素, 地域, 検索ワード, 日時などの検索条件
+    ///   (`matchAvailability`を指定する場合はログインが必要)
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `VolunteerElementsReadModel` - ボランティア要素情報
+    /// - `Connection<VolunteerReadModel>` - 関連度順のボランティア情報のページ
     async fn search_volunteer_by_elements<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-        regions: Vec<String>,
-        required_regions: Vec<String>,
-        themes: Vec<String>,
-        required_themes: Vec<String>,
-        conditions: Vec<String>,
-        required_conditions: Vec<String>,
-        target_status: Vec<String>,
-        search_words: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid = "";
-        let volunteer: Vec<VolunteerReadModel> = ctx
+        input: VolunteerSearchInput,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, VolunteerReadModel, ConnectionFields>> {
+        // 参加可能な時間帯で並べ替える場合は, リクエストした参加者の時間帯を使用する
+        let uid: Option<UserId> = if input.match_availability {
+            Some(login_user(ctx, "match availability")?)
+        } else {
+            None
+        };
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        validate_elements(&input.elements())?;
+        input
+            .validate()
+            .map_err(|error| ApiError::Validation(error.to_string()).extend())?;
+        let page: PageRequest = page_request::<VolunteerSearchCursor>(first, after)?;
+
+        let volunteers: Page<VolunteerReadModel> = ctx
             .volunteer_dao
-            .find_by_elements(
-                &VolunteerElementsReadModel::new(
-                    vid.to_string(),
-                    regions,
-                    Some(required_regions),
-                    themes,
-                    required_themes,
-                    conditions,
-                    required_conditions,
-                    target_status,
-                ),
-                search_words,
-            )
+            .find_by_elements(&input, uid.as_ref(), &page)
             .await?;
 
-        Ok(volunteer)
+        Ok(connection(volunteers, &page))
+    }
+
+    /// ログイン中の参加者におすすめのボランティアを取得する
+    ///
+    /// 登録した地域・要素, お気に入り, 応募, 評価から求めた嗜好との一致度が高い順に並べる
+    ///
+    /// ## 引数
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
+    ///
+    /// ## 返り値
+    /// - `Connection<RecommendedVolunteer>` - 一致度順のボランティア情報と一致した要素のページ
+    async fn recommend_volunteers<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, RecommendedVolunteer, ConnectionFields>> {
+        let uid: UserId = login_user(ctx, "get recommendations")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let page: PageRequest = page_request::<RecommendationCursor>(first, after)?;
+
+        let volunteers: Page<RecommendedVolunteer> =
+            ctx.volunteer_dao.find_recommended(&uid, &page).await?;
+
+        Ok(connection(volunteers, &page))
     }
 
     /// 指定されたvidのボランティア情報を取得する
@@ -558,156 +750,220 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         vid: String,
     ) -> Result<VolunteerReadModel> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid = VolunteerId::from_str(&vid);
-        let volunteer: VolunteerReadModel = ctx.volunteer_dao.find_by_id(&vid).await?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let volunteer: VolunteerReadModel = ctx.volunteer_dao.find_by_id(&vid).await.extend()?;
 
         Ok(volunteer)
     }
 
+    /// 指定されたvidのボランティアの回を開始日時の早い順に取得する
+    ///
+    /// ## 引数
+    /// - `vid` - vid
+    ///
+    /// ## 返り値
+    /// - `Vec<VolunteerSessionReadModel>` - 回の日時, 募集人数, 承認済の人数
+    async fn get_volunteer_sessions<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        vid: String,
+    ) -> Result<Vec<VolunteerSessionReadModel>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let sessions: Vec<VolunteerSessionReadModel> =
+            ctx.volunteer_dao.find_sessions(&vid).await?;
+
+        Ok(sessions)
+    }
+
     /// 指定されたgidのボランティア情報を取得する
     ///
     /// ## 引数
     /// - `gid` - gid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<VolunteerReadModel>` - ボランティア情報の配列
+    /// - `Connection<VolunteerReadModel>` - ボランティア情報のページ
     async fn get_volunteer_by_gid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         gid: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid = UserId::from_str(&gid).unwrap();
-        let volunteers: Vec<VolunteerReadModel> = ctx.volunteer_dao.find_by_gid(&gid).await?;
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, VolunteerReadModel, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
+        let page: PageRequest = page_request::<VolunteerId>(first, after)?;
+        let volunteers: Page<VolunteerReadModel> = ctx
+            .volunteer_dao
+            .find_by_gid(&gid, &page)
+            .await?;
 
-        Ok(volunteers)
+        Ok(connection(volunteers, &page))
     }
 
     /// 指定されたuidがお気に入りに登録しているボランティア情報を取得する
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<VolunteerReadModel>` - ボランティア情報の配列
+    /// - `Connection<VolunteerReadModel>` - ボランティア情報のページ
     async fn get_favorite_by_uid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         uid: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let volunteers: Vec<VolunteerReadModel> =
-            ctx.volunteer_dao.find_favorite_by_id(&uid).await?;
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, VolunteerReadModel, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<VolunteerId>(first, after)?;
+        let volunteers: Page<VolunteerReadModel> = ctx
+            .volunteer_dao
+            .find_favorite_by_id(&uid, &page)
+            .await?;
 
-        Ok(volunteers)
+        Ok(connection(volunteers, &page))
     }
 
-    /// 指定されたuidが過去に活動したボランティア情報を取得する
+    /// 指定されたuidが過去に出席したボランティアの回を取得する
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<VolunteerReadModel>` - ボランティア情報の配列
+    /// - `Connection<SessionActivityReadModel>` - 回とボランティア情報のページ
     async fn get_activities_by_uid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         uid: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let volunteers: Vec<VolunteerReadModel> =
-            ctx.volunteer_dao.find_activity_by_id(&uid).await?;
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, SessionActivityReadModel, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<SessionId>(first, after)?;
+        let activities: Page<SessionActivityReadModel> = ctx
+            .volunteer_dao
+            .find_activity_by_id(&uid, &page)
+            .await?;
 
-        Ok(volunteers)
+        Ok(connection(activities, &page))
     }
 
-    /// 指定されたuidがこれから活動を予定しているボランティア情報を取得する
+    /// 指定されたuidがこれから活動を予定しているボランティアの回を取得する
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<VolunteerReadModel>` - ボランティア情報の配列
+    /// - `Connection<SessionActivityReadModel>` - 回とボランティア情報のページ
     async fn get_scheduled_activities_by_uid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         uid: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let volunteers: Vec<VolunteerReadModel> = ctx
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, SessionActivityReadModel, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<SessionId>(first, after)?;
+        let activities: Page<SessionActivityReadModel> = ctx
             .volunteer_dao
-            .find_scheduled_activity_by_id(&uid)
+            .find_scheduled_activity_by_id(&uid, &page)
             .await?;
 
-        Ok(volunteers)
+        Ok(connection(activities, &page))
     }
 
-    /// 指定されたuidが応募し、未承認のボランティア情報を取得する
+    /// 指定されたuidが応募し、未承認のボランティアの回を取得する
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<VolunteerReadModel>` - ボランティア情報の配列
+    /// - `Connection<SessionActivityReadModel>` - 回とボランティア情報のページ
     async fn get_not_allowed_activities_by_uid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         uid: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let volunteers: Vec<VolunteerReadModel> = ctx
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, SessionActivityReadModel, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<SessionId>(first, after)?;
+        let activities: Page<SessionActivityReadModel> = ctx
             .volunteer_dao
-            .find_not_allowed_activity_by_id(&uid)
+            .find_not_allowed_activity_by_id(&uid, &page)
             .await?;
 
-        Ok(volunteers)
+        Ok(connection(activities, &page))
     }
 
-    /// 指定されたgidが過去に活動したボランティア情報を取得する
+    /// 指定されたgidが過去に活動したボランティアの回を取得する
     ///
     /// ## 引数
     /// - `gid` - gid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<VolunteerReadModel>` - ボランティア情報の配列
+    /// - `Connection<SessionActivityReadModel>` - 回とボランティア情報のページ
     async fn get_activities_by_gid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         gid: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid = UserId::from_str(&gid).unwrap();
-        let volunteers: Vec<VolunteerReadModel> =
-            ctx.volunteer_dao.find_activity_by_gid(&gid).await?;
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, SessionActivityReadModel, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
+        let page: PageRequest = page_request::<SessionId>(first, after)?;
+        let activities: Page<SessionActivityReadModel> = ctx
+            .volunteer_dao
+            .find_activity_by_gid(&gid, &page)
+            .await?;
 
-        Ok(volunteers)
+        Ok(connection(activities, &page))
     }
 
-    /// 指定されたgidがこれから活動を予定しているボランティア情報を取得する
+    /// 指定されたgidがこれから活動を予定しているボランティアの回を取得する
     ///
     /// ## 引数
     /// - `gid` - gid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Vec<VolunteerReadModel>` - ボランティア情報の配列
+    /// - `Connection<SessionActivityReadModel>` - 回とボランティア情報のページ
     async fn get_scheduled_activities_by_gid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         gid: String,
-    ) -> Result<Vec<VolunteerReadModel>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid = UserId::from_str(&gid).unwrap();
-        let volunteers: Vec<VolunteerReadModel> = ctx
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, SessionActivityReadModel, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
+        let page: PageRequest = page_request::<SessionId>(first, after)?;
+        let activities: Page<SessionActivityReadModel> = ctx
             .volunteer_dao
-            .find_scheduled_activity_by_gid(&gid)
+            .find_scheduled_activity_by_gid(&gid, &page)
             .await?;
 
-        Ok(volunteers)
+        Ok(connection(activities, &page))
     }
 
     /// 指定されたuidとvidの参加者レビュー情報を取得する
@@ -724,10 +980,10 @@ impl QueryRoot {
         uid: String,
         vid: String,
     ) -> Result<Review> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let vid = VolunteerId::from_str(&vid);
-        let review: Review = ctx.participant_review_dao.find_by_ids(&uid, &vid).await?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let review: Review = ctx.participant_review_dao.find_by_ids(&uid, &vid).await.extend()?;
 
         Ok(review)
     }
@@ -736,38 +992,48 @@ impl QueryRoot {
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Review` - レビュー情報
+    /// - `Connection<Review>` - レビュー情報のページ
     async fn get_participant_review_by_uid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         uid: String,
-    ) -> Result<Vec<Review>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let review: Vec<Review> = ctx.participant_review_dao.find_by_uid(&uid).await?;
-
-        Ok(review)
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Review, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<ReviewCursor>(first, after)?;
+        let reviews: Page<Review> = ctx.participant_review_dao.find_by_uid(&uid, &page).await?;
+
+        Ok(connection(reviews, &page))
     }
 
     /// 指定されたvidの参加者レビュー情報を取得する
     ///
     /// ## 引数
     /// - `vid` - vid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Review` - レビュー情報
+    /// - `Connection<Review>` - レビュー情報のページ
     async fn get_participant_review_by_vid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         vid: String,
-    ) -> Result<Vec<Review>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid = VolunteerId::from_str(&vid);
-        let review: Vec<Review> = ctx.participant_review_dao.find_by_vid(&vid).await?;
-
-        Ok(review)
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Review, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let page: PageRequest = page_request::<ReviewCursor>(first, after)?;
+        let reviews: Page<Review> = ctx.participant_review_dao.find_by_vid(&vid, &page).await?;
+
+        Ok(connection(reviews, &page))
     }
 
     /// 指定されたuidとvidのボランティアレビュー情報を取得する
@@ -784,10 +1050,10 @@ impl QueryRoot {
         uid: String,
         vid: String,
     ) -> Result<Review> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let vid = VolunteerId::from_str(&vid);
-        let review: Review = ctx.volunteer_review_dao.find_by_ids(&uid, &vid).await?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let review: Review = ctx.volunteer_review_dao.find_by_ids(&uid, &vid).await.extend()?;
 
         Ok(review)
     }
@@ -796,57 +1062,72 @@ impl QueryRoot {
     ///
     /// ## 引数
     /// - `uid` - uid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Review` - レビュー情報
+    /// - `Connection<Review>` - レビュー情報のページ
     async fn get_volunteer_review_by_uid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         uid: String,
-    ) -> Result<Vec<Review>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let uid = UserId::from_str(&uid).unwrap();
-        let review: Vec<Review> = ctx.volunteer_review_dao.find_by_uid(&uid).await?;
-
-        Ok(review)
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Review, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let page: PageRequest = page_request::<ReviewCursor>(first, after)?;
+        let reviews: Page<Review> = ctx.volunteer_review_dao.find_by_uid(&uid, &page).await?;
+
+        Ok(connection(reviews, &page))
     }
 
     /// 指定されたvidのボランティアレビュー情報を取得する
     ///
     /// ## 引数
     /// - `vid` - vid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Review` - レビュー情報
+    /// - `Connection<Review>` - レビュー情報のページ
     async fn get_volunteer_review_by_vid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         vid: String,
-    ) -> Result<Vec<Review>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let vid = VolunteerId::from_str(&vid);
-        let review: Vec<Review> = ctx.volunteer_review_dao.find_by_vid(&vid).await?;
-
-        Ok(review)
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Review, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let page: PageRequest = page_request::<ReviewCursor>(first, after)?;
+        let reviews: Page<Review> = ctx.volunteer_review_dao.find_by_vid(&vid, &page).await?;
+
+        Ok(connection(reviews, &page))
     }
 
     /// 指定されたgidのボランティアレビュー情報を取得する
     ///
     /// ## 引数
     /// - `gid` - gid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
     ///
     /// ## 返り値
-    /// - `Review` - レビュー情報
+    /// - `Connection<Review>` - レビュー情報のページ
     async fn get_volunteer_review_by_gid<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         gid: String,
-    ) -> Result<Vec<Review>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let gid = UserId::from_str(&gid).unwrap();
-        let review: Vec<Review> = ctx.volunteer_review_dao.find_by_gid(&gid).await?;
-
-        Ok(review)
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Review, ConnectionFields>> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let gid: UserId = parse_id(&gid)?;
+        let page: PageRequest = page_request::<ReviewCursor>(first, after)?;
+        let reviews: Page<Review> = ctx.volunteer_review_dao.find_by_gid(&gid, &page).await?;
+
+        Ok(connection(reviews, &page))
     }
 
     /// 指定されたuidの参加者レビュー情報の平均を取得する
@@ -861,11 +1142,11 @@ impl QueryRoot {
         ctx: &Context<'ctx>,
         uids: Vec<String>,
     ) -> Result<Vec<ParticipantReviewPointAverage>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
         let uids: Vec<UserId> = uids
             .iter()
-            .map(|uid| UserId::from_str(uid).unwrap())
-            .collect();
+            .map(|uid| parse_id(uid))
+            .collect::<Result<Vec<UserId>>>()?;
 
         let reviews: Vec<ParticipantReviewPointAverage> =
             ctx.participant_review_dao.find_by_uids(&uids).await?;
@@ -873,7 +1154,7 @@ impl QueryRoot {
         Ok(reviews)
     }
 
-    /// スカウトに適した参加者を検索する
+    /// スカウトに適した参加者を検索する. ボランティアを所有する団体のみ検索できる
     ///
     /// ## 引数
     /// - `regions` - 地域: Vec<String>
@@ -882,9 +1163,11 @@ impl QueryRoot {
     /// - `conditions` - 設定した条件: Vec<String>,
     /// - `required_conditions` - 必須で設定した条件: Vec<String>,
     /// - `target_status` - 対象者: Vec<String>
+    /// - `exclude_scouted` - 同じボランティアにスカウト済みの参加者を除外するか(既定値: true)
+    /// - `exclude_applied` - 同じボランティアに応募済みの参加者を除外するか(既定値: true)
     ///
     /// ## 返り値
-    /// - `ScoutParticipant` - スカウトに必要な参加者情報
+    /// - `ScoutParticipant` - スカウトに必要な参加者情報と一致度の内訳(点数が高い順)
     async fn scout_participant_by_elements<'ctx>(
         &self,
         ctx: &Context<'ctx>,
@@ -895,37 +1178,400 @@ impl QueryRoot {
         conditions: Vec<String>,
         required_conditions: Vec<String>,
         target_status: Vec<String>,
+        #[graphql(default = true)] exclude_scouted: bool,
+        #[graphql(default = true)] exclude_applied: bool,
     ) -> Result<Vec<ScoutParticipant>> {
-        let ctx: &ServiceContext = ctx.data::<ServiceContext>().unwrap();
-        let participants: Vec<ScoutParticipant> = ctx
+        let volunteer_id: VolunteerId = parse_id(&vid)?;
+        let gid: UserId = volunteer_owner(ctx, &volunteer_id, "search participants to scout").await?;
+        let access: &Arc<dyn AccessRepository> = ctx.data::<Arc<dyn AccessRepository>>()?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let elements: VolunteerElementsReadModel = VolunteerElementsReadModel::new(
+            vid,
+            regions,
+            None,
+            themes,
+            required_themes,
+            conditions,
+            required_conditions,
+            target_status,
+        );
+        validate_elements(&elements)?;
+
+        let filter: ScoutCandidateFilter = ScoutCandidateFilter {
+            exclude_scouted,
+            exclude_applied,
+        };
+
+        let mut participants: Vec<ScoutParticipant> = ctx
             .participant_account_dao
-            .find_by_elements(&VolunteerElementsReadModel::new(
-                vid,
-                regions,
-                None,
-                themes,
-                required_themes,
-                conditions,
-                required_conditions,
-                target_status,
-            ))
+            .find_by_elements(&elements, &filter)
             .await?;
+        for participant in participants.iter_mut() {
+            hide_birthday(access.as_ref(), &gid, &participant.uid, &mut participant.birthday)
+                .await?;
+        }
 
         Ok(participants)
     }
+
+    /// ログインしている団体・参加者のメッセージのスレッドを新しい順に取得する
+    ///
+    /// ## 引数
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
+    ///
+    /// ## 返り値
+    /// - `Connection<MessageThread>` - スレッドと未読メッセージ数のページ
+    async fn get_message_threads<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, MessageThread, ConnectionFields>> {
+        let viewer: UserId = login_user(ctx, "get message threads")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let page: PageRequest = page_request::<ThreadId>(first, after)?;
+        let threads: Page<MessageThread> = ctx.message_dao.find_threads(&viewer, &page).await?;
+
+        Ok(connection(threads, &page))
+    }
+
+    /// 指定されたtidのメッセージのスレッドを取得する. スレッドの団体・参加者のみ取得できる
+    ///
+    /// ## 引数
+    /// - `tid` - tid
+    ///
+    /// ## 返り値
+    /// - `MessageThread` - スレッドと未読メッセージ数
+    async fn get_message_thread<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        tid: String,
+    ) -> Result<MessageThread> {
+        let viewer: UserId = login_user(ctx, "get message thread")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let tid: ThreadId = parse_id(&tid)?;
+
+        find_member_thread(ctx, &tid, &viewer).await
+    }
+
+    /// 指定されたtidのスレッドのメッセージを新しい順に取得する. スレッドの団体・参加者のみ取得できる
+    ///
+    /// ## 引数
+    /// - `tid` - tid
+    /// - `first` - 取得する件数
+    /// - `after` - 前のページの最後のカーソル
+    ///
+    /// ## 返り値
+    /// - `Connection<Message>` - メッセージのページ. 削除済みのメッセージは本文がnull
+    async fn get_messages<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        tid: String,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Message, ConnectionFields>> {
+        let viewer: UserId = login_user(ctx, "get messages")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let tid: ThreadId = parse_id(&tid)?;
+        let page: PageRequest = page_request::<MessageId>(first, after)?;
+
+        find_member_thread(ctx, &tid, &viewer).await?;
+        let messages: Page<Message> = ctx.message_dao.find_messages(&tid, &page).await?;
+
+        Ok(connection(messages, &page))
+    }
+
+    /// ログインしている団体・参加者の全てのスレッドの未読メッセージ数を取得する
+    ///
+    /// ## 返り値
+    /// - `i64` - 未読メッセージ数
+    async fn get_unread_message_count<'ctx>(&self, ctx: &Context<'ctx>) -> Result<i64> {
+        let viewer: UserId = login_user(ctx, "get unread message count")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let unread_count: i64 = ctx.message_dao.count_unread(&viewer).await?;
+
+        Ok(unread_count)
+    }
+
+    /// 指定されたvidのボランティアの最後に発行したチェックインコードを取得する. ボランティアを所有する団体のみ取得できる
+    ///
+    /// ## 引数
+    /// - `vid` - vid
+    ///
+    /// ## 返り値
+    /// - `CheckInCode` - チェックインコードと有効期限
+    async fn get_check_in_code<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        vid: String,
+    ) -> Result<CheckInCode> {
+        let viewer: UserId = login_user(ctx, "get check-in code")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let vid: VolunteerId = parse_id(&vid)?;
+        let code: CheckInCode = ctx.attendance_dao.find_check_in_code(&vid).await.extend()?;
+        if code.gid != viewer.to_string() {
+            return Err(ApiError::Forbidden(
+                "only the group that owns the volunteer can get the check-in code".to_string(),
+            )
+            .extend());
+        }
+
+        Ok(code)
+    }
+
+    /// 指定されたuidの参加者の出欠の実績を取得する
+    ///
+    /// ## 引数
+    /// - `uid` - uid
+    ///
+    /// ## 返り値
+    /// - `ParticipantReliability` - 出席・欠席・取消の数と出席率
+    async fn get_participant_reliability<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        uid: String,
+    ) -> Result<ParticipantReliability> {
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        let reliability: ParticipantReliability =
+            ctx.attendance_dao.find_reliability(&uid).await?;
+
+        Ok(reliability)
+    }
+
+    /// 指定されたuidの参加者の累計活動時間を取得する. 参加者本人のみ取得できる
+    ///
+    /// ## 引数
+    /// - `uid` - uid
+    /// - `from` - 集計する開始日(日本時間). 指定しない場合は全期間
+    /// - `until` - 集計する終了日(日本時間). 指定しない場合は全期間
+    ///
+    /// ## 返り値
+    /// - `VolunteerHoursSummary` - 出席したボランティアの数, 累計活動時間, 活動ごとの活動時間
+    async fn get_volunteer_hours_summary<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        uid: String,
+        from: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> Result<VolunteerHoursSummary> {
+        let viewer: UserId = login_user(ctx, "get volunteer hours summary")?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let uid: UserId = parse_id(&uid)?;
+        if uid.to_string() != viewer.to_string() {
+            return Err(ApiError::Forbidden(
+                "only the participant can get their volunteer hours".to_string(),
+            )
+            .extend());
+        }
+        let activities: Vec<VolunteerHours> = ctx.certificate_dao.find_hours(&uid).await?;
+
+        Ok(VolunteerHoursSummary::new(
+            uid.to_string(),
+            activities,
+            from,
+            until,
+        ))
+    }
+
+    /// 証明書に記載された証明書IDと署名が, 発行された証明書と一致するか確認する. ログインしていなくても確認できる
+    ///
+    /// ## 引数
+    /// - `certificate_id` - 証明書ID
+    /// - `signature` - 署名
+    ///
+    /// ## 返り値
+    /// - `bool` - 一致する場合はtrue. 証明書を発行できない応募の場合はfalse
+    async fn verify_certificate<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        certificate_id: String,
+        signature: String,
+    ) -> Result<bool> {
+        let signer: &Arc<CertificateSigner> = ctx.data::<Arc<CertificateSigner>>()?;
+        let ctx: &ServiceContext = ctx.data::<ServiceContext>()?;
+        let aid: ApplyId = parse_id(&certificate_id)?;
+        let source: CertificateSource = match ctx.certificate_dao.find_source(&aid).await {
+            Ok(source) => source,
+            Err(QueryError::NotFound(_)) => return Ok(false),
+            Err(error) => return Err(error.extend()),
+        };
+
+        Ok(match source.issue(Utc::now()) {
+            Ok(certificate) => signer.verify(&certificate, &signature),
+            Err(_) => false,
+        })
+    }
+}
+
+/// ログインしているユーザーのid. ログインしていない場合は`UNAUTHENTICATED`のエラーを返す
+fn login_user<'ctx>(ctx: &Context<'ctx>, action: &str) -> Result<UserId> {
+    match ctx.data_opt::<Viewer>().and_then(|viewer| viewer.uid.as_deref()) {
+        Some(uid) => parse_id(uid),
+        None => Err(ApiError::Unauthorized(format!("login is required to {}", action)).extend()),
+    }
+}
+
+/// ボランティアを所有する団体のid
+///
+/// ログインしていない場合は`UNAUTHENTICATED`, 所有する団体でない場合は`FORBIDDEN`のエラーを返す
+async fn volunteer_owner<'ctx>(
+    ctx: &Context<'ctx>,
+    vid: &VolunteerId,
+    action: &str,
+) -> Result<UserId> {
+    let viewer: UserId = login_user(ctx, action)?;
+    let volunteer: VolunteerReadModel = ctx
+        .data::<ServiceContext>()?
+        .volunteer_dao
+        .find_by_id(vid)
+        .await
+        .extend()?;
+    if volunteer.gid != viewer.to_string() {
+        return Err(ApiError::Forbidden(format!(
+            "only the group that owns the volunteer can {}",
+            action
+        ))
+        .extend());
+    }
+    Ok(viewer)
+}
+
+/// 団体と承認済みの応募・スカウトがない参加者の生年月日を`None`にする
+async fn hide_birthday(
+    access: &dyn AccessRepository,
+    gid: &UserId,
+    uid: &str,
+    birthday: &mut Option<NaiveDate>,
+) -> Result<()> {
+    let uid: UserId = parse_id(uid)?;
+    if !access.has_accepted_relationship(gid, &uid).await? {
+        *birthday = None;
+    }
+    Ok(())
+}
+
+/// スレッドを取得する. スレッドの団体・参加者以外の場合は`FORBIDDEN`のエラーを返す
+async fn find_member_thread(
+    ctx: &ServiceContext,
+    tid: &ThreadId,
+    viewer: &UserId,
+) -> Result<MessageThread> {
+    let thread: MessageThread = ctx.message_dao.find_thread(tid, viewer).await.extend()?;
+    if !thread.is_member(&viewer.to_string()) {
+        return Err(ApiError::Forbidden(
+            "only the group and the participant of the thread can read it".to_string(),
+        )
+        .extend());
+    }
+    Ok(thread)
+}
+
+/// 文字列の入力をIDや要素に変換する. 変換できない場合は`VALIDATION_ERROR`のエラーを返す
+fn parse_id<T>(value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    T::from_str(value).map_err(|error| ApiError::Validation(error.to_string()).extend())
+}
+
+/// ページの取得条件を作成する. 件数やカーソルが不正な場合は`VALIDATION_ERROR`のエラーを返す
+fn page_request<C>(first: Option<i32>, after: Option<String>) -> Result<PageRequest>
+where
+    C: FromStr,
+    C::Err: std::fmt::Display,
+{
+    if let Some(after) = &after {
+        parse_id::<C>(after)?;
+    }
+    PageRequest::new(first, after)
+        .map_err(|error| ApiError::Validation(error.to_string()).extend())
 }
 
+/// 取得したページをRelay形式のコネクションに変換する
+fn connection<T: OutputType>(
+    page: Page<T>,
+    request: &PageRequest,
+) -> Connection<String, T, ConnectionFields> {
+    let mut connection = Connection::with_additional_fields(
+        request.after.is_some(),
+        page.has_next_page,
+        ConnectionFields {
+            total_count: page.total_count,
+        },
+    );
+    connection.edges.extend(
+        page.edges
+            .into_iter()
+            .map(|(cursor, node)| Edge::new(cursor, node)),
+    );
+    connection
+}
+
+/// 検索条件の要素が全て定義済みの値か確認する
+fn validate_elements(elements: &VolunteerElementsReadModel) -> Result<()> {
+    let regions = elements.required_regions.iter().flatten();
+    for region in elements.regions.iter().chain(regions) {
+        parse_id::<Region>(region)?;
+    }
+    for theme in elements.themes.iter().chain(elements.required_themes.iter()) {
+        parse_id::<Theme>(theme)?;
+    }
+    for condition in elements
+        .conditions
+        .iter()
+        .chain(elements.required_conditions.iter())
+    {
+        parse_id::<Condition>(condition)?;
+    }
+    for target_status in elements.target_status.iter() {
+        parse_id::<TargetStatus>(target_status)?;
+    }
+    Ok(())
+}
+
+/// ログインしているユーザーに配信される出来事を購読する
+async fn subscribe_events<'ctx>(
+    ctx: &Context<'ctx>,
+) -> Result<BoxStream<'static, ActivityEvent>> {
+    let uid: String = login_user(ctx, "subscribe to events")?.to_string();
+    let subscriber: &Arc<dyn EventSubscriber> = ctx.data::<Arc<dyn EventSubscriber>>()?;
+    subscriber
+        .subscribe(&uid)
+        .await
+        .map_err(|error| ApiError::Internal(error.to_string()).extend())
+}
+
+/// サブスクリプション
+///
+/// WebSocketの接続時に`Authorization`でBearerトークンを送信し, ログインしたユーザー宛ての出来事を受信する
 pub struct SubscriptionRoot;
 
 #[Subscription]
 impl SubscriptionRoot {
-    async fn foo<'ctx>(&self, ctx: &Context<'ctx>) -> Result<impl Stream<Item = String>> {
-        let client = ctx.data_unchecked::<Client>();
-        let mut conn = client.get_async_connection().await?.into_pubsub();
-        conn.subscribe("foo").await?;
-        Ok(conn
-            .into_on_message()
-            .filter_map(|msg| async move { msg.get_payload().ok() }))
+    /// ログインしている参加者が受けたスカウトを購読する
+    ///
+    /// ## 返り値
+    /// - `ScoutReceivedEvent` - スカウトID, ボランティアID, スカウトした団体のID
+    async fn scout_received<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> Result<impl Stream<Item = ScoutReceivedEvent>> {
+        let events = subscribe_events(ctx).await?;
+        Ok(events.filter_map(|event| async move { ScoutReceivedEvent::from_event(event) }))
+    }
+
+    /// ログインしているユーザーに関係する応募の作成・取消・承認・棄却を購読する
+    ///
+    /// 団体は自身のボランティアへの全ての出来事を, 参加者は自身の応募の承認・棄却を受信する
+    ///
+    /// ## 返り値
+    /// - `ApplyEvent` - 出来事の種類, 応募ID, ボランティアID, 参加者のID, 団体のID
+    async fn apply_events<'ctx>(&self, ctx: &Context<'ctx>) -> Result<impl Stream<Item = ApplyEvent>> {
+        let events = subscribe_events(ctx).await?;
+        Ok(events.filter_map(|event| async move { ApplyEvent::from_event(event) }))
     }
 }
 
@@ -935,7 +1581,11 @@ pub fn create_schema_builder() -> SchemaBuilder<QueryRoot, EmptyMutation, Subscr
     Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
 }
 
-pub fn create_schema(pool: MySqlPool) -> ApiSchema {
+pub fn create_schema(
+    pool: MySqlPool,
+    subscriber: Arc<dyn EventSubscriber>,
+    signer: Arc<CertificateSigner>,
+) -> ApiSchema {
     let group_account_dao: GroupAccountImpl = GroupAccountImpl::new(pool.clone());
     let participant_account_dao: ParticipantAccountImpl = ParticipantAccountImpl::new(pool.clone());
     let scout_dao: ScoutImpl = ScoutImpl::new(pool.clone());
@@ -944,6 +1594,10 @@ pub fn create_schema(pool: MySqlPool) -> ApiSchema {
         VolunteerQueryRepositoryImpl::new(pool.clone());
     let participant_review_dao: ReviewImpl = ReviewImpl::new(pool.clone());
     let volunteer_review_dao: ReviewImpl = ReviewImpl::new(pool.clone());
+    let message_dao: MessageImpl = MessageImpl::new(pool.clone());
+    let attendance_dao: AttendanceImpl = AttendanceImpl::new(pool.clone());
+    let certificate_dao: CertificateImpl = CertificateImpl::new(pool.clone());
+    let access_dao: Arc<dyn AccessRepository> = Arc::new(AccessImpl::new(pool.clone()));
 
     let ctx: ServiceContext = ServiceContext::new(
         Arc::new(group_account_dao),
@@ -953,7 +1607,15 @@ pub fn create_schema(pool: MySqlPool) -> ApiSchema {
         Arc::new(volunteer_dao),
         Arc::new(participant_review_dao),
         Arc::new(volunteer_review_dao),
+        Arc::new(message_dao),
+        Arc::new(attendance_dao),
+        Arc::new(certificate_dao),
     );
 
-    create_schema_builder().data(ctx).finish()
+    create_schema_builder()
+        .data(ctx)
+        .data(access_dao)
+        .data(subscriber)
+        .data(signer)
+        .finish()
 }
@@ -0,0 +1,486 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::Deserialize;
+use sqlx::{MySql, QueryBuilder};
+
+use domain::{
+    consts::{
+        conditions::ConditionMap, region::RegionMap, target_status::TargetStatusMap,
+        themes::ThemeMap,
+    },
+    model::{condition::Condition, region::Region, target_status::TargetStatus, theme::Theme},
+};
+use query_repository::activities::volunteer::VolunteerElementsReadModel;
+
+/// 検索対象のテーブル
+///
+/// 対象のテーブルと, 対象に紐づく要素・地域のテーブルの組
+pub struct SearchTables {
+    /// 検索対象のテーブル
+    pub subject: &'static str,
+    /// 検索対象のテーブルの主キー. 要素・地域のテーブルでも同じカラム名で参照する
+    pub key: &'static str,
+    /// 要素(テーマ, 条件, 対象者)のテーブル
+    pub element: &'static str,
+    /// 地域のテーブル
+    pub region: &'static str,
+}
+
+/// ボランティア検索で使用するテーブル
+pub const VOLUNTEER_TABLES: SearchTables = SearchTables {
+    subject: "volunteer",
+    key: "vid",
+    element: "volunteer_element",
+    region: "volunteer_region",
+};
+
+/// 参加者のスカウト検索で使用するテーブル
+pub const PARTICIPANT_TABLES: SearchTables = SearchTables {
+    subject: "participant_account",
+    key: "uid",
+    element: "participant_element",
+    region: "participant_region",
+};
+
+/// 要素と地域による検索条件
+///
+/// ボランティア検索と参加者のスカウト検索で共通して使用する
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchFilter {
+    /// OR条件の要素ID
+    pub or_elements: Vec<String>,
+    /// OR条件の地域ID
+    pub or_regions: Vec<u8>,
+    /// AND条件の要素ID
+    pub req_elements: Vec<String>,
+    /// AND条件の地域ID
+    pub req_regions: Vec<u8>,
+}
+
+impl SearchFilter {
+    /// 検索条件の要素名をIDに変換する
+    pub fn new(elements: &VolunteerElementsReadModel) -> Result<SearchFilter> {
+        let or_regions: Vec<u8> = elements
+            .regions
+            .iter()
+            .map(|r: &String| Region::from_str(r).map(|r| r.to_uint()))
+            .collect::<Result<Vec<u8>>>()?;
+        let req_regions: Vec<u8> = elements
+            .required_regions
+            .iter()
+            .flatten()
+            .map(|r: &String| Region::from_str(r).map(|r| r.to_uint()))
+            .collect::<Result<Vec<u8>>>()?;
+
+        let mut or_elements: Vec<String> = elements
+            .themes
+            .iter()
+            .map(|r: &String| Theme::from_str(r).map(|r| r.to_id()))
+            .collect::<Result<Vec<String>>>()?;
+        or_elements.extend(
+            elements
+                .conditions
+                .iter()
+                .map(|r: &String| Condition::from_str(r).map(|r| r.to_id()))
+                .collect::<Result<Vec<String>>>()?,
+        );
+        or_elements.extend(
+            elements
+                .target_status
+                .iter()
+                .map(|r: &String| TargetStatus::from_str(r).map(|r| r.to_id()))
+                .collect::<Result<Vec<String>>>()?,
+        );
+
+        let mut req_elements: Vec<String> = elements
+            .required_themes
+            .iter()
+            .map(|r: &String| Theme::from_str(r).map(|r| r.to_id()))
+            .collect::<Result<Vec<String>>>()?;
+        req_elements.extend(
+            elements
+                .required_conditions
+                .iter()
+                .map(|r: &String| Condition::from_str(r).map(|r| r.to_id()))
+                .collect::<Result<Vec<String>>>()?,
+        );
+
+        Ok(SearchFilter {
+            or_elements,
+            or_regions,
+            req_elements,
+            req_regions,
+        })
+    }
+
+    /// 一致数のカラム(`eid_match_count`, `rid_match_count`, `req_eid_match_count`)を追加する
+    pub fn push_match_counts<'args>(
+        &self,
+        builder: &mut QueryBuilder<'args, MySql>,
+        tables: &SearchTables,
+    ) {
+        push_count(builder, tables, tables.element, |builder| {
+            push_in(builder, "eid", &self.or_elements);
+        });
+        builder.push(" AS eid_match_count, ");
+        push_count(builder, tables, tables.region, |builder| {
+            push_in(builder, "rid", &self.or_regions);
+        });
+        builder.push(" AS rid_match_count, ");
+        push_count(builder, tables, tables.element, |builder| {
+            builder.push("is_need = true AND ");
+            let elements: Vec<String> = self
+                .or_elements
+                .iter()
+                .chain(self.req_elements.iter())
+                .cloned()
+                .collect();
+            push_in(builder, "eid", &elements);
+        });
+        builder.push(" AS req_eid_match_count");
+    }
+
+    /// 一致した要素と地域のカラム(`matched_elements`, `matched_rids`)をJSON配列で追加する
+    pub fn push_matched_elements<'args>(
+        &self,
+        builder: &mut QueryBuilder<'args, MySql>,
+        tables: &SearchTables,
+    ) {
+        let elements: Vec<String> = self
+            .or_elements
+            .iter()
+            .chain(self.req_elements.iter())
+            .cloned()
+            .collect();
+        let regions: Vec<u8> = self
+            .or_regions
+            .iter()
+            .chain(self.req_regions.iter())
+            .copied()
+            .collect();
+        builder.push(format!(
+            "(SELECT JSON_ARRAYAGG(JSON_OBJECT('eid', eid, 'is_need', is_need)) FROM {table} WHERE {table}.{key} = {subject}.{key} AND ",
+            table = tables.element,
+            key = tables.key,
+            subject = tables.subject
+        ));
+        push_in(builder, "eid", &elements);
+        builder.push(format!(
+            ") AS matched_elements, (SELECT JSON_ARRAYAGG(rid) FROM {table} WHERE {table}.{key} = {subject}.{key} AND ",
+            table = tables.region,
+            key = tables.key,
+            subject = tables.subject
+        ));
+        push_in(builder, "rid", &regions);
+        builder.push(") AS matched_rids");
+    }
+
+    /// AND条件の要素と地域を全て持つものに絞り込む条件を追加する
+    pub fn push_required_condition<'args>(
+        &self,
+        builder: &mut QueryBuilder<'args, MySql>,
+        tables: &SearchTables,
+    ) {
+        builder.push("TRUE");
+        if !self.req_elements.is_empty() {
+            builder.push(" AND ");
+            push_count(builder, tables, tables.element, |builder| {
+                push_in(builder, "eid", &self.req_elements);
+            });
+            builder.push(" = ");
+            builder.push_bind(self.req_elements.len() as i64);
+        }
+        if !self.req_regions.is_empty() {
+            builder.push(" AND ");
+            push_count(builder, tables, tables.region, |builder| {
+                push_in(builder, "rid", &self.req_regions);
+            });
+            builder.push(" = ");
+            builder.push_bind(self.req_regions.len() as i64);
+        }
+    }
+}
+
+/// 対象に紐づく行のうち, 条件に一致する行数を数えるサブクエリを追加する
+fn push_count<'args, F>(
+    builder: &mut QueryBuilder<'args, MySql>,
+    tables: &SearchTables,
+    table: &str,
+    condition: F,
+) where
+    F: FnOnce(&mut QueryBuilder<'args, MySql>),
+{
+    builder.push(format!(
+        "(SELECT COUNT(*) FROM {table} WHERE {table}.{key} = {subject}.{key} AND ",
+        table = table,
+        key = tables.key,
+        subject = tables.subject
+    ));
+    condition(builder);
+    builder.push(")");
+}
+
+/// `column IN (?, ...)`の条件を追加する. 値が空の場合は常に偽となる
+pub fn push_in<'args, T>(builder: &mut QueryBuilder<'args, MySql>, column: &str, values: &[T])
+where
+    T: 'args + Clone + Send + sqlx::Encode<'args, MySql> + sqlx::Type<MySql>,
+{
+    if values.is_empty() {
+        builder.push("FALSE");
+        return;
+    }
+    builder.push(format!("{} IN (", column));
+    let mut separated = builder.separated(", ");
+    for value in values {
+        separated.push_bind(value.clone());
+    }
+    separated.push_unseparated(")");
+}
+
+/// 対象に紐づく要素・地域の重みを合計するサブクエリを追加する. 重みが無い場合は0となる
+pub fn push_weight_sum<'args, T>(
+    builder: &mut QueryBuilder<'args, MySql>,
+    tables: &SearchTables,
+    table: &str,
+    column: &str,
+    weights: &[(T, i64)],
+) where
+    T: 'args + Clone + Send + sqlx::Encode<'args, MySql> + sqlx::Type<MySql>,
+{
+    if weights.is_empty() {
+        builder.push("0");
+        return;
+    }
+    builder.push(format!("(SELECT COALESCE(SUM(CASE {}", column));
+    for (value, weight) in weights {
+        builder.push(" WHEN ");
+        builder.push_bind(value.clone());
+        builder.push(" THEN ");
+        builder.push_bind(*weight);
+    }
+    builder.push(format!(
+        " ELSE 0 END), 0) FROM {table} WHERE {table}.{key} = {subject}.{key})",
+        table = table,
+        key = tables.key,
+        subject = tables.subject
+    ));
+}
+
+/// 曜日と時刻の条件に使用するタイムゾーン
+pub const LOCAL_TIME_ZONE: &str = "+09:00";
+
+/// UTCで保存している日時のカラムを日本時間に変換する
+pub fn local_time(column: &str) -> String {
+    format!("CONVERT_TZ({}, '+00:00', '{}')", column, LOCAL_TIME_ZONE)
+}
+
+/// 参加者の参加可能な時間帯と, ボランティアのこれからの回の時間帯が重なる時間(分)のサブクエリを追加する
+///
+/// 回を日本時間の曜日・時間帯ごとにまとめ, 各時間帯と参加可能な時間帯が重なる時間の合計を求める.
+/// 毎週同じ時間帯の回は1つとして数える. 回の終了日が開始日より後の場合は, 開始日の終わりまでを対象とする
+pub fn push_availability_overlap<'args, F>(
+    builder: &mut QueryBuilder<'args, MySql>,
+    volunteer: &str,
+    uid: F,
+) where
+    F: FnOnce(&mut QueryBuilder<'args, MySql>),
+{
+    let start_at: String = local_time("volunteer_session.start_at");
+    let finish_at: String = local_time("volunteer_session.finish_at");
+    builder.push(format!(
+        r#"(
+            SELECT CAST(COALESCE(SUM(GREATEST(0,
+                LEAST(TIME_TO_SEC(volunteer_dates.finish_time), session_window.finish_sec)
+                - GREATEST(TIME_TO_SEC(volunteer_dates.start_time), session_window.start_sec)
+            )), 0) DIV 60 AS SIGNED)
+            FROM (
+                SELECT DISTINCT
+                    ELT(DAYOFWEEK({start_at}), 'Sun', 'Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat') AS dates,
+                    TIME_TO_SEC(TIME({start_at})) AS start_sec,
+                    IF(DATE({finish_at}) > DATE({start_at}), 86400, TIME_TO_SEC(TIME({finish_at}))) AS finish_sec
+                FROM volunteer_session
+                WHERE volunteer_session.vid = {volunteer}.vid
+                AND volunteer_session.is_deleted = false
+                AND volunteer_session.finish_at > NOW()
+            ) AS session_window
+            INNER JOIN volunteer_dates ON volunteer_dates.dates = session_window.dates
+            WHERE volunteer_dates.uid = "#,
+        start_at = start_at,
+        finish_at = finish_at,
+        volunteer = volunteer
+    ));
+    uid(builder);
+    builder.push(")");
+}
+
+/// `JSON_ARRAYAGG(JSON_OBJECT('eid', eid, 'is_need', is_need))`で集約した要素
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ElementRow {
+    pub eid: String,
+    /// MySQLのBOOLEANは0か1の数値として集約される
+    pub is_need: u8,
+}
+
+impl ElementRow {
+    pub fn new(eid: String, is_need: bool) -> ElementRow {
+        ElementRow {
+            eid,
+            is_need: is_need as u8,
+        }
+    }
+
+    pub fn is_need(&self) -> bool {
+        self.is_need == 1
+    }
+}
+
+/// 要素IDを種類ごとの名前に振り分けたもの
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ElementNames {
+    pub themes: Vec<String>,
+    pub required_themes: Vec<String>,
+    pub conditions: Vec<String>,
+    pub required_conditions: Vec<String>,
+    pub target_status: Vec<String>,
+}
+
+impl ElementNames {
+    pub fn new(elements: &[ElementRow]) -> ElementNames {
+        let themes_map = ThemeMap::new().themes_id_to_name;
+        let conditions_map = ConditionMap::new().conditions_id_to_name;
+        let target_status_map = TargetStatusMap::new().target_statuses_index_to_name;
+
+        let mut names: ElementNames = ElementNames::default();
+        for element in elements {
+            if let Some(theme) = themes_map.get(&element.eid) {
+                if element.is_need() {
+                    names.required_themes.push(theme.to_string());
+                } else {
+                    names.themes.push(theme.to_string());
+                }
+            } else if let Some(condition) = conditions_map.get(&element.eid) {
+                if element.is_need() {
+                    names.required_conditions.push(condition.to_string());
+                } else {
+                    names.conditions.push(condition.to_string());
+                }
+            } else if let Some(target_status) = target_status_map.get(&element.eid) {
+                names.target_status.push(target_status.to_string());
+            }
+        }
+        names
+    }
+}
+
+/// 地域IDを地域名に変換する. 定義されていないIDは無視する
+pub fn region_names(rids: &[u8]) -> Vec<String> {
+    let regions_map = RegionMap::new().regions_index_to_name;
+    rids.iter()
+        .filter_map(|rid| regions_map.get(&(*rid as usize)))
+        .map(|region| region.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::*;
+
+    #[test]
+    fn required_condition() {
+        let filter = SearchFilter {
+            or_elements: vec!["th0".to_string()],
+            or_regions: vec![],
+            req_elements: vec!["th1".to_string(), "cd2".to_string()],
+            req_regions: vec![13],
+        };
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new("SELECT vid FROM volunteer WHERE ");
+        filter.push_required_condition(&mut builder, &VOLUNTEER_TABLES);
+        assert_eq!(
+            builder.sql(),
+            "SELECT vid FROM volunteer WHERE TRUE \
+            AND (SELECT COUNT(*) FROM volunteer_element WHERE volunteer_element.vid = volunteer.vid AND eid IN (?, ?)) = ? \
+            AND (SELECT COUNT(*) FROM volunteer_region WHERE volunteer_region.vid = volunteer.vid AND rid IN (?)) = ?"
+        );
+    }
+
+    #[test]
+    fn match_counts_without_elements() {
+        let filter = SearchFilter::default();
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new("");
+        filter.push_match_counts(&mut builder, &PARTICIPANT_TABLES);
+        assert_eq!(
+            builder.sql(),
+            "(SELECT COUNT(*) FROM participant_element WHERE participant_element.uid = participant_account.uid AND FALSE) AS eid_match_count, \
+            (SELECT COUNT(*) FROM participant_region WHERE participant_region.uid = participant_account.uid AND FALSE) AS rid_match_count, \
+            (SELECT COUNT(*) FROM participant_element WHERE participant_element.uid = participant_account.uid AND is_need = true AND FALSE) AS req_eid_match_count"
+        );
+    }
+
+    #[test]
+    fn matched_elements() {
+        let filter = SearchFilter {
+            or_elements: vec!["th0".to_string()],
+            or_regions: vec![13],
+            req_elements: vec!["cd2".to_string()],
+            req_regions: vec![],
+        };
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new("");
+        filter.push_matched_elements(&mut builder, &PARTICIPANT_TABLES);
+        assert_eq!(
+            builder.sql(),
+            "(SELECT JSON_ARRAYAGG(JSON_OBJECT('eid', eid, 'is_need', is_need)) FROM participant_element \
+            WHERE participant_element.uid = participant_account.uid AND eid IN (?, ?)) AS matched_elements, \
+            (SELECT JSON_ARRAYAGG(rid) FROM participant_region \
+            WHERE participant_region.uid = participant_account.uid AND rid IN (?)) AS matched_rids"
+        );
+    }
+
+    #[test]
+    fn weight_sum() {
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new("");
+        push_weight_sum(
+            &mut builder,
+            &VOLUNTEER_TABLES,
+            VOLUNTEER_TABLES.element,
+            "eid",
+            &[("th0".to_string(), 3), ("cd1".to_string(), -1)],
+        );
+        assert_eq!(
+            builder.sql(),
+            "(SELECT COALESCE(SUM(CASE eid WHEN ? THEN ? WHEN ? THEN ? ELSE 0 END), 0) \
+            FROM volunteer_element WHERE volunteer_element.vid = volunteer.vid)"
+        );
+
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new("");
+        push_weight_sum::<u8>(&mut builder, &VOLUNTEER_TABLES, VOLUNTEER_TABLES.region, "rid", &[]);
+        assert_eq!(builder.sql(), "0");
+    }
+
+    #[test]
+    fn availability_overlap() {
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new("");
+        push_availability_overlap(&mut builder, "volunteer", |builder| {
+            builder.push("participant_account.uid");
+        });
+        let sql: &str = builder.sql();
+        // 毎週月曜・木曜に開催するボランティアでは, 初回だけでなく全ての回の曜日・時間帯と重ねる
+        assert!(sql.contains("DAYOFWEEK(CONVERT_TZ(volunteer_session.start_at, '+00:00', '+09:00'))"));
+        assert!(sql.contains("SELECT DISTINCT"));
+        assert!(sql.contains("WHERE volunteer_session.vid = volunteer.vid"));
+        assert!(sql.contains("AND volunteer_session.is_deleted = false"));
+        assert!(sql.contains("AND volunteer_session.finish_at > NOW()"));
+        assert!(sql.contains("ON volunteer_dates.dates = session_window.dates"));
+        assert!(!sql.contains("volunteer.start_at"));
+        assert!(!sql.contains("volunteer.finish_at"));
+        assert!(sql.ends_with("WHERE volunteer_dates.uid = participant_account.uid)"));
+    }
+
+    #[test]
+    fn element_rows() {
+        let elements: Vec<ElementRow> =
+            serde_json::from_str(r#"[{"eid": "unknown", "is_need": 1}]"#).unwrap();
+        assert_eq!(elements, vec![ElementRow::new("unknown".to_string(), true)]);
+        assert_eq!(ElementNames::new(&elements), ElementNames::default());
+    }
+}
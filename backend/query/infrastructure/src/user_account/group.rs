@@ -2,8 +2,16 @@ use anyhow::Result;
 use async_trait::async_trait;
 use sqlx::MySqlPool;
 
-use domain::model::user_account::user_id::UserId;
-use query_repository::user_account::group::{GroupAccount, GroupUserRepository};
+use domain::model::{
+    plan::{BillingPeriod, Plan},
+    user_account::user_id::UserId,
+};
+use query_repository::{
+    error::QueryError,
+    user_account::group::{
+        GroupAccount, GroupInvoice, GroupUserRepository, PlanHistory, PlanUsage,
+    },
+};
 
 pub struct GroupAccountImpl {
     pool: MySqlPool,
@@ -17,24 +25,25 @@ impl GroupAccountImpl {
 
 #[async_trait]
 impl GroupUserRepository for GroupAccountImpl {
-    async fn find_by_id(&self, gid: &UserId) -> Result<GroupAccount> {
-        let group: GroupAccount = sqlx::query_as!(
+    async fn find_by_id(&self, gid: &UserId) -> Result<GroupAccount, QueryError> {
+        let group: Option<GroupAccount> = sqlx::query_as!(
             GroupAccount,
             r#"
             SELECT
                 gid, name, furigana, phone, address, contents, representative_name, representative_furigana, is_paid as "is_paid: bool", is_deleted as "is_deleted: bool", deleted_at
             FROM group_account
-            WHERE gid = ? AND is_deleted = false
+            WHERE gid = ?
             "#,
             gid.to_string()
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
-        if group.is_deleted {
-            return Err(anyhow::anyhow!("this group_account is deleted but existed"));
+        match group {
+            None => Err(QueryError::NotFound("group_account")),
+            Some(group) if group.is_deleted => Err(QueryError::Deleted("group_account")),
+            Some(group) => Ok(group),
         }
-        Ok(group)
     }
 
     async fn find_by_ids(&self, gids: &[UserId]) -> Result<Vec<GroupAccount>> {
@@ -84,4 +93,88 @@ impl GroupUserRepository for GroupAccountImpl {
 
         Ok(exists.exist == 1)
     }
+
+    async fn find_plan_usage(
+        &self,
+        gid: &UserId,
+        period: &BillingPeriod,
+    ) -> Result<PlanUsage, QueryError> {
+        let (start_at, next_start_at) = period.utc_range();
+        let usage = sqlx::query!(
+            r#"
+            SELECT
+                plan as "plan: u8", is_deleted as "is_deleted: bool",
+                (
+                    SELECT COUNT(*) FROM scout
+                    INNER JOIN volunteer ON scout.vid = volunteer.vid
+                    WHERE volunteer.gid = group_account.gid
+                    AND scout.scouted_at >= ? AND scout.scouted_at < ?
+                ) as "scouts!: i64",
+                (
+                    SELECT COUNT(*) FROM volunteer
+                    WHERE volunteer.gid = group_account.gid
+                    AND volunteer.is_deleted = false AND volunteer.deadline_on >= CURDATE()
+                ) as "open_volunteers!: i64"
+            FROM group_account
+            WHERE gid = ?
+            "#,
+            start_at,
+            next_start_at,
+            gid.to_string()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match usage {
+            None => Err(QueryError::NotFound("group_account")),
+            Some(usage) if usage.is_deleted => Err(QueryError::Deleted("group_account")),
+            Some(usage) => Ok(PlanUsage::new(
+                Plan::from_u8(usage.plan).map_err(|error| QueryError::Db(error.into()))?,
+                period,
+                usage.scouts as u32,
+                usage.open_volunteers as u32,
+            )),
+        }
+    }
+
+    async fn find_plan_history(&self, gid: &UserId) -> Result<Vec<PlanHistory>> {
+        let history = sqlx::query_as!(
+            PlanHistory,
+            r#"
+            SELECT
+                ELT(plan + 1, 'Free', 'Standard', 'Premium') as "plan!: String",
+                ELT(status + 1, 'Trialing', 'Active', 'PastDue') as "status!: String",
+                trial_ends_at, effective_from, effective_until
+            FROM group_plan_history
+            WHERE gid = ?
+            ORDER BY effective_from DESC, hid DESC
+            "#,
+            gid.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(history)
+    }
+
+    async fn find_invoices(&self, gid: &UserId) -> Result<Vec<GroupInvoice>> {
+        let invoices = sqlx::query_as!(
+            GroupInvoice,
+            r#"
+            SELECT
+                iid,
+                ELT(plan + 1, 'Free', 'Standard', 'Premium') as "plan!: String",
+                amount as "amount: u32",
+                period_start_on, period_end_on,
+                ELT(status + 1, 'Pending', 'Paid', 'Failed', 'Refunded') as "status!: String",
+                issued_at, paid_at
+            FROM invoice
+            WHERE gid = ?
+            ORDER BY issued_at DESC, iid DESC
+            "#,
+            gid.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(invoices)
+    }
 }
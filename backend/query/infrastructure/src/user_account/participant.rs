@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::cmp::Reverse;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -6,18 +6,21 @@ use domain::consts::conditions::ConditionMap;
 use domain::consts::target_status::{TargetStatusMap, TARGET_STATUSES_PREFIX};
 use domain::consts::themes::ThemeMap;
 use domain::model::apply::ApplyId;
-use domain::model::condition::Condition;
-use domain::model::region::Region;
-use domain::model::target_status::TargetStatus;
-use domain::model::theme::Theme;
 use query_repository::activities::volunteer::VolunteerElementsReadModel;
-use sqlx::{query, MySqlPool, Row};
+use query_repository::error::QueryError;
+use sqlx::{types::Json, MySql, MySqlPool, QueryBuilder, Row};
 
 use domain::consts::region::RegionMap;
 use domain::model::user_account::user_id::UserId;
 use query_repository::user_account::participant::{
-    GroupParticipant, ParticipantAccount, ParticipantCondition, ParticipantRegion,
-    ParticipantTargetStatus, ParticipantTheme, ParticipantUserRepository, ScoutParticipant,
+    GroupParticipant, ParticipantAccount, ParticipantAvailability, ParticipantCondition,
+    ParticipantRegion, ParticipantTargetStatus, ParticipantTheme, ParticipantUserRepository,
+    ScoutCandidateFilter, ScoutMatchBreakdown, ScoutParticipant,
+};
+
+use crate::search::{
+    push_availability_overlap, region_names, ElementNames, ElementRow, SearchFilter,
+    PARTICIPANT_TABLES,
 };
 
 pub struct ParticipantAccountImpl {
@@ -32,8 +35,8 @@ impl ParticipantAccountImpl {
 
 #[async_trait]
 impl ParticipantUserRepository for ParticipantAccountImpl {
-    async fn find_by_id(&self, pid: &UserId) -> Result<ParticipantAccount> {
-        let user = sqlx::query_as!(
+    async fn find_by_id(&self, pid: &UserId) -> Result<ParticipantAccount, QueryError> {
+        let user: Option<ParticipantAccount> = sqlx::query_as!(
             ParticipantAccount,
             r#"
             SELECT
@@ -43,13 +46,14 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
             "#,
             pid.to_string()
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
-        if user.is_deleted {
-            return Err(anyhow::anyhow!("this participant_account is deleted but existed"));
+        match user {
+            None => Err(QueryError::NotFound("participant_account")),
+            Some(user) if user.is_deleted => Err(QueryError::Deleted("participant_account")),
+            Some(user) => Ok(user),
         }
-        Ok(user)
     }
 
     async fn find_by_ids(&self, pids: &[UserId]) -> Result<Vec<ParticipantAccount>> {
@@ -161,7 +165,10 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
         Ok(conditions)
     }
 
-    async fn find_target_status_by_id(&self, pid: &UserId) -> Result<ParticipantTargetStatus> {
+    async fn find_target_status_by_id(
+        &self,
+        pid: &UserId,
+    ) -> Result<ParticipantTargetStatus, QueryError> {
         let response = sqlx::query!(
             r#"
             SELECT eid FROM participant_element WHERE uid = ? AND eid like ?
@@ -169,18 +176,39 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
             pid.to_string(),
             format!("{}%", TARGET_STATUSES_PREFIX)
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(QueryError::NotFound("participant_target_status"))?;
 
         let target_status_map = TargetStatusMap::new().target_statuses_index_to_name;
 
-        let target_status = target_status_map.get(&response.eid).unwrap().to_string();
+        let target_status = target_status_map
+            .get(&response.eid)
+            .ok_or_else(|| anyhow::anyhow!("unknown target_status: {}", response.eid))?
+            .to_string();
 
         Ok(ParticipantTargetStatus {
             name: target_status,
         })
     }
 
+    async fn find_availability_by_id(&self, pid: &UserId) -> Result<Vec<ParticipantAvailability>> {
+        let availabilities = sqlx::query_as!(
+            ParticipantAvailability,
+            r#"
+            SELECT did, dates AS weekday, start_time, finish_time
+            FROM volunteer_dates
+            WHERE uid = ?
+            ORDER BY FIELD(dates, 'Sun', 'Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat'), start_time
+            "#,
+            pid.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(availabilities)
+    }
+
     async fn exists(&self, pid: &UserId) -> Result<bool> {
         let response = sqlx::query!(
             r#"
@@ -198,7 +226,7 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
         let response = sqlx::query_as!(
             GroupParticipant,
             r#"
-            SELECT serial as "serial: u16", name, furigana, gender as "gender: u8", age as "age: u8"
+            SELECT serial as "serial: u16", name, furigana, gender as "gender: u8", age as "age: u8", attendance_status as "attendance_status: u8"
             FROM group_participants
             WHERE gpid = ?
             "#,
@@ -210,165 +238,109 @@ impl ParticipantUserRepository for ParticipantAccountImpl {
         Ok(response)
     }
 
-
     /// 参加者の検索
     async fn find_by_elements(
         &self,
-        elements: &VolunteerElementsReadModel
+        elements: &VolunteerElementsReadModel,
+        candidate_filter: &ScoutCandidateFilter,
     ) -> Result<Vec<ScoutParticipant>> {
-        // OR条件の要素一覧
-        let mut or_elements: Vec<String> = Vec::new();
-
-        // OR条件の地域一覧
-        let mut or_regions = elements
-            .regions
-            .clone()
-            .iter()
-            .map(|r: &String| Region::from_str(r).unwrap().to_uint())
-            .collect::<Vec<u8>>();
-
-        or_elements.extend(
-            elements
-                .themes
-                .iter()
-                .map(|r: &String| Theme::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
-        );
-        or_elements.extend(
-            elements
-                .conditions
-                .iter()
-                .map(|r: &String| Condition::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
-        );
-        or_elements.extend(
-            elements
-                .target_status
-                .iter()
-                .map(|r: &String| TargetStatus::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
-        );
+        let filter: SearchFilter = SearchFilter::new(elements)?;
 
-        let mut req_elements: Vec<String> = Vec::new();
-        req_elements.extend(
-            elements
-                .required_themes
-                .iter()
-                .map(|r: &String| Theme::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
+        let mut query: QueryBuilder<MySql> = QueryBuilder::new(
+            r#"
+            SELECT
+                participant_account.uid, name, gender, birthday,
+                (
+                    SELECT CAST(AVG(point) AS FLOAT) FROM participant_review
+                    WHERE participant_review.uid = participant_account.uid
+                ) AS point,
+            "#,
         );
-        req_elements.extend(
-            elements
-                .required_conditions
-                .iter()
-                .map(|r: &String| Condition::from_str(r).unwrap().to_id()), // .collect::<Vec<String>>()
+        push_availability_overlap(&mut query, "volunteer", |query| {
+            query.push("participant_account.uid");
+        });
+        query.push(" AS availability_overlap, ");
+        filter.push_matched_elements(&mut query, &PARTICIPANT_TABLES);
+        // スカウトするボランティアの日時と参加可能な時間帯を比較する
+        query.push(
+            r#"
+            FROM participant_account
+            INNER JOIN volunteer ON volunteer.vid = "#,
         );
-        if or_elements.len() == 0 { or_elements.push("".to_string()) }
-        if or_regions.len() == 0 { or_regions.push(100 as u8) }
-        let query_str = format!(
+        query.push_bind(elements.vid.to_string());
+        query.push(
             r#"
-                SELECT
-                    participant_account.uid, name, gender, birthday, CAST(AVG(point) AS FLOAT) as point,
-                    GROUP_CONCAT(DISTINCT
-                        JSON_OBJECT(
-                            'eid', participant_element.eid,
-                            'is_need', participant_element.is_need
-                        )
-                    ) as eids,
-                    GROUP_CONCAT(DISTINCT
-                        JSON_OBJECT(
-                            'rid', participant_region.rid
-                        )
-                    ) as rids,
-                    COUNT(DISTINCT CASE WHEN participant_element.eid IN ({}) THEN participant_element.eid END) AS eid_match_count,
-                    COUNT(DISTINCT CASE WHEN participant_region.rid IN ({}) THEN participant_region.rid END) AS rid_match_count,
-                    COUNT(DISTINCT CASE WHEN participant_element.is_need = true AND participant_element.eid IN ({}) THEN participant_element.eid END) AS req_eid_match_count
-                FROM
-                    participant_account
-                LEFT JOIN
-                    participant_element ON participant_account.uid = participant_element.uid
-                LEFT JOIN
-                    participant_region ON participant_account.uid = participant_region.uid
-                LEFT JOIN
-                    participant_review ON participant_account.uid = participant_review.uid
-                WHERE
-                    participant_account.uid IN (
-                        SELECT participant_account.uid
-                        FROM participant_account
-                        LEFT JOIN participant_element ON participant_account.uid = participant_element.uid
-                        LEFT JOIN participant_region ON participant_account.uid = participant_region.uid
-                        {}
-                        GROUP BY participant_account.uid
-                        {}
-                    )
-                AND NOT participant_account.is_deleted
-                GROUP BY
-                    participant_account.uid
-                HAVING
-                    COUNT(
-                        (SELECT sid FROM scout WHERE uid = participant_account.uid AND vid = ?)
-                    ) = 0
-                AND
-                    COUNT(
-                        (SELECT aid FROM apply WHERE uid = participant_account.uid AND vid = ?)
-                    ) = 0
-                ORDER BY
-                    eid_match_count + rid_match_count DESC, req_eid_match_count DESC, point DESC, uid DESC;
-            "#,
-            format!("?{}", ", ?".repeat(or_elements.len() - 1)),
-            format!("?{}", ", ?".repeat(or_regions.len() - 1)),
-            format!("?{}", ", ?".repeat(or_elements.len() + req_elements.len() - 1)),
-
-            if req_elements.len() > 0 {
-                format!(
-                    "WHERE participant_element.eid IN ({})",
-                    format!("?{}", ", ?".repeat(req_elements.len() - 1))
-                )
-            } else {"".to_string()},
-
-            if req_elements.len() > 0 {
-                format!(
-                    "HAVING COUNT(DISTINCT participant_element.eid) = {}",
-                    req_elements.len()
-                )
-            } else {"".to_string()},
+            WHERE NOT participant_account.is_deleted
+            AND "#,
         );
-
-        let mut query = query(&query_str);
-
-        for or_element in or_elements.clone() {
-            query = query.bind(or_element);
+        filter.push_required_condition(&mut query, &PARTICIPANT_TABLES);
+        if candidate_filter.exclude_scouted {
+            query.push(
+                " AND NOT EXISTS (SELECT sid FROM scout WHERE uid = participant_account.uid AND vid = ",
+            );
+            query.push_bind(elements.vid.to_string());
+            query.push(")");
         }
-        for or_region in or_regions {
-            query = query.bind(or_region);
+        if candidate_filter.exclude_applied {
+            query.push(
+                " AND NOT EXISTS (SELECT aid FROM apply WHERE uid = participant_account.uid AND vid = ",
+            );
+            query.push_bind(elements.vid.to_string());
+            query.push(")");
         }
+        query.push(" ORDER BY point DESC, uid DESC");
 
-        for or_element in or_elements {
-            query = query.bind(or_element.to_string());
-        }
-        for req_element in req_elements.clone() {
-            query = query.bind(req_element.to_string());
-        }
-
-        for req_element in req_elements.clone() {
-            query = query.bind(req_element.to_string());
-        }
-
-        query = query.bind(elements.vid.to_string());
-        query = query.bind(elements.vid.to_string());
+        let participants = query.build().fetch_all(&self.pool).await?;
 
-        let participants = query.fetch_all(&self.pool).await?;
-
-        let participants = participants
+        let mut participants = participants
             .into_iter()
             .map(|participant| {
-                ScoutParticipant {
-                    uid: participant.get("uid"),
-                    name: participant.get("name"),
-                    gender: participant.get::<i8, _>("gender") as i8,
-                    birthday: participant.get("birthday"),
-                    point: participant.get::<Option<f32>, _>("point")
-                }
+                let matched_elements: Vec<ElementRow> = participant
+                    .try_get::<Option<Json<Vec<ElementRow>>>, _>("matched_elements")?
+                    .map(|elements| elements.0)
+                    .unwrap_or_default();
+                let matched_elements: ElementNames = ElementNames::new(&matched_elements);
+                let matched_rids: Vec<u8> = participant
+                    .try_get::<Option<Json<Vec<u8>>>, _>("matched_rids")?
+                    .map(|rids| rids.0)
+                    .unwrap_or_default();
+
+                let breakdown = ScoutMatchBreakdown {
+                    regions: region_names(&matched_rids),
+                    themes: [
+                        matched_elements.themes,
+                        matched_elements.required_themes.clone(),
+                    ]
+                    .concat(),
+                    conditions: [
+                        matched_elements.conditions,
+                        matched_elements.required_conditions.clone(),
+                    ]
+                    .concat(),
+                    target_status: matched_elements.target_status,
+                    required_elements: [
+                        matched_elements.required_themes,
+                        matched_elements.required_conditions,
+                    ]
+                    .concat(),
+                    availability_overlap: participant.try_get("availability_overlap")?,
+                };
+
+                Ok(ScoutParticipant {
+                    uid: participant.try_get("uid")?,
+                    name: participant.try_get("name")?,
+                    gender: participant.try_get::<i8, _>("gender")?,
+                    birthday: participant.try_get("birthday")?,
+                    point: participant.try_get::<Option<f32>, _>("point")?,
+                    score: breakdown.score(),
+                    breakdown,
+                })
             })
-            .collect();
+            .collect::<Result<Vec<ScoutParticipant>, sqlx::Error>>()?;
+
+        // 点数が同じ場合は, 平均評価値が高い順のまま並べる
+        participants.sort_by_key(|participant| Reverse(participant.score));
 
         Ok(participants)
     }
-
 }
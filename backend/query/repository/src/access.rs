@@ -0,0 +1,195 @@
+use std::{str::FromStr, sync::Arc};
+
+use anyhow::Result;
+use async_graphql::{Context, Error, ErrorExtensions, Guard};
+use async_trait::async_trait;
+
+use api_error::ApiError;
+use domain::model::{apply::ApplyId, user_account::user_id::UserId};
+
+/// リクエストしたユーザー
+///
+/// Bearerトークンが無い場合は`uid`がNoneになる
+#[derive(Debug, Clone, Default)]
+pub struct Viewer {
+    pub uid: Option<String>,
+}
+
+#[async_trait]
+pub trait AccessRepository: Send + Sync {
+    /// 団体と参加者の間に承認済みの応募・スカウトがあるか確認する
+    async fn has_accepted_relationship(&self, gid: &UserId, uid: &UserId) -> Result<bool>;
+
+    /// 応募したユーザーと, 応募先のボランティアを所有する団体のIDを取得する
+    async fn find_applicant(&self, aid: &ApplyId) -> Result<Option<Applicant>>;
+}
+
+/// 応募したユーザーと応募先の団体
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Applicant {
+    pub uid: String,
+    pub gid: String,
+}
+
+/// 個人情報の所有者
+#[derive(Debug, Clone)]
+pub enum Owner {
+    Participant(String),
+    Group(String),
+}
+
+/// 個人情報を所有者本人と, 承認済みの応募・スカウトがある相手にのみ公開するガード
+pub struct PrivateInfoGuard {
+    owner: Owner,
+}
+
+impl PrivateInfoGuard {
+    pub fn participant(uid: &str) -> Self {
+        Self {
+            owner: Owner::Participant(uid.to_string()),
+        }
+    }
+
+    pub fn group(gid: &str) -> Self {
+        Self {
+            owner: Owner::Group(gid.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl Guard for PrivateInfoGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let viewer: &str = match ctx.data_opt::<Viewer>().and_then(|v| v.uid.as_deref()) {
+            Some(uid) => uid,
+            None => return Err(forbidden()),
+        };
+        let access = ctx.data::<Arc<dyn AccessRepository>>()?;
+
+        let (gid, uid) = match &self.owner {
+            Owner::Participant(uid) if uid == viewer => return Ok(()),
+            Owner::Group(gid) if gid == viewer => return Ok(()),
+            Owner::Participant(uid) => (viewer, uid.as_str()),
+            Owner::Group(gid) => (gid.as_str(), viewer),
+        };
+
+        let (gid, uid) = match (UserId::new(gid), UserId::new(uid)) {
+            (Ok(gid), Ok(uid)) => (gid, uid),
+            _ => return Err(forbidden()),
+        };
+
+        if access.has_accepted_relationship(&gid, &uid).await? {
+            Ok(())
+        } else {
+            Err(forbidden())
+        }
+    }
+}
+
+/// 集団応募のメンバーを, 応募者本人と応募先の団体にのみ公開するガード
+///
+/// 応募先の団体は承認・棄却を判断するため, 承認前でも取得できる
+pub struct ApplyMembersGuard {
+    aid: String,
+}
+
+impl ApplyMembersGuard {
+    pub fn new(aid: &str) -> Self {
+        Self {
+            aid: aid.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Guard for ApplyMembersGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let viewer: &str = match ctx.data_opt::<Viewer>().and_then(|v| v.uid.as_deref()) {
+            Some(uid) => uid,
+            None => return Err(forbidden()),
+        };
+        let access = ctx.data::<Arc<dyn AccessRepository>>()?;
+        let aid: ApplyId = match ApplyId::from_str(&self.aid) {
+            Ok(aid) => aid,
+            Err(error) => return Err(ApiError::Validation(error.to_string()).extend()),
+        };
+
+        match access.find_applicant(&aid).await? {
+            Some(applicant) if applicant.uid == viewer || applicant.gid == viewer => Ok(()),
+            _ => Err(forbidden()),
+        }
+    }
+}
+
+fn forbidden() -> Error {
+    ApiError::Forbidden("Forbidden".to_string()).extend()
+}
+
+#[cfg(test)]
+mod test_apply_members_guard {
+    use super::*;
+
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Schema};
+    use futures::executor::block_on;
+
+    const AID: &str = "01HPAY3C1A1Z1XQ6Z2J6S0V7KX";
+    const APPLICANT: &str = "participant_account000000000";
+    const GROUP_A: &str = "group_account_a0000000000000";
+    const GROUP_B: &str = "group_account_b0000000000000";
+
+    /// 参加者がグループAのボランティアに集団応募し, グループBとは承認済みの関係がある
+    struct FakeAccessRepository;
+
+    #[async_trait]
+    impl AccessRepository for FakeAccessRepository {
+        async fn has_accepted_relationship(&self, gid: &UserId, uid: &UserId) -> Result<bool> {
+            Ok(gid.to_string() == GROUP_B && uid.to_string() == APPLICANT)
+        }
+
+        async fn find_applicant(&self, _aid: &ApplyId) -> Result<Option<Applicant>> {
+            Ok(Some(Applicant {
+                uid: APPLICANT.to_string(),
+                gid: GROUP_A.to_string(),
+            }))
+        }
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        #[graphql(guard = "ApplyMembersGuard::new(&aid)")]
+        async fn get_group_participants(&self, aid: String) -> String {
+            aid
+        }
+    }
+
+    fn execute(viewer: Option<&str>) -> Option<String> {
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription)
+            .data(Arc::new(FakeAccessRepository) as Arc<dyn AccessRepository>)
+            .finish();
+        let request =
+            Request::new(format!(r#"{{ getGroupParticipants(aid: "{}") }}"#, AID)).data(Viewer {
+                uid: viewer.map(str::to_string),
+            });
+        let response = block_on(schema.execute(request));
+        response
+            .errors
+            .first()
+            .and_then(|error| error.extensions.as_ref())
+            .and_then(|extensions| extensions.get("code"))
+            .map(|code| code.to_string())
+    }
+
+    #[test]
+    fn applicant_and_pending_group() {
+        assert_eq!(execute(Some(APPLICANT)), None);
+        assert_eq!(execute(Some(GROUP_A)), None);
+    }
+
+    #[test]
+    fn other_group() {
+        assert_eq!(execute(Some(GROUP_B)), Some(r#""FORBIDDEN""#.to_string()));
+        assert_eq!(execute(None), Some(r#""FORBIDDEN""#.to_string()));
+    }
+}
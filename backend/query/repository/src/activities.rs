@@ -2,3 +2,6 @@ pub mod scout;
 pub mod apply;
 pub mod volunteer;
 pub mod review;
+pub mod message;
+pub mod attendance;
+pub mod certificate;
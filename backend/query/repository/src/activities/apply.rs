@@ -5,6 +5,11 @@ use chrono::{NaiveDate, NaiveDateTime};
 
 use domain::model::{apply::ApplyId, user_account::user_id::UserId, volunteer::VolunteerId};
 
+use crate::{
+    error::QueryError,
+    pagination::{Page, PageRequest},
+};
+
 /// 応募リードモデル
 #[derive(SimpleObject, sqlx::Type)]
 pub struct Apply {
@@ -12,40 +17,60 @@ pub struct Apply {
     pub aid: String,
     /// ボランティアID
     pub vid: String,
+    /// 応募した回のID
+    pub vsid: String,
     /// 参加者ID
     pub uid: String,
     /// 応募日時
     pub applied_at: NaiveDateTime,
     /// 集団応募有無
     pub as_group: bool,
-    /// 認証データ 0:未認証 1:承認済み 2:棄却済み
+    /// 認証データ 0:未認証 1:承認済み 2:棄却済み 3:取消済み
     pub allowed_status: i8,
     /// 認証日時
     pub decided_at: Option<NaiveDateTime>,
     /// 送信日時
     pub is_sent: bool,
+    /// 参加者による取消の理由
+    pub cancel_reason: Option<String>,
+    /// 参加者による取消の日時
+    pub cancelled_at: Option<NaiveDateTime>,
+    /// 出欠 0:未記録 1:出席 2:欠席
+    pub attendance_status: i8,
+    /// 参加者がチェックインコードでチェックインした日時
+    pub checked_in_at: Option<NaiveDateTime>,
 }
 
 impl Apply {
     pub fn new(
         aid: String,
         vid: String,
+        vsid: String,
         uid: String,
         applied_at: NaiveDateTime,
         as_group: bool,
         allowed_status: i8,
         decided_at: Option<NaiveDateTime>,
         is_sent: bool,
+        cancel_reason: Option<String>,
+        cancelled_at: Option<NaiveDateTime>,
+        attendance_status: i8,
+        checked_in_at: Option<NaiveDateTime>,
     ) -> Apply {
         Apply {
             aid,
             vid,
+            vsid,
             uid,
             applied_at,
             as_group,
             allowed_status,
             decided_at,
             is_sent,
+            cancel_reason,
+            cancelled_at,
+            attendance_status,
+            checked_in_at,
         }
     }
 }
@@ -62,24 +87,26 @@ pub struct PastVolunteerParticipantReadModel {
     /// 0: 男性, 1: 女性, 2: その他
     pub gender: u8,
     /// 生年月日
-    pub birthday: NaiveDate,
+    ///
+    /// ボランティアを所有する団体と承認済みの応募・スカウトがある参加者のみ. それ以外は`None`
+    pub birthday: Option<NaiveDate>,
 }
 
 #[async_trait]
 pub trait ApplyRepository: Send + Sync {
     /// 応募情報を応募IDで取得する
-    async fn find_by_sid(&self, sid: &ApplyId) -> Result<Apply>;
+    async fn find_by_sid(&self, sid: &ApplyId) -> Result<Apply, QueryError>;
 
     /// 応募情報を団体IDで一括取得する
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<Apply>>;
+    async fn find_by_gid(&self, gid: &UserId, page: &PageRequest) -> Result<Page<Apply>>;
 
     /// 応募情報を参加者IDで一括取得する
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Apply>>;
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Apply>>;
 
     /// 応募情報をボランティアIDで一括取得する
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<Apply>>;
+    async fn find_by_vid(&self, vid: &VolunteerId, page: &PageRequest) -> Result<Page<Apply>>;
 
-    /// 過去に開催されたボランティアに参加した参加者を取得する
+    /// 過去に開催されたボランティアに出席した参加者を取得する
     async fn find_past_volunteer_participants(
         &self,
         vid: &VolunteerId,
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use async_graphql::SimpleObject;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use domain::model::{user_account::user_id::UserId, volunteer::VolunteerId};
+
+use crate::error::QueryError;
+
+/// チェックインコードリードモデル
+#[derive(SimpleObject, Debug, Clone)]
+pub struct CheckInCode {
+    /// ボランティアID
+    pub vid: String,
+    /// ボランティアを所有する団体ID
+    pub gid: String,
+    /// 参加者が入力するコード. QRコードにはこの値を埋め込む
+    pub code: String,
+    /// 発行日時
+    pub issued_at: NaiveDateTime,
+    /// 有効期限
+    pub expires_at: NaiveDateTime,
+}
+
+/// 参加者の出欠の実績リードモデル
+#[derive(SimpleObject, Debug, Clone, PartialEq)]
+pub struct ParticipantReliability {
+    /// 参加者ID
+    pub uid: String,
+    /// 出席した応募の数
+    pub attended_count: i64,
+    /// 欠席した応募の数
+    pub no_show_count: i64,
+    /// 参加者が取り消した応募の数
+    pub cancelled_count: i64,
+    /// 出欠を記録した応募のうち出席した割合. 記録がない場合はnull
+    pub attendance_rate: Option<f64>,
+}
+
+impl ParticipantReliability {
+    pub fn new(
+        uid: String,
+        attended_count: i64,
+        no_show_count: i64,
+        cancelled_count: i64,
+    ) -> ParticipantReliability {
+        let recorded: i64 = attended_count + no_show_count;
+        ParticipantReliability {
+            uid,
+            attended_count,
+            no_show_count,
+            cancelled_count,
+            attendance_rate: if recorded > 0 {
+                Some(attended_count as f64 / recorded as f64)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+#[async_trait]
+pub trait AttendanceRepository: Send + Sync {
+    /// ボランティアの最後に発行したチェックインコードを取得する
+    async fn find_check_in_code(&self, vid: &VolunteerId) -> Result<CheckInCode, QueryError>;
+
+    /// 参加者の出欠の実績を取得する
+    async fn find_reliability(&self, uid: &UserId) -> Result<ParticipantReliability>;
+}
+
+#[cfg(test)]
+mod test_participant_reliability {
+    use super::*;
+
+    #[test]
+    fn attendance_rate() {
+        let reliability = ParticipantReliability::new("participant".to_string(), 3, 1, 2);
+        assert_eq!(reliability.attendance_rate, Some(0.75));
+        assert_eq!(
+            ParticipantReliability::new("participant".to_string(), 0, 0, 1).attendance_rate,
+            None
+        );
+    }
+}
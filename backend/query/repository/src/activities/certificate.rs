@@ -0,0 +1,193 @@
+use anyhow::Result;
+use async_graphql::SimpleObject;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use domain::model::{
+    apply::ApplyId,
+    attendance::AttendanceStatus,
+    certificate::{to_jst, volunteer_minutes, Certificate, CertifiedActivity},
+    user_account::user_id::UserId,
+};
+
+use crate::error::QueryError;
+
+/// 証明書を発行するための応募・ボランティア・団体の情報
+#[derive(Debug, Clone)]
+pub struct CertificateSource {
+    /// 応募ID
+    pub aid: String,
+    /// 参加者ID
+    pub uid: String,
+    /// 団体ID
+    pub gid: String,
+    /// ボランティアID
+    pub vid: String,
+    pub participant_name: String,
+    pub group_name: String,
+    /// 団体の代表者名
+    pub representative_name: String,
+    pub volunteer_title: String,
+    pub start_at: NaiveDateTime,
+    pub finish_at: NaiveDateTime,
+    /// 出欠 0:未記録 1:出席 2:欠席
+    pub attendance_status: u8,
+}
+
+impl CertificateSource {
+    /// 証明書を発行する. 出席していない場合, ボランティアが終了していない場合はエラーを返す
+    pub fn issue(self, now: DateTime<Utc>) -> Result<Certificate> {
+        let attendance: AttendanceStatus = AttendanceStatus::from_u8(self.attendance_status)?;
+        let activity: CertifiedActivity = CertifiedActivity {
+            certificate_id: self.aid,
+            uid: self.uid,
+            gid: self.gid,
+            vid: self.vid,
+            participant_name: self.participant_name,
+            group_name: self.group_name,
+            representative_name: self.representative_name,
+            volunteer_title: self.volunteer_title,
+            start_at: self.start_at.and_utc(),
+            finish_at: self.finish_at.and_utc(),
+        };
+        Certificate::issue(activity, attendance, now)
+    }
+}
+
+/// 出席したボランティアの活動時間リードモデル
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq)]
+pub struct VolunteerHours {
+    /// 応募ID. 証明書IDとして使用する
+    pub aid: String,
+    /// ボランティアID
+    pub vid: String,
+    /// ボランティア名
+    pub title: String,
+    /// 団体名
+    pub group_name: String,
+    /// 開始日時
+    pub start_at: NaiveDateTime,
+    /// 終了日時
+    pub finish_at: NaiveDateTime,
+    /// 活動時間(分)
+    pub minutes: i64,
+}
+
+impl VolunteerHours {
+    pub fn new(
+        aid: String,
+        vid: String,
+        title: String,
+        group_name: String,
+        start_at: NaiveDateTime,
+        finish_at: NaiveDateTime,
+    ) -> VolunteerHours {
+        VolunteerHours {
+            aid,
+            vid,
+            title,
+            group_name,
+            start_at,
+            finish_at,
+            minutes: volunteer_minutes(start_at.and_utc(), finish_at.and_utc()),
+        }
+    }
+}
+
+/// 参加者の累計活動時間リードモデル
+#[derive(SimpleObject, Debug, Clone, PartialEq)]
+pub struct VolunteerHoursSummary {
+    /// 参加者ID
+    pub uid: String,
+    /// 出席したボランティアの数
+    pub activity_count: i64,
+    /// 累計活動時間(分)
+    pub total_minutes: i64,
+    /// 累計活動時間(時間). 小数点以下は分を60で割った値
+    pub total_hours: f64,
+    /// 出席したボランティアの活動時間. 開始日時の新しい順
+    pub activities: Vec<VolunteerHours>,
+}
+
+impl VolunteerHoursSummary {
+    /// 開始日(日本時間)が`from`から`until`までの活動を集計する
+    pub fn new(
+        uid: String,
+        activities: Vec<VolunteerHours>,
+        from: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> VolunteerHoursSummary {
+        let activities: Vec<VolunteerHours> = activities
+            .into_iter()
+            .filter(|activity| {
+                let started_on: NaiveDate = to_jst(activity.start_at.and_utc()).date_naive();
+                from.is_none_or(|from| from <= started_on)
+                    && until.is_none_or(|until| started_on <= until)
+            })
+            .collect();
+        let total_minutes: i64 = activities.iter().map(|activity| activity.minutes).sum();
+
+        VolunteerHoursSummary {
+            uid,
+            activity_count: activities.len() as i64,
+            total_minutes,
+            total_hours: total_minutes as f64 / 60.0,
+            activities,
+        }
+    }
+}
+
+#[async_trait]
+pub trait CertificateRepository: Send + Sync {
+    /// 証明書を発行するための情報を応募IDで取得する
+    async fn find_source(&self, aid: &ApplyId) -> Result<CertificateSource, QueryError>;
+
+    /// 参加者が出席した終了済みのボランティアの活動時間を開始日時の新しい順に取得する
+    async fn find_hours(&self, uid: &UserId) -> Result<Vec<VolunteerHours>>;
+}
+
+#[cfg(test)]
+mod test_volunteer_hours_summary {
+    use super::*;
+
+    fn hours(day: u32, start_hour: u32, minutes: i64) -> VolunteerHours {
+        let start_at = NaiveDate::from_ymd_opt(2024, 3, day)
+            .unwrap()
+            .and_hms_opt(start_hour, 0, 0)
+            .unwrap();
+        VolunteerHours::new(
+            format!("aid{}", day),
+            "vid".to_string(),
+            "公園の清掃".to_string(),
+            "ボランティア団体".to_string(),
+            start_at,
+            start_at + chrono::Duration::minutes(minutes),
+        )
+    }
+
+    #[test]
+    fn total() {
+        let summary = VolunteerHoursSummary::new(
+            "participant".to_string(),
+            vec![hours(10, 0, 210), hours(3, 0, 90)],
+            None,
+            None,
+        );
+        assert_eq!(summary.activity_count, 2);
+        assert_eq!(summary.total_minutes, 300);
+        assert_eq!(summary.total_hours, 5.0);
+    }
+
+    #[test]
+    fn filter_by_jst_date() {
+        // UTCの3月9日16時は日本時間の3月10日
+        let summary = VolunteerHoursSummary::new(
+            "participant".to_string(),
+            vec![hours(10, 0, 60), hours(9, 16, 60), hours(9, 14, 60)],
+            NaiveDate::from_ymd_opt(2024, 3, 10),
+            NaiveDate::from_ymd_opt(2024, 3, 10),
+        );
+        assert_eq!(summary.activity_count, 2);
+        assert_eq!(summary.total_minutes, 120);
+    }
+}
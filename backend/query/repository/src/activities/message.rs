@@ -0,0 +1,143 @@
+use anyhow::Result;
+use async_graphql::SimpleObject;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use domain::model::{message::ThreadId, user_account::user_id::UserId};
+
+use crate::{
+    error::QueryError,
+    pagination::{Page, PageRequest},
+};
+
+/// メッセージのスレッドリードモデル
+#[derive(SimpleObject, Debug, Clone)]
+pub struct MessageThread {
+    /// スレッドID
+    pub tid: String,
+    /// 応募のスレッドの場合は応募ID
+    pub aid: Option<String>,
+    /// スカウトのスレッドの場合はスカウトID
+    pub sid: Option<String>,
+    /// ボランティアID
+    pub vid: String,
+    /// 団体ID
+    pub gid: String,
+    /// 参加者ID
+    pub uid: String,
+    /// 作成日時
+    pub created_at: NaiveDateTime,
+    /// 最後にメッセージが送信された日時
+    pub last_message_at: Option<NaiveDateTime>,
+    /// 閲覧しているユーザーの未読メッセージ数
+    pub unread_count: i64,
+}
+
+impl MessageThread {
+    /// スレッドの団体または参加者か
+    pub fn is_member(&self, uid: &str) -> bool {
+        self.gid == uid || self.uid == uid
+    }
+}
+
+/// メッセージリードモデル
+#[derive(SimpleObject, Debug, Clone)]
+pub struct Message {
+    /// メッセージID
+    pub mid: String,
+    /// スレッドID
+    pub tid: String,
+    /// 送信した団体または参加者のID
+    pub sender: String,
+    /// 本文. 削除済みの場合はnull
+    pub body: Option<String>,
+    /// 送信日時
+    pub sent_at: NaiveDateTime,
+    /// 削除有無
+    pub is_deleted: bool,
+    /// 削除日時
+    pub deleted_at: Option<NaiveDateTime>,
+    /// スレッドの相手が既読にしたか
+    pub is_read: bool,
+}
+
+impl Message {
+    pub fn new(
+        mid: String,
+        tid: String,
+        sender: String,
+        body: String,
+        sent_at: NaiveDateTime,
+        is_deleted: bool,
+        deleted_at: Option<NaiveDateTime>,
+        is_read: bool,
+    ) -> Message {
+        Message {
+            mid,
+            tid,
+            sender,
+            // 削除済みのメッセージの本文は公開しない
+            body: if is_deleted { None } else { Some(body) },
+            sent_at,
+            is_deleted,
+            deleted_at,
+            is_read,
+        }
+    }
+}
+
+#[async_trait]
+pub trait MessageRepository: Send + Sync {
+    /// スレッドをスレッドIDで取得する. 未読数は`viewer`のものを返す
+    async fn find_thread(
+        &self,
+        tid: &ThreadId,
+        viewer: &UserId,
+    ) -> Result<MessageThread, QueryError>;
+
+    /// 団体または参加者のスレッドを新しい順に一括取得する
+    async fn find_threads(
+        &self,
+        viewer: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<MessageThread>>;
+
+    /// スレッドのメッセージを新しい順に一括取得する. 削除済みのメッセージも含む
+    async fn find_messages(&self, tid: &ThreadId, page: &PageRequest) -> Result<Page<Message>>;
+
+    /// 団体または参加者の全てのスレッドの未読メッセージ数を取得する
+    async fn count_unread(&self, viewer: &UserId) -> Result<i64>;
+}
+
+#[cfg(test)]
+mod test_message {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    fn message(is_deleted: bool) -> Message {
+        let sent_at = NaiveDate::from_ymd_opt(2024, 2, 10)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        Message::new(
+            "01HPAY3C1A1Z1XQ6Z2J6S0V7KX".to_string(),
+            "01HPAY2W3Q8T4BGN5S1M8D4E0R".to_string(),
+            "participant_account000000000".to_string(),
+            "集合場所はどこですか?".to_string(),
+            sent_at,
+            is_deleted,
+            if is_deleted { Some(sent_at) } else { None },
+            false,
+        )
+    }
+
+    #[test]
+    fn hide_deleted_body() {
+        assert_eq!(
+            message(false).body.as_deref(),
+            Some("集合場所はどこですか?")
+        );
+        assert_eq!(message(true).body, None);
+    }
+}
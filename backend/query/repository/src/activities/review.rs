@@ -1,9 +1,16 @@
+use std::str::FromStr;
+
 use anyhow::Result;
 use async_graphql::SimpleObject;
 use async_trait::async_trait;
 
 use domain::model::{user_account::user_id::UserId, volunteer::VolunteerId};
 
+use crate::{
+    error::QueryError,
+    pagination::{Page, PageError, PageRequest},
+};
+
 /// レビューリードモデル
 #[derive(SimpleObject, sqlx::Type)]
 pub struct Review {
@@ -43,16 +50,58 @@ impl ParticipantReviewPointAverage {
     }
 }
 
+/// レビュー一覧のカーソル
+///
+/// レビューはボランティアIDと参加者IDの組で一意になるため, 両方を保持する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewCursor {
+    pub vid: String,
+    pub uid: String,
+}
+
+impl ReviewCursor {
+    pub fn of(review: &Review) -> ReviewCursor {
+        ReviewCursor {
+            vid: review.vid.clone(),
+            uid: review.uid.clone(),
+        }
+    }
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for ReviewCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.vid, self.uid)
+    }
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for ReviewCursor {
+    type Err = PageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((vid, uid)) if VolunteerId::from_str(vid).is_ok() && UserId::new(uid).is_ok() => {
+                Ok(ReviewCursor {
+                    vid: vid.to_string(),
+                    uid: uid.to_string(),
+                })
+            }
+            _ => Err(PageError::InvalidCursor),
+        }
+    }
+}
+
 #[async_trait]
 pub trait ParticipantReviewRepository: Send + Sync {
     /// 参加者へのレビュー情報を参加者IDとボランティアIDで1件取得する
-    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review>;
+    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review, QueryError>;
 
     /// 参加者へのレビュー情報を参加者IDで一括取得する
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Review>>;
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Review>>;
 
     /// 参加者へのレビュー情報をボランティアIDで一括取得する
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<Review>>;
+    async fn find_by_vid(&self, vid: &VolunteerId, page: &PageRequest) -> Result<Page<Review>>;
 
     /// 複数の参加者のレビュー情報を一括取得する
     async fn find_by_uids(&self, uids: &[UserId]) -> Result<Vec<ParticipantReviewPointAverage>>;
@@ -61,14 +110,33 @@ pub trait ParticipantReviewRepository: Send + Sync {
 #[async_trait]
 pub trait VolunteerReviewRepository: Send + Sync {
     /// ボランティアへのレビュー情報を参加者IDとボランティアIDで1件取得する
-    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review>;
+    async fn find_by_ids(&self, uid: &UserId, vid: &VolunteerId) -> Result<Review, QueryError>;
 
     /// ボランティアへのレビュー情報を参加者IDで一括取得する
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Review>>;
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Review>>;
 
     /// ボランティアへのレビュー情報をボランティアIDで一括取得する
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<Review>>;
+    async fn find_by_vid(&self, vid: &VolunteerId, page: &PageRequest) -> Result<Page<Review>>;
 
     /// ボランティアへのレビュー情報を団体IDで一括取得する
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<Review>>;
+    async fn find_by_gid(&self, gid: &UserId, page: &PageRequest) -> Result<Page<Review>>;
+}
+
+#[cfg(test)]
+mod test_review_cursor {
+    use super::*;
+
+    #[test]
+    fn ok() {
+        let value = "01H5Z3A2Q4Y8X7W6V5T4S3R2P1:abcdefghijklmnopqrstuvwxyz01";
+        let cursor = ReviewCursor::from_str(value).unwrap();
+        assert_eq!(cursor.uid, "abcdefghijklmnopqrstuvwxyz01");
+        assert_eq!(cursor.to_string(), value);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(ReviewCursor::from_str("invalid"), Err(PageError::InvalidCursor));
+        assert_eq!(ReviewCursor::from_str(":"), Err(PageError::InvalidCursor));
+    }
 }
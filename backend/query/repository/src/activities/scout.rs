@@ -5,6 +5,11 @@ use chrono::{NaiveDate, NaiveDateTime};
 
 use domain::model::{scout::ScoutId, user_account::user_id::UserId, volunteer::VolunteerId};
 
+use crate::{
+    error::QueryError,
+    pagination::{Page, PageRequest},
+};
+
 /// スカウトリードモデル
 #[derive(SimpleObject, sqlx::Type)]
 pub struct ScoutFromGroup {
@@ -28,10 +33,18 @@ pub struct ScoutFromGroup {
     pub is_denied: bool,
     // 辞退日時
     pub denied_at: Option<NaiveDateTime>,
+    /// スカウトの状態
+    ///
+    /// Sent, Read, Accepted, Declined, Expired, Withdrawn.
+    /// 返答待ちのままボランティアの募集締切日を過ぎた場合はExpired
+    pub status: String,
 
     pub name: String,
     pub gender: u8,
-    pub birthday: NaiveDate,
+    /// 生年月日
+    ///
+    /// ボランティアを所有する団体と承認済みの応募・スカウトがある参加者のみ. それ以外は`None`
+    pub birthday: Option<NaiveDate>,
     pub point: Option<f32>,
 }
 
@@ -57,6 +70,11 @@ pub struct Scout {
     pub is_denied: bool,
     // 辞退日時
     pub denied_at: Option<NaiveDateTime>,
+    /// スカウトの状態
+    ///
+    /// Sent, Read, Accepted, Declined, Expired, Withdrawn.
+    /// 返答待ちのままボランティアの募集締切日を過ぎた場合はExpired
+    pub status: String,
 }
 
 impl Scout {
@@ -71,6 +89,7 @@ impl Scout {
         sent_at: Option<NaiveDateTime>,
         is_denied: bool,
         denied_at: Option<NaiveDateTime>,
+        status: String,
     ) -> Scout {
         Scout {
             sid,
@@ -83,6 +102,7 @@ impl Scout {
             sent_at,
             is_denied,
             denied_at,
+            status,
         }
     }
 }
@@ -90,14 +110,18 @@ impl Scout {
 #[async_trait]
 pub trait ScoutRepository: Send + Sync {
     /// スカウト情報をスカウトIDで取得する
-    async fn find_by_sid(&self, sid: &ScoutId) -> Result<Scout>;
+    async fn find_by_sid(&self, sid: &ScoutId) -> Result<Scout, QueryError>;
 
     /// スカウト情報を団体IDで一括取得する
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<Scout>>;
+    async fn find_by_gid(&self, gid: &UserId, page: &PageRequest) -> Result<Page<Scout>>;
 
     /// スカウト情報を参加者IDで一括取得する
-    async fn find_by_uid(&self, uid: &UserId) -> Result<Vec<Scout>>;
+    async fn find_by_uid(&self, uid: &UserId, page: &PageRequest) -> Result<Page<Scout>>;
 
     /// スカウト情報をボランティアIDで一括取得する
-    async fn find_by_vid(&self, vid: &VolunteerId) -> Result<Vec<ScoutFromGroup>>;
+    async fn find_by_vid(
+        &self,
+        vid: &VolunteerId,
+        page: &PageRequest,
+    ) -> Result<Page<ScoutFromGroup>>;
 }
@@ -1,9 +1,20 @@
+use std::{collections::BTreeMap, str::FromStr};
+
 use anyhow::Result;
-use async_graphql::SimpleObject;
+use async_graphql::{Enum, InputObject, SimpleObject};
 use async_trait::async_trait;
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use thiserror::Error;
+
+use domain::model::{
+    location::GeoPoint, user_account::user_id::UserId, volunteer::VolunteerId,
+};
+
+use crate::{
+    error::QueryError,
+    pagination::{Page, PageError, PageRequest},
+};
 
-use domain::model::{user_account::user_id::UserId, volunteer::VolunteerId};
 /// ボランティアリードモデル
 #[derive(SimpleObject, sqlx::Type)]
 pub struct VolunteerReadModel {
@@ -14,9 +25,17 @@ pub struct VolunteerReadModel {
     pub overview: String,
     pub recruited_num: u32,
     pub place: String,
+    /// 開催場所の緯度. 開催場所を緯度経度に変換できなかった場合は`None`
+    pub latitude: Option<f64>,
+    /// 開催場所の経度. 開催場所を緯度経度に変換できなかった場合は`None`
+    pub longitude: Option<f64>,
     pub reward: Option<String>,
+    /// 最初の回の開始日時
     pub start_at: NaiveDateTime,
+    /// 最後の回の終了日時
     pub finish_at: NaiveDateTime,
+    /// 繰り返しの規則. 繰り返さないボランティアは`None`
+    pub recurrence: Option<String>,
     pub deadline_on: NaiveDate,
     pub as_group: bool,
     pub is_deleted: bool,
@@ -41,9 +60,12 @@ impl VolunteerReadModel {
         overview: String,
         recruited_num: u32,
         place: String,
+        latitude: Option<f64>,
+        longitude: Option<f64>,
         reward: Option<String>,
         start_at: NaiveDateTime,
         finish_at: NaiveDateTime,
+        recurrence: Option<String>,
         deadline_on: NaiveDate,
         as_group: bool,
         is_deleted: bool,
@@ -66,9 +88,12 @@ impl VolunteerReadModel {
             overview,
             recruited_num,
             place,
+            latitude,
+            longitude,
             reward,
             start_at,
             finish_at,
+            recurrence,
             deadline_on,
             as_group,
             is_deleted,
@@ -86,6 +111,25 @@ impl VolunteerReadModel {
     }
 }
 
+/// ボランティアの回リードモデル
+#[derive(SimpleObject)]
+pub struct VolunteerSessionReadModel {
+    pub vsid: String,
+    pub vid: String,
+    pub start_at: NaiveDateTime,
+    pub finish_at: NaiveDateTime,
+    pub recruited_num: u32,
+    /// 承認済の人数. 集団応募はメンバー数として数える
+    pub approved_num: u32,
+}
+
+/// 回ごとの活動リードモデル. 繰り返し開催されるボランティアは回ごとに1件となる
+#[derive(SimpleObject)]
+pub struct SessionActivityReadModel {
+    pub session: VolunteerSessionReadModel,
+    pub volunteer: VolunteerReadModel,
+}
+
 /// ボランティア要素類リードモデル
 #[derive(SimpleObject, sqlx::Type)]
 pub struct VolunteerElementsReadModel {
@@ -123,40 +167,693 @@ impl VolunteerElementsReadModel {
     }
 }
 
+/// ボランティア検索のカーソル
+///
+/// 検索結果の並び順に使う値を全て保持し, キーセットページネーションに使用する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolunteerSearchCursor {
+    /// 検索地点からの距離(m). 検索地点を指定しない場合は0
+    pub distance: i64,
+    /// 一致した要素と地域の数
+    pub match_count: i64,
+    /// 参加者の参加可能な時間帯と重なる時間(分). 参加者を指定しない場合は0
+    pub availability_overlap: i64,
+    /// 検索ワードとの関連度
+    pub relevance: i64,
+    pub is_paid: bool,
+    /// 一致した必須要素の数
+    pub required_match_count: i64,
+    pub deadline_on: NaiveDate,
+    pub registered_at: NaiveDateTime,
+    pub vid: String,
+}
+
+/// カーソルに含める日時のフォーマット
+const CURSOR_DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for VolunteerSearchCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            self.distance,
+            self.match_count,
+            self.availability_overlap,
+            self.relevance,
+            self.is_paid as u8,
+            self.required_match_count,
+            self.deadline_on,
+            self.registered_at.format(CURSOR_DATETIME_FORMAT),
+            self.vid
+        )
+    }
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for VolunteerSearchCursor {
+    type Err = PageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values: Vec<&str> = s.split('|').collect();
+        if values.len() != 9 || VolunteerId::from_str(values[8]).is_err() {
+            return Err(PageError::InvalidCursor);
+        }
+        let cursor = || -> Result<VolunteerSearchCursor> {
+            Ok(VolunteerSearchCursor {
+                distance: values[0].parse()?,
+                match_count: values[1].parse()?,
+                availability_overlap: values[2].parse()?,
+                relevance: values[3].parse()?,
+                is_paid: values[4].parse::<u8>()? == 1,
+                required_match_count: values[5].parse()?,
+                deadline_on: values[6].parse()?,
+                registered_at: NaiveDateTime::parse_from_str(values[7], CURSOR_DATETIME_FORMAT)?,
+                vid: values[8].to_string(),
+            })
+        };
+        cursor().map_err(|_| PageError::InvalidCursor)
+    }
+}
+
+/// ボランティア検索の検索ワード
+///
+/// 空白で区切った語は全て含むもの(AND), `OR`で繋いだ語はいずれかを含むもの(OR)として検索する
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchWords {
+    /// OR条件の語のまとまり. まとまり同士はAND条件となる
+    groups: Vec<Vec<String>>,
+}
+
+impl SearchWords {
+    pub fn new(value: &str) -> SearchWords {
+        let mut groups: Vec<Vec<String>> = Vec::new();
+        let mut is_or: bool = false;
+        for word in value.split_whitespace() {
+            if word == "OR" || word == "|" {
+                is_or = !groups.is_empty();
+                continue;
+            }
+            // 全文検索の演算子として解釈されないよう, フレーズの区切りを取り除く
+            let word: String = word.replace('"', "");
+            if word.is_empty() {
+                continue;
+            }
+            match groups.last_mut() {
+                Some(group) if is_or => group.push(word),
+                _ => groups.push(vec![word]),
+            }
+            is_or = false;
+        }
+        SearchWords { groups }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// MySQLの全文検索(BOOLEAN MODE)の検索式に変換する
+    pub fn to_boolean_query(&self) -> String {
+        self.groups
+            .iter()
+            .map(|group| {
+                let words: Vec<String> =
+                    group.iter().map(|word| format!("\"{}\"", word)).collect();
+                if words.len() == 1 {
+                    format!("+{}", words[0])
+                } else {
+                    format!("+({})", words.join(" "))
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+/// 曜日
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayOfWeek {
+    Sunday = 0,
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+}
+
+impl DayOfWeek {
+    /// MySQLの`DAYOFWEEK()`の値(日曜日が1, 土曜日が7)に変換する
+    pub fn to_mysql(&self) -> u8 {
+        *self as u8 + 1
+    }
+}
+
+/// 距離検索で指定できる半径の上限(km)
+pub const MAX_RADIUS_KM: f64 = 200.0;
+
+/// 距離検索の条件
+#[derive(InputObject, Debug, Clone, Copy, PartialEq)]
+pub struct DistanceInput {
+    /// 検索地点の緯度
+    pub latitude: f64,
+    /// 検索地点の経度
+    pub longitude: f64,
+    /// 検索地点からの半径(km)
+    pub radius_km: f64,
+}
+
+impl DistanceInput {
+    /// 検索地点
+    pub fn center(&self) -> Result<GeoPoint> {
+        GeoPoint::new(self.latitude, self.longitude)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum VolunteerSearchInputError {
+    #[error("{0} must not be later than {1}")]
+    InvalidRange(&'static str, &'static str),
+    #[error("radiusKm must be greater than 0 and not greater than {}", MAX_RADIUS_KM)]
+    InvalidRadius,
+    #[error("{0}")]
+    InvalidLocation(String),
+}
+
+/// ボランティア検索の条件
+///
+/// 範囲の条件は両端を含む. 指定しない条件では絞り込まない
+#[derive(InputObject, Debug, Clone, Default)]
+pub struct VolunteerSearchInput {
+    /// OR検索の地域
+    #[graphql(default)]
+    pub regions: Vec<String>,
+    /// AND検索の地域(東京23区外のボランティアを除外する等の場合で使用を推奨)
+    #[graphql(default)]
+    pub required_regions: Vec<String>,
+    /// OR検索のテーマ
+    #[graphql(default)]
+    pub themes: Vec<String>,
+    /// AND検索のテーマ
+    #[graphql(default)]
+    pub required_themes: Vec<String>,
+    /// OR検索の条件
+    #[graphql(default)]
+    pub conditions: Vec<String>,
+    /// AND検索の条件
+    #[graphql(default)]
+    pub required_conditions: Vec<String>,
+    /// 対象者
+    #[graphql(default)]
+    pub target_status: Vec<String>,
+    /// 検索ワード(空白区切りでAND検索, `OR`で繋ぐとOR検索)
+    #[graphql(default)]
+    pub search_words: String,
+    /// 開始日時の下限
+    pub start_from: Option<DateTime<Utc>>,
+    /// 開始日時の上限
+    pub start_until: Option<DateTime<Utc>>,
+    /// 終了日時の下限
+    pub finish_from: Option<DateTime<Utc>>,
+    /// 終了日時の上限
+    pub finish_until: Option<DateTime<Utc>>,
+    /// 開始日の曜日(日本時間). 空の場合は全ての曜日
+    #[graphql(default)]
+    pub days_of_week: Vec<DayOfWeek>,
+    /// この時刻(日本時間)以降に開始するボランティアに絞り込む
+    pub time_from: Option<NaiveTime>,
+    /// この時刻(日本時間)までに終了するボランティアに絞り込む
+    pub time_until: Option<NaiveTime>,
+    /// 募集締切日の下限
+    pub deadline_from: Option<NaiveDate>,
+    /// 募集締切日の上限
+    pub deadline_until: Option<NaiveDate>,
+    /// 団体での参加の可否
+    pub as_group: Option<bool>,
+    /// 残りの募集人数の下限
+    pub min_remaining: Option<u32>,
+    /// 検索地点から指定した半径内のボランティアに絞り込み, 近い順に並べる
+    pub near: Option<DistanceInput>,
+    /// ログイン中の参加者の参加可能な時間帯と重なる時間が長い順に並べる
+    #[graphql(default)]
+    pub match_availability: bool,
+}
+
+impl VolunteerSearchInput {
+    /// 要素と地域の検索条件
+    pub fn elements(&self) -> VolunteerElementsReadModel {
+        VolunteerElementsReadModel::new(
+            "".to_string(),
+            self.regions.clone(),
+            Some(self.required_regions.clone()),
+            self.themes.clone(),
+            self.required_themes.clone(),
+            self.conditions.clone(),
+            self.required_conditions.clone(),
+            self.target_status.clone(),
+        )
+    }
+
+    pub fn search_words(&self) -> SearchWords {
+        SearchWords::new(&self.search_words)
+    }
+
+    /// 範囲の条件の下限が上限を超えていないか, 距離検索の条件が正しいか確認する
+    pub fn validate(&self) -> Result<(), VolunteerSearchInputError> {
+        fn check<T: PartialOrd>(
+            from: &Option<T>,
+            until: &Option<T>,
+            names: (&'static str, &'static str),
+        ) -> Result<(), VolunteerSearchInputError> {
+            match (from, until) {
+                (Some(from), Some(until)) if from > until => {
+                    Err(VolunteerSearchInputError::InvalidRange(names.0, names.1))
+                }
+                _ => Ok(()),
+            }
+        }
+        check(&self.start_from, &self.start_until, ("startFrom", "startUntil"))?;
+        check(&self.finish_from, &self.finish_until, ("finishFrom", "finishUntil"))?;
+        check(&self.time_from, &self.time_until, ("timeFrom", "timeUntil"))?;
+        check(&self.deadline_from, &self.deadline_until, ("deadlineFrom", "deadlineUntil"))?;
+        if let Some(near) = &self.near {
+            near.center()
+                .map_err(|error| VolunteerSearchInputError::InvalidLocation(error.to_string()))?;
+            if !(near.radius_km > 0.0 && near.radius_km <= MAX_RADIUS_KM) {
+                return Err(VolunteerSearchInputError::InvalidRadius);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// おすすめの根拠となる参加者の行動
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecommendationSignal {
+    /// 参加者が登録した地域・要素
+    Preference,
+    /// お気に入りに登録したボランティアの地域・要素
+    Favorite,
+    /// 応募したボランティアの地域・要素
+    Apply,
+    /// 評価したボランティアの地域・要素. 値は評価値
+    Review(i64),
+}
+
+/// 評価値がこの値より高いと正, 低いと負の重みとなる
+const NEUTRAL_REVIEW_POINT: i64 = 3;
+
+impl RecommendationSignal {
+    /// 嗜好に加える重み
+    pub fn weight(&self) -> i64 {
+        match self {
+            RecommendationSignal::Preference => 3,
+            RecommendationSignal::Favorite => 2,
+            RecommendationSignal::Apply => 2,
+            RecommendationSignal::Review(point) => point - NEUTRAL_REVIEW_POINT,
+        }
+    }
+}
+
+/// おすすめのボランティアを選ぶための参加者の嗜好
+///
+/// 要素IDと地域IDごとに, 参加者の行動から求めた重みを合計する
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RecommendationProfile {
+    pub elements: BTreeMap<String, i64>,
+    pub regions: BTreeMap<u8, i64>,
+}
+
+impl RecommendationProfile {
+    pub fn add_element(&mut self, eid: String, signal: RecommendationSignal) {
+        *self.elements.entry(eid).or_insert(0) += signal.weight();
+    }
+
+    pub fn add_region(&mut self, rid: u8, signal: RecommendationSignal) {
+        *self.regions.entry(rid).or_insert(0) += signal.weight();
+    }
+
+    /// 重みが正の要素ID
+    pub fn preferred_elements(&self) -> Vec<String> {
+        self.elements
+            .iter()
+            .filter(|(_, weight)| **weight > 0)
+            .map(|(eid, _)| eid.clone())
+            .collect()
+    }
+
+    /// 重みが正の地域ID
+    pub fn preferred_regions(&self) -> Vec<u8> {
+        self.regions
+            .iter()
+            .filter(|(_, weight)| **weight > 0)
+            .map(|(rid, _)| *rid)
+            .collect()
+    }
+
+    /// 重みが正の要素・地域が無い場合は, おすすめを選べない
+    pub fn is_empty(&self) -> bool {
+        self.elements.values().all(|weight| *weight <= 0)
+            && self.regions.values().all(|weight| *weight <= 0)
+    }
+}
+
+/// おすすめのボランティアリードモデル
+#[derive(SimpleObject)]
+pub struct RecommendedVolunteer {
+    pub volunteer: VolunteerReadModel,
+    /// 参加者の嗜好との一致度
+    pub score: i64,
+    /// 参加者の嗜好と一致した地域
+    pub matched_regions: Vec<String>,
+    /// 参加者の嗜好と一致したテーマ
+    pub matched_themes: Vec<String>,
+    /// 参加者の嗜好と一致した条件
+    pub matched_conditions: Vec<String>,
+    /// 参加者の嗜好と一致した対象者
+    pub matched_target_status: Vec<String>,
+}
+
+/// おすすめのボランティアのカーソル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecommendationCursor {
+    pub score: i64,
+    pub is_paid: bool,
+    pub deadline_on: NaiveDate,
+    pub registered_at: NaiveDateTime,
+    pub vid: String,
+}
+
+// Displayを実装することで, to_string()で文字列に変換できるようになる
+impl std::fmt::Display for RecommendationCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}|{}",
+            self.score,
+            self.is_paid as u8,
+            self.deadline_on,
+            self.registered_at.format(CURSOR_DATETIME_FORMAT),
+            self.vid
+        )
+    }
+}
+
+// FromStrを実装することで, from_str()で文字列から変換できるようになる
+impl FromStr for RecommendationCursor {
+    type Err = PageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values: Vec<&str> = s.split('|').collect();
+        if values.len() != 5 || VolunteerId::from_str(values[4]).is_err() {
+            return Err(PageError::InvalidCursor);
+        }
+        let cursor = || -> Result<RecommendationCursor> {
+            Ok(RecommendationCursor {
+                score: values[0].parse()?,
+                is_paid: values[1].parse::<u8>()? == 1,
+                deadline_on: values[2].parse()?,
+                registered_at: NaiveDateTime::parse_from_str(values[3], CURSOR_DATETIME_FORMAT)?,
+                vid: values[4].to_string(),
+            })
+        };
+        cursor().map_err(|_| PageError::InvalidCursor)
+    }
+}
+
 #[async_trait]
 pub trait VolunteerQueryRepository: Send + Sync {
     /// ボランティアに関連する要素をボランティアIDから取得する
     async fn find_elements_by_id(&self, vid: &VolunteerId) -> Result<VolunteerElementsReadModel>;
 
     /// ボランティアをボランティアidで取得する
-    async fn find_by_id(&self, vid: &VolunteerId) -> Result<VolunteerReadModel>;
+    async fn find_by_id(&self, vid: &VolunteerId) -> Result<VolunteerReadModel, QueryError>;
+
+    /// ボランティアの回を開始日時の早い順に取得する. 日程の更新で除かれた回は含まない
+    async fn find_sessions(&self, vid: &VolunteerId) -> Result<Vec<VolunteerSessionReadModel>>;
 
     /// ボランティアを条件検索を用いて取得する
+    ///
+    /// `uid`を指定した場合は, 参加者の参加可能な時間帯と重なる時間で並べ替える
     async fn find_by_elements(
         &self,
-        elements: &VolunteerElementsReadModel,
-        search_words: String,
-    ) -> Result<Vec<VolunteerReadModel>>;
+        input: &VolunteerSearchInput,
+        uid: Option<&UserId>,
+        page: &PageRequest,
+    ) -> Result<Page<VolunteerReadModel>>;
+
+    /// 参加者におすすめのボランティアを取得する
+    ///
+    /// 登録した地域・要素, お気に入り, 応募, 評価から求めた嗜好との一致度が高い順に並べる.
+    /// 応募済みのボランティアは除外する
+    async fn find_recommended(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<RecommendedVolunteer>>;
 
     /// ボランティアをグループidで取得する
-    async fn find_by_gid(&self, gid: &UserId) -> Result<Vec<VolunteerReadModel>>;
+    async fn find_by_gid(
+        &self,
+        gid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<VolunteerReadModel>>;
 
     /// 参加者のお気に入りを取得する
-    async fn find_favorite_by_id(&self, pid: &UserId) -> Result<Vec<VolunteerReadModel>>;
+    async fn find_favorite_by_id(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<VolunteerReadModel>>;
 
-    /// 参加者の活動履歴を取得する
-    async fn find_activity_by_id(&self, pid: &UserId) -> Result<Vec<VolunteerReadModel>>;
+    /// 参加者の活動履歴を回ごとに取得する. 出席した終了済みの回のみ含む
+    async fn find_activity_by_id(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>>;
 
-    /// 参加者の予定を取得する
-    async fn find_scheduled_activity_by_id(&self, pid: &UserId) -> Result<Vec<VolunteerReadModel>>;
+    /// 参加者の予定を回ごとに取得する. 承認済の終了していない回のみ含む
+    async fn find_scheduled_activity_by_id(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>>;
 
-    /// 参加者の応募済ボランティア(未承認)を取得する
-    async fn find_not_allowed_activity_by_id(&self, pid: &UserId) -> Result<Vec<VolunteerReadModel>>;
+    /// 参加者の応募済の回(未承認)を取得する
+    async fn find_not_allowed_activity_by_id(
+        &self,
+        pid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>>;
 
-    /// 団体の活動履歴を取得する
-    async fn find_activity_by_gid(&self, gid: &UserId) -> Result<Vec<VolunteerReadModel>>;
+    /// 団体の活動履歴を回ごとに取得する. 終了済みの回のみ含む
+    async fn find_activity_by_gid(
+        &self,
+        gid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>>;
 
-    /// 団体の予定を取得する
-    async fn find_scheduled_activity_by_gid(&self, gid: &UserId)
-        -> Result<Vec<VolunteerReadModel>>;
+    /// 団体の予定を回ごとに取得する. 終了していない回のみ含む
+    async fn find_scheduled_activity_by_gid(
+        &self,
+        gid: &UserId,
+        page: &PageRequest,
+    ) -> Result<Page<SessionActivityReadModel>>;
+}
+
+#[cfg(test)]
+mod test_volunteer_search_cursor {
+    use super::*;
+
+    #[test]
+    fn ok() {
+        let cursor = VolunteerSearchCursor {
+            distance: 2500,
+            match_count: 3,
+            availability_overlap: 90,
+            relevance: 1520,
+            is_paid: true,
+            required_match_count: 1,
+            deadline_on: NaiveDate::from_ymd_opt(2023, 8, 1).unwrap(),
+            registered_at: NaiveDate::from_ymd_opt(2023, 7, 1)
+                .unwrap()
+                .and_hms_micro_opt(12, 30, 0, 123456)
+                .unwrap(),
+            vid: "01H5Z3A2Q4Y8X7W6V5T4S3R2P1".to_string(),
+        };
+        let value = cursor.to_string();
+        assert_eq!(
+            value,
+            "2500|3|90|1520|1|1|2023-08-01|2023-07-01T12:30:00.123456|01H5Z3A2Q4Y8X7W6V5T4S3R2P1"
+        );
+        assert_eq!(VolunteerSearchCursor::from_str(&value), Ok(cursor));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            VolunteerSearchCursor::from_str("01H5Z3A2Q4Y8X7W6V5T4S3R2P1"),
+            Err(PageError::InvalidCursor)
+        );
+        assert_eq!(
+            VolunteerSearchCursor::from_str(
+                "0|a|0|0|1|1|2023-08-01|2023-07-01T12:30:00|01H5Z3A2Q4Y8X7W6V5T4S3R2P1"
+            ),
+            Err(PageError::InvalidCursor)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_recommendation {
+    use super::*;
+
+    #[test]
+    fn profile() {
+        let mut profile = RecommendationProfile::default();
+        assert!(profile.is_empty());
+
+        profile.add_element("th0".to_string(), RecommendationSignal::Preference);
+        profile.add_element("th0".to_string(), RecommendationSignal::Favorite);
+        profile.add_element("cd1".to_string(), RecommendationSignal::Apply);
+        profile.add_element("cd1".to_string(), RecommendationSignal::Review(1));
+        profile.add_region(13, RecommendationSignal::Review(5));
+        profile.add_region(14, RecommendationSignal::Review(3));
+
+        assert_eq!(profile.elements.get("th0"), Some(&5));
+        assert_eq!(profile.elements.get("cd1"), Some(&0));
+        assert_eq!(profile.preferred_elements(), vec!["th0".to_string()]);
+        assert_eq!(profile.preferred_regions(), vec![13]);
+        assert!(!profile.is_empty());
+    }
+
+    #[test]
+    fn negative_review() {
+        let mut profile = RecommendationProfile::default();
+        profile.add_region(13, RecommendationSignal::Review(2));
+        assert_eq!(profile.regions.get(&13), Some(&-1));
+        assert!(profile.is_empty());
+    }
+
+    #[test]
+    fn cursor() {
+        let cursor = RecommendationCursor {
+            score: 7,
+            is_paid: false,
+            deadline_on: NaiveDate::from_ymd_opt(2023, 8, 1).unwrap(),
+            registered_at: NaiveDate::from_ymd_opt(2023, 7, 1)
+                .unwrap()
+                .and_hms_opt(12, 30, 0)
+                .unwrap(),
+            vid: "01H5Z3A2Q4Y8X7W6V5T4S3R2P1".to_string(),
+        };
+        let value = cursor.to_string();
+        assert_eq!(value, "7|0|2023-08-01|2023-07-01T12:30:00|01H5Z3A2Q4Y8X7W6V5T4S3R2P1");
+        assert_eq!(RecommendationCursor::from_str(&value), Ok(cursor));
+        assert_eq!(
+            RecommendationCursor::from_str("7|0|2023-08-01|01H5Z3A2Q4Y8X7W6V5T4S3R2P1"),
+            Err(PageError::InvalidCursor)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_search_words {
+    use super::*;
+
+    #[test]
+    fn and() {
+        let search_words = SearchWords::new("海 清掃\u{3000}ゴミ拾い");
+        assert_eq!(search_words.to_boolean_query(), "+\"海\" +\"清掃\" +\"ゴミ拾い\"");
+    }
+
+    #[test]
+    fn or() {
+        let search_words = SearchWords::new("海 清掃 OR ゴミ拾い | 草刈り");
+        assert_eq!(
+            search_words.to_boolean_query(),
+            "+\"海\" +(\"清掃\" \"ゴミ拾い\" \"草刈り\")"
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert!(SearchWords::new(" \u{3000}").is_empty());
+        assert!(SearchWords::new("OR \"\"").is_empty());
+        assert_eq!(SearchWords::new("OR 海 OR").to_boolean_query(), "+\"海\"");
+    }
+
+    #[test]
+    fn escape() {
+        let search_words = SearchWords::new("\"海\"* -清掃");
+        assert_eq!(search_words.to_boolean_query(), "+\"海*\" +\"-清掃\"");
+    }
+}
+
+#[cfg(test)]
+mod test_volunteer_search_input {
+    use super::*;
+
+    #[test]
+    fn day_of_week() {
+        assert_eq!(DayOfWeek::Sunday.to_mysql(), 1);
+        assert_eq!(DayOfWeek::Saturday.to_mysql(), 7);
+    }
+
+    #[test]
+    fn validate() {
+        let input = VolunteerSearchInput {
+            deadline_from: NaiveDate::from_ymd_opt(2023, 8, 1),
+            deadline_until: NaiveDate::from_ymd_opt(2023, 8, 1),
+            time_from: NaiveTime::from_hms_opt(17, 0, 0),
+            ..Default::default()
+        };
+        assert_eq!(input.validate(), Ok(()));
+
+        let input = VolunteerSearchInput {
+            time_from: NaiveTime::from_hms_opt(17, 0, 0),
+            time_until: NaiveTime::from_hms_opt(9, 0, 0),
+            ..Default::default()
+        };
+        assert_eq!(
+            input.validate(),
+            Err(VolunteerSearchInputError::InvalidRange("timeFrom", "timeUntil"))
+        );
+    }
+
+    #[test]
+    fn validate_near() {
+        let near = DistanceInput {
+            latitude: 35.681236,
+            longitude: 139.767125,
+            radius_km: 10.0,
+        };
+        let input = VolunteerSearchInput {
+            near: Some(near),
+            ..Default::default()
+        };
+        assert_eq!(input.validate(), Ok(()));
+
+        let input = VolunteerSearchInput {
+            near: Some(DistanceInput {
+                radius_km: 0.0,
+                ..near
+            }),
+            ..Default::default()
+        };
+        assert_eq!(input.validate(), Err(VolunteerSearchInputError::InvalidRadius));
+
+        let input = VolunteerSearchInput {
+            near: Some(DistanceInput {
+                latitude: 135.0,
+                ..near
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            input.validate(),
+            Err(VolunteerSearchInputError::InvalidLocation(_))
+        ));
+    }
 }
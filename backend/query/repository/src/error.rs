@@ -0,0 +1,70 @@
+use api_error::{ApiError, INTERNAL_ERROR_MESSAGE};
+use async_graphql::{Error, ErrorExtensions};
+use thiserror::Error;
+
+/// 1件を取得するクエリのエラー
+///
+/// 存在しない場合と削除済みの場合を, データベースの障害と区別する
+#[derive(Error, Debug)]
+pub enum QueryError {
+    /// 対象が存在しない
+    #[error("{0} not found")]
+    NotFound(&'static str),
+    /// 対象が削除済み
+    #[error("{0} is deleted")]
+    Deleted(&'static str),
+    /// データベースのエラー
+    #[error(transparent)]
+    Db(#[from] anyhow::Error),
+}
+
+impl From<sqlx::Error> for QueryError {
+    fn from(error: sqlx::Error) -> Self {
+        QueryError::Db(error.into())
+    }
+}
+
+impl From<&QueryError> for ApiError {
+    fn from(error: &QueryError) -> Self {
+        match error {
+            QueryError::NotFound(_) => ApiError::NotFound(error.to_string()),
+            QueryError::Deleted(_) => ApiError::Deleted(error.to_string()),
+            QueryError::Db(_) => {
+                // SQLやテーブル名を含むため, 内容はログにのみ出力する
+                log::error!("query failed: {}", error);
+                ApiError::Internal(INTERNAL_ERROR_MESSAGE.to_string())
+            }
+        }
+    }
+}
+
+impl ErrorExtensions for QueryError {
+    fn extend(&self) -> Error {
+        ApiError::from(self).extend()
+    }
+}
+
+#[cfg(test)]
+mod test_query_error {
+    use super::*;
+
+    #[test]
+    fn code() {
+        assert_eq!(ApiError::from(&QueryError::NotFound("volunteer")).code(), "NOT_FOUND");
+        assert_eq!(ApiError::from(&QueryError::Deleted("volunteer")).code(), "DELETED");
+        assert_eq!(
+            ApiError::from(&QueryError::Db(anyhow::anyhow!("connection refused"))).code(),
+            "INTERNAL_SERVER_ERROR"
+        );
+    }
+
+    #[test]
+    fn message() {
+        assert_eq!(
+            ApiError::from(&QueryError::Db(anyhow::anyhow!("Table 'volunteer' doesn't exist"))),
+            ApiError::Internal(INTERNAL_ERROR_MESSAGE.to_string())
+        );
+        assert_eq!(QueryError::NotFound("volunteer").to_string(), "volunteer not found");
+        assert_eq!(QueryError::Deleted("group_account").to_string(), "group_account is deleted");
+    }
+}
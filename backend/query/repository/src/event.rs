@@ -0,0 +1,124 @@
+use anyhow::Result;
+use async_graphql::SimpleObject;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+
+use domain::model::{apply::ApplyStatus, event::ActivityEvent};
+
+/// 参加者がスカウトを受けた通知
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq)]
+pub struct ScoutReceivedEvent {
+    /// スカウトID
+    pub sid: String,
+    /// ボランティアID
+    pub vid: String,
+    /// スカウトした団体のID
+    pub gid: String,
+}
+
+impl ScoutReceivedEvent {
+    /// スカウトの出来事の場合のみ変換する
+    pub fn from_event(event: ActivityEvent) -> Option<ScoutReceivedEvent> {
+        match event {
+            ActivityEvent::ScoutReceived { sid, vid, gid, .. } => {
+                Some(ScoutReceivedEvent { sid, vid, gid })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 応募の作成・取消・承認・棄却の通知
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq)]
+pub struct ApplyEvent {
+    /// 出来事の種類
+    ///
+    /// Received, Cancelled, Approved, Rejected
+    pub kind: String,
+    /// 応募ID
+    pub aid: String,
+    /// ボランティアID
+    pub vid: String,
+    /// 応募した参加者のID
+    pub uid: String,
+    /// ボランティアを募集する団体のID
+    pub gid: String,
+}
+
+impl ApplyEvent {
+    /// 応募の出来事の場合のみ変換する
+    pub fn from_event(event: ActivityEvent) -> Option<ApplyEvent> {
+        let (kind, aid, vid, uid, gid) = match event {
+            ActivityEvent::ApplyReceived { aid, vid, uid, gid } => ("Received", aid, vid, uid, gid),
+            ActivityEvent::ApplyCancelled { aid, vid, uid, gid } => {
+                ("Cancelled", aid, vid, uid, gid)
+            }
+            ActivityEvent::ApplyDecided {
+                aid,
+                vid,
+                uid,
+                gid,
+                status: ApplyStatus::Approved,
+            } => ("Approved", aid, vid, uid, gid),
+            ActivityEvent::ApplyDecided {
+                aid,
+                vid,
+                uid,
+                gid,
+                status: ApplyStatus::Rejected,
+            } => ("Rejected", aid, vid, uid, gid),
+            _ => return None,
+        };
+        Some(ApplyEvent {
+            kind: kind.to_string(),
+            aid,
+            vid,
+            uid,
+            gid,
+        })
+    }
+}
+
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    /// `uid`のユーザーに配信される応募・スカウトの出来事を購読する
+    async fn subscribe(&self, uid: &str) -> Result<BoxStream<'static, ActivityEvent>>;
+}
+
+#[cfg(test)]
+mod test_event {
+    use super::*;
+
+    fn decided(status: ApplyStatus) -> ActivityEvent {
+        ActivityEvent::ApplyDecided {
+            aid: "01HKXZS5TT1GMJD8PVC5RKJH42".to_string(),
+            vid: "01HKXVVVKBR6G8240N7HWSPR7M".to_string(),
+            uid: "participant_account000000000".to_string(),
+            gid: "group_account000000000000000".to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn from_event() {
+        let event = ApplyEvent::from_event(decided(ApplyStatus::Rejected)).unwrap();
+        assert_eq!(event.kind, "Rejected");
+        assert_eq!(event.aid, "01HKXZS5TT1GMJD8PVC5RKJH42");
+
+        assert_eq!(
+            ScoutReceivedEvent::from_event(decided(ApplyStatus::Approved)),
+            None
+        );
+        let scout = ActivityEvent::ScoutReceived {
+            sid: "01HMAWQDS2Y50QHNGB8GSWJQNW".to_string(),
+            vid: "01HKXVVVKBR6G8240N7HWSPR7M".to_string(),
+            uid: "participant_account000000000".to_string(),
+            gid: "group_account000000000000000".to_string(),
+        };
+        assert_eq!(ApplyEvent::from_event(scout.clone()), None);
+        assert_eq!(
+            ScoutReceivedEvent::from_event(scout).unwrap().gid,
+            "group_account000000000000000"
+        );
+    }
+}
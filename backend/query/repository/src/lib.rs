@@ -1,2 +1,6 @@
+pub mod access;
 pub mod activities;
+pub mod error;
+pub mod event;
+pub mod pagination;
 pub mod user_account;
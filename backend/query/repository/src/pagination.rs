@@ -0,0 +1,139 @@
+use async_graphql::SimpleObject;
+use thiserror::Error;
+
+/// 1ページに取得する件数の既定値
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// 1ページに取得する件数の上限
+pub const MAX_PAGE_SIZE: usize = 100;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PageError {
+    #[error("first must be between 0 and {}", MAX_PAGE_SIZE)]
+    InvalidFirst,
+    #[error("invalid cursor")]
+    InvalidCursor,
+}
+
+/// ページの取得条件
+///
+/// `after`のカーソルより後ろの要素を`first`件取得する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRequest {
+    pub first: usize,
+    pub after: Option<String>,
+}
+
+impl PageRequest {
+    pub fn new(first: Option<i32>, after: Option<String>) -> Result<PageRequest, PageError> {
+        let first: usize = match first {
+            None => DEFAULT_PAGE_SIZE,
+            Some(first) if (0..=MAX_PAGE_SIZE as i32).contains(&first) => first as usize,
+            Some(_) => return Err(PageError::InvalidFirst),
+        };
+        Ok(PageRequest { first, after })
+    }
+
+    /// 次のページの有無を判定するため, `first`より1件多く取得する
+    pub fn limit(&self) -> i64 {
+        self.first as i64 + 1
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        PageRequest {
+            first: DEFAULT_PAGE_SIZE,
+            after: None,
+        }
+    }
+}
+
+/// 取得したページ
+#[derive(Debug)]
+pub struct Page<T> {
+    /// カーソルと要素の組
+    pub edges: Vec<(String, T)>,
+    pub has_next_page: bool,
+    /// カーソルに関係なく, 条件に一致する要素の総数
+    pub total_count: i64,
+}
+
+impl<T> Page<T> {
+    /// [PageRequest::limit]件まで取得した要素からページを作成する
+    pub fn new<F>(mut nodes: Vec<T>, request: &PageRequest, total_count: i64, cursor: F) -> Page<T>
+    where
+        F: Fn(&T) -> String,
+    {
+        let has_next_page: bool = nodes.len() > request.first;
+        nodes.truncate(request.first);
+        Page {
+            edges: nodes.into_iter().map(|node| (cursor(&node), node)).collect(),
+            has_next_page,
+            total_count,
+        }
+    }
+
+    /// カーソルを保ったまま要素を変換する
+    pub fn map<U, F>(self, mut f: F) -> Page<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Page {
+            edges: self
+                .edges
+                .into_iter()
+                .map(|(cursor, node)| (cursor, f(node)))
+                .collect(),
+            has_next_page: self.has_next_page,
+            total_count: self.total_count,
+        }
+    }
+}
+
+/// コネクションに追加するフィールド
+#[derive(SimpleObject, Debug, Clone)]
+pub struct ConnectionFields {
+    pub total_count: i64,
+}
+
+#[cfg(test)]
+mod test_pagination {
+    use super::*;
+
+    #[test]
+    fn page_request_default() {
+        let request = PageRequest::new(None, None).unwrap();
+        assert_eq!(request.first, DEFAULT_PAGE_SIZE);
+        assert_eq!(request.limit(), DEFAULT_PAGE_SIZE as i64 + 1);
+    }
+
+    #[test]
+    fn page_request_invalid_first() {
+        assert_eq!(PageRequest::new(Some(-1), None), Err(PageError::InvalidFirst));
+        assert_eq!(
+            PageRequest::new(Some(MAX_PAGE_SIZE as i32 + 1), None),
+            Err(PageError::InvalidFirst)
+        );
+    }
+
+    #[test]
+    fn page_has_next() {
+        let request = PageRequest::new(Some(2), None).unwrap();
+        let page = Page::new(vec!["c", "b", "a"], &request, 3, |node| node.to_string());
+        assert!(page.has_next_page);
+        assert_eq!(page.total_count, 3);
+        assert_eq!(
+            page.edges,
+            vec![("c".to_string(), "c"), ("b".to_string(), "b")]
+        );
+    }
+
+    #[test]
+    fn page_last() {
+        let request = PageRequest::new(Some(2), Some("b".to_string())).unwrap();
+        let page = Page::new(vec!["a"], &request, 3, |node| node.to_string());
+        assert!(!page.has_next_page);
+        assert_eq!(page.edges.len(), 1);
+    }
+}
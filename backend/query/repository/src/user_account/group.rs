@@ -1,9 +1,14 @@
 use anyhow::Result;
 use async_graphql::SimpleObject;
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 
-use domain::model::user_account::user_id::UserId;
+use domain::model::{
+    plan::{BillingPeriod, Plan},
+    user_account::user_id::UserId,
+};
+
+use crate::{access::PrivateInfoGuard, error::QueryError};
 
 // Read server で返す型. GraphQLのスキーマに対応する
 /// 団体アカウントリードモデル
@@ -16,6 +21,9 @@ pub struct GroupAccount {
     /// 団体名(フリガナ)
     pub furigana: String,
     /// 電話番号
+    ///
+    /// 団体本人と承認済みの応募・スカウトがある参加者のみ閲覧できる
+    #[graphql(guard = "PrivateInfoGuard::group(&self.gid)")]
     pub phone: String,
     /// 住所
     pub address: String,
@@ -63,10 +71,146 @@ impl GroupAccount {
     }
 }
 
+/// プランの上限に対する利用数
+#[derive(SimpleObject, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaUsage {
+    /// 上限
+    pub limit: u32,
+    /// 利用数
+    pub used: u32,
+    /// 残り. 上限を超えている場合は0
+    pub remaining: u32,
+}
+
+impl QuotaUsage {
+    pub fn new(limit: u32, used: u32) -> QuotaUsage {
+        QuotaUsage {
+            limit,
+            used,
+            remaining: limit.saturating_sub(used),
+        }
+    }
+}
+
+/// 団体のプランの利用状況リードモデル
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq)]
+pub struct PlanUsage {
+    /// プラン
+    ///
+    /// Free, Standard, Premium
+    pub plan: String,
+    /// 請求期間の開始日(日本時間)
+    pub period_start_on: NaiveDate,
+    /// 請求期間の終了日(日本時間)
+    pub period_end_on: NaiveDate,
+    /// 請求期間内に送信したスカウト
+    pub scouts: QuotaUsage,
+    /// 募集中のボランティア
+    pub open_volunteers: QuotaUsage,
+    /// ボランティア1件あたりに登録できる写真の数
+    pub volunteer_photo_limit: u32,
+}
+
+impl PlanUsage {
+    pub fn new(
+        plan: Plan,
+        period: &BillingPeriod,
+        scouts_used: u32,
+        open_volunteers: u32,
+    ) -> PlanUsage {
+        let limits = plan.limits();
+        PlanUsage {
+            plan: plan.to_string(),
+            period_start_on: period.start_on,
+            period_end_on: period.end_on,
+            scouts: QuotaUsage::new(limits.monthly_scouts, scouts_used),
+            open_volunteers: QuotaUsage::new(limits.open_volunteers, open_volunteers),
+            volunteer_photo_limit: limits.volunteer_photos,
+        }
+    }
+}
+
+/// プランの一覧リードモデル
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq)]
+pub struct PlanCatalogItem {
+    /// プラン
+    ///
+    /// Free, Standard, Premium
+    pub plan: String,
+    /// 月額料金(円, 税込)
+    pub monthly_price: u32,
+    /// 無料体験の日数. 無料体験がない場合は0
+    pub trial_days: u32,
+    /// 請求期間あたりに送信できるスカウトの数
+    pub monthly_scouts: u32,
+    /// 同時に募集できるボランティアの数
+    pub open_volunteers: u32,
+    /// ボランティア1件あたりに登録できる写真の数
+    pub volunteer_photos: u32,
+}
+
+impl PlanCatalogItem {
+    pub fn new(plan: Plan) -> PlanCatalogItem {
+        let limits = plan.limits();
+        PlanCatalogItem {
+            plan: plan.to_string(),
+            monthly_price: plan.monthly_price(),
+            trial_days: plan.trial_days(),
+            monthly_scouts: limits.monthly_scouts,
+            open_volunteers: limits.open_volunteers,
+            volunteer_photos: limits.volunteer_photos,
+        }
+    }
+}
+
+/// 団体のプランの変更履歴リードモデル
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq)]
+pub struct PlanHistory {
+    /// プラン
+    ///
+    /// Free, Standard, Premium
+    pub plan: String,
+    /// 契約状態
+    ///
+    /// Trialing, Active, PastDue
+    pub status: String,
+    /// 無料体験の終了日時
+    pub trial_ends_at: Option<NaiveDateTime>,
+    /// 適用開始日時
+    pub effective_from: NaiveDateTime,
+    /// 適用終了日時. 現在のプランの場合はnull
+    pub effective_until: Option<NaiveDateTime>,
+}
+
+/// 団体の請求書リードモデル
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq)]
+pub struct GroupInvoice {
+    /// 請求書id
+    pub iid: String,
+    /// プラン
+    ///
+    /// Free, Standard, Premium
+    pub plan: String,
+    /// 請求額(円, 税込)
+    pub amount: u32,
+    /// 請求期間の開始日(日本時間)
+    pub period_start_on: NaiveDate,
+    /// 請求期間の終了日(日本時間)
+    pub period_end_on: NaiveDate,
+    /// 支払い状態
+    ///
+    /// Pending, Paid, Failed, Refunded
+    pub status: String,
+    /// 発行日時
+    pub issued_at: NaiveDateTime,
+    /// 支払日時
+    pub paid_at: Option<NaiveDateTime>,
+}
+
 #[async_trait]
 pub trait GroupUserRepository: Send + Sync {
     /// 団体アカウントをIDで取得する
-    async fn find_by_id(&self, gid: &UserId) -> Result<GroupAccount>;
+    async fn find_by_id(&self, gid: &UserId) -> Result<GroupAccount, QueryError>;
 
     /// 団体アカウントをIDで複数取得する
     async fn find_by_ids(&self, gids: &[UserId]) -> Result<Vec<GroupAccount>>;
@@ -76,4 +220,45 @@ pub trait GroupUserRepository: Send + Sync {
 
     /// 団体アカウントが存在するか確認する
     async fn exists(&self, gid: &UserId) -> Result<bool>;
+
+    /// 団体のプランの請求期間内の利用状況を取得する
+    async fn find_plan_usage(
+        &self,
+        gid: &UserId,
+        period: &BillingPeriod,
+    ) -> Result<PlanUsage, QueryError>;
+
+    /// 団体のプランの変更履歴を新しい順に取得する
+    async fn find_plan_history(&self, gid: &UserId) -> Result<Vec<PlanHistory>>;
+
+    /// 団体の請求書を新しい順に取得する
+    async fn find_invoices(&self, gid: &UserId) -> Result<Vec<GroupInvoice>>;
+}
+
+#[cfg(test)]
+mod test_plan_usage {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let period = BillingPeriod {
+            start_on: NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+            end_on: NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+        };
+        let limits = Plan::Free.limits();
+        let usage = PlanUsage::new(Plan::Free, &period, 4, limits.open_volunteers + 1);
+        assert_eq!(usage.plan, "Free");
+        assert_eq!(usage.scouts, QuotaUsage::new(limits.monthly_scouts, 4));
+        assert_eq!(usage.scouts.remaining, limits.monthly_scouts - 4);
+        // プランを変更して上限を超えた場合も残りは0にする
+        assert_eq!(usage.open_volunteers.remaining, 0);
+    }
+
+    #[test]
+    fn catalog_item() {
+        let item = PlanCatalogItem::new(Plan::Standard);
+        assert_eq!(item.plan, "Standard");
+        assert_eq!(item.monthly_price, Plan::Standard.monthly_price());
+        assert_eq!(item.monthly_scouts, Plan::Standard.limits().monthly_scouts);
+    }
 }
@@ -1,11 +1,13 @@
 use anyhow::Result;
 use async_graphql::SimpleObject;
 use async_trait::async_trait;
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 
 use domain::model::{apply::ApplyId, user_account::user_id::UserId};
 
-use crate::activities::volunteer::VolunteerElementsReadModel;
+use crate::{
+    access::PrivateInfoGuard, activities::volunteer::VolunteerElementsReadModel, error::QueryError,
+};
 
 /// 参加者アカウントリードモデル
 #[derive(SimpleObject, sqlx::Type)]
@@ -17,12 +19,18 @@ pub struct ParticipantAccount {
     /// 参加者氏名(フリガナ)
     pub furigana: String,
     /// 電話番号
+    ///
+    /// 本人と承認済みの応募・スカウトがある団体のみ閲覧できる
+    #[graphql(guard = "PrivateInfoGuard::participant(&self.uid)")]
     pub phone: String,
     /// 性別
     ///
     /// 0: 男性, 1: 女性, 2: その他
     pub gender: i8,
     /// 生年月日
+    ///
+    /// 本人と承認済みの応募・スカウトがある団体のみ閲覧できる
+    #[graphql(guard = "PrivateInfoGuard::participant(&self.uid)")]
     pub birthday: NaiveDate,
     /// プロフィール
     pub profile: String,
@@ -63,6 +71,21 @@ pub struct ParticipantTargetStatus {
     pub name: String,
 }
 
+/// 参加者の参加可能な時間帯リードモデル
+#[derive(SimpleObject)]
+pub struct ParticipantAvailability {
+    /// 参加可能な時間帯id
+    pub did: String,
+    /// 曜日
+    ///
+    /// Mon, Tue, Wed, Thu, Fri, Sat, Sun
+    pub weekday: String,
+    /// 開始時刻(日本時間)
+    pub start_time: NaiveTime,
+    /// 終了時刻(日本時間)
+    pub finish_time: NaiveTime,
+}
+
 /// 集団応募者リードモデル
 #[derive(SimpleObject)]
 pub struct GroupParticipant {
@@ -78,10 +101,57 @@ pub struct GroupParticipant {
     pub gender: u8,
     /// 年齢
     pub age: u8,
+    /// 出欠
+    ///
+    /// 0: 未記録, 1: 出席, 2: 欠席
+    pub attendance_status: u8,
+}
+
+/// スカウト候補が検索条件に一致した内訳
+#[derive(SimpleObject, Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScoutMatchBreakdown {
+    /// 一致した地域
+    pub regions: Vec<String>,
+    /// 一致したテーマ
+    pub themes: Vec<String>,
+    /// 一致した条件
+    pub conditions: Vec<String>,
+    /// 一致した対象者
+    pub target_status: Vec<String>,
+    /// 参加者が必須としたテーマ・条件のうち, 検索条件に含まれるもの
+    pub required_elements: Vec<String>,
+    /// 参加可能な時間帯とボランティアの日時が重なる時間(分)
+    pub availability_overlap: i64,
+}
+
+/// 一致した地域・要素1つあたりの点数
+const MATCH_SCORE: i64 = 10;
+/// 満たした必須のテーマ・条件1つあたりの点数
+const REQUIRED_SCORE: i64 = 5;
+/// 重なる時間を点数にする単位(分)
+const OVERLAP_UNIT_MINUTES: i64 = 30;
+/// 重なる時間の点数の上限
+const MAX_OVERLAP_SCORE: i64 = 8;
+
+impl ScoutMatchBreakdown {
+    /// 一致度の点数
+    ///
+    /// 一致した地域・要素を最も重視し, 必須のテーマ・条件, 重なる時間の順に加点する
+    pub fn score(&self) -> i64 {
+        let match_count: usize = self.regions.len()
+            + self.themes.len()
+            + self.conditions.len()
+            + self.target_status.len();
+        let overlap_score: i64 =
+            (self.availability_overlap / OVERLAP_UNIT_MINUTES).clamp(0, MAX_OVERLAP_SCORE);
+        MATCH_SCORE * match_count as i64
+            + REQUIRED_SCORE * self.required_elements.len() as i64
+            + overlap_score
+    }
 }
 
 /// スカウト用参加者アカウントリードモデル
-#[derive(SimpleObject, sqlx::Type)]
+#[derive(SimpleObject)]
 pub struct ScoutParticipant {
     /// 参加者アカウントid
     pub uid: String,
@@ -92,15 +162,39 @@ pub struct ScoutParticipant {
     /// 0: 男性, 1: 女性, 2: その他
     pub gender: i8,
     /// 生年月日
-    pub birthday: NaiveDate,
+    ///
+    /// ボランティアを所有する団体と承認済みの応募・スカウトがある参加者のみ. それ以外は`None`
+    pub birthday: Option<NaiveDate>,
     /// 平均評価値
-    pub point: Option<f32>
+    pub point: Option<f32>,
+    /// 一致度の点数
+    pub score: i64,
+    /// 検索条件に一致した内訳
+    pub breakdown: ScoutMatchBreakdown,
+}
+
+/// スカウト候補の絞り込み条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoutCandidateFilter {
+    /// 同じボランティアにスカウト済みの参加者を除外する
+    pub exclude_scouted: bool,
+    /// 同じボランティアに応募済みの参加者を除外する
+    pub exclude_applied: bool,
+}
+
+impl Default for ScoutCandidateFilter {
+    fn default() -> Self {
+        ScoutCandidateFilter {
+            exclude_scouted: true,
+            exclude_applied: true,
+        }
+    }
 }
 
 #[async_trait]
 pub trait ParticipantUserRepository: Send + Sync {
     /// 参加者アカウントをIDで取得する
-    async fn find_by_id(&self, pid: &UserId) -> Result<ParticipantAccount>;
+    async fn find_by_id(&self, pid: &UserId) -> Result<ParticipantAccount, QueryError>;
 
     /// 参加者アカウントをIDで複数取得する
     async fn find_by_ids(&self, pids: &[UserId]) -> Result<Vec<ParticipantAccount>>;
@@ -115,7 +209,13 @@ pub trait ParticipantUserRepository: Send + Sync {
     async fn find_condition_by_id(&self, pid: &UserId) -> Result<Vec<ParticipantCondition>>;
 
     /// 参加者の区分を取得する
-    async fn find_target_status_by_id(&self, pid: &UserId) -> Result<ParticipantTargetStatus>;
+    async fn find_target_status_by_id(
+        &self,
+        pid: &UserId,
+    ) -> Result<ParticipantTargetStatus, QueryError>;
+
+    /// 参加者の参加可能な時間帯を曜日, 開始時刻の順で取得する
+    async fn find_availability_by_id(&self, pid: &UserId) -> Result<Vec<ParticipantAvailability>>;
 
     /// 参加者が存在するか確認する
     async fn exists(&self, pid: &UserId) -> Result<bool>;
@@ -124,8 +224,38 @@ pub trait ParticipantUserRepository: Send + Sync {
     async fn find_group_participants(&self, aid: &ApplyId) -> Result<Vec<GroupParticipant>>;
 
     /// 参加者を条件検索を用いて取得する
+    ///
+    /// 一致度の点数が高い順に並べる
     async fn find_by_elements(
         &self,
-        elements: &VolunteerElementsReadModel
+        elements: &VolunteerElementsReadModel,
+        filter: &ScoutCandidateFilter,
     ) -> Result<Vec<ScoutParticipant>>;
 }
+
+#[cfg(test)]
+mod test_scout_match_breakdown {
+    use super::*;
+
+    #[test]
+    fn score() {
+        let breakdown = ScoutMatchBreakdown {
+            regions: vec!["東京都".to_string()],
+            themes: vec!["地域".to_string(), "子ども".to_string()],
+            required_elements: vec!["子ども".to_string()],
+            availability_overlap: 95,
+            ..Default::default()
+        };
+        assert_eq!(breakdown.score(), 3 * MATCH_SCORE + REQUIRED_SCORE + 3);
+        assert_eq!(ScoutMatchBreakdown::default().score(), 0);
+    }
+
+    #[test]
+    fn overlap_score_is_capped() {
+        let breakdown = ScoutMatchBreakdown {
+            availability_overlap: 24 * 60,
+            ..Default::default()
+        };
+        assert_eq!(breakdown.score(), MAX_OVERLAP_SCORE);
+    }
+}
@@ -5,6 +5,7 @@ INSERT INTO participant_account VALUES (
   0,
   "2002-06-22",
   "08012345678",
+  "ken@example.com",
   "kendesu",
   DEFAULT,
   NULL
@@ -17,6 +18,7 @@ INSERT INTO participant_account VALUES (
   0,
   "2002-10-11",
   "08012341234",
+  "taro@example.com",
   "yorodesu",
   DEFAULT,
   NULL
@@ -27,12 +29,14 @@ INSERT INTO group_account VALUES (
   "VolunScout",
   "boran sukauto",
   "08012345678",
+  "volunscout@example.com",
   "Tokyo",
   "Hello",
   "katogi",
   "katogi",
   DEFAULT,
   DEFAULT,
+  DEFAULT,
   NULL
 );
 
@@ -41,12 +45,14 @@ INSERT INTO group_account VALUES (
   "VolunScoutSub",
   "boran sukauto sabu",
   "09012345678",
+  "volunscout-sub@example.com",
   "Kanagawa",
   "Nice to meet you",
   "sakamoto",
   "sakamoto",
   DEFAULT,
   DEFAULT,
+  DEFAULT,
   NULL
 );
 
@@ -142,6 +148,30 @@ INSERT INTO volunteer(
   NOW()
 );
 
+INSERT INTO volunteer_session (vsid, vid, start_at, finish_at, recruited_num) VALUES (
+  "01HKXVVVKBR6G8240N7HWSPR8A",
+  "01HKXVVVKBR6G8240N7HWSPR7M",
+  "2023-12-24 11:00:00",
+  "2023-12-24 17:00:00",
+  10
+);
+
+INSERT INTO volunteer_session (vsid, vid, start_at, finish_at, recruited_num) VALUES (
+  "01HKXZRM5AF35HHXJ8284PN0C1",
+  "01HKXZRM5AF35HHXJ8284PN0B7",
+  "2023-12-24 11:00:00",
+  "2023-12-24 17:00:00",
+  10
+);
+
+INSERT INTO volunteer_session (vsid, vid, start_at, finish_at, recruited_num) VALUES (
+  "01HN22FWVK9949XC4KP8P517D4",
+  "01HN22FWVK9949XC4KP8P517C3",
+  "2024-4-1 11:00:00",
+  "2024-4-1 17:00:00",
+  5
+);
+
 INSERT INTO volunteer_region VALUES (
   "01HKXVVVKBR6G8240N7HWSPR7M",
   1
@@ -210,38 +240,44 @@ INSERT INTO favorite VALUES (
   NOW()
 );
 
-INSERT INTO apply VALUES (
+INSERT INTO apply (aid, vid, vsid, uid, applied_at, as_group, allowed_status, decided_at, is_sent, sent_at) VALUES (
   "01HKXZS5TT1GMJD8PVC5RKJH42",
   "01HKXVVVKBR6G8240N7HWSPR7M",
+  "01HKXVVVKBR6G8240N7HWSPR8A",
   "participant_account000000000",
   "2024-1-12 12:00:00",
   true,
   1,
   "2024-1-12 16:20:00",
-  true
+  true,
+  "2024-1-12 12:05:00"
 );
 
-INSERT INTO apply VALUES (
+INSERT INTO apply (aid, vid, vsid, uid, applied_at, as_group, allowed_status, decided_at, is_sent, sent_at) VALUES (
   "01HKXZSD39EDT7F528X87EPHX9",
   "01HKXVVVKBR6G8240N7HWSPR7M",
+  "01HKXVVVKBR6G8240N7HWSPR8A",
   "participant_account000000001",
   "2024-1-13 12:00:00",
   false,
   0,
   NULL,
-  false
+  false,
+  NULL
 );
 
 
-INSERT INTO apply VALUES (
+INSERT INTO apply (aid, vid, vsid, uid, applied_at, as_group, allowed_status, decided_at, is_sent, sent_at) VALUES (
   "01HN22NKTWY3SHA4SMC8GQX83T",
   "01HN22FWVK9949XC4KP8P517C3",
+  "01HN22FWVK9949XC4KP8P517D4",
   "participant_account000000000",
   "2024-1-23 12:00:00",
   false,
   0,
   NULL,
-  false
+  false,
+  NULL
 );
 
 INSERT INTO group_participants VALUES (
@@ -282,6 +318,8 @@ INSERT INTO scout VALUES(
   DEFAULT,
   NULL,
   DEFAULT,
+  NULL,
+  DEFAULT,
   NULL
 );
 
@@ -295,6 +333,8 @@ INSERT INTO scout VALUES(
   true,
   "2023-01-16 17:00:01",
   true,
+  "2023-01-17 09:00:00",
+  3,
   "2023-01-17 09:00:00"
 );
 
@@ -8,6 +8,8 @@ CREATE TABLE IF NOT EXISTS `participant_account`
   `gender` TINYINT NOT NULL DEFAULT 2,
   `birthday` DATE NOT NULL,
   `phone` VARCHAR(11) NOT NULL,
+  -- 応募・スカウトのメールの宛先. Firebase ID トークンのメールアドレスを保存する
+  `email` VARCHAR(254),
   `profile` TEXT NOT NULL,
   `is_deleted` BOOLEAN NOT NULL DEFAULT false,
   `deleted_at` DATETIME,
@@ -20,16 +22,65 @@ CREATE TABLE IF NOT EXISTS `group_account`
   `name` VARCHAR(50) NOT NULL,
   `furigana` VARCHAR(50) NOT NULL,
   `phone` VARCHAR(11) NOT NULL,
+  -- 応募のメールの宛先. Firebase ID トークンのメールアドレスを保存する
+  `email` VARCHAR(254),
   `address` VARCHAR(100) NOT NULL,
   `contents` TEXT NOT NULL,
   `representative_name` VARCHAR(50) NOT NULL,
   `representative_furigana` VARCHAR(50) NOT NULL,
   `is_paid` BOOLEAN NOT NULL DEFAULT false,
+  -- 現在のプラン. 0: 無料, 1: スタンダード, 2: プレミアム. is_paidはプランが有料かを表す
+  `plan` TINYINT NOT NULL DEFAULT 0,
   `is_deleted` BOOLEAN NOT NULL DEFAULT false,
   `deleted_at` DATETIME,
   PRIMARY KEY (`gid`)
 );
 
+-- 団体のプランの変更履歴. effective_untilがNULLの行が現在のプラン
+CREATE TABLE IF NOT EXISTS `group_plan_history`
+(
+  `hid` BIGINT UNSIGNED AUTO_INCREMENT,
+  `gid` CHAR(28) NOT NULL,
+  `plan` TINYINT NOT NULL,
+  -- 0: 無料体験中, 1: 契約中, 2: 未払い
+  `status` TINYINT NOT NULL,
+  `payment_method` VARCHAR(255),
+  `trial_ends_at` DATETIME,
+  `effective_from` DATETIME NOT NULL,
+  `effective_until` DATETIME,
+  PRIMARY KEY (`hid`),
+  INDEX `idx_group_plan_history_gid` (`gid`, `effective_until`),
+  FOREIGN KEY(`gid`) REFERENCES `group_account`(`gid`)
+);
+
+CREATE TABLE IF NOT EXISTS `invoice`
+(
+  `iid` CHAR(26),
+  `gid` CHAR(28) NOT NULL,
+  `plan` TINYINT NOT NULL,
+  `amount` INT UNSIGNED NOT NULL,
+  `period_start_on` DATE NOT NULL,
+  `period_end_on` DATE NOT NULL,
+  -- 0: 未払い, 1: 支払済, 2: 支払失敗, 3: 返金済
+  `status` TINYINT NOT NULL DEFAULT 0,
+  -- 決済サービスでの請求id
+  `charge_id` VARCHAR(255),
+  `issued_at` DATETIME NOT NULL,
+  `paid_at` DATETIME,
+  PRIMARY KEY (`iid`),
+  UNIQUE (`charge_id`),
+  FOREIGN KEY(`gid`) REFERENCES `group_account`(`gid`)
+);
+
+-- 処理済みの決済サービスのWebhookのイベント. 同じイベントを2回処理しないために使用する
+CREATE TABLE IF NOT EXISTS `payment_event`
+(
+  `event_id` VARCHAR(255),
+  `type` VARCHAR(50) NOT NULL,
+  `received_at` DATETIME NOT NULL,
+  PRIMARY KEY (`event_id`)
+);
+
 CREATE TABLE IF NOT EXISTS `volunteer`
 (
   `vid` CHAR(26),
@@ -37,10 +88,14 @@ CREATE TABLE IF NOT EXISTS `volunteer`
   `title` VARCHAR(50) NOT NULL,
   `message` TEXT NOT NULL,
   `overview` TEXT NOT NULL,
+  -- 回を指定しない場合の1回あたりの募集人数
   `recruited_num` UNSIGNED INT NOT NULL,
   `place` VARCHAR(100) NOT NULL,
+  -- 最初の回の開始日時と最後の回の終了日時
   `start_at` DATETIME NOT NULL,
   `finish_at` DATETIME NOT NULL,
+  -- 繰り返しの規則(例: FREQ=WEEKLY;INTERVAL=1;COUNT=52). 回を指定した場合はNULL
+  `recurrence` VARCHAR(255),
   `as_group` BOOLEAN NOT NULL,
   `reward` VARCHAR(100),
   `is_deleted` BOOLEAN NOT NULL DEFAULT false,
@@ -49,7 +104,25 @@ CREATE TABLE IF NOT EXISTS `volunteer`
   `registered_at` DATETIME NOT NULL,
   `updated_at` DATETIME NOT NULL,
   PRIMARY KEY (`vid`),
-  FOREIGN KEY(`gid`) REFERENCES `group_account`(`gid`)
+  FOREIGN KEY(`gid`) REFERENCES `group_account`(`gid`),
+  -- 日本語の全文検索のためにngramパーサーを使用する
+  FULLTEXT INDEX `ft_volunteer_text` (`title`, `overview`, `message`, `place`) WITH PARSER ngram
+);
+
+-- ボランティアの回. 応募は回に対して行い, 回ごとに募集人数を持つ
+CREATE TABLE IF NOT EXISTS `volunteer_session`
+(
+  `vsid` CHAR(26),
+  `vid` CHAR(26) NOT NULL,
+  `start_at` DATETIME NOT NULL,
+  `finish_at` DATETIME NOT NULL,
+  `recruited_num` UNSIGNED INT NOT NULL,
+  -- 日程の更新で除かれた回. 応募の参照を残すため論理削除する
+  `is_deleted` BOOLEAN NOT NULL DEFAULT false,
+  `deleted_at` DATETIME,
+  PRIMARY KEY (`vsid`),
+  FOREIGN KEY(`vid`) REFERENCES `volunteer`(`vid`),
+  INDEX `idx_volunteer_session_vid` (`vid`, `start_at`)
 );
 
 CREATE TABLE IF NOT EXISTS `scout`
@@ -64,6 +137,9 @@ CREATE TABLE IF NOT EXISTS `scout`
   `sent_at` DATETIME,
   `is_denied` BOOLEAN NOT NULL DEFAULT false,
   `denied_at` DATETIME,
+  -- 0: 送信済, 1: 既読, 2: 承諾済, 3: 辞退済, 4: 期限切れ, 5: 取下済
+  `status` TINYINT NOT NULL DEFAULT 0,
+  `decided_at` DATETIME,
   PRIMARY KEY (`sid`),
   FOREIGN KEY(`vid`) REFERENCES `volunteer`(`vid`),
   FOREIGN KEY(`uid`) REFERENCES `participant_account`(`uid`)
@@ -73,17 +149,138 @@ CREATE TABLE IF NOT EXISTS `apply`
 (
   `aid` CHAR(26),
   `vid` CHAR(26) NOT NULL,
+  -- 応募した回
+  `vsid` CHAR(26) NOT NULL,
   `uid` CHAR(28) NOT NULL,
   `applied_at` DATETIME NOT NULL,
   `as_group` BOOLEAN NOT NULL,
   `allowed_status` TINYINT NOT NULL DEFAULT 0,
   `decided_at` DATETIME,
   `is_sent` BOOLEAN NOT NULL DEFAULT false,
+  `sent_at` DATETIME,
+  -- 参加者による取消の理由と日時
+  `cancel_reason` TEXT,
+  `cancelled_at` DATETIME,
+  -- 0: 未記録, 1: 出席, 2: 欠席
+  `attendance_status` TINYINT NOT NULL DEFAULT 0,
+  -- 参加者がチェックインコードでチェックインした日時
+  `checked_in_at` DATETIME,
+  `attendance_recorded_at` DATETIME,
   PRIMARY KEY (`aid`),
   FOREIGN KEY(`vid`) REFERENCES `volunteer`(`vid`),
+  FOREIGN KEY(`vsid`) REFERENCES `volunteer_session`(`vsid`),
+  FOREIGN KEY(`uid`) REFERENCES `participant_account`(`uid`)
+);
+
+-- 送信待ちのメール. 応募・スカウトの書き込みと同じトランザクションで追加し, 通知ワーカーが送信する
+CREATE TABLE IF NOT EXISTS `email_outbox`
+(
+  `oid` BIGINT UNSIGNED AUTO_INCREMENT,
+  -- 0: 応募, 1: 応募の承認, 2: 応募の棄却, 3: スカウト, 4: 応募の取消
+  `kind` TINYINT NOT NULL,
+  -- 応募id または スカウトid
+  `target_id` CHAR(26) NOT NULL,
+  -- 宛先のメールアドレス
+  `recipient` VARCHAR(254) NOT NULL,
+  -- メールの本文に埋め込む値
+  `payload` JSON NOT NULL,
+  -- 0: 送信待ち, 1: 送信済, 2: 送信失敗
+  `status` TINYINT NOT NULL DEFAULT 0,
+  `attempts` INT UNSIGNED NOT NULL DEFAULT 0,
+  `next_attempt_at` DATETIME NOT NULL,
+  `last_error` TEXT,
+  `created_at` DATETIME NOT NULL,
+  `sent_at` DATETIME,
+  PRIMARY KEY (`oid`),
+  INDEX `idx_email_outbox_due` (`status`, `next_attempt_at`)
+);
+
+-- 応募・スカウトの出来事. Redisを使わない場合にWrite API Serverが追加し, Read API Serverがポーリングして配信する
+CREATE TABLE IF NOT EXISTS `activity_event`
+(
+  `eid` BIGINT UNSIGNED AUTO_INCREMENT,
+  -- 配信先のユーザーのid
+  `recipient` CHAR(28) NOT NULL,
+  `payload` JSON NOT NULL,
+  `created_at` DATETIME NOT NULL,
+  PRIMARY KEY (`eid`),
+  INDEX `idx_activity_event_recipient` (`recipient`, `eid`),
+  INDEX `idx_activity_event_created_at` (`created_at`)
+);
+
+-- 参加者がチェックインに使用するワンタイムコード. ボランティアごとに最後に発行したものだけ有効
+CREATE TABLE IF NOT EXISTS `check_in_code`
+(
+  `vid` CHAR(26) NOT NULL,
+  `code` CHAR(8) NOT NULL,
+  `issued_at` DATETIME NOT NULL,
+  `expires_at` DATETIME NOT NULL,
+  PRIMARY KEY (`vid`),
+  FOREIGN KEY(`vid`) REFERENCES `volunteer`(`vid`)
+);
+
+-- 承諾したスカウトから作成した応募
+CREATE TABLE IF NOT EXISTS `scout_apply`
+(
+  `sid` CHAR(26) NOT NULL,
+  `aid` CHAR(26) NOT NULL,
+  PRIMARY KEY (`sid`),
+  UNIQUE (`aid`),
+  FOREIGN KEY(`sid`) REFERENCES `scout`(`sid`),
+  FOREIGN KEY(`aid`) REFERENCES `apply`(`aid`)
+);
+
+-- 団体と参加者のメッセージのスレッド. 応募またはスカウトごとに1つ作成する
+CREATE TABLE IF NOT EXISTS `message_thread`
+(
+  `tid` CHAR(26),
+  -- 応募またはスカウトのどちらか一方を設定する
+  `aid` CHAR(26),
+  `sid` CHAR(26),
+  `vid` CHAR(26) NOT NULL,
+  `gid` CHAR(28) NOT NULL,
+  `uid` CHAR(28) NOT NULL,
+  `created_at` DATETIME NOT NULL,
+  `last_message_at` DATETIME,
+  PRIMARY KEY (`tid`),
+  UNIQUE (`aid`),
+  UNIQUE (`sid`),
+  INDEX `idx_message_thread_gid` (`gid`),
+  INDEX `idx_message_thread_uid` (`uid`),
+  FOREIGN KEY(`aid`) REFERENCES `apply`(`aid`),
+  FOREIGN KEY(`sid`) REFERENCES `scout`(`sid`),
+  FOREIGN KEY(`vid`) REFERENCES `volunteer`(`vid`),
+  FOREIGN KEY(`gid`) REFERENCES `group_account`(`gid`),
   FOREIGN KEY(`uid`) REFERENCES `participant_account`(`uid`)
 );
 
+CREATE TABLE IF NOT EXISTS `message`
+(
+  `mid` CHAR(26),
+  `tid` CHAR(26) NOT NULL,
+  -- 送信した団体または参加者のid
+  `sender` CHAR(28) NOT NULL,
+  `body` TEXT NOT NULL,
+  `sent_at` DATETIME NOT NULL,
+  `is_deleted` BOOLEAN NOT NULL DEFAULT false,
+  `deleted_at` DATETIME,
+  PRIMARY KEY (`mid`),
+  INDEX `idx_message_tid` (`tid`, `mid`),
+  FOREIGN KEY(`tid`) REFERENCES `message_thread`(`tid`)
+);
+
+-- スレッドごとに, 団体・参加者が最後に読んだメッセージ. last_read_mid以前のメッセージを既読とする
+CREATE TABLE IF NOT EXISTS `message_read`
+(
+  `tid` CHAR(26) NOT NULL,
+  -- 読んだ団体または参加者のid
+  `uid` CHAR(28) NOT NULL,
+  `last_read_mid` CHAR(26) NOT NULL,
+  `read_at` DATETIME NOT NULL,
+  PRIMARY KEY (`tid`, `uid`),
+  FOREIGN KEY(`tid`) REFERENCES `message_thread`(`tid`)
+);
+
 CREATE TABLE IF NOT EXISTS `group_participants`
 (
   `gpid` CHAR(26),
@@ -92,6 +289,8 @@ CREATE TABLE IF NOT EXISTS `group_participants`
   `furigana` VARCHAR(50) NOT NULL,
   `gender` TINYINT NOT NULL DEFAULT 2,
   `age` TINYINT NOT NULL,
+  -- 0: 未記録, 1: 出席, 2: 欠席
+  `attendance_status` TINYINT NOT NULL DEFAULT 0,
   PRIMARY KEY (`gpid`, `serial`),
   FOREIGN KEY(`gpid`) REFERENCES `apply`(`aid`)
 );
@@ -121,6 +320,16 @@ CREATE TABLE IF NOT EXISTS `volunteer_region`
   FOREIGN KEY(`vid`) REFERENCES `volunteer`(`vid`)
 );
 
+CREATE TABLE IF NOT EXISTS `volunteer_location`
+(
+  `vid` CHAR(26) NOT NULL,
+  -- 緯度経度(WGS84). 空間インデックスを使用するためにSRIDを指定する
+  `location` POINT NOT NULL SRID 4326,
+  PRIMARY KEY (`vid`),
+  SPATIAL INDEX `sp_volunteer_location` (`location`),
+  FOREIGN KEY(`vid`) REFERENCES `volunteer`(`vid`)
+);
+
 CREATE TABLE IF NOT EXISTS `participant_element`
 (
   `uid` CHAR(28) NOT NULL,
@@ -137,12 +346,16 @@ CREATE TABLE IF NOT EXISTS `volunteer_element`
   PRIMARY KEY (`vid`, `eid`)
 );
 
+-- 参加者の毎週の参加可能な時間帯. datesは曜日(Mon, Tue, ...), 時刻は日本時間
 CREATE TABLE IF NOT EXISTS `volunteer_dates`
 (
   `did` CHAR(26),
   `uid` CHAR(28) NOT NULL,
   `dates` CHAR(3) NOT NULL,
+  `start_time` TIME NOT NULL,
+  `finish_time` TIME NOT NULL,
   PRIMARY KEY (`did`),
+  INDEX `idx_volunteer_dates_uid` (`uid`, `dates`),
   FOREIGN KEY(`uid`) REFERENCES `participant_account`(`uid`)
 );
 
@@ -7,45 +7,62 @@ import {
   ControllersScoutApi,
   ControllersVolunteerApi,
 } from "@/__generated__/command";
+import { auth } from "@/firebaseConfig";
+import axios from "axios";
+
+// Write API Serverへのリクエストに, ログイン中のユーザーのFirebase ID トークンを付与する
+// getIdTokenは有効期限が近い場合に更新したトークンを返す
+const axiosInstance = axios.create();
+axiosInstance.interceptors.request.use(async (config) => {
+  const idToken = await auth.currentUser?.getIdToken();
+  if (idToken) {
+    config.headers.Authorization = `Bearer ${idToken}`;
+  }
+  return config;
+});
+
+const configuration = new Configuration({
+  basePath: process.env.NEXT_PUBLIC_WRITE_API_SERVER_BASE_URL,
+});
 
 // グループ用APIクライアントを作成
 export const apiClientGroup = new ControllersGroupApi(
-  new Configuration({
-    basePath: process.env.NEXT_PUBLIC_WRITE_API_SERVER_BASE_URL,
-  })
+  configuration,
+  undefined,
+  axiosInstance
 );
 
 // 参加者用APIクライアントを作成
 export const apiClientParticipant = new ControllersParticipantApi(
-  new Configuration({
-    basePath: process.env.NEXT_PUBLIC_WRITE_API_SERVER_BASE_URL,
-  })
+  configuration,
+  undefined,
+  axiosInstance
 );
 
 // ボランティア用APIクライアントを作成
 export const apiClientVolunteer = new ControllersVolunteerApi(
-  new Configuration({
-    basePath: process.env.NEXT_PUBLIC_WRITE_API_SERVER_BASE_URL,
-  })
+  configuration,
+  undefined,
+  axiosInstance
 );
 
 // 応募用APIクライアントを作成
 export const apiClientApply = new ControllersApplyApi(
-  new Configuration({
-    basePath: process.env.NEXT_PUBLIC_WRITE_API_SERVER_BASE_URL,
-  })
+  configuration,
+  undefined,
+  axiosInstance
 );
 
 // レビュー用APIクライアントを作成
 export const apiClientReview = new ControllersReviewApi(
-  new Configuration({
-    basePath: process.env.NEXT_PUBLIC_WRITE_API_SERVER_BASE_URL,
-  })
+  configuration,
+  undefined,
+  axiosInstance
 );
 
 // スカウト用APIクライアントを作成
 export const apiClientScout = new ControllersScoutApi(
-  new Configuration({
-    basePath: process.env.NEXT_PUBLIC_WRITE_API_SERVER_BASE_URL,
-  })
+  configuration,
+  undefined,
+  axiosInstance
 );
@@ -1,7 +1,24 @@
-import { ApolloClient, InMemoryCache } from "@apollo/client";
+import { ApolloClient, InMemoryCache, createHttpLink } from "@apollo/client";
+import { setContext } from "@apollo/client/link/context";
+import { auth } from "@/firebaseConfig";
+
+const httpLink = createHttpLink({
+  uri: process.env.NEXT_PUBLIC_READ_API_SERVER_BASE_URL,
+});
+
+// ログイン中の場合はFirebase ID トークンを付与する
+const authLink = setContext(async (_, { headers }) => {
+  const idToken = await auth.currentUser?.getIdToken();
+  return {
+    headers: {
+      ...headers,
+      ...(idToken ? { Authorization: `Bearer ${idToken}` } : {}),
+    },
+  };
+});
 
 // GraphQLクライアントを作成
 export const graphqlClient = new ApolloClient({
   cache: new InMemoryCache(),
-  uri: process.env.NEXT_PUBLIC_READ_API_SERVER_BASE_URL,
+  link: authLink.concat(httpLink),
 });